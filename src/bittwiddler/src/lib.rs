@@ -1,10 +1,11 @@
 extern crate proc_macro;
+extern crate proc_macro2;
 extern crate syn;
 #[macro_use]
 extern crate quote;
 
-use proc_macro::TokenStream;
-use std::collections::HashSet;
+use proc_macro::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 fn parse_string_attr_helper<T, F>(attrs: &[syn::Attribute], attr_name: &str, cb: F) -> Option<T>
     where F: FnOnce(syn::LitStr) -> T {
@@ -37,6 +38,39 @@ fn parse_string_attr_helper<T, F>(attrs: &[syn::Attribute], attr_name: &str, cb:
     ret.map(cb)
 }
 
+// With the "gen" feature, both derives emit functions for enumerating every value of a type. Values are numbered
+// from 0 to `value_count() - 1`, and everything else is built on top of `nth_value`.
+fn gen_value_fns(input_ident: syn::Ident, value_count: quote::Tokens, nth_value: quote::Tokens,
+    random_value: quote::Tokens) -> quote::Tokens {
+
+    quote! {
+        impl #input_ident {
+            /// Returns the number of distinct values of this type that can be encoded, or `u64::MAX` if there are
+            /// more than that (in which case only the first `u64::MAX` values can be reached by number)
+            pub fn value_count() -> u64 {
+                #value_count
+            }
+
+            /// Returns value number `n` of this type, where `n` must be less than `value_count()`
+            #[allow(unused_assignments)]
+            pub fn nth_value(n: u64) -> Self {
+                #nth_value
+            }
+
+            /// Returns an iterator over every value of this type that can be encoded
+            pub fn all_values() -> impl Iterator<Item = Self> {
+                (0..Self::value_count()).map(Self::nth_value)
+            }
+
+            /// Returns a random value of this type, using `rng` as the source of random numbers. Every value is
+            /// equally likely if `rng` returns uniformly distributed numbers.
+            pub fn random_value<R: FnMut() -> u64>(rng: &mut R) -> Self {
+                #random_value
+            }
+        }
+    }
+}
+
 #[proc_macro_derive(BitPattern, attributes(bits, bits_default, bits_errtype))]
 pub fn bitpattern(input: TokenStream) -> TokenStream {
     // Parse the input tokens into a syntax tree
@@ -151,38 +185,146 @@ pub fn bitpattern(input: TokenStream) -> TokenStream {
         };
 
         encode_tokens.append_all(deocde_tokens);
+
+        if cfg!(feature = "gen") {
+            let num_vars = var_bits.len() as u64;
+            let var_nums = 0..num_vars;
+            let idents_dummy_list3 = var_bits.iter().map(|_| input_ident);
+            let var_names3 = var_bits.iter().map(|x| x.0);
+            encode_tokens.append_all(gen_value_fns(input_ident, quote!{#num_vars}, quote! {
+                match n {
+                    #(#var_nums => #idents_dummy_list3::#var_names3,)*
+                    _ => panic!("value number out of range"),
+                }
+            }, quote! {
+                // Numbers at the very top of the range would make the first few variants more likely, so they are
+                // thrown away
+                let limit = u64::MAX - u64::MAX % #num_vars;
+                loop {
+                    let x = rng();
+                    if x < limit {
+                        return Self::nth_value(x % #num_vars);
+                    }
+                }
+            }));
+        }
+
         encode_tokens.into()
     } else {
         panic!("BitPattern must be used on an enum");
     }
 }
 
-fn parse_multi_string_attr_helper<T, F>(attrs: &[syn::Attribute], attr_name: &str, cb: F)
-    -> std::iter::Map<std::vec::IntoIter<syn::LitStr>, F>
-    where F: FnMut(syn::LitStr) -> T {
+// An error in the input of a derive. This is reported using `compile_error!` at the given location.
+struct DeriveError(Span, String);
+
+impl DeriveError {
+    fn into_compile_error(self) -> TokenStream {
+        let DeriveError(span, msg) = self;
+
+        let mut msg = proc_macro::Literal::string(&msg);
+        msg.set_span(span);
+        let mut args = proc_macro::Group::new(Delimiter::Parenthesis, TokenTree::Literal(msg).into());
+        args.set_span(span);
+        let mut bang = proc_macro::Punct::new('!', Spacing::Alone);
+        bang.set_span(span);
+        let mut semi = proc_macro::Punct::new(';', Spacing::Alone);
+        semi.set_span(span);
+
+        vec![
+            TokenTree::Ident(proc_macro::Ident::new("compile_error", span)),
+            TokenTree::Punct(bang),
+            TokenTree::Group(args),
+            TokenTree::Punct(semi),
+        ].into_iter().collect()
+    }
+}
+
+// The locations of the attributes in the input of a derive. proc-macro2 (without its "nightly" feature) does not
+// keep the locations of the tokens it is given, so they are collected from the original input instead. The
+// locations of the attributes with each name are kept in source order, which is also the order they are parsed in.
+struct AttrSpans {
+    // The name of the type being derived for, which is used for errors not caused by a particular attribute
+    item: Span,
+    attrs: HashMap<String, VecDeque<Span>>,
+}
+
+impl AttrSpans {
+    fn new(input: TokenStream) -> Self {
+        let mut ret = AttrSpans {
+            item: Span::call_site(),
+            attrs: HashMap::new(),
+        };
+
+        let mut prev_keyword = false;
+        for tt in input.clone() {
+            if let TokenTree::Ident(ref ident) = tt {
+                if prev_keyword {
+                    ret.item = ident.span();
+                    break;
+                }
+                let ident = ident.to_string();
+                prev_keyword = ident == "struct" || ident == "enum" || ident == "union";
+            }
+        }
+
+        ret.collect(input);
+        ret
+    }
+
+    fn collect(&mut self, input: TokenStream) {
+        let mut prev_pound = false;
+        for tt in input {
+            if let TokenTree::Group(ref group) = tt {
+                if prev_pound && group.delimiter() == Delimiter::Bracket {
+                    let mut attr_tts = group.stream().into_iter();
+                    if let Some(TokenTree::Ident(name)) = attr_tts.next() {
+                        // Point at the value of the attribute if there is one
+                        let span = attr_tts.last().map_or(group.span(), |x| x.span());
+                        self.attrs.entry(name.to_string()).or_default().push_back(span);
+                    }
+                } else {
+                    self.collect(group.stream());
+                }
+            }
+
+            prev_pound = if let TokenTree::Punct(ref punct) = tt { punct.as_char() == '#' } else { false };
+        }
+    }
+
+    // Returns the location of the next attribute with the given name
+    fn next(&mut self, attr_name: &str) -> Span {
+        let item = self.item;
+        self.attrs.get_mut(attr_name).and_then(|x| x.pop_front()).unwrap_or(item)
+    }
+}
+
+fn parse_multi_string_attr_helper(attrs: &[syn::Attribute], attr_name: &str, spans: &mut AttrSpans)
+    -> Result<Vec<(Span, syn::LitStr)>, DeriveError> {
 
     let mut ret = Vec::new();
     for attr in attrs {
         if attr.path.leading_colon.is_none() && attr.path.segments.len() == 1 &&
            attr.path.segments[0].ident.as_ref() == attr_name {
+            let attr_span = spans.next(attr_name);
             let attr_val = attr.interpret_meta();
             if attr_val.is_none() {
-                panic!("Failed to parse {} attribute", attr_name);
+                return Err(DeriveError(attr_span, format!("Failed to parse {} attribute", attr_name)));
             }
 
             if let syn::Meta::NameValue(nameval) = attr_val.unwrap() {
                 if let syn::Lit::Str(litstr) = nameval.lit {
-                    ret.push(litstr);
+                    ret.push((attr_span, litstr));
                 } else {
-                    panic!("{} attribute must be set to a string", attr_name)
+                    return Err(DeriveError(attr_span, format!("{} attribute must be set to a string", attr_name)));
                 }
             } else {
-                panic!("Malformed {} attribute", attr_name);
+                return Err(DeriveError(attr_span, format!("Malformed {} attribute", attr_name)));
             }
         }
     }
 
-    ret.into_iter().map(cb)
+    Ok(ret)
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -296,26 +438,149 @@ impl<'a> Iterator for StringSplitter<'a> {
     }
 }
 
-fn is_bool(ty: &syn::Type) -> bool {
+// The kinds of values that can be stored in a field (or in each element of an array field)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum BitTwiddlerValueKind {
+    Bool,
+    // An unsigned integer with the given number of bits
+    Int(usize),
+    // Anything else is expected to have the encode/decode functions generated by BitPattern
+    Pattern,
+}
+
+fn value_kind(ty: &syn::Type) -> BitTwiddlerValueKind {
     if let &syn::Type::Path(ref typep) = ty {
-        if typep.qself.is_none() && typep.path.leading_colon.is_none() && typep.path.segments.len() == 1 &&
-            typep.path.segments[0].ident.as_ref() == "bool" {
+        if typep.qself.is_none() && typep.path.leading_colon.is_none() && typep.path.segments.len() == 1 {
+            match typep.path.segments[0].ident.as_ref() {
+                "bool" => return BitTwiddlerValueKind::Bool,
+                "u8" => return BitTwiddlerValueKind::Int(8),
+                "u16" => return BitTwiddlerValueKind::Int(16),
+                _ => {}
+            }
+        }
+    }
+
+    BitTwiddlerValueKind::Pattern
+}
 
-            true
+// For a fixed-size array, returns the element type and the length (if it is a literal)
+fn array_info(ty: &syn::Type) -> Option<(syn::Type, Option<usize>)> {
+    if let &syn::Type::Array(ref typea) = ty {
+        let len = if let syn::Expr::Lit(ref exprlit) = typea.len {
+            if let syn::Lit::Int(ref litint) = exprlit.lit {
+                Some(litint.value() as usize)
+            } else {
+                None
+            }
         } else {
-            false
-        }
+            None
+        };
+
+        Some(((*typea.elem).clone(), len))
     } else {
-        false
+        None
     }
 }
 
+/// Derives functions for reading and writing a struct from/to fuses, as described by `bittwiddler` attributes (one
+/// per layout) on the struct and `bittwiddler_field` attributes on its fields.
+///
+/// The generated functions access fuses through the `jedec::FuseRead`/`jedec::FuseStore` traits and
+/// `jedec::FuseView1D`/`jedec::FuseView2D`, and the generated `FUSE_LAYOUT` table is made of
+/// `jedec::FuseLayoutField`s, so the crate using this derive must also depend on the `jedec` crate.
+///
+/// ```
+/// #[macro_use] extern crate bittwiddler;
+/// extern crate jedec;
+///
+/// #[derive(BitTwiddler)]
+/// #[bittwiddler = "jed width=2"]
+/// #[bittwiddler = "crbit mirror0 width=2x1"]
+/// struct Example {
+///     #[bittwiddler_field = "jed 0"]
+///     #[bittwiddler_field = "crbit 0|0"]
+///     a: bool,
+///     #[bittwiddler_field = "jed !1"]
+///     #[bittwiddler_field = "crbit 1|0"]
+///     b: bool,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Mistakes in the attributes are reported as errors at the offending attribute. Two fields cannot use the same bit:
+///
+/// ```compile_fail
+/// #[macro_use] extern crate bittwiddler;
+/// extern crate jedec;
+///
+/// #[derive(BitTwiddler)]
+/// #[bittwiddler = "jed"]
+/// struct Example {
+///     #[bittwiddler_field = "jed 0"]
+///     a: bool,
+///     #[bittwiddler_field = "jed 0"]
+///     b: bool,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Bits must match the declared `width=`:
+///
+/// ```compile_fail
+/// #[macro_use] extern crate bittwiddler;
+/// extern crate jedec;
+///
+/// #[derive(BitTwiddler)]
+/// #[bittwiddler = "crbit width=2x1"]
+/// struct Example {
+///     #[bittwiddler_field = "crbit 0"]
+///     a: bool,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use] extern crate bittwiddler;
+/// extern crate jedec;
+///
+/// #[derive(BitTwiddler)]
+/// #[bittwiddler = "jed width=1"]
+/// struct Example {
+///     #[bittwiddler_field = "jed 1"]
+///     a: bool,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Unknown options on a layout are rejected:
+///
+/// ```compile_fail
+/// #[macro_use] extern crate bittwiddler;
+/// extern crate jedec;
+///
+/// #[derive(BitTwiddler)]
+/// #[bittwiddler = "jed mirror2"]
+/// struct Example {
+///     #[bittwiddler_field = "jed 0"]
+///     a: bool,
+/// }
+/// # fn main() {}
+/// ```
 #[proc_macro_derive(BitTwiddler, attributes(bittwiddler, bittwiddler_field))]
 pub fn bittwiddler(input: TokenStream) -> TokenStream {
+    let mut spans = AttrSpans::new(input.clone());
     let input: syn::DeriveInput = syn::parse(input).unwrap();
 
-    let main_bittwiddler_attribs = parse_multi_string_attr_helper(&input.attrs, "bittwiddler",
-        |x| x.value()).collect::<Vec<_>>();
+    match bittwiddler_impl(input, &mut spans) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+fn bittwiddler_impl(input: syn::DeriveInput, spans: &mut AttrSpans) -> Result<quote::Tokens, DeriveError> {
+    let input_ident = input.ident;
+
+    let main_bittwiddler_attribs = parse_multi_string_attr_helper(&input.attrs, "bittwiddler", spans)?;
 
     let mut fields_and_attrs = Vec::new();
 
@@ -327,14 +592,12 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
                 syn::Fields::Named(named) => {
                     for field in &named.named {
                         let id = field.ident.unwrap();
-                        let bittwiddler_field_attrs = parse_multi_string_attr_helper(&field.attrs, "bittwiddler_field",
-                            |x| x.value()).collect::<Vec<_>>();
-
-                        let is_bool = is_bool(&field.ty);
+                        let bittwiddler_field_attrs = parse_multi_string_attr_helper(&field.attrs,
+                            "bittwiddler_field", spans)?;
 
                         if bittwiddler_field_attrs.len() > 0 {
                             fields_and_attrs.push((BitTwiddlerFieldRef::Ident(id),
-                                bittwiddler_field_attrs, is_bool, Some(field.ty.clone())));
+                                bittwiddler_field_attrs, Some(field.ty.clone())));
                         }
                     }
 
@@ -342,14 +605,12 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
                 },
                 syn::Fields::Unnamed(unnamed) => {
                     for (idx, field) in unnamed.unnamed.iter().enumerate() {
-                        let bittwiddler_field_attrs = parse_multi_string_attr_helper(&field.attrs, "bittwiddler_field",
-                            |x| x.value()).collect::<Vec<_>>();
-
-                        let is_bool = is_bool(&field.ty);
+                        let bittwiddler_field_attrs = parse_multi_string_attr_helper(&field.attrs,
+                            "bittwiddler_field", spans)?;
 
                         if bittwiddler_field_attrs.len() > 0 {
                             fields_and_attrs.push((BitTwiddlerFieldRef::Index(idx),
-                                bittwiddler_field_attrs, is_bool, Some(field.ty.clone())));
+                                bittwiddler_field_attrs, Some(field.ty.clone())));
                         }
                     }
 
@@ -363,22 +624,55 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
         },
         syn::Data::Enum(_dataenum) => {
             let bittwiddler_field_attrs = parse_multi_string_attr_helper(&input.attrs, "bittwiddler_field",
-                |x| x.value()).collect::<Vec<_>>();
+                spans)?;
 
             if bittwiddler_field_attrs.len() > 0 {
-                fields_and_attrs.push((BitTwiddlerFieldRef::Self_, bittwiddler_field_attrs, false, None));
+                fields_and_attrs.push((BitTwiddlerFieldRef::Self_, bittwiddler_field_attrs, None));
             }
 
             overall_type = BitTwiddlerObjType::Enum;
         },
-        _ => panic!("BitPattern must be used on a struct or enum")
+        _ => return Err(DeriveError(spans.item, "BitTwiddler must be used on a struct or enum".to_owned()))
     }
 
-    let input_ident = input.ident;
+    // Every attribute on a field must belong to an instance declared on the struct
+    let mut instance_names = HashSet::new();
+    for &(span, ref x) in &main_bittwiddler_attribs {
+        let x_str = x.value();
+        match StringSplitter::new(&x_str).next() {
+            Some(instance_name) => {
+                if !instance_names.insert(instance_name.to_owned()) {
+                    return Err(DeriveError(span,
+                        format!("Instance {} on {} is declared more than once", instance_name, input_ident.as_ref())));
+                }
+            },
+            None => return Err(DeriveError(span, format!("Empty bittwiddler attribute on {}", input_ident.as_ref()))),
+        }
+    }
+    let fields_and_attrs = fields_and_attrs.into_iter().map(|(field_id, attrs, field_ty)| {
+        let attrs = attrs.iter().map(|&(span, ref x)| (span, x.value())).collect::<Vec<_>>();
+        for &(span, ref x) in &attrs {
+            match StringSplitter::new(x).next() {
+                Some(instance_name) => if !instance_names.contains(instance_name) {
+                    return Err(DeriveError(span, format!("Field {} of {} refers to undeclared instance {}",
+                        field_id.to_string(), input_ident.as_ref(), instance_name)));
+                },
+                None => return Err(DeriveError(span, format!("Empty bittwiddler_field attribute on field {} of {}",
+                    field_id.to_string(), input_ident.as_ref()))),
+            }
+        }
+        Ok((field_id, attrs, field_ty))
+    }).collect::<Result<Vec<_>, _>>()?;
 
     let mut all_tokens = quote!{};
-
-    for bittwiddler_instance_attrib in main_bittwiddler_attribs {
+    let mut layout_entries = Vec::new();
+    // (field, kind, element type, array length, fewest locations per element in any instance)
+    let mut gen_fields = Vec::new();
+    let mut roundtrip_tests = quote!{};
+    let mut roundtrip_needs_store2d = false;
+
+    for (instance_span, bittwiddler_instance_attrib) in main_bittwiddler_attribs {
+        let bittwiddler_instance_attrib = bittwiddler_instance_attrib.value();
         let mut attrib_split = StringSplitter::new(&bittwiddler_instance_attrib);
         let instance_name = attrib_split.next().unwrap();
 
@@ -389,27 +683,51 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
         }
 
         let this_instance_attribs = fields_and_attrs.iter().map(|x|
-            (x.0, x.1.iter().map(|y| StringSplitter::new(y).collect::<Vec<_>>()).filter(|z|
-                z[0] == instance_name).collect::<Vec<_>>(), x.2, x.3.clone()));
+            (x.0, x.1.iter().map(|&(span, ref y)| (span, StringSplitter::new(y).collect::<Vec<_>>())).filter(|z|
+                z.1[0] == instance_name).collect::<Vec<_>>(), x.2.clone()));
 
         let mut errtype = None;
+        let mut width = None;
         for x in &instance_attribs {
             if x.starts_with("err=") {
                 let mut errtype_tmp = x.split_at(4).1;
                 if errtype_tmp.starts_with("'") {
-                    if !errtype_tmp.ends_with("'") {
-                        panic!("Malformed error type for {} on {}", instance_name, input_ident.as_ref());
+                    if !errtype_tmp.ends_with("'") || errtype_tmp.len() < 2 {
+                        return Err(DeriveError(instance_span,
+                            format!("Malformed error type for {} on {}", instance_name, input_ident.as_ref())));
                     }
 
                     errtype_tmp = errtype_tmp.split_at(1).1;
                     errtype_tmp = errtype_tmp.split_at(errtype_tmp.len() - 1).0;
                 }
 
-                errtype = Some(syn::parse_str::<syn::TypeParam>(errtype_tmp).expect("Failed to parse err= attribute"));
+                errtype = Some(syn::parse_str::<syn::TypeParam>(errtype_tmp).map_err(|_| DeriveError(instance_span,
+                    format!("Failed to parse err= attribute for {} on {}", instance_name, input_ident.as_ref())))?);
+            } else if x.starts_with("width=") {
+                // Either a number of fuses, or a width and height of the form WxH
+                let width_tmp = x.split_at(6).1.split('x').map(|w| w.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>().map_err(|_| DeriveError(instance_span,
+                        format!("Failed to parse width= attribute for {} on {}", instance_name, input_ident.as_ref())))?;
+                width = Some(width_tmp);
+            } else if *x != "pub" && *x != "abs" && *x != "mirror0" && *x != "mirror1" {
+                return Err(DeriveError(instance_span,
+                    format!("Unknown attribute {} for {} on {}", x, instance_name, input_ident.as_ref())));
             }
         }
 
+        if instance_attribs_hash.contains("abs") &&
+            (instance_attribs_hash.contains("mirror0") || instance_attribs_hash.contains("mirror1")) {
+
+            return Err(DeriveError(instance_span, "Mirror and abs cannot be used at the same time".to_owned()));
+        }
+
+        // Which field uses each bit, for detecting overlaps
+        let mut used_bits = HashMap::new();
+
         let mut dimensions = None;
+        // The largest coordinate used in each dimension, and the conditions for a value to be representable
+        let mut max_coords = Vec::new();
+        let mut const_checks = Vec::new();
 
         let encode_fn_ident = syn::Ident::from(format!("encode_{}", instance_name));
 
@@ -421,287 +739,413 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
         let mut decode_field_ids = Vec::new();
 
         let is_abs = instance_attribs_hash.contains("abs");
+        let layout_mirror0 = instance_attribs_hash.contains("mirror0");
+        let layout_mirror1 = instance_attribs_hash.contains("mirror1");
 
-        for (field_id, field_locs, field_isbool, field_ty) in this_instance_attribs {
+        for (field_id, field_locs, field_ty) in this_instance_attribs {
             if field_locs.len() == 0 {
                 continue;
             }
 
             if field_locs.len() > 1 {
-                panic!("{} has multiple bittwiddler_field for type {}", field_id.to_string(), instance_name);
+                return Err(DeriveError(field_locs[1].0, format!("{} has multiple bittwiddler_field for type {}",
+                    field_id.to_string(), instance_name)));
             }
-            let mut field_locs = &field_locs[0][1..];
+            let field_span = field_locs[0].0;
+            let mut field_locs = &field_locs[0].1[1..];
 
+            // Flags that come before the list of locations
             let mut needs_err = false;
-            if field_locs[0] == "err" {
-                needs_err = true;
+            let mut has_bit_order = false;
+            let mut msb_first = false;
+            loop {
+                match field_locs.first() {
+                    Some(&"err") => needs_err = true,
+                    Some(&"lsb") => {
+                        has_bit_order = true;
+                        msb_first = false;
+                    },
+                    Some(&"msb") => {
+                        has_bit_order = true;
+                        msb_first = true;
+                    },
+                    _ => break,
+                }
                 field_locs = &field_locs[1..];
             }
 
-            if field_isbool && field_locs.len() != 1 {
-                panic!("Field {} of {} on {} has too many locations for a boolean",
-                    field_id.to_string(), instance_name, input_ident.as_ref());
+            if field_locs.is_empty() {
+                return Err(DeriveError(field_span, format!("Field {} of {} on {} has no locations",
+                    field_id.to_string(), instance_name, input_ident.as_ref())));
             }
 
-            // Now we can actually generate code
-            let mut encode_this_field = if !field_isbool {
-                match field_id {
-                    BitTwiddlerFieldRef::Ident(id) => {
-                        quote! {
-                            let x = self.#id.encode();
-                        }
-                    },
-                    BitTwiddlerFieldRef::Index(idx) => {
-                        let field_idx = syn::Index {
-                            index: idx as u32,
-                            // TODO: IDK wtf to do here?!
-                            span: input_ident.span()
-                        };
-                        quote! {
-                            let x = self.#field_idx.encode();
-                        }
-                    },
-                    BitTwiddlerFieldRef::Self_ => {
-                        quote! {
-                            let x = self.encode();
-                        }
+            // Arrays are handled by splitting the locations evenly between the elements
+            let (elem_ty, array_len) = match field_ty {
+                Some(ref ty) => match array_info(ty) {
+                    Some((elem_ty, Some(len))) => (Some(elem_ty), Some(len)),
+                    Some((_, None)) => return Err(DeriveError(field_span,
+                        format!("Could not determine the length of array field {} of {}",
+                            field_id.to_string(), input_ident.as_ref()))),
+                    None => (Some(ty.clone()), None),
+                },
+                None => (None, None),
+            };
+            let kind = match elem_ty {
+                Some(ref ty) => value_kind(ty),
+                None => BitTwiddlerValueKind::Pattern,
+            };
+
+            if needs_err && kind != BitTwiddlerValueKind::Pattern {
+                return Err(DeriveError(field_span, format!("Field {} of {} on {} has \"err\" flag, but it's a {}",
+                    field_id.to_string(), instance_name, input_ident.as_ref(),
+                    if kind == BitTwiddlerValueKind::Bool {"boolean"} else {"integer"})));
+            }
+
+            if has_bit_order && kind == BitTwiddlerValueKind::Pattern {
+                return Err(DeriveError(field_span, format!("Field {} of {} on {} has a bit order, but isn't an integer",
+                    field_id.to_string(), instance_name, input_ident.as_ref())));
+            }
+
+            let num_elems = array_len.unwrap_or(1);
+            if num_elems == 0 || field_locs.len() % num_elems != 0 {
+                return Err(DeriveError(field_span,
+                    format!("Field {} of {} on {} has {} locations, which cannot be split between {} array elements",
+                        field_id.to_string(), instance_name, input_ident.as_ref(), field_locs.len(), num_elems)));
+            }
+            let locs_per_elem = field_locs.len() / num_elems;
+
+            match kind {
+                BitTwiddlerValueKind::Bool => if locs_per_elem != 1 {
+                    return Err(DeriveError(field_span, format!("Field {} of {} on {} has too many locations for a boolean",
+                        field_id.to_string(), instance_name, input_ident.as_ref())));
+                },
+                BitTwiddlerValueKind::Int(int_bits) => if locs_per_elem > int_bits {
+                    return Err(DeriveError(field_span,
+                        format!("Field {} of {} on {} has too many locations for a {}-bit integer",
+                            field_id.to_string(), instance_name, input_ident.as_ref(), int_bits)));
+                },
+                BitTwiddlerValueKind::Pattern => {},
+            }
+
+            // Integers can only take on values that fit in the locations of every instance
+            match gen_fields.iter().position(|x: &(BitTwiddlerFieldRef, _, _, _, _)|
+                x.0.to_string() == field_id.to_string()) {
+
+                Some(i) => if gen_fields[i].4 > locs_per_elem {
+                    gen_fields[i].4 = locs_per_elem;
+                },
+                None => gen_fields.push((field_id, kind, elem_ty.clone(), array_len, locs_per_elem)),
+            }
+
+            // How to refer to the entire field from within an encode function
+            let self_field = match field_id {
+                BitTwiddlerFieldRef::Ident(id) => {
+                    quote! {
+                        self.#id
+                    }
+                },
+                BitTwiddlerFieldRef::Index(idx) => {
+                    let field_idx = syn::Index {
+                        index: idx as u32,
+                        // TODO: IDK wtf to do here?!
+                        span: input_ident.span()
+                    };
+                    quote! {
+                        self.#field_idx
+                    }
+                },
+                BitTwiddlerFieldRef::Self_ => {
+                    quote! {
+                        self
                     }
                 }
-            } else {
-                match field_id {
-                    BitTwiddlerFieldRef::Ident(id) => {
+            };
+            // How to refer to the entire field of the value being tested in a round-trip test
+            let test_field = match field_id {
+                BitTwiddlerFieldRef::Ident(id) => {
+                    quote! {
+                        v.#id
+                    }
+                },
+                BitTwiddlerFieldRef::Index(idx) => {
+                    let field_idx = syn::Index {
+                        index: idx as u32,
+                        // TODO: IDK wtf to do here?!
+                        span: input_ident.span()
+                    };
+                    quote! {
+                        v.#field_idx
+                    }
+                },
+                BitTwiddlerFieldRef::Self_ => {
+                    quote! {
+                        v
+                    }
+                }
+            };
+
+            let question_token = if needs_err {quote!{?}} else {quote!{}};
+
+            let mut decode_elems = Vec::with_capacity(num_elems);
+
+            for elem_i in 0..num_elems {
+                let elem_locs = &field_locs[elem_i * locs_per_elem..(elem_i + 1) * locs_per_elem];
+
+                let (elem_ref, test_elem_ref, elem_name) = if array_len.is_some() {
+                    (quote!{#self_field[#elem_i]}, quote!{#test_field[#elem_i]},
+                        format!("{}[{}]", field_id.to_string(), elem_i))
+                } else {
+                    (self_field.clone(), test_field.clone(), field_id.to_string())
+                };
+
+                // Now we can actually generate code
+                let mut encode_this_field = match kind {
+                    BitTwiddlerValueKind::Pattern => {
                         quote! {
-                            let x = self.#id;
+                            let x = #elem_ref.encode();
                         }
                     },
-                    BitTwiddlerFieldRef::Index(idx) => {
-                        let field_idx = syn::Index {
-                            index: idx as u32,
-                            // TODO: IDK wtf to do here?!
-                            span: input_ident.span()
-                        };
+                    _ => {
                         quote! {
-                            let x = self.#field_idx;
+                            let x = #elem_ref;
                         }
                     },
-                    BitTwiddlerFieldRef::Self_ => {
-                        unreachable!();
-                    }
-                }
-            };
-
-            let mut decode_this_field_locs = Vec::new();
+                };
 
-            for (field_bit_i, loc) in field_locs.iter().enumerate() {
-                let mut loc = *loc;
-                let inv = loc.get(0..1) == Some("!");
-                if inv {
-                    loc = loc.split_at(1).1;
-                }
+                let mut decode_this_field_locs = Vec::new();
+                let mut layout_bits = Vec::new();
 
-                if loc == "T" || loc == "F" {
-                    let inv_token = if inv {quote!{!}} else {quote!{}};
-                    let tf = loc == "T";
-                    // Shut up an unused variable warning
-                    encode_this_field.append_all(quote! {
-                        match x {
-                            _ => {}
-                        }
-                    });
-                    decode_this_field_locs.push(quote! {
-                        #inv_token #tf
-                    });
-                } else {
-                    let coords = loc.split('|').collect::<Vec<_>>();
+                for (field_bit_i, loc) in elem_locs.iter().enumerate() {
+                    let mut loc = *loc;
+                    let inv = loc.get(0..1) == Some("!");
+                    if inv {
+                        loc = loc.split_at(1).1;
+                    }
 
-                    if dimensions.is_none() {
-                        dimensions = Some(coords.len());
+                    let shift = if msb_first {locs_per_elem - 1 - field_bit_i} else {field_bit_i};
+                    let field_bit_idx = syn::Index {
+                        index: field_bit_i as u32,
+                        // TODO: IDK wtf to do here?!
+                        span: input_ident.span()
+                    };
+
+                    if loc == "T" || loc == "F" {
+                        let inv_token = if inv {quote!{!}} else {quote!{}};
+                        let tf = loc == "T";
+                        // Values that need this bit to be something else can't be represented in this instance
+                        let test_bit = match kind {
+                            BitTwiddlerValueKind::Bool => quote!{#test_elem_ref},
+                            BitTwiddlerValueKind::Int(_) => quote!{(#test_elem_ref >> #shift) & 1 != 0},
+                            BitTwiddlerValueKind::Pattern => quote!{#test_elem_ref.encode().#field_bit_idx},
+                        };
+                        const_checks.push(quote! {
+                            (#test_bit) == (#inv_token #tf)
+                        });
+                        // Shut up an unused variable warning
+                        encode_this_field.append_all(quote! {
+                            match x {
+                                _ => {}
+                            }
+                        });
+                        decode_this_field_locs.push(quote! {
+                            #inv_token #tf
+                        });
+                        layout_bits.push(quote! {
+                            ::jedec::FuseLayoutBit {
+                                loc: ::jedec::FuseLayoutLoc::Const(#tf),
+                                invert: #inv,
+                            }
+                        });
                     } else {
-                        if dimensions.unwrap() != coords.len() {
-                            panic!("Instance {} on {} has mismatched dimensions", instance_name, input_ident.as_ref());
+                        let coords = loc.split('|').collect::<Vec<_>>();
+
+                        if dimensions.is_none() {
+                            dimensions = Some(coords.len());
+                        } else {
+                            if dimensions.unwrap() != coords.len() {
+                                return Err(DeriveError(field_span,
+                                    format!("Instance {} on {} has mismatched dimensions", instance_name, input_ident.as_ref())));
+                            }
                         }
-                    }
 
-                    let mut index_each_dim = Vec::with_capacity(coords.len());
-                    for (dim_i, coord_str) in coords.iter().enumerate() {
-                        let mirror_attrib = format!("mirror{}", dim_i);
-                        let mirror = instance_attribs_hash.contains::<str>(&mirror_attrib);
-                        let coord_i = coord_str.parse::<usize>().expect("Could not parse coordinate as number or T/F");
+                        let layout_coords = coords.iter().map(|x| x.parse::<usize>()).collect::<Result<Vec<_>, _>>()
+                            .map_err(|_| DeriveError(field_span, format!("Could not parse {} as number or T/F in field {} of {}",
+                                loc, field_id.to_string(), instance_name)))?;
 
-                        if mirror && is_abs {
-                            panic!("Mirror and abs cannot be used at the same time");
+                        if max_coords.is_empty() {
+                            max_coords = layout_coords.clone();
+                        }
+                        for (max_coord, &coord) in max_coords.iter_mut().zip(layout_coords.iter()) {
+                            if coord > *max_coord {
+                                *max_coord = coord;
+                            }
                         }
 
-                        if dimensions.unwrap() > 1 {
-                            let dim_idx = syn::Index {
-                                index: dim_i as u32,
-                                // TODO: IDK wtf to do here?!
-                                span: input_ident.span()
-                            };
-
-                            if mirror {
-                                let mirror_ident = syn::Ident::from(format!("mirror_{}", dim_i));
-                                // mirror_idents.push(quote!{#mirror_ident: bool});
-                                index_each_dim.push(quote! {
-                                    (start_coord.#dim_idx as isize +
-                                        (if #mirror_ident {-1} else {1}) * #coord_i as isize) as usize
-                                });
-                            } else if !is_abs {
-                                index_each_dim.push(quote! {
-                                    start_coord.#dim_idx + #coord_i
-                                });
-                            } else {
-                                index_each_dim.push(quote! {
-                                    #coord_i
-                                });
+                        if let Some(ref width) = width {
+                            if width.len() != layout_coords.len() {
+                                return Err(DeriveError(instance_span, format!("Instance {} on {} has mismatched dimensions",
+                                    instance_name, input_ident.as_ref())));
                             }
-                        } else {
-                            if mirror {
-                                let mirror_ident = syn::Ident::from(format!("mirror_{}", dim_i));
-                                // mirror_idents.push(quote!{#mirror_ident: bool});
-                                index_each_dim.push(quote! {
-                                    (start_coord as isize +
-                                        (if #mirror_ident {-1} else {1}) * #coord_i as isize) as usize
-                                });
-                            } else if !is_abs {
-                                index_each_dim.push(quote! {
-                                    start_coord + #coord_i
-                                });
-                            } else {
-                                index_each_dim.push(quote! {
-                                    #coord_i
-                                });
+                            for dim_i in 0..width.len() {
+                                if layout_coords[dim_i] >= width[dim_i] {
+                                    return Err(DeriveError(field_span,
+                                        format!("Bit {} of field {} of {} on {} is beyond the declared width",
+                                            loc, elem_name, instance_name, input_ident.as_ref())));
+                                }
                             }
                         }
-                    }
 
-                    let index_each_dim2 = index_each_dim.clone();
-
-                    let inv_token = if inv {quote!{!}} else {quote!{}};
+                        if let Some(other_field) = used_bits.insert(layout_coords.clone(), elem_name.clone()) {
+                            return Err(DeriveError(field_span, format!("Bit {} of field {} of {} on {} is also used by field {}",
+                                loc, elem_name, instance_name, input_ident.as_ref(), other_field)));
+                        }
 
-                    if !field_isbool {
-                        let field_bit_idx = syn::Index {
-                            index: field_bit_i as u32,
-                            // TODO: IDK wtf to do here?!
-                            span: input_ident.span()
+                        // Offsetting and mirroring is done by a view of the fuses set up at the start of the function
+                        let index_each_dim = layout_coords.clone();
+                        let index_each_dim2 = index_each_dim.clone();
+
+                        let layout_loc = match layout_coords.len() {
+                            1 => {
+                                let coord_0 = layout_coords[0];
+                                quote!{::jedec::FuseLayoutLoc::Fuse(#coord_0)}
+                            },
+                            2 => {
+                                let (coord_0, coord_1) = (layout_coords[0], layout_coords[1]);
+                                quote!{::jedec::FuseLayoutLoc::Coord(#coord_0, #coord_1)}
+                            },
+                            _ => return Err(DeriveError(field_span, format!("Instance {} on {} has more than 2 dimensions",
+                                instance_name, input_ident.as_ref()))),
                         };
-
-                        encode_this_field.append_all(quote! {
-                            fuses[(#(#index_each_dim),*)] = #inv_token x.#field_bit_idx;
+                        layout_bits.push(quote! {
+                            ::jedec::FuseLayoutBit {
+                                loc: #layout_loc,
+                                invert: #inv,
+                            }
                         });
-                    } else {
-                        encode_this_field.append_all(quote! {
-                            fuses[(#(#index_each_dim),*)] = #inv_token x;
+
+                        let inv_token = if inv {quote!{!}} else {quote!{}};
+
+                        match kind {
+                            BitTwiddlerValueKind::Bool => {
+                                encode_this_field.append_all(quote! {
+                                    ::jedec::FuseStore::set_fuse(&mut *fuses, (#(#index_each_dim),*), #inv_token x);
+                                });
+                            },
+                            BitTwiddlerValueKind::Int(_) => {
+                                encode_this_field.append_all(quote! {
+                                    ::jedec::FuseStore::set_fuse(&mut *fuses, (#(#index_each_dim),*),
+                                        #inv_token ((x >> #shift) & 1 != 0));
+                                });
+                            },
+                            BitTwiddlerValueKind::Pattern => {
+                                encode_this_field.append_all(quote! {
+                                    ::jedec::FuseStore::set_fuse(&mut *fuses, (#(#index_each_dim),*), #inv_token x.#field_bit_idx);
+                                });
+                            },
+                        }
+
+                        decode_this_field_locs.push(quote! {
+                            #inv_token ::jedec::FuseRead::get_fuse(fuses, (#(#index_each_dim2),*))
                         });
                     }
-
-                    decode_this_field_locs.push(quote! {
-                        #inv_token fuses[(#(#index_each_dim2),*)]
-                    });
                 }
-            }
 
-            encode_field_tokens.append_all(encode_this_field);
+                encode_field_tokens.append_all(encode_this_field);
+
+                let layout_field_name = match field_id {
+                    BitTwiddlerFieldRef::Self_ => String::new(),
+                    _ => elem_name,
+                };
+                layout_entries.push(quote! {
+                    ::jedec::FuseLayoutField {
+                        instance: #instance_name,
+                        field: #layout_field_name,
+                        bits: &[#(#layout_bits),*],
+                        abs: #is_abs,
+                        mirrorable: [#layout_mirror0, #layout_mirror1],
+                    }
+                });
 
-            let mut decode_this_field = if decode_this_field_locs.len() != 1 {
-                quote! {
-                    let x = (#(#decode_this_field_locs),*);
-                }
-            } else {
-                // Suppress warnings about extra parens
-                quote! {
-                    let x = #(#decode_this_field_locs),*;
-                }
-            };
-            decode_this_field.append_all(if !field_isbool {
-                match field_id {
-                    BitTwiddlerFieldRef::Ident(id) => {
-                        let ty = field_ty.unwrap();
-                        decode_field_ids.push(id);
-                        if needs_err {
-                            quote! {
-                                let #id = #ty::decode(x)?;
-                            }
-                        } else {
-                            quote! {
-                                let #id = #ty::decode(x);
-                            }
+                decode_elems.push(match kind {
+                    BitTwiddlerValueKind::Bool => {
+                        quote! {
+                            #(#decode_this_field_locs),*
                         }
                     },
-                    BitTwiddlerFieldRef::Index(idx) => {
-                        let ty = field_ty.unwrap();
-                        let id = syn::Ident::from(format!{"field{}", idx});
-                        decode_field_ids.push(id);
-                        if needs_err {
-                            quote! {
-                                let #id = #ty::decode(x)?;
-                            }
-                        } else {
-                            quote! {
-                                let #id = #ty::decode(x);
-                            }
+                    BitTwiddlerValueKind::Int(_) => {
+                        let ty = elem_ty.as_ref().unwrap();
+                        let tys = (0..locs_per_elem).map(|_| ty);
+                        let shifts = (0..locs_per_elem).map(|field_bit_i|
+                            if msb_first {locs_per_elem - 1 - field_bit_i} else {field_bit_i});
+                        quote! {
+                            0 #(| (((#decode_this_field_locs) as #tys) << #shifts))*
                         }
                     },
-                    BitTwiddlerFieldRef::Self_ => {
-                        if needs_err {
+                    BitTwiddlerValueKind::Pattern => {
+                        let x = if decode_this_field_locs.len() != 1 {
                             quote! {
-                                let self_ = Self::decode(x)?;
+                                (#(#decode_this_field_locs),*)
                             }
                         } else {
+                            // Suppress warnings about extra parens
                             quote! {
-                                let self_ = Self::decode(x);
+                                #(#decode_this_field_locs),*
+                            }
+                        };
+                        match elem_ty {
+                            Some(ref ty) => {
+                                quote! {
+                                    #ty::decode(#x)#question_token
+                                }
+                            },
+                            None => {
+                                quote! {
+                                    Self::decode(#x)#question_token
+                                }
                             }
                         }
-                    }
+                    },
+                });
+            }
+
+            let decode_value = if array_len.is_some() {
+                quote! {
+                    [#(#decode_elems),*]
                 }
             } else {
-                if needs_err {
-                    panic!("Field {} of {} on {} has \"err\" flag, but it's a boolean",
-                        field_id.to_string(), instance_name, input_ident.as_ref());
-                }
-                match field_id {
-                    BitTwiddlerFieldRef::Ident(id) => {
-                        decode_field_ids.push(id);
-                        quote! {
-                            let #id = x;
-                        }
-                    },
-                    BitTwiddlerFieldRef::Index(idx) => {
-                        let id = syn::Ident::from(format!{"field{}", idx});
-                        decode_field_ids.push(id);
-                        quote! {
-                            let #id = x;
-                        }
-                    },
-                    BitTwiddlerFieldRef::Self_ => {
-                        unreachable!();
+                decode_elems.pop().unwrap()
+            };
+
+            decode_field_tokens.append_all(match field_id {
+                BitTwiddlerFieldRef::Ident(id) => {
+                    decode_field_ids.push(id);
+                    quote! {
+                        let #id = #decode_value;
+                    }
+                },
+                BitTwiddlerFieldRef::Index(idx) => {
+                    let id = syn::Ident::from(format!{"field{}", idx});
+                    decode_field_ids.push(id);
+                    quote! {
+                        let #id = #decode_value;
+                    }
+                },
+                BitTwiddlerFieldRef::Self_ => {
+                    quote! {
+                        let self_ = #decode_value;
                     }
                 }
             });
-
-            decode_field_tokens.append_all(decode_this_field);
         }
 
         if dimensions.is_none() {
-            panic!("Instance {} on {} has zero fields", instance_name, input_ident.as_ref());
+            return Err(DeriveError(instance_span,
+                format!("Instance {} on {} has zero fields", instance_name, input_ident.as_ref())));
         }
 
-        let mut usize_idents = Vec::with_capacity(dimensions.unwrap());
-        for _ in 0..dimensions.unwrap() {
-            usize_idents.push(quote!{usize});
-        }
-        let mut usize_idents2 = Vec::with_capacity(dimensions.unwrap());
-        for _ in 0..dimensions.unwrap() {
-            usize_idents2.push(quote!{usize});
-        }
-        let mut usize_idents3 = Vec::with_capacity(dimensions.unwrap());
-        for _ in 0..dimensions.unwrap() {
-            usize_idents3.push(quote!{usize});
-        }
-        let mut usize_idents4 = Vec::with_capacity(dimensions.unwrap());
-        for _ in 0..dimensions.unwrap() {
-            usize_idents4.push(quote!{usize});
-        }
+        // The coordinate type, e.g. `(usize, usize)`, used for both the start coordinate and the fuse trait bounds
+        let usize_idents = (0..dimensions.unwrap()).map(|_| quote!{usize});
+        let coord_ty = quote!{(#(#usize_idents),*)};
 
         let mut mirror_idents = Vec::new();
         for dim_i in 0..dimensions.unwrap() {
@@ -716,6 +1160,21 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
 
         let mirror_idents2 = mirror_idents.clone();
 
+        // Non-absolute instances access the fuses through a view that handles the starting coordinate and mirroring
+        let mirror_args = (0..dimensions.unwrap()).map(|dim_i| {
+            if instance_attribs_hash.contains::<str>(&format!("mirror{}", dim_i)) {
+                let mirror_ident = syn::Ident::from(format!("mirror_{}", dim_i));
+                quote!{#mirror_ident}
+            } else {
+                quote!{false}
+            }
+        }).collect::<Vec<_>>();
+        let make_view = if dimensions.unwrap() == 1 {
+            quote!{::jedec::FuseView1D::new(fuses, start_coord, #(#mirror_args),*)}
+        } else {
+            quote!{::jedec::FuseView2D::new(fuses, start_coord, (#(#mirror_args),*))}
+        };
+
         let ispub_token = if instance_attribs_hash.contains("pub") {
             quote!{pub}
         } else {
@@ -726,9 +1185,10 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #encode_fn_ident<T>(&self,
-                        fuses: &mut T, start_coord: (#(#usize_idents),*), #(#mirror_idents),*)
-                        where T: ::std::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
+                        fuses: &mut T, start_coord: #coord_ty, #(#mirror_idents),*)
+                        where T: ::jedec::FuseStore<#coord_ty> + ?Sized
                     {
+                        let fuses = &mut #make_view;
                         #encode_field_tokens
                     }
                 }
@@ -737,7 +1197,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #encode_fn_ident<T>(&self, fuses: &mut T)
-                        where T: ::std::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
+                        where T: ::jedec::FuseStore<#coord_ty> + ?Sized
                     {
                         #encode_field_tokens
                     }
@@ -794,10 +1254,11 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #decode_fn_ident<T>(
-                        fuses: &T, start_coord: (#(#usize_idents3),*), #(#mirror_idents2),*)
+                        fuses: &T, start_coord: #coord_ty, #(#mirror_idents2),*)
                         -> #decode_output_tokens
-                        where T: ::std::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
+                        where T: ::jedec::FuseRead<#coord_ty> + ?Sized
                     {
+                        let fuses = &#make_view;
                         #decode_field_tokens
                         
                         #decode_return_tokens
@@ -808,7 +1269,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #decode_fn_ident<T>(fuses: &T) -> #decode_output_tokens
-                        where T: ::std::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
+                        where T: ::jedec::FuseRead<#coord_ty> + ?Sized
                     {
                         #decode_field_tokens
                         
@@ -819,7 +1280,227 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
         };
 
         all_tokens.append_all(decode_tokens);
+
+        if cfg!(feature = "gen") {
+            // Encode and decode (a sample of) every value that this instance can represent. Instances that can be
+            // mirrored are tested both ways.
+            let num_dims = dimensions.unwrap();
+            let store_init = if num_dims == 1 {
+                let size = max_coords[0] + 1;
+                quote!{vec![false; #size]}
+            } else {
+                roundtrip_needs_store2d = true;
+                let (w, h) = (max_coords[0] + 1, max_coords[1] + 1);
+                quote!{Store2D {w: #w, v: vec![false; #w * #h]}}
+            };
+
+            let mirrored_dims = (0..num_dims).filter(|dim_i|
+                instance_attribs_hash.contains::<str>(&format!("mirror{}", dim_i))).collect::<Vec<_>>();
+            let mut roundtrip_each_combo = Vec::new();
+            for combo in 0..(1 << mirrored_dims.len()) {
+                let mut start_coord = Vec::with_capacity(num_dims);
+                let mut mirror_args = Vec::with_capacity(mirrored_dims.len());
+                for (dim_i, &max_coord) in max_coords.iter().enumerate() {
+                    match mirrored_dims.iter().position(|&x| x == dim_i) {
+                        Some(mirror_i) => {
+                            let mirror = combo & (1 << mirror_i) != 0;
+                            start_coord.push(if mirror {max_coord} else {0});
+                            mirror_args.push(mirror);
+                        },
+                        None => start_coord.push(0),
+                    }
+                }
+
+                let (encode_call, decode_call) = if is_abs {
+                    (quote!{v.#encode_fn_ident(&mut fuses)}, quote!{#input_ident::#decode_fn_ident(&fuses)})
+                } else {
+                    let start_coord2 = start_coord.clone();
+                    let mirror_args2 = mirror_args.clone();
+                    (quote!{v.#encode_fn_ident(&mut fuses, (#(#start_coord),*), #(#mirror_args),*)},
+                     quote!{#input_ident::#decode_fn_ident(&fuses, (#(#start_coord2),*), #(#mirror_args2),*)})
+                };
+                let decoded = if errtype.is_some() {
+                    quote!{#decode_call.ok()}
+                } else {
+                    quote!{Some(#decode_call)}
+                };
+
+                roundtrip_each_combo.push(quote! {
+                    #encode_call;
+                    let decoded = #decoded;
+                    assert!(decoded.as_ref() == Some(&v),
+                        "value {} of {} did not survive a round trip through {}", n, stringify!(#input_ident),
+                        #instance_name);
+                });
+            }
+
+            let test_fn_ident = syn::Ident::from(instance_name);
+            roundtrip_tests.append_all(quote! {
+                #[test]
+                fn #test_fn_ident() {
+                    const MAX_VALUES: u64 = 1 << 16;
+                    let count = #input_ident::value_count();
+                    let mut rng_state = 0x2545F4914F6CDD1Du64;
+                    let mut fuses = #store_init;
+                    for i in 0..(if count > MAX_VALUES {MAX_VALUES} else {count}) {
+                        // If there are too many values, test a random sample of them
+                        let n = if count > MAX_VALUES {
+                            rng_state ^= rng_state << 13;
+                            rng_state ^= rng_state >> 7;
+                            rng_state ^= rng_state << 17;
+                            rng_state % count
+                        } else {
+                            i
+                        };
+                        let v = #input_ident::nth_value(n);
+                        if !(true #(&& #const_checks)*) {
+                            continue;
+                        }
+
+                        #(#roundtrip_each_combo)*
+                    }
+                }
+            });
+        }
+    }
+
+    // Table describing the fuse locations of every field in every instance
+    all_tokens.append_all(quote! {
+        impl #input_ident {
+            pub const FUSE_LAYOUT: &'static [::jedec::FuseLayoutField] = &[#(#layout_entries),*];
+        }
+    });
+
+    if cfg!(feature = "gen") {
+        // Enums get these functions from BitPattern instead
+        match overall_type {
+            BitTwiddlerObjType::Named | BitTwiddlerObjType::Unnamed => {
+                let mut field_counts = Vec::with_capacity(gen_fields.len());
+                let mut field_values = Vec::with_capacity(gen_fields.len());
+                let mut field_randoms = Vec::with_capacity(gen_fields.len());
+                for &(field_id, kind, ref elem_ty, array_len, min_locs) in &gen_fields {
+                    let elem_ty = elem_ty.as_ref().unwrap();
+                    let int_count = 1u64.checked_shl(min_locs as u32);
+                    let elem_count = match kind {
+                        BitTwiddlerValueKind::Bool => quote!{2},
+                        BitTwiddlerValueKind::Int(_) => {
+                            let count = int_count.unwrap_or(u64::MAX);
+                            quote!{#count}
+                        },
+                        BitTwiddlerValueKind::Pattern => quote!{<#elem_ty>::value_count()},
+                    };
+                    let elem_value = match kind {
+                        BitTwiddlerValueKind::Bool => quote!{n % 2 != 0},
+                        BitTwiddlerValueKind::Int(_) => quote!{(n % (#elem_count)) as #elem_ty},
+                        BitTwiddlerValueKind::Pattern => quote!{<#elem_ty>::nth_value(n % (#elem_count))},
+                    };
+                    // Each field is picked on its own, since picking a number below `value_count()` would be biased
+                    let elem_random = match (kind, int_count) {
+                        (BitTwiddlerValueKind::Bool, _) => quote!{rng() % 2 != 0},
+                        (BitTwiddlerValueKind::Int(_), Some(count)) => quote!{(rng() % #count) as #elem_ty},
+                        (BitTwiddlerValueKind::Int(_), None) => quote!{rng() as #elem_ty},
+                        (BitTwiddlerValueKind::Pattern, _) => quote!{<#elem_ty>::random_value(rng)},
+                    };
+                    // Each value of the whole struct is a mixed-radix number where each digit is a field
+                    let (count, value, random) = match array_len {
+                        Some(len) => {
+                            let elem_counts = (0..len).map(|_| elem_count.clone());
+                            let elem_counts2 = (0..len).map(|_| elem_count.clone());
+                            let elem_values = (0..len).map(|_| elem_value.clone());
+                            let elem_randoms = (0..len).map(|_| elem_random.clone());
+                            (quote!{1u64 #(.saturating_mul(#elem_counts))*}, quote!{
+                                [#({
+                                    let x = #elem_values;
+                                    n /= #elem_counts2;
+                                    x
+                                }),*]
+                            }, quote!{
+                                [#(#elem_randoms),*]
+                            })
+                        },
+                        None => {
+                            (elem_count.clone(), quote! {{
+                                let x = #elem_value;
+                                n /= #elem_count;
+                                x
+                            }}, elem_random)
+                        },
+                    };
+                    field_counts.push(count);
+                    let (value, random) = match field_id {
+                        BitTwiddlerFieldRef::Ident(id) => (quote!{#id: #value}, quote!{#id: #random}),
+                        BitTwiddlerFieldRef::Index(_) => (value, random),
+                        BitTwiddlerFieldRef::Self_ => unreachable!(),
+                    };
+                    field_values.push(value);
+                    field_randoms.push(random);
+                }
+
+                let (nth_value, random_value) = match overall_type {
+                    BitTwiddlerObjType::Named => (quote! {
+                        let mut n = n;
+                        #input_ident {
+                            #(#field_values),*
+                        }
+                    }, quote! {
+                        #input_ident {
+                            #(#field_randoms),*
+                        }
+                    }),
+                    _ => (quote! {
+                        let mut n = n;
+                        #input_ident (
+                            #(#field_values),*
+                        )
+                    }, quote! {
+                        #input_ident (
+                            #(#field_randoms),*
+                        )
+                    }),
+                };
+
+                all_tokens.append_all(gen_value_fns(input_ident, quote!{1u64 #(.saturating_mul(#field_counts))*},
+                    nth_value, random_value));
+            },
+            BitTwiddlerObjType::Enum => {},
+        }
+
+        let store2d = if roundtrip_needs_store2d {
+            quote! {
+                struct Store2D {
+                    w: usize,
+                    v: Vec<bool>,
+                }
+
+                impl ::jedec::FuseRead<(usize, usize)> for Store2D {
+                    fn get_fuse(&self, coords: (usize, usize)) -> bool {
+                        self.v[coords.1 * self.w + coords.0]
+                    }
+                }
+
+                impl ::jedec::FuseStore<(usize, usize)> for Store2D {
+                    fn set_fuse(&mut self, coords: (usize, usize), val: bool) {
+                        self.v[coords.1 * self.w + coords.0] = val;
+                    }
+                }
+            }
+        } else {
+            quote!{}
+        };
+
+        let test_mod_ident = syn::Ident::from(format!("__bittwiddler_roundtrip_{}", input_ident.as_ref()));
+        all_tokens.append_all(quote! {
+            #[cfg(test)]
+            #[allow(non_snake_case)]
+            mod #test_mod_ident {
+                use super::*;
+
+                #store2d
+
+                #roundtrip_tests
+            }
+        });
     }
 
-    all_tokens.into()
+    Ok(all_tokens)
 }
@@ -0,0 +1,385 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Bit-packed fuse storage
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+use fusestore::{FuseRead, FuseStore};
+
+const WORD_BITS: usize = 64;
+
+static TRUE: bool = true;
+static FALSE: bool = false;
+
+/// A growable array of bits that stores 64 bits per machine word. Bits that are past the end of the array but inside
+/// the last word are always 0, so words can be compared, hashed, and counted directly.
+///
+/// Individual bits cannot be borrowed mutably, so this implements `Index` but not `IndexMut`. Use `set` to change
+/// a bit.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Constructs an empty bit vector
+    pub fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Constructs a bit vector containing `len` copies of `val`
+    pub fn from_elem(len: usize, val: bool) -> Self {
+        let mut ret = Self {
+            words: vec![if val {!0} else {0}; (len + WORD_BITS - 1) / WORD_BITS],
+            len,
+        };
+        ret.clear_unused_bits();
+        ret
+    }
+
+    fn clear_unused_bits(&mut self) {
+        let used_bits = self.len % WORD_BITS;
+        if used_bits != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1u64 << used_bits) - 1;
+        }
+    }
+
+    /// Returns the number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bit at index `i`. Panics if `i` is out of range.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit index {} out of range for length {}", i, self.len);
+        self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0
+    }
+
+    /// Set the bit at index `i`. Panics if `i` is out of range.
+    pub fn set(&mut self, i: usize, val: bool) {
+        assert!(i < self.len, "bit index {} out of range for length {}", i, self.len);
+        if val {
+            self.words[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+        } else {
+            self.words[i / WORD_BITS] &= !(1 << (i % WORD_BITS));
+        }
+    }
+
+    /// Appends a bit to the end
+    pub fn push(&mut self, val: bool) {
+        if self.len % WORD_BITS == 0 {
+            self.words.push(0);
+        }
+        self.len += 1;
+        let i = self.len - 1;
+        self.set(i, val);
+    }
+
+    /// Returns an iterator over all of the bits
+    pub fn iter(&self) -> BitVecIter<'_> {
+        BitVecIter {
+            bits: self,
+            i: 0,
+        }
+    }
+
+    /// Returns the underlying words. Bit `i` is stored in bit `i % 64` of word `i / 64`.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the number of bits that are set to 1
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    /// Returns the number of bits that are set to 0
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns a bit vector containing the XOR of this one and `other`, i.e. a bit vector where the bits that differ
+    /// are set. Panics if the lengths are different.
+    pub fn xor(&self, other: &BitVec) -> BitVec {
+        assert_eq!(self.len, other.len, "cannot xor bit vectors of different lengths");
+        BitVec {
+            words: self.words.iter().zip(other.words.iter()).map(|(a, b)| a ^ b).collect(),
+            len: self.len,
+        }
+    }
+
+    /// Returns the indices of all bits that differ between this bit vector and `other`. Panics if the lengths are
+    /// different.
+    pub fn diff_indices(&self, other: &BitVec) -> Vec<usize> {
+        self.xor(other).iter_ones().collect()
+    }
+
+    /// Returns an iterator over the indices of all bits that are set to 1
+    pub fn iter_ones(&self) -> BitVecOnesIter<'_> {
+        BitVecOnesIter {
+            words: &self.words,
+            word_idx: 0,
+            cur_word: self.words.first().cloned().unwrap_or(0),
+        }
+    }
+
+    /// Unpacks the bits into a `Vec<bool>`
+    pub fn to_vec(&self) -> Vec<bool> {
+        self.iter().collect()
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BitVec[")?;
+        for x in self.iter() {
+            write!(f, "{}", if x {"1"} else {"0"})?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl Index<usize> for BitVec {
+    type Output = bool;
+
+    fn index(&self, i: usize) -> &bool {
+        if self.get(i) {&TRUE} else {&FALSE}
+    }
+}
+
+impl FuseRead<usize> for BitVec {
+    fn get_fuse(&self, idx: usize) -> bool {
+        self.get(idx)
+    }
+}
+
+impl FuseStore<usize> for BitVec {
+    fn set_fuse(&mut self, idx: usize, val: bool) {
+        self.set(idx, val);
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut ret = BitVec::new();
+        for x in iter {
+            ret.push(x);
+        }
+        ret
+    }
+}
+
+impl<'a> From<&'a [bool]> for BitVec {
+    fn from(x: &'a [bool]) -> Self {
+        x.iter().cloned().collect()
+    }
+}
+
+impl From<Vec<bool>> for BitVec {
+    fn from(x: Vec<bool>) -> Self {
+        x.into_iter().collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = BitVecIter<'a>;
+
+    fn into_iter(self) -> BitVecIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the bits of a `BitVec`
+pub struct BitVecIter<'a> {
+    bits: &'a BitVec,
+    i: usize,
+}
+
+impl<'a> Iterator for BitVecIter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.i < self.bits.len {
+            let ret = self.bits.get(self.i);
+            self.i += 1;
+            Some(ret)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bits.len - self.i;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Iterator over the indices of the bits of a `BitVec` that are set to 1
+pub struct BitVecOnesIter<'a> {
+    words: &'a [u64],
+    word_idx: usize,
+    cur_word: u64,
+}
+
+impl<'a> Iterator for BitVecOnesIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.cur_word == 0 {
+            self.word_idx += 1;
+            if self.word_idx >= self.words.len() {
+                return None;
+            }
+            self.cur_word = self.words[self.word_idx];
+        }
+
+        let bit = self.cur_word.trailing_zeros() as usize;
+        self.cur_word &= self.cur_word - 1;
+        Some(self.word_idx * WORD_BITS + bit)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::fmt::Write;
+
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::Error;
+
+    use super::BitVec;
+
+    /// Serialized form of a `BitVec`. Each hex digit of `bits` holds four bits, with the lowest-numbered bit in the
+    /// most significant position, so the string reads in the same order as a `L` field in a .jed file.
+    #[derive(Serialize, Deserialize)]
+    struct BitVecRepr {
+        len: usize,
+        bits: String,
+    }
+
+    impl Serialize for BitVec {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut bits = String::with_capacity((self.len + 3) / 4);
+            for i in (0..self.len).step_by(4) {
+                let mut nybble = 0;
+                for j in 0..4 {
+                    if i + j < self.len && self.get(i + j) {
+                        nybble |= 8 >> j;
+                    }
+                }
+                write!(bits, "{:X}", nybble).unwrap();
+            }
+
+            BitVecRepr {
+                len: self.len,
+                bits,
+            }.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BitVec {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = BitVecRepr::deserialize(deserializer)?;
+            if repr.bits.len() != (repr.len + 3) / 4 {
+                return Err(D::Error::custom("wrong number of hex digits for bit vector length"));
+            }
+
+            let mut ret = BitVec::from_elem(repr.len, false);
+            for (i, c) in repr.bits.chars().enumerate() {
+                let nybble = c.to_digit(16).ok_or_else(|| D::Error::custom("invalid hex digit in bit vector"))?;
+                for j in 0..4 {
+                    if nybble & (8 >> j) != 0 {
+                        if i * 4 + j >= repr.len {
+                            return Err(D::Error::custom("bit set past the end of bit vector"));
+                        }
+                        ret.set(i * 4 + j, true);
+                    }
+                }
+            }
+
+            Ok(ret)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitvec_get_set() {
+        let mut x = BitVec::from_elem(130, false);
+        x.set(0, true);
+        x.set(64, true);
+        x.set(129, true);
+        assert!(x[0] && x[64] && x[129]);
+        assert!(!x[1] && !x[63] && !x[128]);
+        assert_eq!(x.count_ones(), 3);
+        x.set(64, false);
+        assert_eq!(x.iter_ones().collect::<Vec<_>>(), vec![0, 129]);
+    }
+
+    #[test]
+    fn bitvec_unused_bits_clear() {
+        let x = BitVec::from_elem(65, true);
+        assert_eq!(x.words(), &[!0, 1]);
+        assert_eq!(x.count_ones(), 65);
+        assert_eq!(x, vec![true; 65].into());
+    }
+
+    #[test]
+    fn bitvec_diff() {
+        let a: BitVec = vec![true, false, true, false].into();
+        let b: BitVec = vec![true, true, true, true].into();
+        assert_eq!(a.xor(&b), vec![false, true, false, true].into());
+        assert_eq!(a.diff_indices(&b), vec![1, 3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let a: BitVec = vec![true, false, false, true, true].into();
+        let json = ::serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"len":5,"bits":"98"}"#);
+        assert_eq!(::serde_json::from_str::<BitVec>(&json).unwrap(), a);
+
+        assert!(::serde_json::from_str::<BitVec>(r#"{"len":5,"bits":"9"}"#).is_err());
+        assert!(::serde_json::from_str::<BitVec>(r#"{"len":5,"bits":"9C"}"#).is_err());
+        assert!(::serde_json::from_str::<BitVec>(r#"{"len":5,"bits":"9G"}"#).is_err());
+    }
+}
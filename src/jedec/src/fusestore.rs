@@ -0,0 +1,245 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Traits for things that fuses can be stored in, and views of part of such a store
+
+/// Trait for anything that fuse values can be read from using an index of type `I`. This is what the code generated
+/// by `bittwiddler` uses to read fuses, so it is implemented for both 1-dimensional (`usize`) and 2-dimensional
+/// (`(usize, usize)`) storage.
+pub trait FuseRead<I> {
+    /// Get the value of the fuse at the given index
+    fn get_fuse(&self, idx: I) -> bool;
+}
+
+/// Trait for anything that fuse values can be read from and written to using an index of type `I`.
+pub trait FuseStore<I>: FuseRead<I> {
+    /// Set the value of the fuse at the given index
+    fn set_fuse(&mut self, idx: I, val: bool);
+}
+
+impl FuseRead<usize> for [bool] {
+    fn get_fuse(&self, idx: usize) -> bool {
+        self[idx]
+    }
+}
+
+impl FuseStore<usize> for [bool] {
+    fn set_fuse(&mut self, idx: usize, val: bool) {
+        self[idx] = val;
+    }
+}
+
+impl FuseRead<usize> for Vec<bool> {
+    fn get_fuse(&self, idx: usize) -> bool {
+        self[idx]
+    }
+}
+
+impl FuseStore<usize> for Vec<bool> {
+    fn set_fuse(&mut self, idx: usize, val: bool) {
+        self[idx] = val;
+    }
+}
+
+// References can be used wherever the thing they refer to can, which is what allows views to borrow their store
+
+impl<I, S: FuseRead<I> + ?Sized> FuseRead<I> for &S {
+    fn get_fuse(&self, idx: I) -> bool {
+        (**self).get_fuse(idx)
+    }
+}
+
+impl<I, S: FuseRead<I> + ?Sized> FuseRead<I> for &mut S {
+    fn get_fuse(&self, idx: I) -> bool {
+        (**self).get_fuse(idx)
+    }
+}
+
+impl<I, S: FuseStore<I> + ?Sized> FuseStore<I> for &mut S {
+    fn set_fuse(&mut self, idx: I, val: bool) {
+        (**self).set_fuse(idx, val)
+    }
+}
+
+fn view_coord(origin: usize, mirror: bool, idx: usize) -> usize {
+    if !mirror {
+        origin + idx
+    } else {
+        origin - idx
+    }
+}
+
+/// A view of part of a 1-dimensional fuse store. Index 0 of the view is at `origin` in the underlying store. If the
+/// view is mirrored, increasing indices in the view go towards lower indices in the underlying store.
+///
+/// `S` is normally a reference to the underlying store. A view made from a `&` reference can only be read, and a view
+/// made from a `&mut` reference can be both read and written.
+#[derive(Copy, Clone, Debug)]
+pub struct FuseView1D<S> {
+    store: S,
+    origin: usize,
+    mirror: bool,
+}
+
+impl<S> FuseView1D<S> {
+    /// Creates a view of `store` starting at `origin`
+    pub fn new(store: S, origin: usize, mirror: bool) -> Self {
+        FuseView1D {
+            store,
+            origin,
+            mirror,
+        }
+    }
+
+    /// Returns the underlying store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    /// Converts an index in this view into an index in the underlying store
+    pub fn map_idx(&self, idx: usize) -> usize {
+        view_coord(self.origin, self.mirror, idx)
+    }
+}
+
+impl<S: FuseRead<usize>> FuseRead<usize> for FuseView1D<S> {
+    fn get_fuse(&self, idx: usize) -> bool {
+        self.store.get_fuse(self.map_idx(idx))
+    }
+}
+
+impl<S: FuseStore<usize>> FuseStore<usize> for FuseView1D<S> {
+    fn set_fuse(&mut self, idx: usize, val: bool) {
+        let idx = self.map_idx(idx);
+        self.store.set_fuse(idx, val)
+    }
+}
+
+/// A view of part of a 2-dimensional fuse store. Coordinate (0, 0) of the view is at `origin` in the underlying store.
+/// Each of the two dimensions can be mirrored independently, in which case increasing coordinates in the view go
+/// towards lower coordinates in the underlying store.
+///
+/// `S` is normally a reference to the underlying store. A view made from a `&` reference can only be read, and a view
+/// made from a `&mut` reference can be both read and written.
+#[derive(Copy, Clone, Debug)]
+pub struct FuseView2D<S> {
+    store: S,
+    origin: (usize, usize),
+    mirror: (bool, bool),
+}
+
+impl<S> FuseView2D<S> {
+    /// Creates a view of `store` with (0, 0) at `origin`
+    pub fn new(store: S, origin: (usize, usize), mirror: (bool, bool)) -> Self {
+        FuseView2D {
+            store,
+            origin,
+            mirror,
+        }
+    }
+
+    /// Returns the underlying store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    /// Converts a coordinate in this view into a coordinate in the underlying store
+    pub fn map_coord(&self, coord: (usize, usize)) -> (usize, usize) {
+        (view_coord(self.origin.0, self.mirror.0, coord.0), view_coord(self.origin.1, self.mirror.1, coord.1))
+    }
+}
+
+impl<S: FuseRead<(usize, usize)>> FuseRead<(usize, usize)> for FuseView2D<S> {
+    fn get_fuse(&self, coord: (usize, usize)) -> bool {
+        self.store.get_fuse(self.map_coord(coord))
+    }
+}
+
+impl<S: FuseStore<(usize, usize)>> FuseStore<(usize, usize)> for FuseView2D<S> {
+    fn set_fuse(&mut self, coord: (usize, usize), val: bool) {
+        let coord = self.map_coord(coord);
+        self.store.set_fuse(coord, val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Store2D {
+        w: usize,
+        v: Vec<bool>,
+    }
+
+    impl FuseRead<(usize, usize)> for Store2D {
+        fn get_fuse(&self, coord: (usize, usize)) -> bool {
+            self.v[coord.1 * self.w + coord.0]
+        }
+    }
+
+    impl FuseStore<(usize, usize)> for Store2D {
+        fn set_fuse(&mut self, coord: (usize, usize), val: bool) {
+            self.v[coord.1 * self.w + coord.0] = val;
+        }
+    }
+
+    #[test]
+    fn view_1d() {
+        let mut fuses = vec![false; 8];
+        FuseView1D::new(&mut fuses[..], 2, false).set_fuse(1, true);
+        FuseView1D::new(&mut fuses[..], 6, true).set_fuse(1, true);
+        assert_eq!(fuses, [false, false, false, true, false, true, false, false]);
+
+        let view = FuseView1D::new(&fuses, 5, true);
+        assert!(view.get_fuse(0));
+        assert!(view.get_fuse(2));
+        assert!(!view.get_fuse(1));
+
+        // Views of views
+        let inner = FuseView1D::new(FuseView1D::new(&fuses, 1, false), 4, true);
+        assert_eq!(inner.map_idx(0), 4);
+        assert!(inner.get_fuse(0));
+        assert!(inner.get_fuse(2));
+    }
+
+    #[test]
+    fn view_2d() {
+        let mut fuses = Store2D {w: 4, v: vec![false; 12]};
+        {
+            let mut view = FuseView2D::new(&mut fuses, (3, 0), (true, false));
+            view.set_fuse((0, 0), true);
+            view.set_fuse((1, 2), true);
+        }
+        assert_eq!(fuses.v, [false, false, false, true,
+                             false, false, false, false,
+                             false, false, true, false]);
+
+        let view = FuseView2D::new(&fuses, (2, 2), (false, true));
+        assert_eq!(view.map_coord((1, 2)), (3, 0));
+        assert!(view.get_fuse((1, 2)));
+        assert!(view.get_fuse((0, 0)));
+        assert!(!view.get_fuse((0, 1)));
+    }
+}
@@ -0,0 +1,614 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Decoding and encoding of GAL16V8 and GAL22V10 fuse maps
+//!
+//! In the AND array, each product term has two columns per input: one for the true form of the input and one for
+//! the complement. An intact fuse (0) connects the column to the product term. A product term with no connected
+//! columns is always true, and a product term with both forms of any input connected is always false.
+//!
+//! Inputs are numbered by their column pair in the AND array. `GAL16V8::input_pins` and `GAL22V10_INPUT_PINS` give
+//! the device pin that drives each of them, and the human-readable dumps write the product terms in terms of pins.
+
+use std::error;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use *;
+
+/// Errors that can occur when decoding a GAL fuse map
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GALError {
+    /// The number of fuses was incorrect for the device
+    WrongFuseCount,
+}
+
+impl error::Error for GALError {
+    fn description(&self) -> &'static str {
+        match *self {
+            GALError::WrongFuseCount => "wrong number of fuses",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            GALError::WrongFuseCount => None,
+        }
+    }
+}
+
+impl fmt::Display for GALError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// One product term (row) of a GAL AND array
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GALProductTerm {
+    /// Whether the true form of each input is connected
+    input: Vec<bool>,
+    /// Whether the complement of each input is connected
+    input_b: Vec<bool>,
+}
+
+impl GALProductTerm {
+    /// Constructs a product term with the given number of inputs where every column is connected (i.e. the term is
+    /// always false). This is the state of an erased device.
+    pub fn new(num_inputs: usize) -> Self {
+        Self {
+            input: vec![true; num_inputs],
+            input_b: vec![true; num_inputs],
+        }
+    }
+
+    /// Returns the number of inputs into this product term
+    pub fn num_inputs(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Returns `true` if the true form of input `i` is used in this product term
+    pub fn get(&self, i: usize) -> bool {
+        self.input[i]
+    }
+
+    /// Returns `true` if the complement of input `i` is used in this product term
+    pub fn get_b(&self, i: usize) -> bool {
+        self.input_b[i]
+    }
+
+    /// Sets whether the true form of input `i` is used in this product term
+    pub fn set(&mut self, i: usize, val: bool) {
+        self.input[i] = val;
+    }
+
+    /// Sets whether the complement of input `i` is used in this product term
+    pub fn set_b(&mut self, i: usize, val: bool) {
+        self.input_b[i] = val;
+    }
+
+    fn from_jed(fuses: &BitVec, row_idx: usize, num_inputs: usize) -> Self {
+        let mut ret = Self::new(num_inputs);
+        for i in 0..num_inputs {
+            ret.input[i] = !fuses.get(row_idx + i * 2);
+            ret.input_b[i] = !fuses.get(row_idx + i * 2 + 1);
+        }
+        ret
+    }
+
+    fn to_jed(&self, fuses: &mut BitVec, row_idx: usize) {
+        for i in 0..self.input.len() {
+            fuses.set(row_idx + i * 2, !self.input[i]);
+            fuses.set(row_idx + i * 2 + 1, !self.input_b[i]);
+        }
+    }
+
+    /// Write the product term as an expression, e.g. `I0 & !I3`
+    pub fn dump_human_readable<W: Write>(&self, writer: W) -> Result<(), io::Error> {
+        self.dump_named(writer, |i| format!("I{}", i))
+    }
+
+    /// Write the product term as an expression using the device pin that drives each input, e.g. `pin2 & !pin19`.
+    /// `pins` gives the pin number for each input, as returned by `input_pins` on the device types.
+    pub fn dump_pins<W: Write>(&self, writer: W, pins: &[u32]) -> Result<(), io::Error> {
+        self.dump_named(writer, |i| format!("pin{}", pins[i]))
+    }
+
+    fn dump_named<W: Write, F: Fn(usize) -> String>(&self, mut writer: W, name: F) -> Result<(), io::Error> {
+        if (0..self.input.len()).any(|i| self.input[i] && self.input_b[i]) {
+            return write!(writer, "0");
+        }
+
+        let mut first = true;
+        for i in 0..self.input.len() {
+            if self.input[i] || self.input_b[i] {
+                if !first {
+                    write!(writer, " & ")?;
+                }
+                first = false;
+                write!(writer, "{}{}", if self.input_b[i] {"!"} else {""}, name(i))?;
+            }
+        }
+        if first {
+            write!(writer, "1")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn signature_from_jed(fuses: &BitVec, idx: usize) -> [u8; 8] {
+    let mut ret = [0u8; 8];
+    for i in 0..64 {
+        if fuses.get(idx + i) {
+            ret[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    ret
+}
+
+fn signature_to_jed(signature: &[u8; 8], fuses: &mut BitVec, idx: usize) {
+    for i in 0..64 {
+        fuses.set(idx + i, signature[i / 8] & (0x80 >> (i % 8)) != 0);
+    }
+}
+
+fn dump_signature<W: Write>(signature: &[u8; 8], mut writer: W) -> Result<(), io::Error> {
+    write!(writer, "signature:")?;
+    for x in signature {
+        write!(writer, " {:02X}", x)?;
+    }
+    write!(writer, "\n")?;
+
+    Ok(())
+}
+
+/// The number of fuses in a GAL16V8
+pub const GAL16V8_FUSE_COUNT: usize = 2194;
+/// The number of inputs into each GAL16V8 product term
+pub const GAL16V8_INPUTS: usize = 16;
+/// The number of product terms per GAL16V8 OLMC
+pub const GAL16V8_TERMS_PER_OLMC: usize = 8;
+
+// Device pin (DIP/SOIC numbering) that drives each input of the AND array, from the logic diagrams in the GAL16V8
+// datasheet. Pins 1 and 11 are the clock and output enable in registered mode. In the other modes they take over the
+// inputs of the outer OLMCs, and in simple mode each of the remaining OLMC inputs is driven by the pin of the
+// neighbouring OLMC (so pins 15 and 16 cannot be used as inputs).
+const GAL16V8_SIMPLE_PINS: [u32; GAL16V8_INPUTS] = [2, 1, 3, 19, 4, 18, 5, 17, 6, 14, 7, 13, 8, 12, 9, 11];
+const GAL16V8_COMPLEX_PINS: [u32; GAL16V8_INPUTS] = [2, 1, 3, 18, 4, 17, 5, 16, 6, 15, 7, 14, 8, 13, 9, 11];
+const GAL16V8_REGISTERED_PINS: [u32; GAL16V8_INPUTS] = [2, 19, 3, 18, 4, 17, 5, 16, 6, 15, 7, 14, 8, 13, 9, 12];
+
+const GAL16V8_XOR_IDX: usize = 2048;
+const GAL16V8_SIG_IDX: usize = 2056;
+const GAL16V8_AC1_IDX: usize = 2120;
+const GAL16V8_PTD_IDX: usize = 2128;
+const GAL16V8_SYN_IDX: usize = 2192;
+const GAL16V8_AC0_IDX: usize = 2193;
+
+/// Overall GAL16V8 operating mode, selected by the SYN and AC0 fuses
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum GAL16V8Mode {
+    /// SYN = 1, AC0 = 0: combinatorial outputs and dedicated inputs only
+    Simple,
+    /// SYN = 1, AC0 = 1: combinatorial outputs with product-term output enables
+    Complex,
+    /// SYN = 0, AC0 = 1: registered outputs are available
+    Registered,
+    /// SYN = 0, AC0 = 0: not a valid configuration
+    Invalid,
+}
+
+impl fmt::Display for GAL16V8Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            GAL16V8Mode::Simple => "simple",
+            GAL16V8Mode::Complex => "complex",
+            GAL16V8Mode::Registered => "registered",
+            GAL16V8Mode::Invalid => "invalid",
+        })
+    }
+}
+
+/// Configuration of one GAL16V8 output logic macrocell
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GAL16V8OLMC {
+    /// XOR fuse. `true` makes the output active-high.
+    pub xor: bool,
+    /// AC1 fuse. Its meaning depends on the overall device mode.
+    pub ac1: bool,
+    /// Product term disable fuses. `true` means that the product term is enabled.
+    pub pt_enable: [bool; GAL16V8_TERMS_PER_OLMC],
+}
+
+/// Decoded GAL16V8 fuse map. OLMC 0 is connected to pin 19 and OLMC 7 is connected to pin 12. Product terms
+/// `8 * n` through `8 * n + 7` belong to OLMC n.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GAL16V8 {
+    /// AND array (64 product terms)
+    pub and_terms: Vec<GALProductTerm>,
+    /// Output logic macrocells
+    pub olmcs: [GAL16V8OLMC; 8],
+    /// User electronic signature
+    pub signature: [u8; 8],
+    /// SYN fuse
+    pub syn: bool,
+    /// AC0 fuse
+    pub ac0: bool,
+}
+
+impl GAL16V8 {
+    /// Returns the overall operating mode
+    pub fn mode(&self) -> GAL16V8Mode {
+        match (self.syn, self.ac0) {
+            (true, false) => GAL16V8Mode::Simple,
+            (true, true) => GAL16V8Mode::Complex,
+            (false, true) => GAL16V8Mode::Registered,
+            (false, false) => GAL16V8Mode::Invalid,
+        }
+    }
+
+    /// Returns the device pin (DIP/SOIC numbering) that drives each of the `GAL16V8_INPUTS` inputs of the AND array.
+    /// This depends on the operating mode, so `None` is returned in the invalid mode.
+    pub fn input_pins(&self) -> Option<&'static [u32; GAL16V8_INPUTS]> {
+        match self.mode() {
+            GAL16V8Mode::Simple => Some(&GAL16V8_SIMPLE_PINS),
+            GAL16V8Mode::Complex => Some(&GAL16V8_COMPLEX_PINS),
+            GAL16V8Mode::Registered => Some(&GAL16V8_REGISTERED_PINS),
+            GAL16V8Mode::Invalid => None,
+        }
+    }
+
+    /// Decodes a fuse map
+    pub fn from_jed(jed: &JEDECFile) -> Result<Self, GALError> {
+        let fuses = &jed.f;
+        if fuses.len() != GAL16V8_FUSE_COUNT {
+            return Err(GALError::WrongFuseCount);
+        }
+
+        let and_terms = (0..64)
+            .map(|i| GALProductTerm::from_jed(fuses, i * GAL16V8_INPUTS * 2, GAL16V8_INPUTS))
+            .collect::<Vec<_>>();
+
+        let mut olmcs = [GAL16V8OLMC {
+            xor: false,
+            ac1: false,
+            pt_enable: [false; GAL16V8_TERMS_PER_OLMC],
+        }; 8];
+        for (i, olmc) in olmcs.iter_mut().enumerate() {
+            olmc.xor = fuses.get(GAL16V8_XOR_IDX + i);
+            olmc.ac1 = fuses.get(GAL16V8_AC1_IDX + i);
+            for j in 0..GAL16V8_TERMS_PER_OLMC {
+                olmc.pt_enable[j] = fuses.get(GAL16V8_PTD_IDX + i * GAL16V8_TERMS_PER_OLMC + j);
+            }
+        }
+
+        Ok(Self {
+            and_terms,
+            olmcs,
+            signature: signature_from_jed(fuses, GAL16V8_SIG_IDX),
+            syn: fuses.get(GAL16V8_SYN_IDX),
+            ac0: fuses.get(GAL16V8_AC0_IDX),
+        })
+    }
+
+    /// Encodes into a fuse map
+    pub fn to_jed(&self) -> JEDECFile {
+        let mut jed = JEDECFile::new(GAL16V8_FUSE_COUNT);
+        jed.dev_name_str = Some("GAL16V8".to_owned());
+
+        for (i, term) in self.and_terms.iter().enumerate() {
+            term.to_jed(&mut jed.f, i * GAL16V8_INPUTS * 2);
+        }
+
+        for i in 0..8 {
+            jed.f.set(GAL16V8_XOR_IDX + i, self.olmcs[i].xor);
+            jed.f.set(GAL16V8_AC1_IDX + i, self.olmcs[i].ac1);
+            for j in 0..GAL16V8_TERMS_PER_OLMC {
+                jed.f.set(GAL16V8_PTD_IDX + i * GAL16V8_TERMS_PER_OLMC + j, self.olmcs[i].pt_enable[j]);
+            }
+        }
+
+        signature_to_jed(&self.signature, &mut jed.f, GAL16V8_SIG_IDX);
+        jed.f.set(GAL16V8_SYN_IDX, self.syn);
+        jed.f.set(GAL16V8_AC0_IDX, self.ac0);
+
+        jed
+    }
+
+    /// Dump a human-readable explanation of the fuse map to the given `writer` object.
+    pub fn dump_human_readable<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write!(writer, "GAL16V8 dump\n")?;
+        write!(writer, "mode: {}\n", self.mode())?;
+        dump_signature(&self.signature, &mut writer)?;
+
+        for i in 0..8 {
+            let olmc = &self.olmcs[i];
+            let olmc_mode = match (self.mode(), olmc.ac1) {
+                (GAL16V8Mode::Simple, false) => "combinatorial output",
+                (GAL16V8Mode::Simple, true) => "input",
+                (GAL16V8Mode::Complex, true) => "combinatorial I/O",
+                (GAL16V8Mode::Registered, false) => "registered output",
+                (GAL16V8Mode::Registered, true) => "combinatorial I/O",
+                _ => "invalid",
+            };
+
+            write!(writer, "\n")?;
+            write!(writer, "OLMC {} (pin {}): {}, active {}\n", i, 19 - i, olmc_mode,
+                if olmc.xor {"high"} else {"low"})?;
+            for j in 0..GAL16V8_TERMS_PER_OLMC {
+                let term_idx = i * GAL16V8_TERMS_PER_OLMC + j;
+                write!(writer, "term {}{}: ", term_idx, if olmc.pt_enable[j] {""} else {" (disabled)"})?;
+                if let Some(pins) = self.input_pins() {
+                    self.and_terms[term_idx].dump_pins(&mut writer, pins)?;
+                } else {
+                    self.and_terms[term_idx].dump_human_readable(&mut writer)?;
+                }
+                write!(writer, "\n")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of fuses in a GAL22V10
+pub const GAL22V10_FUSE_COUNT: usize = 5892;
+/// The number of inputs into each GAL22V10 product term
+pub const GAL22V10_INPUTS: usize = 22;
+/// The number of sum-of-products terms of each GAL22V10 OLMC (not including the output enable term)
+pub const GAL22V10_TERMS_PER_OLMC: [usize; 10] = [8, 10, 12, 14, 16, 16, 14, 12, 10, 8];
+
+/// The device pin (DIP/SOIC numbering) that drives each input of the GAL22V10 AND array, from the logic diagram in
+/// the GAL22V10 datasheet. The dedicated inputs alternate with the feedback from the OLMCs, starting with pin 1 and
+/// the OLMC on pin 23.
+pub const GAL22V10_INPUT_PINS: [u32; GAL22V10_INPUTS] =
+    [1, 23, 2, 22, 3, 21, 4, 20, 5, 19, 6, 18, 7, 17, 8, 16, 9, 15, 10, 14, 11, 13];
+
+const GAL22V10_ROWS: usize = 132;
+const GAL22V10_S_IDX: usize = 5808;
+const GAL22V10_SIG_IDX: usize = 5828;
+
+/// Configuration of one GAL22V10 output logic macrocell
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GAL22V10OLMC {
+    /// S0 fuse. `true` makes the output active-high.
+    pub s0: bool,
+    /// S1 fuse. `true` makes the output combinatorial, `false` makes it registered.
+    pub s1: bool,
+}
+
+/// Decoded GAL22V10 fuse map. OLMC 0 is connected to pin 23 and OLMC 9 is connected to pin 14.
+///
+/// Product term 0 is the asynchronous reset term. Then, for each OLMC in order, there is one output enable term
+/// followed by the number of terms given in `GAL22V10_TERMS_PER_OLMC`. The last product term is the synchronous
+/// preset term.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GAL22V10 {
+    /// AND array (132 product terms)
+    pub and_terms: Vec<GALProductTerm>,
+    /// Output logic macrocells
+    pub olmcs: [GAL22V10OLMC; 10],
+    /// User electronic signature
+    pub signature: [u8; 8],
+}
+
+impl GAL22V10 {
+    /// Returns the index of the output enable term of the given OLMC. The terms for the sum-of-products follow it.
+    pub fn olmc_first_term(olmc: usize) -> usize {
+        1 + GAL22V10_TERMS_PER_OLMC[..olmc].iter().map(|x| x + 1).sum::<usize>()
+    }
+
+    /// Decodes a fuse map
+    pub fn from_jed(jed: &JEDECFile) -> Result<Self, GALError> {
+        let fuses = &jed.f;
+        if fuses.len() != GAL22V10_FUSE_COUNT {
+            return Err(GALError::WrongFuseCount);
+        }
+
+        let and_terms = (0..GAL22V10_ROWS)
+            .map(|i| GALProductTerm::from_jed(fuses, i * GAL22V10_INPUTS * 2, GAL22V10_INPUTS))
+            .collect::<Vec<_>>();
+
+        let mut olmcs = [GAL22V10OLMC {
+            s0: false,
+            s1: false,
+        }; 10];
+        for (i, olmc) in olmcs.iter_mut().enumerate() {
+            olmc.s0 = fuses.get(GAL22V10_S_IDX + i * 2);
+            olmc.s1 = fuses.get(GAL22V10_S_IDX + i * 2 + 1);
+        }
+
+        Ok(Self {
+            and_terms,
+            olmcs,
+            signature: signature_from_jed(fuses, GAL22V10_SIG_IDX),
+        })
+    }
+
+    /// Encodes into a fuse map
+    pub fn to_jed(&self) -> JEDECFile {
+        let mut jed = JEDECFile::new(GAL22V10_FUSE_COUNT);
+        jed.dev_name_str = Some("GAL22V10".to_owned());
+
+        for (i, term) in self.and_terms.iter().enumerate() {
+            term.to_jed(&mut jed.f, i * GAL22V10_INPUTS * 2);
+        }
+
+        for i in 0..10 {
+            jed.f.set(GAL22V10_S_IDX + i * 2, self.olmcs[i].s0);
+            jed.f.set(GAL22V10_S_IDX + i * 2 + 1, self.olmcs[i].s1);
+        }
+
+        signature_to_jed(&self.signature, &mut jed.f, GAL22V10_SIG_IDX);
+
+        jed
+    }
+
+    /// Dump a human-readable explanation of the fuse map to the given `writer` object.
+    pub fn dump_human_readable<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write!(writer, "GAL22V10 dump\n")?;
+        dump_signature(&self.signature, &mut writer)?;
+        write!(writer, "async reset: ")?;
+        self.and_terms[0].dump_pins(&mut writer, &GAL22V10_INPUT_PINS)?;
+        write!(writer, "\n")?;
+        write!(writer, "sync preset: ")?;
+        self.and_terms[GAL22V10_ROWS - 1].dump_pins(&mut writer, &GAL22V10_INPUT_PINS)?;
+        write!(writer, "\n")?;
+
+        for (i, olmc) in self.olmcs.iter().enumerate() {
+            let first_term = Self::olmc_first_term(i);
+
+            write!(writer, "\n")?;
+            write!(writer, "OLMC {} (pin {}): {}, active {}\n", i, 23 - i,
+                if olmc.s1 {"combinatorial"} else {"registered"}, if olmc.s0 {"high"} else {"low"})?;
+            write!(writer, "output enable: ")?;
+            self.and_terms[first_term].dump_pins(&mut writer, &GAL22V10_INPUT_PINS)?;
+            write!(writer, "\n")?;
+            for term_idx in first_term + 1..first_term + 1 + GAL22V10_TERMS_PER_OLMC[i] {
+                write!(writer, "term {}: ", term_idx)?;
+                self.and_terms[term_idx].dump_pins(&mut writer, &GAL22V10_INPUT_PINS)?;
+                write!(writer, "\n")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gal16v8_roundtrip() {
+        let mut jed = JEDECFile::new(GAL16V8_FUSE_COUNT);
+        for i in 0..GAL16V8_FUSE_COUNT {
+            jed.f.set(i, i % 3 == 0 || i % 7 == 0);
+        }
+
+        let gal = GAL16V8::from_jed(&jed).unwrap();
+        assert_eq!(gal.to_jed().f, jed.f);
+    }
+
+    #[test]
+    fn gal16v8_decode() {
+        let mut jed = JEDECFile::new(GAL16V8_FUSE_COUNT);
+        // Term 0 is I0 & !I2
+        for i in 0..32 {
+            jed.f.set(i, i != 0 && i != 5);
+        }
+        jed.f.set(GAL16V8_XOR_IDX, true);
+        jed.f.set(GAL16V8_PTD_IDX, true);
+        jed.f.set(GAL16V8_SIG_IDX + 1, true);
+        jed.f.set(GAL16V8_AC0_IDX, true);
+
+        let gal = GAL16V8::from_jed(&jed).unwrap();
+        assert_eq!(gal.mode(), GAL16V8Mode::Registered);
+        assert_eq!(gal.signature, [0x40, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(gal.olmcs[0].xor && !gal.olmcs[1].xor);
+        assert!(gal.olmcs[0].pt_enable[0] && !gal.olmcs[0].pt_enable[1]);
+
+        let mut term = Vec::new();
+        gal.and_terms[0].dump_human_readable(&mut term).unwrap();
+        assert_eq!(term, b"I0 & !I2");
+        let mut term = Vec::new();
+        gal.and_terms[1].dump_human_readable(&mut term).unwrap();
+        assert_eq!(term, b"0");
+    }
+
+    #[test]
+    fn gal16v8_input_pins() {
+        let mut gal = GAL16V8::from_jed(&JEDECFile::new(GAL16V8_FUSE_COUNT)).unwrap();
+        for &(syn, ac0, unused) in &[(true, false, [10, 15, 16, 20]), (true, true, [10, 12, 19, 20]),
+                                     (false, true, [1, 10, 11, 20])] {
+            gal.syn = syn;
+            gal.ac0 = ac0;
+            let mut pins = gal.input_pins().unwrap().to_vec();
+            pins.extend_from_slice(&unused);
+            pins.sort();
+            assert_eq!(pins, (1..21).collect::<Vec<_>>());
+        }
+        gal.syn = false;
+        gal.ac0 = false;
+        assert_eq!(gal.input_pins(), None);
+
+        // Term 0 is I0 & !I1, which is pin 2 and either pin 19 (registered) or pin 1 (simple and complex)
+        gal.and_terms[0] = GALProductTerm::new(GAL16V8_INPUTS);
+        for i in 0..GAL16V8_INPUTS {
+            gal.and_terms[0].set(i, i == 0);
+            gal.and_terms[0].set_b(i, i == 1);
+        }
+        gal.olmcs[0].pt_enable[0] = true;
+        gal.ac0 = true;
+        let mut dump = Vec::new();
+        gal.dump_human_readable(&mut dump).unwrap();
+        assert!(String::from_utf8(dump).unwrap().contains("\nterm 0: pin2 & !pin19\n"));
+        gal.syn = true;
+        let mut dump = Vec::new();
+        gal.dump_human_readable(&mut dump).unwrap();
+        assert!(String::from_utf8(dump).unwrap().contains("\nterm 0: pin2 & !pin1\n"));
+    }
+
+    #[test]
+    fn gal22v10_roundtrip() {
+        let mut jed = JEDECFile::new(GAL22V10_FUSE_COUNT);
+        for i in 0..GAL22V10_FUSE_COUNT {
+            jed.f.set(i, i % 5 == 0 || i % 11 == 0);
+        }
+
+        let gal = GAL22V10::from_jed(&jed).unwrap();
+        assert_eq!(gal.to_jed().f, jed.f);
+        assert_eq!(GAL22V10::olmc_first_term(9) + 1 + 8, GAL22V10_ROWS - 1);
+    }
+
+    #[test]
+    fn gal22v10_input_pins() {
+        // Every pin except ground (12) and power (24) drives exactly one input
+        let mut pins = GAL22V10_INPUT_PINS.to_vec();
+        pins.extend_from_slice(&[12, 24]);
+        pins.sort();
+        assert_eq!(pins, (1..25).collect::<Vec<_>>());
+
+        let mut jed = JEDECFile::new(GAL22V10_FUSE_COUNT);
+        for i in 0..GAL22V10_FUSE_COUNT {
+            jed.f.set(i, true);
+        }
+        // The async reset term is pin 1 & !pin 13
+        jed.f.set(0, false);
+        jed.f.set(43, false);
+        let gal = GAL22V10::from_jed(&jed).unwrap();
+        let mut dump = Vec::new();
+        gal.dump_human_readable(&mut dump).unwrap();
+        assert!(String::from_utf8(dump).unwrap().contains("\nasync reset: pin1 & !pin13\n"));
+    }
+
+    #[test]
+    fn gal_wrong_fuse_count() {
+        assert_eq!(GAL16V8::from_jed(&JEDECFile::new(100)), Err(GALError::WrongFuseCount));
+        assert_eq!(GAL22V10::from_jed(&JEDECFile::new(GAL16V8_FUSE_COUNT)), Err(GALError::WrongFuseCount));
+    }
+}
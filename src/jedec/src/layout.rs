@@ -0,0 +1,127 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Types describing where the fields of a structure are stored in a fuse array. Tables of these are generated by the
+//! `BitTwiddler` derive in the `bittwiddler` crate.
+
+/// Where one bit of a field is stored
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum FuseLayoutLoc {
+    /// An index into a 1-D fuse array (e.g. a .jed file)
+    Fuse(usize),
+    /// An (x, y) coordinate in a 2-D fuse array
+    Coord(usize, usize),
+    /// The bit is not stored in any fuse and always has the given value
+    Const(bool),
+}
+
+/// Describes one bit of a field
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FuseLayoutBit {
+    /// Where the bit is stored
+    pub loc: FuseLayoutLoc,
+    /// Whether the value stored in the fuse is the inverse of the value of the bit
+    pub invert: bool,
+}
+
+/// Describes where one field of a structure is stored for one particular layout ("instance")
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FuseLayoutField {
+    /// Name of the layout, e.g. `crbit32`
+    pub instance: &'static str,
+    /// Name of the field. For a tuple struct, this is the index of the field. For an enum, this is empty.
+    pub field: &'static str,
+    /// Locations of each bit of the field. For fields that are not `bool`, the order matches the bits of the
+    /// `encode`/`decode` functions of the field type.
+    pub bits: &'static [FuseLayoutBit],
+    /// Whether the locations are absolute. If they are not, they are offsets from a starting coordinate.
+    pub abs: bool,
+    /// Whether the instance can be mirrored in each dimension. Whether it actually is mirrored depends on where it
+    /// is placed and is passed to `fuse_idx` and `coord`.
+    pub mirrorable: [bool; 2],
+}
+
+impl FuseLayoutBit {
+    /// Returns the 1-D fuse index of this bit given a starting coordinate and whether the instance is mirrored there,
+    /// or `None` if this bit is not stored in a 1-D fuse array. `start` is ignored for absolute layouts, and `mirror`
+    /// is ignored if the instance is not mirrorable.
+    pub fn fuse_idx(&self, field: &FuseLayoutField, start: usize, mirror: bool) -> Option<usize> {
+        match self.loc {
+            FuseLayoutLoc::Fuse(i) => Some(offset_coord(field.abs, field.mirrorable[0] && mirror, start, i)),
+            _ => None,
+        }
+    }
+
+    /// Returns the 2-D fuse coordinate of this bit given a starting coordinate and whether the instance is mirrored
+    /// there in each dimension, or `None` if this bit is not stored in a 2-D fuse array. `start` is ignored for
+    /// absolute layouts, and `mirror` is ignored for dimensions that the instance cannot be mirrored in.
+    pub fn coord(&self, field: &FuseLayoutField, start: (usize, usize), mirror: (bool, bool))
+        -> Option<(usize, usize)> {
+
+        match self.loc {
+            FuseLayoutLoc::Coord(x, y) =>
+                Some((offset_coord(field.abs, field.mirrorable[0] && mirror.0, start.0, x),
+                      offset_coord(field.abs, field.mirrorable[1] && mirror.1, start.1, y))),
+            _ => None,
+        }
+    }
+}
+
+fn offset_coord(abs: bool, mirror: bool, start: usize, offset: usize) -> usize {
+    if abs {
+        offset
+    } else if mirror {
+        start - offset
+    } else {
+        start + offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_coords() {
+        static BITS: [FuseLayoutBit; 2] = [
+            FuseLayoutBit {loc: FuseLayoutLoc::Coord(1, 2), invert: false},
+            FuseLayoutBit {loc: FuseLayoutLoc::Const(true), invert: true},
+        ];
+        let field = FuseLayoutField {
+            instance: "test",
+            field: "x",
+            bits: &BITS,
+            abs: false,
+            mirrorable: [true, false],
+        };
+
+        assert_eq!(BITS[0].coord(&field, (10, 10), (false, false)), Some((11, 12)));
+        assert_eq!(BITS[0].coord(&field, (10, 10), (true, false)), Some((9, 12)));
+        // Not mirrorable in y
+        assert_eq!(BITS[0].coord(&field, (10, 10), (true, true)), Some((9, 12)));
+        assert_eq!(BITS[0].fuse_idx(&field, 10, false), None);
+        assert_eq!(BITS[1].coord(&field, (10, 10), (false, false)), None);
+    }
+}
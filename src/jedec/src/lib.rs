@@ -24,6 +24,13 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 //! JEDEC programming file format parser and writer
+//!
+//! # Upgrading from 0.0.2
+//!
+//! `JEDECFile::f` is now a bit-packed `BitVec` instead of a `Vec<bool>`. Reading fuses with `f[i]`, `f.get(i)`,
+//! `f.len()` or `f.iter()` works as before, but a packed bit cannot be borrowed mutably, so `f[i] = val` no longer
+//! compiles and has to be written as `f.set(i, val)`. Use `f.to_vec()` and `BitVec::from(v)` to convert to and from
+//! a `Vec<bool>`.
 
 use std::error;
 use std::error::Error;
@@ -34,9 +41,34 @@ use std::num;
 use std::num::Wrapping;
 use std::str;
 
-/// Errors that can occur when parsing a .jed file
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+mod bitvec;
+pub use bitvec::{BitVec, BitVecIter, BitVecOnesIter};
+
+mod fusestore;
+pub use fusestore::{FuseRead, FuseStore, FuseView1D, FuseView2D};
+
+mod gal;
+pub use gal::{GALError, GALProductTerm, GAL16V8, GAL16V8Mode, GAL16V8OLMC, GAL22V10, GAL22V10OLMC,
+              GAL16V8_FUSE_COUNT, GAL16V8_INPUTS, GAL16V8_TERMS_PER_OLMC,
+              GAL22V10_FUSE_COUNT, GAL22V10_INPUTS, GAL22V10_INPUT_PINS, GAL22V10_TERMS_PER_OLMC};
+
+mod layout;
+pub use layout::{FuseLayoutLoc, FuseLayoutBit, FuseLayoutField};
+
+mod preserving;
+pub use preserving::{PreservedJEDECFile};
+
+mod testvec;
+pub use testvec::{TestCondition, TestVector, TestVectors};
+
+/// The different kinds of errors that can occur when parsing a .jed file
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum JedParserError {
+pub enum JedParserErrorKind {
     /// No STX byte found
     MissingSTX,
     /// No ETX byte found
@@ -61,41 +93,41 @@ pub enum JedParserError {
     UnrecognizedField,
 }
 
-impl error::Error for JedParserError {
+impl error::Error for JedParserErrorKind {
     fn description(&self) -> &'static str {
         match *self {
-            JedParserError::MissingSTX => "STX not found",
-            JedParserError::MissingETX => "ETX not found",
-            JedParserError::InvalidUtf8(_) => "invalid utf8 character",
-            JedParserError::InvalidCharacter => "invalid character in field",
-            JedParserError::UnexpectedEnd => "unexpected end of file",
-            JedParserError::BadFileChecksum => "invalid file checksum",
-            JedParserError::BadFuseChecksum => "invalid fuse checksum",
-            JedParserError::InvalidFuseIndex => "invalid fuse index value",
-            JedParserError::MissingQF => "missing QF field",
-            JedParserError::MissingF => "missing F field",
-            JedParserError::UnrecognizedField => "unrecognized field",
+            JedParserErrorKind::MissingSTX => "STX not found",
+            JedParserErrorKind::MissingETX => "ETX not found",
+            JedParserErrorKind::InvalidUtf8(_) => "invalid utf8 character",
+            JedParserErrorKind::InvalidCharacter => "invalid character in field",
+            JedParserErrorKind::UnexpectedEnd => "unexpected end of file",
+            JedParserErrorKind::BadFileChecksum => "invalid file checksum",
+            JedParserErrorKind::BadFuseChecksum => "invalid fuse checksum",
+            JedParserErrorKind::InvalidFuseIndex => "invalid fuse index value",
+            JedParserErrorKind::MissingQF => "missing QF field",
+            JedParserErrorKind::MissingF => "missing F field",
+            JedParserErrorKind::UnrecognizedField => "unrecognized field",
         }
     }
 
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            JedParserError::MissingSTX => None,
-            JedParserError::MissingETX => None,
-            JedParserError::InvalidUtf8(ref err) => Some(err),
-            JedParserError::InvalidCharacter => None,
-            JedParserError::UnexpectedEnd => None,
-            JedParserError::BadFileChecksum => None,
-            JedParserError::BadFuseChecksum => None,
-            JedParserError::InvalidFuseIndex => None,
-            JedParserError::MissingQF => None,
-            JedParserError::MissingF => None,
-            JedParserError::UnrecognizedField => None,
+            JedParserErrorKind::MissingSTX => None,
+            JedParserErrorKind::MissingETX => None,
+            JedParserErrorKind::InvalidUtf8(ref err) => Some(err),
+            JedParserErrorKind::InvalidCharacter => None,
+            JedParserErrorKind::UnexpectedEnd => None,
+            JedParserErrorKind::BadFileChecksum => None,
+            JedParserErrorKind::BadFuseChecksum => None,
+            JedParserErrorKind::InvalidFuseIndex => None,
+            JedParserErrorKind::MissingQF => None,
+            JedParserErrorKind::MissingF => None,
+            JedParserErrorKind::UnrecognizedField => None,
         }
     }
 }
 
-impl fmt::Display for JedParserError {
+impl fmt::Display for JedParserErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(source) = self.source() {
             write!(f, "{}: {}", self.description(), source)
@@ -105,15 +137,62 @@ impl fmt::Display for JedParserError {
     }
 }
 
-impl From<str::Utf8Error> for JedParserError {
+impl From<str::Utf8Error> for JedParserErrorKind {
     fn from(err: str::Utf8Error) -> Self {
-        JedParserError::InvalidUtf8(err)
+        JedParserErrorKind::InvalidUtf8(err)
     }
 }
 
-impl From<num::ParseIntError> for JedParserError {
+impl From<num::ParseIntError> for JedParserErrorKind {
     fn from(_: num::ParseIntError) -> Self {
-        JedParserError::InvalidCharacter
+        JedParserErrorKind::InvalidCharacter
+    }
+}
+
+/// Errors that can occur when parsing a .jed file, along with where in the file they occurred
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct JedParserError {
+    /// What went wrong
+    pub kind: JedParserErrorKind,
+    /// Byte offset into the file
+    pub offset: usize,
+    /// Line number (starting from 1)
+    pub line: usize,
+    /// Column number in bytes (starting from 1)
+    pub col: usize,
+}
+
+impl JedParserError {
+    fn new(kind: JedParserErrorKind, in_bytes: &[u8], offset: usize) -> Self {
+        let before = &in_bytes[..offset];
+        let line = before.iter().filter(|&&x| x == b'\n').count() + 1;
+        let col = match before.iter().rposition(|&x| x == b'\n') {
+            Some(nl_pos) => offset - nl_pos,
+            None => offset + 1,
+        };
+
+        Self {
+            kind,
+            offset,
+            line,
+            col,
+        }
+    }
+}
+
+impl error::Error for JedParserError {
+    fn description(&self) -> &str {
+        self.kind.description()
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl fmt::Display for JedParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.kind, self.line, self.col)
     }
 }
 
@@ -130,32 +209,68 @@ const ETX: u8 = 0x03;
 /// Struct representing a JEDEC programming file. Primarily consists of a fuse array, and also contains some other
 /// miscellaneous fields.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct JEDECFile {
-    /// Fuse array
-    pub f: Vec<bool>,
+    /// Fuse array. Since jedec 0.0.3 this is a packed `BitVec` rather than a `Vec<bool>`, so fuses are read with
+    /// `f[i]` or `f.get(i)` but must be written with `f.set(i, val)` instead of `f[i] = val`.
+    pub f: BitVec,
     /// Possibly contains a device name
     pub dev_name_str: Option<String>,
+    /// State of the security fuse (`G` field), if the file specifies one. `Some(true)` means that readback of the
+    /// device should be disabled after programming.
+    pub secure: Option<bool>,
+    /// User data bits (`U` field, e.g. a USERCODE), if the file specifies them. Bits are stored in the same order they
+    /// appear in the file (most significant bit first).
+    pub usercode: Option<Vec<bool>>,
+    /// Test vectors, if the file contains any `P`, `X`, or `V` fields
+    pub test_vectors: Option<TestVectors>,
 }
 
 impl JEDECFile {
     /// Reads .jed file and outputs the fuses as an array of booleans and optional device name
     pub fn from_bytes(in_bytes: &[u8]) -> Result<Self, JedParserError> {
-        let mut fuse_csum = Wrapping(0u16);
+        Self::parse(in_bytes, None)
+    }
+
+    /// Reads .jed file like `from_bytes`, but treats problems that do not prevent the fuses from being read (such as
+    /// an incorrect or missing file checksum, an incorrect fuse checksum, or an unrecognized field) as warnings
+    /// rather than errors. The warnings are returned along with the file.
+    pub fn from_bytes_lenient(in_bytes: &[u8]) -> Result<(Self, Vec<JedParserError>), JedParserError> {
+        let mut warnings = Vec::new();
+        let ret = Self::parse(in_bytes, Some(&mut warnings))?;
+        Ok((ret, warnings))
+    }
+
+    fn parse(in_bytes: &[u8], mut warnings: Option<&mut Vec<JedParserError>>) -> Result<Self, JedParserError> {
         let mut fuse_expected_csum = None;
         let mut file_csum = Wrapping(0u16);
         let mut num_fuses: u32 = 0;
         let mut device = None;
+        let mut secure = None;
+        let mut usercode = None;
+        let mut test_vectors: Option<TestVectors> = None;
         let mut jed_stx: usize = 0;
         let mut jed_etx: usize;
         let mut fuses_ternary = vec![];
         let mut default_fuse = Ternary::Undef;
 
+        // Non-fatal problems are errors in strict mode and warnings in lenient mode
+        let mut report = |err: JedParserErrorKind, offset: usize| -> Result<(), JedParserError> {
+            let err = JedParserError::new(err, in_bytes, offset);
+            if let Some(ref mut warnings) = warnings {
+                warnings.push(err);
+                Ok(())
+            } else {
+                Err(err)
+            }
+        };
+
         // Find STX
-        while in_bytes[jed_stx] != STX {
+        while jed_stx < in_bytes.len() && in_bytes[jed_stx] != STX {
             jed_stx += 1;
-            if jed_stx >= in_bytes.len() {
-                return Err(JedParserError::MissingSTX);
-            }
+        }
+        if jed_stx >= in_bytes.len() {
+            return Err(JedParserError::new(JedParserErrorKind::MissingSTX, in_bytes, in_bytes.len()));
         }
 
         // Checksum and find ETX
@@ -164,7 +279,7 @@ impl JEDECFile {
             file_csum += Wrapping(in_bytes[jed_etx] as u16);
             jed_etx += 1;
             if jed_etx >= in_bytes.len() {
-                return Err(JedParserError::MissingETX);
+                return Err(JedParserError::new(JedParserErrorKind::MissingETX, in_bytes, in_bytes.len()));
             }
         }
         // Add the ETX to the checksum too
@@ -172,20 +287,34 @@ impl JEDECFile {
 
         // Check the checksum
         if jed_etx + 4 >= in_bytes.len() {
-            return Err(JedParserError::UnexpectedEnd);
-        }
-        let csum_expected = &in_bytes[jed_etx + 1..jed_etx + 5];
-        let csum_expected = str::from_utf8(csum_expected)?;
-        let csum_expected = u16::from_str_radix(csum_expected, 16)?;
-        if csum_expected != 0 && csum_expected != file_csum.0 {
-            return Err(JedParserError::BadFileChecksum);
+            report(JedParserErrorKind::UnexpectedEnd, in_bytes.len())?;
+        } else {
+            let csum_expected = &in_bytes[jed_etx + 1..jed_etx + 5];
+            let csum_expected = str::from_utf8(csum_expected).map_err(JedParserErrorKind::from)
+                .and_then(|x| u16::from_str_radix(x, 16).map_err(JedParserErrorKind::from));
+            match csum_expected {
+                Ok(csum_expected) => {
+                    if csum_expected != 0 && csum_expected != file_csum.0 {
+                        report(JedParserErrorKind::BadFileChecksum, jed_etx + 1)?;
+                    }
+                },
+                Err(err) => report(err, jed_etx + 1)?,
+            }
         }
 
         // Make a str object out of the body
-        let jed_body = str::from_utf8(&in_bytes[jed_stx + 1..jed_etx])?;
+        let jed_body = match str::from_utf8(&in_bytes[jed_stx + 1..jed_etx]) {
+            Ok(x) => x,
+            Err(err) => return Err(JedParserError::new(JedParserErrorKind::InvalidUtf8(err), in_bytes,
+                jed_stx + 1 + err.valid_up_to())),
+        };
 
         // Ready to parse each line
+        let mut field_offset = jed_stx + 1;
         for l in jed_body.split('*') {
+            let l_offset = field_offset + l.len() - l.trim_start_matches(|c| c == ' ' || c == '\r' || c == '\n').len();
+            field_offset += l.len() + 1;
+
             let l = l.trim_matches(|c| c == ' ' || c == '\r' || c == '\n');
             if l.len() == 0 {
                 // FIXME: Should we do something else here?
@@ -193,87 +322,152 @@ impl JEDECFile {
                 continue;
             }
 
+            let field_result = (|| -> Result<(), JedParserErrorKind> {
             // Now we can look at the first byte to figure out what we have
-            match l.chars().next().unwrap() {
-                'J' => {}, // TODO: "Official" device type
-                'G' => {}, // TODO: Security fuse
-                'B' | 'I' | 'K' | 'M' | 'O' | 'W' | 'Y' | 'Z' => {}, // Explicitly reserved in spec, ignore
-                'D' => {}, // Obsolete
-                'E' | 'U' => {}, // TODO: Extra fuses, unsupported for now
-                'X' | 'V' | 'P' | 'S' | 'R' | 'T' | 'A' => {}, // Testing-related, no intent to support for now
-                'F' => {
-                    // Default state
-                    let (_, default_state_str) = l.split_at(1);
-                    default_fuse = match default_state_str {
-                        "0" => Ternary::Zero,
-                        "1" => Ternary::One,
-                        _ => return Err(JedParserError::InvalidCharacter)
-                    }
-                },
-                'N' => {
-                    // Notes; we want to extract N DEVICE but otherwise ignore it
-                    let note_pieces = l.split(|c| c == ' ' || c == '\r' || c == '\n').collect::<Vec<_>>();
-                    if note_pieces.len() == 3 && note_pieces[1] == "DEVICE" {
-                        device = Some(note_pieces[2].to_owned());
-                    }
-                },
-                'Q' => {
-                    // Look for QF
-                    if l.starts_with("QF") {
-                        let (_, num_fuses_str) = l.split_at(2);
-                        num_fuses = u32::from_str_radix(num_fuses_str, 10)?;
-                        fuses_ternary.reserve(num_fuses as usize);
-                        for _ in 0..num_fuses {
-                            fuses_ternary.push(Ternary::Undef);
+                match l.chars().next().unwrap() {
+                    'J' => {}, // TODO: "Official" device type
+                    'G' => {
+                        // Security fuse
+                        let (_, secure_str) = l.split_at(1);
+                        secure = Some(match secure_str {
+                            "0" => false,
+                            "1" => true,
+                            _ => return Err(JedParserErrorKind::InvalidCharacter)
+                        });
+                    },
+                    'B' | 'I' | 'K' | 'M' | 'O' | 'W' | 'Y' | 'Z' => {}, // Explicitly reserved in spec, ignore
+                    'D' => {}, // Obsolete
+                    'E' => {}, // TODO: Extra fuses, unsupported for now
+                    'U' => {
+                        // User data; can be in ASCII, hex, or binary form
+                        let mut bits = Vec::new();
+                        if l.starts_with("UA") {
+                            let (_, usercode_str) = l.split_at(2);
+                            for c in usercode_str.bytes() {
+                                for i in (0..8).rev() {
+                                    bits.push(c & (1 << i) != 0);
+                                }
+                            }
+                        } else if l.starts_with("UH") {
+                            let (_, usercode_str) = l.split_at(2);
+                            for c in usercode_str.chars() {
+                                let nybble = match c.to_digit(16) {
+                                    Some(x) => x,
+                                    None => return Err(JedParserErrorKind::InvalidCharacter),
+                                };
+                                for i in (0..4).rev() {
+                                    bits.push(nybble & (1 << i) != 0);
+                                }
+                            }
+                        } else {
+                            let (_, usercode_str) = l.split_at(1);
+                            for c in usercode_str.chars() {
+                                match c {
+                                    '0' => bits.push(false),
+                                    '1' => bits.push(true),
+                                    ' ' | '\r' | '\n' => {}, // Do nothing
+                                    _ => return Err(JedParserErrorKind::InvalidCharacter),
+                                }
+                            }
+                        }
+                        usercode = Some(bits);
+                    },
+                    'S' | 'R' | 'T' | 'A' => {}, // Testing-related, no intent to support for now
+                    'P' => {
+                        // Pin list for test vectors
+                        test_vectors.get_or_insert_with(TestVectors::default).parse_p_field(l)?;
+                    },
+                    'X' => {
+                        // Default test condition
+                        test_vectors.get_or_insert_with(TestVectors::default).parse_x_field(l)?;
+                    },
+                    'V' => {
+                        // Test vector
+                        test_vectors.get_or_insert_with(TestVectors::default).parse_v_field(l)?;
+                    },
+                    'F' => {
+                        // Default state
+                        let (_, default_state_str) = l.split_at(1);
+                        default_fuse = match default_state_str {
+                            "0" => Ternary::Zero,
+                            "1" => Ternary::One,
+                            _ => return Err(JedParserErrorKind::InvalidCharacter)
+                        }
+                    },
+                    'N' => {
+                        // Notes; we want to extract N DEVICE but otherwise ignore it
+                        let note_pieces = l.split(|c| c == ' ' || c == '\r' || c == '\n').collect::<Vec<_>>();
+                        if note_pieces.len() == 3 && note_pieces[1] == "DEVICE" {
+                            device = Some(note_pieces[2].to_owned());
+                        }
+                    },
+                    'Q' => {
+                        // Look for QF
+                        if l.starts_with("QF") {
+                            let (_, num_fuses_str) = l.split_at(2);
+                            num_fuses = u32::from_str_radix(num_fuses_str, 10)?;
+                            fuses_ternary.reserve(num_fuses as usize);
+                            for _ in 0..num_fuses {
+                                fuses_ternary.push(Ternary::Undef);
+                            }
+                        }
+                    },
+                    'L' => {
+                        // A set of fuses
+                        if num_fuses == 0 {
+                            return Err(JedParserErrorKind::MissingQF);
                         }
-                    }
-                },
-                'L' => {
-                    // A set of fuses
-                    if num_fuses == 0 {
-                        return Err(JedParserError::MissingQF);
-                    }
 
-                    let mut fuse_field_splitter = l.splitn(2, |c| c == ' ' || c == '\r' || c == '\n');
-                    let fuse_idx_str = fuse_field_splitter.next();
-                    let (_, fuse_idx_str) = fuse_idx_str.unwrap().split_at(1);
-                    let mut fuse_idx = u32::from_str_radix(fuse_idx_str, 10)?;
+                        let mut fuse_field_splitter = l.splitn(2, |c| c == ' ' || c == '\r' || c == '\n');
+                        let fuse_idx_str = fuse_field_splitter.next();
+                        let (_, fuse_idx_str) = fuse_idx_str.unwrap().split_at(1);
+                        let mut fuse_idx = u32::from_str_radix(fuse_idx_str, 10)?;
 
-                    let fuse_bits_part = fuse_field_splitter.next();
-                    if fuse_bits_part.is_none() {
-                        return Err(JedParserError::InvalidFuseIndex);
-                    }
-                    let fuse_bits_part = fuse_bits_part.unwrap();
-                    for fuse in fuse_bits_part.chars() {
-                        match fuse {
-                            '0' => {
-                                if fuse_idx >= num_fuses {
-                                    return Err(JedParserError::InvalidFuseIndex);
-                                }
-                                fuses_ternary[fuse_idx as usize] = Ternary::Zero;
-                                fuse_idx += 1;
-                            },
-                            '1' => {
-                                if fuse_idx >= num_fuses {
-                                    return Err(JedParserError::InvalidFuseIndex);
-                                }
-                                fuses_ternary[fuse_idx as usize] = Ternary::One;
-                                fuse_idx += 1;
-                            },
-                            ' ' | '\r' | '\n' => {}, // Do nothing
-                            _ => return Err(JedParserError::InvalidCharacter),
+                        let fuse_bits_part = fuse_field_splitter.next();
+                        if fuse_bits_part.is_none() {
+                            return Err(JedParserErrorKind::InvalidFuseIndex);
                         }
+                        let fuse_bits_part = fuse_bits_part.unwrap();
+                        for fuse in fuse_bits_part.chars() {
+                            match fuse {
+                                '0' => {
+                                    if fuse_idx >= num_fuses {
+                                        return Err(JedParserErrorKind::InvalidFuseIndex);
+                                    }
+                                    fuses_ternary[fuse_idx as usize] = Ternary::Zero;
+                                    fuse_idx += 1;
+                                },
+                                '1' => {
+                                    if fuse_idx >= num_fuses {
+                                        return Err(JedParserErrorKind::InvalidFuseIndex);
+                                    }
+                                    fuses_ternary[fuse_idx as usize] = Ternary::One;
+                                    fuse_idx += 1;
+                                },
+                                ' ' | '\r' | '\n' => {}, // Do nothing
+                                _ => return Err(JedParserErrorKind::InvalidCharacter),
+                            }
+                        }
+                    },
+                    'C' => {
+                        // Checksum
+                        let (_, csum_str) = l.split_at(1);
+                        if csum_str.len() != 4 {
+                            return Err(JedParserErrorKind::BadFuseChecksum);
+                        }
+                        fuse_expected_csum = Some((u16::from_str_radix(csum_str, 16)?, l_offset));
                     }
-                },
-                'C' => {
-                    // Checksum
-                    let (_, csum_str) = l.split_at(1);
-                    if csum_str.len() != 4 {
-                        return Err(JedParserError::BadFuseChecksum);
-                    }
-                    fuse_expected_csum = Some(u16::from_str_radix(csum_str, 16)?);
+                    _ => return Err(JedParserErrorKind::UnrecognizedField),
                 }
-                _ => return Err(JedParserError::UnrecognizedField),
+
+                Ok(())
+            })();
+
+            match field_result {
+                Ok(()) => {},
+                Err(err @ JedParserErrorKind::UnrecognizedField) |
+                Err(err @ JedParserErrorKind::BadFuseChecksum) => report(err, l_offset)?,
+                Err(err) => return Err(JedParserError::new(err, in_bytes, l_offset)),
             }
         }
 
@@ -282,7 +476,7 @@ impl JEDECFile {
             if *x == Ternary::Undef {
                 // There cannot be undefined fuses if there isn't an F field
                 if default_fuse == Ternary::Undef {
-                    return Err(JedParserError::MissingF)
+                    return Err(JedParserError::new(JedParserErrorKind::MissingF, in_bytes, jed_etx));
                 }
 
                 *x = default_fuse;
@@ -294,25 +488,21 @@ impl JEDECFile {
             Ternary::Zero => false,
             Ternary::One => true,
             _ => unreachable!(),
-        }).collect::<Vec<_>>();
+        }).collect::<BitVec>();
 
         // Fuse checksum
-        if let Some(fuse_expected_csum) = fuse_expected_csum {
-            for i in 0..num_fuses {
-                if fuses[i as usize] {
-                    // Fuse is a 1 and contributes to the sum
-                    fuse_csum += Wrapping(1u16 << (i % 8));
-                }
-            }
-
-            if fuse_expected_csum != fuse_csum.0 {
-                return Err(JedParserError::BadFuseChecksum);
+        if let Some((fuse_expected_csum, fuse_csum_offset)) = fuse_expected_csum {
+            if fuse_expected_csum != fuse_checksum(&fuses) {
+                report(JedParserErrorKind::BadFuseChecksum, fuse_csum_offset)?;
             }
         }
 
         Ok(Self {
             f: fuses,
-            dev_name_str: device
+            dev_name_str: device,
+            secure,
+            usercode,
+            test_vectors,
         })
     }
 
@@ -323,13 +513,7 @@ impl JEDECFile {
 
         // FIXME: Un-hardcode the number of 0s in the fuse index
 
-        write!(writer, "\x02")?;
-
-        write!(writer, "QF{}*\n", self.f.len())?;
-        if let Some(ref dev_name_str) = self.dev_name_str {
-            write!(writer, "N DEVICE {}*\n", dev_name_str)?;
-        }
-        write!(writer, "\n")?;
+        self.write_header(&mut writer)?;
 
         let mut next_written_fuse = 0;
         for linebreak in linebreaks {
@@ -356,11 +540,99 @@ impl JEDECFile {
             write!(writer, "*\n")?;
         }
 
+        self.write_trailer(&mut writer)
+    }
+
+    /// Writes everything before the fuse data
+    fn write_header<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        write!(writer, "\x02")?;
+
+        write!(writer, "QF{}*\n", self.f.len())?;
+        if let Some(ref dev_name_str) = self.dev_name_str {
+            write!(writer, "N DEVICE {}*\n", dev_name_str)?;
+        }
+        if let Some(secure) = self.secure {
+            write!(writer, "G{}*\n", if secure {"1"} else {"0"})?;
+        }
+        if let Some(ref usercode) = self.usercode {
+            if usercode.len() % 4 == 0 {
+                write!(writer, "UH")?;
+                for nybble in usercode.chunks(4) {
+                    let val = nybble.iter().fold(0, |acc, &x| (acc << 1) | (x as u32));
+                    write!(writer, "{:X}", val)?;
+                }
+            } else {
+                write!(writer, "U")?;
+                for &bit in usercode {
+                    write!(writer, "{}", if bit {"1"} else {"0"})?;
+                }
+            }
+            write!(writer, "*\n")?;
+        }
+        write!(writer, "\n")?;
+
+        Ok(())
+    }
+
+    /// Writes everything after the fuse data
+    fn write_trailer<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        if let Some(ref test_vectors) = self.test_vectors {
+            test_vectors.write(&mut *writer)?;
+        }
+
         write!(writer, "\x030000\n")?;
 
         Ok(())
     }
 
+    /// Writes the contents to a JEDEC file using as few fuse records as practical. Whichever of 0 or 1 is more
+    /// common is written as the default fuse state in an `F` field, and only runs of fuses containing the other value
+    /// are written as `L` fields. A `C` field with the fuse checksum is always written. Note that a `&mut Write` can
+    /// also be passed as a writer.
+    pub fn write_sparse<W>(&self, mut writer: W) -> Result<(), io::Error> where W: Write {
+        // Starting a new L field costs about this many characters, so gaps shorter than this are written out instead
+        const MAX_GAP: usize = 10;
+        // Maximum number of fuses in one L field
+        const MAX_RUN: usize = 64;
+
+        self.write_header(&mut writer)?;
+
+        let default = self.f.count_ones() * 2 > self.f.len();
+        write!(writer, "F{}*\n", if default {"1"} else {"0"})?;
+
+        let mut i = 0;
+        while i < self.f.len() {
+            if self.f.get(i) == default {
+                i += 1;
+                continue;
+            }
+
+            // Extend the run until it is too long or there is a long enough gap of default fuses
+            let start = i;
+            let mut end = i + 1;
+            let mut j = i + 1;
+            while j < self.f.len() && j - start < MAX_RUN {
+                if self.f.get(j) != default {
+                    end = j + 1;
+                } else if j + 1 - end > MAX_GAP {
+                    break;
+                }
+                j += 1;
+            }
+
+            write!(writer, "L{:06} ", start)?;
+            for k in start..end {
+                write!(writer, "{}", if self.f.get(k) {"1"} else {"0"})?;
+            }
+            write!(writer, "*\n")?;
+            i = end;
+        }
+
+        write!(writer, "C{:04X}*\n", fuse_checksum(&self.f))?;
+
+        self.write_trailer(&mut writer)
+    }
+
     /// Writes the contents to a JEDEC file. Note that a `&mut Write` can also be passed as a writer. Line breaks
     /// happen every `break_inverval` fuses.
     pub fn write_with_linebreaks<W>(&self, writer: W, break_inverval: usize) -> Result<(), io::Error> where W: Write {
@@ -377,16 +649,36 @@ impl JEDECFile {
 
     /// Constructs a fuse array with the given number of fuses
     pub fn new(size: usize) -> Self {
-        let mut f = Vec::with_capacity(size);
-        for _ in 0..size {
-            f.push(false);
-        }
-
         Self {
-            f,
+            f: BitVec::from_elem(size, false),
             dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: None,
         }
     }
+
+    /// Returns the indices of all fuses that differ between this file and `other`. If the files have a different
+    /// number of fuses, the fuses that only exist in the longer file are also reported as differing.
+    pub fn diff_fuses(&self, other: &JEDECFile) -> Vec<usize> {
+        if self.f.len() == other.f.len() {
+            return self.f.diff_indices(&other.f);
+        }
+
+        let common_len = ::std::cmp::min(self.f.len(), other.f.len());
+        let longer_len = ::std::cmp::max(self.f.len(), other.f.len());
+        (0..common_len).filter(|&i| self.f.get(i) != other.f.get(i)).chain(common_len..longer_len).collect()
+    }
+}
+
+/// Computes the checksum used in the `C` field
+pub(crate) fn fuse_checksum(fuses: &BitVec) -> u16 {
+    let mut fuse_csum = Wrapping(0u16);
+    for i in fuses.iter_ones() {
+        // Fuse is a 1 and contributes to the sum
+        fuse_csum += Wrapping(1u16 << (i % 8));
+    }
+    fuse_csum.0
 }
 
 struct LinebreakIntervalIter(usize, usize, usize);
@@ -413,44 +705,44 @@ mod tests {
     fn read_no_stx() {
         let ret = JEDECFile::from_bytes(b"asdf");
 
-        assert_eq!(ret, Err(JedParserError::MissingSTX));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::MissingSTX));
     }
 
     #[test]
     fn read_no_etx() {
         let ret = JEDECFile::from_bytes(b"asdf\x02fdsa");
 
-        assert_eq!(ret, Err(JedParserError::MissingETX));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::MissingETX));
     }
 
     #[test]
     fn read_no_csum() {
         let ret = JEDECFile::from_bytes(b"asdf\x02fdsa\x03");
-        assert_eq!(ret, Err(JedParserError::UnexpectedEnd));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::UnexpectedEnd));
 
         let ret = JEDECFile::from_bytes(b"asdf\x02fdsa\x03AAA");
-        assert_eq!(ret, Err(JedParserError::UnexpectedEnd));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::UnexpectedEnd));
     }
 
     #[test]
     fn read_bad_csum() {
         let ret = JEDECFile::from_bytes(b"asdf\x02fdsa\x03AAAA");
 
-        assert_eq!(ret, Err(JedParserError::BadFileChecksum));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::BadFileChecksum));
     }
 
     #[test]
     fn read_malformed_csum() {
         let ret = JEDECFile::from_bytes(b"asdf\x02fdsa\x03AAAZ");
 
-        assert_eq!(ret, Err(JedParserError::InvalidCharacter));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
     }
 
     #[test]
     fn read_no_f() {
         let ret = JEDECFile::from_bytes(b"\x02QF1*\x030000");
 
-        assert_eq!(ret, Err(JedParserError::MissingF));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::MissingF));
     }
 
     #[test]
@@ -458,9 +750,11 @@ mod tests {
         let ret = JEDECFile::from_bytes(b"\x02F0*\x030000");
 
         assert_eq!(ret, Ok(JEDECFile {
-            f: vec![],
-            dev_name_str: None
-
+            f: BitVec::new(),
+            dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: None,
         }));
     }
 
@@ -468,7 +762,7 @@ mod tests {
     fn read_bogus_f_command() {
         let ret = JEDECFile::from_bytes(b"\x02F2*\x030000");
 
-        assert_eq!(ret, Err(JedParserError::InvalidCharacter));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
     }
 
     #[test]
@@ -476,9 +770,11 @@ mod tests {
         let ret = JEDECFile::from_bytes(b"\x02F0*N DEVICE asdf*\x030000");
 
         assert_eq!(ret, Ok(JEDECFile {
-            f: vec![],
-            dev_name_str: Some(String::from("asdf"))
-
+            f: BitVec::new(),
+            dev_name_str: Some(String::from("asdf")),
+            secure: None,
+            usercode: None,
+            test_vectors: None,
         }));
     }
 
@@ -486,7 +782,7 @@ mod tests {
     fn read_l_without_qf() {
         let ret = JEDECFile::from_bytes(b"\x02F0*L0 0*\x030000");
 
-        assert_eq!(ret, Err(JedParserError::MissingQF));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::MissingQF));
     }
 
     #[test]
@@ -494,9 +790,11 @@ mod tests {
         let ret = JEDECFile::from_bytes(b"\x02F0*QF1*L0 1*\x030000");
 
         assert_eq!(ret, Ok(JEDECFile {
-            f: vec![true],
-            dev_name_str: None
-
+            f: vec![true].into(),
+            dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: None,
         }));
     }
 
@@ -505,9 +803,11 @@ mod tests {
         let ret = JEDECFile::from_bytes(b"\x02F0*QF1*L0 1*C0001*\x030000");
 
         assert_eq!(ret, Ok(JEDECFile {
-            f: vec![true],
-            dev_name_str: None
-
+            f: vec![true].into(),
+            dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: None,
         }));
     }
 
@@ -515,7 +815,7 @@ mod tests {
     fn read_one_fuse_csum_bad() {
         let ret = JEDECFile::from_bytes(b"\x02F0*QF1*L0 1*C0002*\x030000");
 
-        assert_eq!(ret, Err(JedParserError::BadFuseChecksum));
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::BadFuseChecksum));
     }
 
     #[test]
@@ -523,9 +823,308 @@ mod tests {
         let ret = JEDECFile::from_bytes(b"\x02F0*QF2*L0 0 1*\x030000");
 
         assert_eq!(ret, Ok(JEDECFile {
-            f: vec![false, true],
-            dev_name_str: None
+            f: vec![false, true].into(),
+            dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: None,
+        }));
+    }
+
+    #[test]
+    fn read_security_fuse() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*QF1*G1*L0 1*\x030000");
+
+        assert_eq!(ret, Ok(JEDECFile {
+            f: vec![true].into(),
+            dev_name_str: None,
+            secure: Some(true),
+            usercode: None,
+            test_vectors: None,
+        }));
+    }
+
+    #[test]
+    fn read_bogus_g_command() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*QF1*G2*L0 1*\x030000");
+
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
+    }
+
+    #[test]
+    fn write_security_fuse() {
+        let mut jed = JEDECFile::new(2);
+        jed.secure = Some(false);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed));
+    }
+
+    #[test]
+    fn read_test_vectors() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*QF1*L0 1*X0*P 1 2 3*V0001 01C*V0002 H\nLZ*\x030000");
 
+        assert_eq!(ret, Ok(JEDECFile {
+            f: vec![true].into(),
+            dev_name_str: None,
+            secure: None,
+            usercode: None,
+            test_vectors: Some(TestVectors {
+                pins: Some(vec![1, 2, 3]),
+                pin_separators: vec![" ".to_owned(), " ".to_owned(), " ".to_owned()],
+                default: Some(false),
+                vectors: vec![
+                    TestVector {
+                        num: 1,
+                        num_digits: 4,
+                        conditions: vec![TestCondition::DriveLow, TestCondition::DriveHigh,
+                                         TestCondition::ClockLowHighLow],
+                        separators: vec![" ".to_owned(), "".to_owned(), "".to_owned()],
+                    },
+                    TestVector {
+                        num: 2,
+                        num_digits: 4,
+                        conditions: vec![TestCondition::TestHigh, TestCondition::TestLow, TestCondition::HighZ],
+                        separators: vec![" ".to_owned(), "\n".to_owned(), "".to_owned()],
+                    },
+                ],
+                pins_before_default: false,
+            }),
         }));
     }
+
+    #[test]
+    fn read_bogus_v_command() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*QF1*V0001 0?1*\x030000");
+
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
+    }
+
+    #[test]
+    fn write_test_vectors() {
+        let jed = JEDECFile::from_bytes(b"\x02F0*QF2*L0 01*P 4 5*V0001 9K*V0002 XN*\x030000").unwrap();
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert!(out.ends_with(b"P 4 5*\nV0001 9K*\nV0002 XN*\n\x030000\n"));
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed));
+    }
+
+    #[test]
+    fn write_test_vectors_verbatim() {
+        let jed = JEDECFile::from_bytes(b"\x02F0*QF2*L0 01*P 4 5*X1*V00001 9K*V2 XN*V123456 01*\x030000").unwrap();
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert!(out.ends_with(b"P 4 5*\nX1*\nV00001 9K*\nV2 XN*\nV123456 01*\n\x030000\n"));
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed));
+    }
+
+    #[test]
+    fn write_test_vectors_layout() {
+        let jed = JEDECFile::from_bytes(b"\x02F0*QF2*L0 01*P  1 2\n3 4*V0001  01 HL\r\nZZ*V0002 0000*\x030000").unwrap();
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert!(out.ends_with(b"P  1 2\n3 4*\nV0001  01 HL\r\nZZ*\nV0002 0000*\n\x030000\n"));
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed.clone()));
+
+        // Vectors and pin lists that no longer match their layout are written in the default layout
+        let mut jed = jed;
+        {
+            let test_vectors = jed.test_vectors.as_mut().unwrap();
+            test_vectors.pins.as_mut().unwrap().push(5);
+            test_vectors.vectors[0].conditions.push(TestCondition::Float);
+            test_vectors.vectors.push(TestVector {
+                num: 3,
+                num_digits: 4,
+                conditions: vec![TestCondition::DontCare; 5],
+                separators: Vec::new(),
+            });
+        }
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert!(out.ends_with(b"P 1 2 3 4 5*\nV0001 01HLZZF*\nV0002 0000*\nV0003 XXXXX*\n\x030000\n"));
+    }
+
+    #[test]
+    fn read_usercode_formats() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*UH5A*\x030000").unwrap();
+        assert_eq!(ret.usercode, Some(vec![false, true, false, true, true, false, true, false]));
+
+        let ret = JEDECFile::from_bytes(b"\x02F0*UAZ*\x030000").unwrap();
+        assert_eq!(ret.usercode, Some(vec![false, true, false, true, true, false, true, false]));
+
+        let ret = JEDECFile::from_bytes(b"\x02F0*U010 11*\x030000").unwrap();
+        assert_eq!(ret.usercode, Some(vec![false, true, false, true, true]));
+    }
+
+    #[test]
+    fn read_bogus_u_command() {
+        let ret = JEDECFile::from_bytes(b"\x02F0*UHXY*\x030000");
+
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
+    }
+
+    #[test]
+    fn write_usercode() {
+        let mut jed = JEDECFile::new(1);
+        jed.usercode = Some(vec![true, false, true, false, false, false, false, true]);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert!(out.starts_with(b"\x02QF1*\nUHA1*\n"));
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed));
+    }
+
+    #[test]
+    fn preserving_roundtrip() {
+        let orig = b"header\x02QF4*\r\nN note*QP20*N  DEVICE asdf *F0*\r\nL0 1 0*L2\n11*C000D*\n \x03ABCDtrailer";
+        let mut file_csum = Wrapping(0u16);
+        for &x in &orig[6..orig.len() - 11] {
+            file_csum += Wrapping(x as u16);
+        }
+        let orig = [&orig[..orig.len() - 11], format!("{:04x}", file_csum.0).as_bytes(), b"trailer"].concat();
+
+        let mut jed = PreservedJEDECFile::from_bytes(&orig).unwrap();
+        assert_eq!(jed.jed.f.to_vec(), vec![true, false, true, true]);
+        assert_eq!(jed.notes(), vec!["note", "DEVICE asdf"]);
+
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+        assert_eq!(out, orig);
+
+        jed.jed.f.set(1, true);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+        let new = JEDECFile::from_bytes(&out).unwrap();
+        assert_eq!(new.f.to_vec(), vec![true, true, true, true]);
+        assert!(out.starts_with(b"header\x02QF4*\r\nN note*QP20*N  DEVICE asdf *F0*\r\nL0 1 1*L2\n11*C000F*\n \x03"));
+    }
+
+    #[test]
+    fn preserving_default_fuses() {
+        // With a C field
+        let mut jed = PreservedJEDECFile::from_bytes(b"\x02QF8*F0*L0 1*C0001*\x030000").unwrap();
+        jed.jed.f.set(5, true);
+        jed.jed.f.set(6, true);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+        assert_eq!(out, b"\x02QF8*F0*L0 1*\nL000005 11*C0061*\x030000");
+        assert_eq!(JEDECFile::from_bytes(&out).unwrap().f, jed.jed.f);
+
+        // Without a C field
+        let mut jed = PreservedJEDECFile::from_bytes(b"\x02QF8*F1*L2 0*N x*\x030000").unwrap();
+        jed.jed.f.set(0, false);
+        jed.jed.f.set(2, true);
+        jed.jed.f.set(7, false);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+        assert_eq!(out, b"\x02QF8*F1*L2 1*\nL000000 0*\nL000007 0*N x*\x030000");
+        assert_eq!(JEDECFile::from_bytes(&out).unwrap().f, jed.jed.f);
+
+        // Without any L fields
+        let mut jed = PreservedJEDECFile::from_bytes(b"\x02QF4*F0*C0000*\x030000").unwrap();
+        jed.jed.f.set(3, true);
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+        assert_eq!(out, b"\x02QF4*F0*\nL000003 1*C0008*\x030000");
+        assert_eq!(JEDECFile::from_bytes(&out).unwrap().f, jed.jed.f);
+    }
+
+    #[test]
+    fn read_error_position() {
+        let ret = JEDECFile::from_bytes(b"asdf\x02F0*\nQF1*\r\n  L0 12*\x030000");
+
+        assert_eq!(ret, Err(JedParserError {
+            kind: JedParserErrorKind::InvalidCharacter,
+            offset: 17,
+            line: 3,
+            col: 3,
+        }));
+    }
+
+    #[test]
+    fn write_sparse() {
+        let mut x = JEDECFile::new(100);
+        x.f.set(3, true);
+        x.f.set(5, true);
+        x.f.set(90, true);
+
+        let mut buf = Vec::new();
+        x.write_sparse(&mut buf).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(),
+            "\x02QF100*\n\nF0*\nL000003 101*\nL000090 1*\nC002C*\n\x030000\n");
+        assert_eq!(JEDECFile::from_bytes(&buf).unwrap(), x);
+    }
+
+    #[test]
+    fn write_sparse_roundtrip() {
+        for &(len, modulus) in &[(0, 1), (1, 1), (1000, 3), (1000, 17), (5000, 100)] {
+            for &invert in &[false, true] {
+                let mut x = JEDECFile::new(len);
+                for i in 0..len {
+                    x.f.set(i, (i * i % modulus == 0) ^ invert);
+                }
+
+                let mut buf = Vec::new();
+                x.write_sparse(&mut buf).unwrap();
+                assert_eq!(JEDECFile::from_bytes(&buf).unwrap(), x);
+
+                let mut dense_buf = Vec::new();
+                x.write(&mut dense_buf).unwrap();
+                if len > 100 {
+                    assert!(buf.len() < dense_buf.len());
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let x = JEDECFile::from_bytes(b"\x02QF8*N DEVICE asdf*G1*UH5*F0*L2 101*P 1 2*V1 01*\x030000").unwrap();
+
+        let json = ::serde_json::to_string(&x).unwrap();
+        assert!(json.contains(r#""f":{"len":8,"bits":"28"}"#));
+        assert_eq!(::serde_json::from_str::<JEDECFile>(&json).unwrap(), x);
+
+        let preserved = PreservedJEDECFile::from_bytes(b"header\x02QF8*N hi*F0*L2 101*\x030000").unwrap();
+        let json = ::serde_json::to_string(&preserved).unwrap();
+        assert_eq!(::serde_json::from_str::<PreservedJEDECFile>(&json).unwrap(), preserved);
+    }
+
+    #[test]
+    fn diff_fuses() {
+        let a = JEDECFile::from_bytes(b"\x02F0*QF6*L0 010010*\x030000").unwrap();
+        let b = JEDECFile::from_bytes(b"\x02F0*QF6*L0 110000*\x030000").unwrap();
+        let c = JEDECFile::from_bytes(b"\x02F0*QF8*L0 01001011*\x030000").unwrap();
+
+        assert_eq!(a.diff_fuses(&a), Vec::<usize>::new());
+        assert_eq!(a.diff_fuses(&b), vec![0, 4]);
+        assert_eq!(a.diff_fuses(&c), vec![6, 7]);
+        assert_eq!(c.diff_fuses(&a), vec![6, 7]);
+    }
+
+    #[test]
+    fn read_lenient() {
+        let ret = JEDECFile::from_bytes_lenient(b"\x02F0*QF1*L0 1*C0002*Q*\n?what*\x03AAAA");
+
+        let (jed, warnings) = ret.unwrap();
+        assert_eq!(jed.f, vec![true].into());
+        assert_eq!(warnings.iter().map(|e| (e.kind, e.offset)).collect::<Vec<_>>(), vec![
+            (JedParserErrorKind::BadFileChecksum, 29),
+            (JedParserErrorKind::UnrecognizedField, 22),
+            (JedParserErrorKind::BadFuseChecksum, 13),
+        ]);
+
+        let ret = JEDECFile::from_bytes_lenient(b"\x02F0*QF1*L0 1*\x03");
+        assert_eq!(ret.unwrap().1.iter().map(|e| e.kind).collect::<Vec<_>>(),
+            vec![JedParserErrorKind::UnexpectedEnd]);
+
+        let ret = JEDECFile::from_bytes_lenient(b"\x02F0*QF1*L0 2*\x030000");
+        assert_eq!(ret.map_err(|e| e.kind), Err(JedParserErrorKind::InvalidCharacter));
+    }
 }
@@ -0,0 +1,257 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Layout-preserving JEDEC file representation
+
+use std::io;
+use std::fmt::Write as FmtWrite;
+use std::io::Write;
+use std::num::Wrapping;
+use std::str;
+
+use *;
+
+/// One field of a JEDEC file as it originally appeared, not including the terminating `*`
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+enum PreservedField {
+    /// Any field that is written back exactly as it was read
+    Verbatim(String),
+    /// An `L` field. `prefix` contains everything up to and including the whitespace after the fuse index, and
+    /// every `0`/`1` in `body` is replaced by the current fuse value when writing.
+    Fuses {
+        prefix: String,
+        start: usize,
+        body: String,
+    },
+    /// A `C` field. `prefix` contains everything up to and including the `C`.
+    FuseChecksum {
+        prefix: String,
+        raw: String,
+        orig_csum: u16,
+    },
+}
+
+/// A JEDEC file together with everything needed to write it back out byte-for-byte: the text outside of the STX/ETX
+/// framing, every field (including ones that `JEDECFile` does not interpret) in its original order and with its
+/// original whitespace, and the original `L` field chunking.
+///
+/// Fuse values are taken from `jed` when writing, so fuses can be modified before writing the file back out. Changed
+/// fuses that were not in any `L` field (i.e. that were set by the `F` field) are written as extra `L` fields after
+/// the last original one. The `C` field and a nonzero file checksum are recomputed if the fuses have changed. Other
+/// fields of `jed` are _not_ used by the writer.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct PreservedJEDECFile {
+    /// The parsed contents of the file
+    pub jed: JEDECFile,
+    orig_num_fuses: usize,
+    orig_fuses: BitVec,
+    fuses_in_l_fields: BitVec,
+    /// Index in `fields` that extra `L` fields are inserted before: after the last `L` field, or before the `C` field
+    /// if there were none
+    extra_fields_pos: usize,
+    header: Vec<u8>,
+    fields: Vec<PreservedField>,
+    body_tail: String,
+    file_csum_raw: String,
+    orig_file_csum: u16,
+    trailer: Vec<u8>,
+}
+
+impl PreservedJEDECFile {
+    /// Reads a .jed file while remembering its exact layout
+    pub fn from_bytes(in_bytes: &[u8]) -> Result<Self, JedParserError> {
+        let jed = JEDECFile::from_bytes(in_bytes)?;
+
+        // The file has already been validated, so STX, ETX, the checksum, and every field must be well-formed
+        let jed_stx = in_bytes.iter().position(|&x| x == STX).unwrap();
+        let jed_etx = jed_stx + in_bytes[jed_stx..].iter().position(|&x| x == ETX).unwrap();
+        let file_csum_raw = str::from_utf8(&in_bytes[jed_etx + 1..jed_etx + 5]).unwrap();
+        let orig_file_csum = u16::from_str_radix(file_csum_raw, 16).unwrap();
+
+        let jed_body = str::from_utf8(&in_bytes[jed_stx + 1..jed_etx]).unwrap();
+        let mut raw_fields = jed_body.split('*').collect::<Vec<_>>();
+        let body_tail = raw_fields.pop().unwrap();
+
+        let mut fields = Vec::with_capacity(raw_fields.len());
+        let mut fuses_in_l_fields = BitVec::from_elem(jed.f.len(), false);
+        let mut last_l_field = None;
+        let mut first_c_field = None;
+        for raw in raw_fields {
+            let l = raw.trim_start_matches(|c| c == ' ' || c == '\r' || c == '\n');
+            let ws_len = raw.len() - l.len();
+
+            if l.starts_with('L') {
+                let idx_end = l.find(|c| c == ' ' || c == '\r' || c == '\n').unwrap();
+                let start = usize::from_str_radix(&l[1..idx_end], 10).unwrap();
+                let (prefix, body) = raw.split_at(ws_len + idx_end + 1);
+                let num_fuses = body.chars().filter(|&c| c == '0' || c == '1').count();
+                for i in start..start + num_fuses {
+                    fuses_in_l_fields.set(i, true);
+                }
+                last_l_field = Some(fields.len());
+                fields.push(PreservedField::Fuses {
+                    prefix: prefix.to_owned(),
+                    start,
+                    body: body.to_owned(),
+                });
+            } else if l.starts_with('C') {
+                let (prefix, csum_str) = raw.split_at(ws_len + 1);
+                first_c_field = first_c_field.or(Some(fields.len()));
+                fields.push(PreservedField::FuseChecksum {
+                    prefix: prefix.to_owned(),
+                    raw: csum_str.to_owned(),
+                    orig_csum: u16::from_str_radix(csum_str, 16).unwrap(),
+                });
+            } else {
+                fields.push(PreservedField::Verbatim(raw.to_owned()));
+            }
+        }
+
+        Ok(Self {
+            orig_num_fuses: jed.f.len(),
+            orig_fuses: jed.f.clone(),
+            fuses_in_l_fields,
+            extra_fields_pos: match last_l_field {
+                Some(i) => i + 1,
+                None => first_c_field.unwrap_or(fields.len()),
+            },
+            jed,
+            header: in_bytes[..jed_stx].to_owned(),
+            fields,
+            body_tail: body_tail.to_owned(),
+            file_csum_raw: file_csum_raw.to_owned(),
+            orig_file_csum,
+            trailer: in_bytes[jed_etx + 5..].to_owned(),
+        })
+    }
+
+    /// Returns the text of every field in the file in their original order, without surrounding whitespace or the
+    /// terminating `*`
+    pub fn fields(&self) -> Vec<String> {
+        let mut ret = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let text = match field {
+                &PreservedField::Verbatim(ref raw) => raw.clone(),
+                &PreservedField::Fuses {ref prefix, ref body, ..} => format!("{}{}", prefix, body),
+                &PreservedField::FuseChecksum {ref prefix, ref raw, ..} => format!("{}{}", prefix, raw),
+            };
+            ret.push(text.trim_matches(|c| c == ' ' || c == '\r' || c == '\n').to_owned());
+        }
+        ret
+    }
+
+    /// Returns the text of every `N` (note) field, without the leading `N`
+    pub fn notes(&self) -> Vec<String> {
+        self.fields().into_iter()
+            .filter(|x| x.starts_with('N'))
+            .map(|x| x[1..].trim_matches(|c| c == ' ' || c == '\r' || c == '\n').to_owned())
+            .collect()
+    }
+
+    /// Writes the file back out using the original layout. If the number of fuses has been changed, the original
+    /// layout can no longer be used and this falls back to `JEDECFile::write`.
+    pub fn write<W>(&self, mut writer: W) -> Result<(), io::Error> where W: Write {
+        if self.orig_num_fuses != self.jed.f.len() {
+            return self.jed.write(writer);
+        }
+
+        // Changed fuses that were set by the F field need L fields of their own
+        let mut extra_fields = String::new();
+        let changed = self.orig_fuses.diff_indices(&self.jed.f).into_iter()
+            .filter(|&i| !self.fuses_in_l_fields.get(i)).collect::<Vec<_>>();
+        let mut i = 0;
+        while i < changed.len() {
+            let mut end = i + 1;
+            while end < changed.len() && changed[end] == changed[end - 1] + 1 {
+                end += 1;
+            }
+            write!(extra_fields, "\nL{:06} ", changed[i]).unwrap();
+            for &fuse_idx in &changed[i..end] {
+                extra_fields.push(if self.jed.f.get(fuse_idx) {'1'} else {'0'});
+            }
+            extra_fields.push('*');
+            i = end;
+        }
+        let extra_fields_pos = self.extra_fields_pos;
+
+        let mut body = Vec::new();
+        body.push(STX);
+        for (field_i, field) in self.fields.iter().enumerate() {
+            if field_i == extra_fields_pos {
+                write!(body, "{}", extra_fields)?;
+            }
+            match field {
+                &PreservedField::Verbatim(ref raw) => {
+                    write!(body, "{}", raw)?;
+                },
+                &PreservedField::Fuses {ref prefix, start, body: ref fuses} => {
+                    write!(body, "{}", prefix)?;
+                    let mut fuse_idx = start;
+                    for c in fuses.chars() {
+                        if c == '0' || c == '1' {
+                            write!(body, "{}", if self.jed.f[fuse_idx] {"1"} else {"0"})?;
+                            fuse_idx += 1;
+                        } else {
+                            write!(body, "{}", c)?;
+                        }
+                    }
+                },
+                &PreservedField::FuseChecksum {ref prefix, ref raw, orig_csum} => {
+                    let csum = fuse_checksum(&self.jed.f);
+                    if csum == orig_csum {
+                        write!(body, "{}{}", prefix, raw)?;
+                    } else {
+                        write!(body, "{}{:04X}", prefix, csum)?;
+                    }
+                },
+            }
+            write!(body, "*")?;
+        }
+        if extra_fields_pos == self.fields.len() {
+            write!(body, "{}", extra_fields)?;
+        }
+        write!(body, "{}", self.body_tail)?;
+        body.push(ETX);
+
+        writer.write_all(&self.header)?;
+        writer.write_all(&body)?;
+
+        let mut file_csum = Wrapping(0u16);
+        for &x in &body {
+            file_csum += Wrapping(x as u16);
+        }
+        if self.orig_file_csum == 0 || self.orig_file_csum == file_csum.0 {
+            write!(writer, "{}", self.file_csum_raw)?;
+        } else {
+            write!(writer, "{:04X}", file_csum.0)?;
+        }
+
+        writer.write_all(&self.trailer)?;
+
+        Ok(())
+    }
+}
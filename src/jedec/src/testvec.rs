@@ -0,0 +1,261 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! JEDEC test vector fields (`P`, `X` and `V`)
+
+use std::io;
+use std::io::Write;
+
+use *;
+
+/// A single condition applied to or checked on one pin during a test vector
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum TestCondition {
+    /// Drive input low (`0`)
+    DriveLow,
+    /// Drive input high (`1`)
+    DriveHigh,
+    /// Drive input to supervoltage number 2-9 (`2`-`9`)
+    SuperVoltage(u8),
+    /// Drive input low, high, low (`C`)
+    ClockLowHighLow,
+    /// Drive input high, low, high (`K`)
+    ClockHighLowHigh,
+    /// Float input or output (`F`)
+    Float,
+    /// Test output high (`H`)
+    TestHigh,
+    /// Test output low (`L`)
+    TestLow,
+    /// Power pins and outputs not tested (`N`)
+    NotTested,
+    /// Preload registers (`P`)
+    Preload,
+    /// Output not tested, input default level (`X`)
+    DontCare,
+    /// Test input or output for high impedance (`Z`)
+    HighZ,
+    /// Any other (vendor-specific) condition character, kept as-is
+    Other(char),
+}
+
+impl TestCondition {
+    /// Converts a character from a `V` field into a test condition
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '0' => Some(TestCondition::DriveLow),
+            '1' => Some(TestCondition::DriveHigh),
+            '2'..='9' => Some(TestCondition::SuperVoltage(c as u8 - b'0')),
+            'C' => Some(TestCondition::ClockLowHighLow),
+            'K' => Some(TestCondition::ClockHighLowHigh),
+            'F' => Some(TestCondition::Float),
+            'H' => Some(TestCondition::TestHigh),
+            'L' => Some(TestCondition::TestLow),
+            'N' => Some(TestCondition::NotTested),
+            'P' => Some(TestCondition::Preload),
+            'X' => Some(TestCondition::DontCare),
+            'Z' => Some(TestCondition::HighZ),
+            'A'..='Z' => Some(TestCondition::Other(c)),
+            _ => None,
+        }
+    }
+
+    /// Converts a test condition back into the character used in a `V` field
+    pub fn to_char(&self) -> char {
+        match *self {
+            TestCondition::DriveLow => '0',
+            TestCondition::DriveHigh => '1',
+            TestCondition::SuperVoltage(x) => (b'0' + x) as char,
+            TestCondition::ClockLowHighLow => 'C',
+            TestCondition::ClockHighLowHigh => 'K',
+            TestCondition::Float => 'F',
+            TestCondition::TestHigh => 'H',
+            TestCondition::TestLow => 'L',
+            TestCondition::NotTested => 'N',
+            TestCondition::Preload => 'P',
+            TestCondition::DontCare => 'X',
+            TestCondition::HighZ => 'Z',
+            TestCondition::Other(c) => c,
+        }
+    }
+}
+
+/// One row of test conditions (a `V` field)
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct TestVector {
+    /// Vector number
+    pub num: u32,
+    /// Number of digits the vector number is written with, including leading zeros (e.g. 4 for `V0001`)
+    pub num_digits: usize,
+    /// One condition per pin. If a pin list is present, these are in the same order as the pin list. Otherwise they
+    /// are in device pin number order starting from pin 1.
+    pub conditions: Vec<TestCondition>,
+    /// The whitespace that came before each condition in the file, so that vectors that are grouped or split across
+    /// lines are written back out the same way. If this does not have exactly one entry per condition, a single
+    /// space is written before the first condition instead.
+    pub separators: Vec<String>,
+}
+
+/// All of the test vector information contained in a JEDEC file
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct TestVectors {
+    /// Pin list (`P` field), if any
+    pub pins: Option<Vec<u32>>,
+    /// The whitespace that came before each pin number in the `P` field. If this does not have exactly one entry per
+    /// pin, a single space is written before each pin number instead.
+    pub pin_separators: Vec<String>,
+    /// Default input level for `X` conditions (`X` field), if any
+    pub default: Option<bool>,
+    /// Test vectors (`V` fields), in file order
+    pub vectors: Vec<TestVector>,
+    /// Whether the `P` field comes before the `X` field. Both of them are always written before the `V` fields.
+    pub pins_before_default: bool,
+}
+
+impl TestVectors {
+    pub(crate) fn parse_p_field(&mut self, l: &str) -> Result<(), JedParserErrorKind> {
+        let (_, pins_str) = l.split_at(1);
+        let mut pins = Vec::new();
+        let mut pin_separators = Vec::new();
+        for (sep, pin) in split_separated(pins_str) {
+            pins.push(u32::from_str_radix(pin, 10)?);
+            pin_separators.push(sep.to_owned());
+        }
+        self.pins = Some(pins);
+        self.pin_separators = pin_separators;
+        self.pins_before_default = self.default.is_none();
+        Ok(())
+    }
+
+    pub(crate) fn parse_x_field(&mut self, l: &str) -> Result<(), JedParserErrorKind> {
+        let (_, default_str) = l.split_at(1);
+        self.default = Some(match default_str {
+            "0" => false,
+            "1" => true,
+            _ => return Err(JedParserErrorKind::InvalidCharacter)
+        });
+        Ok(())
+    }
+
+    pub(crate) fn parse_v_field(&mut self, l: &str) -> Result<(), JedParserErrorKind> {
+        let (_, l) = l.split_at(1);
+        let num_len = l.find(|c| c == ' ' || c == '\r' || c == '\n').unwrap_or(l.len());
+        let (num_str, conditions_str) = l.split_at(num_len);
+        let num = u32::from_str_radix(num_str, 10)?;
+
+        let mut conditions = Vec::new();
+        let mut separators = Vec::new();
+        let mut sep_start = 0;
+        for (i, c) in conditions_str.char_indices() {
+            match c {
+                ' ' | '\r' | '\n' => {}, // Part of the next separator
+                _ => {
+                    if let Some(cond) = TestCondition::from_char(c) {
+                        conditions.push(cond);
+                        separators.push(conditions_str[sep_start..i].to_owned());
+                        sep_start = i + c.len_utf8();
+                    } else {
+                        return Err(JedParserErrorKind::InvalidCharacter);
+                    }
+                }
+            }
+        }
+
+        self.vectors.push(TestVector {
+            num,
+            num_digits: num_str.len(),
+            conditions,
+            separators,
+        });
+        Ok(())
+    }
+
+    /// Writes the `X`, `P`, and `V` fields to the given writer.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        if !self.pins_before_default {
+            self.write_x_field(&mut writer)?;
+        }
+        if let Some(ref pins) = self.pins {
+            write!(writer, "P")?;
+            let keep_layout = self.pin_separators.len() == pins.len();
+            for (i, pin) in pins.iter().enumerate() {
+                write!(writer, "{}{}", if keep_layout {&self.pin_separators[i]} else {" "}, pin)?;
+            }
+            write!(writer, "*\n")?;
+        }
+        if self.pins_before_default {
+            self.write_x_field(&mut writer)?;
+        }
+        for vector in &self.vectors {
+            write!(writer, "V{:01$}", vector.num, vector.num_digits)?;
+            let keep_layout = vector.separators.len() == vector.conditions.len();
+            for (i, cond) in vector.conditions.iter().enumerate() {
+                let sep = match (keep_layout, i) {
+                    (true, _) => &vector.separators[i],
+                    (false, 0) => " ",
+                    (false, _) => "",
+                };
+                write!(writer, "{}{}", sep, cond.to_char())?;
+            }
+            write!(writer, "*\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the `X` field, if there is one
+    fn write_x_field<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        if let Some(default) = self.default {
+            write!(writer, "X{}*\n", if default {"1"} else {"0"})?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a list of whitespace-separated items into (whitespace before the item, item) pairs
+fn split_separated(s: &str) -> Vec<(&str, &str)> {
+    let mut ret = Vec::new();
+    let mut sep_start = 0;
+    let mut item_start = None;
+    for (i, c) in s.char_indices() {
+        let is_space = c == ' ' || c == '\r' || c == '\n';
+        match (item_start, is_space) {
+            (None, false) => item_start = Some(i),
+            (Some(start), true) => {
+                ret.push((&s[sep_start..start], &s[start..i]));
+                sep_start = i;
+                item_start = None;
+            },
+            _ => {},
+        }
+    }
+    if let Some(start) = item_start {
+        ret.push((&s[sep_start..start], &s[start..]));
+    }
+    ret
+}
@@ -0,0 +1,85 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Tool that explains what a fuse controls or lists the fuses of a setting
+
+use std::collections::HashMap;
+
+extern crate xc2bit;
+use xc2bit::*;
+
+fn main() {
+    let args = ::std::env::args().collect::<Vec<_>>();
+
+    if args.len() != 3 {
+        println!("Usage: {} <device>-<speed>-<package> <jed fuse | crbit x,y | setting name>", args[0]);
+        ::std::process::exit(1);
+    }
+
+    let device = match XC2DeviceSpeedPackage::from_str(&args[1]) {
+        Some(part) => part.dev,
+        None => {
+            eprintln!("invalid part name {}", args[1]);
+            ::std::process::exit(1);
+        }
+    };
+
+    let jed_map = jed_fuse_map(device);
+    let crbit_map = crbit_fuse_map(device);
+    let jed_fuses = jed_map.iter().map(|&(fuse, info)| (info.owner, (fuse, info.inverted)))
+        .collect::<HashMap<_, _>>();
+    let crbit_fuses = crbit_map.iter().map(|&(coord, info)| (info.owner, (coord, info.inverted)))
+        .collect::<HashMap<_, _>>();
+
+    // The argument is either a .jed fuse index, a crbit coordinate, or the name of a setting
+    let coord = args[2].split(',').map(|x| x.trim().parse::<usize>()).collect::<Result<Vec<_>, _>>();
+    let owners = match coord {
+        Ok(ref coord) if coord.len() == 1 =>
+            jed_map.iter().filter(|x| x.0 == coord[0]).map(|x| x.1.owner).collect::<Vec<_>>(),
+        Ok(ref coord) if coord.len() == 2 =>
+            crbit_map.iter().filter(|x| x.0 == (coord[0], coord[1])).map(|x| x.1.owner).collect::<Vec<_>>(),
+        // Settings that only exist in the crbit format go at the end
+        _ => jed_map.iter().map(|x| x.1.owner)
+            .chain(crbit_map.iter().map(|x| x.1.owner).filter(|x| !jed_fuses.contains_key(x)))
+            .filter(|x| x.is_part_of(&args[2])).collect::<Vec<_>>(),
+    };
+
+    if owners.is_empty() {
+        eprintln!("{} does not match any fuse of the {}", args[2], device);
+        ::std::process::exit(1);
+    }
+
+    for owner in owners {
+        let jed_str = match jed_fuses.get(&owner) {
+            Some(&(fuse, inverted)) => format!("{}{}", fuse, if inverted {" (inverted)"} else {""}),
+            None => "none".to_owned(),
+        };
+        let crbit_str = match crbit_fuses.get(&owner) {
+            Some(&((x, y), inverted)) => format!("{},{}{}", x, y, if inverted {" (inverted)"} else {""}),
+            None => "none".to_owned(),
+        };
+        println!("{}: jed fuse {}, crbit fuse {}", owner, jed_str, crbit_str);
+    }
+}
@@ -0,0 +1,81 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Tool that prints out the differences between two bitstreams, both by fuse index and by meaning
+
+use std::fs::File;
+use std::io::Read;
+
+extern crate jedec;
+use jedec::*;
+
+extern crate xc2bit;
+use xc2bit::*;
+
+fn read_jed(filename: &str) -> Result<(JEDECFile, XC2Bitstream), String> {
+    let mut data = Vec::new();
+    File::open(filename).and_then(|mut f| f.read_to_end(&mut data)).map_err(|err| format!("{}", err))?;
+    let jed = JEDECFile::from_bytes(&data).map_err(|err| format!("{}", err))?;
+    let bitstream = XC2Bitstream::from_jed(&jed).map_err(|err| format!("not a Coolrunner-II bitstream: {}", err))?;
+
+    Ok((jed, bitstream))
+}
+
+fn main() {
+    let args = ::std::env::args().collect::<Vec<_>>();
+
+    if args.len() != 3 {
+        println!("Usage: {} old.jed new.jed", args[0]);
+        ::std::process::exit(1);
+    }
+
+    // Exit code 1 means that the files differ, so use 2 for errors like diff(1) does
+    let read = |filename: &str| read_jed(filename).unwrap_or_else(|err| {
+        eprintln!("{}: {}", filename, err);
+        ::std::process::exit(2);
+    });
+    let (old_jed, old_bitstream) = read(&args[1]);
+    let (new_jed, new_bitstream) = read(&args[2]);
+
+    let changes = old_bitstream.diff(&new_bitstream);
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    let fuses = old_jed.diff_fuses(&new_jed);
+    if !fuses.is_empty() {
+        println!();
+        println!("{} fuses differ", fuses.len());
+        for fuse in &fuses {
+            let old_val = if *fuse < old_jed.f.len() {if old_jed.f.get(*fuse) {"1"} else {"0"}} else {"-"};
+            let new_val = if *fuse < new_jed.f.len() {if new_jed.f.get(*fuse) {"1"} else {"0"}} else {"-"};
+            println!("fuse {}: {} -> {}", fuse, old_val, new_val);
+        }
+    }
+
+    if !changes.is_empty() || !fuses.is_empty() {
+        ::std::process::exit(1);
+    }
+}
@@ -33,8 +33,9 @@ use jedec::*;
 use *;
 use fusemap_logical::{fb_fuse_idx, gck_fuse_idx, gsr_fuse_idx, gts_fuse_idx, global_term_fuse_idx,
                       total_logical_fuse_count, clock_div_fuse_idx};
-use fusemap_physical::{fuse_array_dims, clock_div_fuse_coord};
-use util::{LinebreakSet};
+use fusemap_physical::{fuse_array_dims, done1_fuse_coord, security_fuse_coords, usercode_fuse_coord};
+use globalbits::{XC2MiscBits32, XC2MiscBits32A, XC2MiscBits128, XC2MiscBits384};
+use util::{LinebreakSet, HumanReadableReader};
 use zia::{zia_get_row_width};
 
 /// Toplevel struct representing an entire Coolrunner-II bitstream
@@ -43,6 +44,15 @@ pub struct XC2Bitstream {
     pub speed_grade: XC2Speed,
     pub package: XC2Package,
     pub bits: XC2BitstreamBits,
+    /// Whether the security fuse is set (readback disabled). `None` if the .jed file this came from did not have a
+    /// `G` field, which behaves the same as `Some(false)`.
+    pub secure: Option<bool>,
+    /// 32-bit USERCODE, if one is programmed. An unprogrammed USERCODE reads back as all 1s, so a USERCODE of
+    /// 0xFFFFFFFF is indistinguishable from `None` in the crbit format.
+    pub usercode: Option<u32>,
+    /// Test vectors (the `P`, `X`, and `V` fields) to pass through to the .jed file. These are not stored in the
+    /// device, so they are lost when going through the crbit format.
+    pub test_vectors: Option<TestVectors>,
 }
 
 impl XC2Bitstream {
@@ -51,26 +61,196 @@ impl XC2Bitstream {
         write!(writer, "xc2bit dump\n")?;
         write!(writer, "device speed grade: {}\n", self.speed_grade)?;
         write!(writer, "device package: {}\n", self.package)?;
+        match self.secure {
+            Some(true) => write!(writer, "security fuse: set\n")?,
+            Some(false) => write!(writer, "security fuse: not set\n")?,
+            None => {},
+        }
+        if let Some(usercode) = self.usercode {
+            write!(writer, "usercode: {:08X}\n", usercode)?;
+        }
         self.bits.dump_human_readable(&mut writer)?;
 
         Ok(())
     }
 
+    /// Parses the output of `dump_human_readable` back into a bitstream object. The text must be in exactly the format
+    /// that `dump_human_readable` produces, but any of the settings in it can be changed.
+    pub fn from_human_readable(text: &str) -> Result<Self, XC2BitError> {
+        let mut reader = HumanReadableReader::new(text);
+
+        reader.expect("xc2bit dump")?;
+        let speed = reader.value("device speed grade: ")?;
+        let package = reader.value("device package: ")?;
+        let secure = match reader.optional_value("security fuse: ") {
+            Some("set") => Some(true),
+            Some("not set") => Some(false),
+            Some(_) => return Err(reader.err()),
+            None => None,
+        };
+        let usercode = match reader.optional_value("usercode: ") {
+            Some(x) => {
+                if x.len() != 8 || !x.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(reader.err());
+                }
+                Some(u32::from_str_radix(x, 16).unwrap())
+            },
+            None => None,
+        };
+        let device = reader.value("device type: ")?;
+
+        let part_name = format!("{}-{}-{}", device, speed, package);
+        let part = XC2DeviceSpeedPackage::from_str(&part_name).ok_or(XC2BitError::BadDeviceName(part_name))?;
+        let mut ret = XC2Bitstream::blank_bitstream(part);
+        ret.secure = secure;
+        ret.usercode = usercode;
+
+        // Bank voltages
+        match ret.bits {
+            XC2BitstreamBits::XC2C32 {ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C64 {ref mut ivoltage, ref mut ovoltage, ..} => {
+                *ovoltage = reader.flag("output voltage range: ", "high", "low")?;
+                *ivoltage = reader.flag("input voltage range: ", "high", "low")?;
+            },
+            XC2BitstreamBits::XC2C32A {ref mut legacy_ivoltage, ref mut legacy_ovoltage,
+                ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C64A {ref mut legacy_ivoltage, ref mut legacy_ovoltage,
+                ref mut ivoltage, ref mut ovoltage, ..} => {
+                *legacy_ovoltage = reader.flag("legacy output voltage range: ", "high", "low")?;
+                *legacy_ivoltage = reader.flag("legacy input voltage range: ", "high", "low")?;
+                read_bank_voltages(&mut reader, "output", ovoltage)?;
+                read_bank_voltages(&mut reader, "input", ivoltage)?;
+            },
+            XC2BitstreamBits::XC2C128 {ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} |
+            XC2BitstreamBits::XC2C256 {ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} => {
+                read_bank_voltages(&mut reader, "output", ovoltage)?;
+                read_bank_voltages(&mut reader, "input", ivoltage)?;
+                *data_gate = reader.flag("DataGate used: ", "yes", "no")?;
+                *use_vref = reader.flag("VREF used: ", "yes", "no")?;
+            },
+            XC2BitstreamBits::XC2C384 {ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} |
+            XC2BitstreamBits::XC2C512 {ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} => {
+                read_bank_voltages(&mut reader, "output", ovoltage)?;
+                read_bank_voltages(&mut reader, "input", ivoltage)?;
+                *data_gate = reader.flag("DataGate used: ", "yes", "no")?;
+                *use_vref = reader.flag("VREF used: ", "yes", "no")?;
+            }
+        }
+
+        // Clock divider
+        match ret.bits {
+            XC2BitstreamBits::XC2C128 {ref mut clock_div, ..} |
+            XC2BitstreamBits::XC2C256 {ref mut clock_div, ..} |
+            XC2BitstreamBits::XC2C384 {ref mut clock_div, ..} |
+            XC2BitstreamBits::XC2C512 {ref mut clock_div, ..} => {
+                *clock_div = XC2ClockDiv::from_human_readable(&mut reader)?;
+            },
+            _ => {}
+        }
+
+        // Global net configuration
+        *ret.bits.get_global_nets_mut() = XC2GlobalNets::from_human_readable(&mut reader)?;
+
+        // IOBs
+        let device = ret.bits.device_type();
+        for i in 0..device.num_iobs() {
+            if let Some(iob) = ret.bits.get_mut_small_iob(i) {
+                *iob = XC2MCSmallIOB::from_human_readable(device, i as u32, &mut reader)?;
+            }
+            if let Some(iob) = ret.bits.get_mut_large_iob(i) {
+                *iob = XC2MCLargeIOB::from_human_readable(device, i as u32, &mut reader)?;
+            }
+        }
+
+        // Input-only pin
+        match ret.bits {
+            XC2BitstreamBits::XC2C32 {ref mut inpin, ..} | XC2BitstreamBits::XC2C32A {ref mut inpin, ..} => {
+                *inpin = XC2ExtraIBuf::from_human_readable(&mut reader)?;
+            },
+            _ => {}
+        }
+
+        // FBs
+        for i in 0..device.num_fbs() {
+            ret.bits.get_fb_mut()[i] = XC2BitstreamFB::from_human_readable(device, i as u32, &mut reader)?;
+        }
+
+        reader.finish()?;
+
+        Ok(ret)
+    }
+
+    /// Decompiles the bitstream into Boolean equations for each macrocell that drives anything
+    pub fn get_equations(&self) -> Vec<XC2MCEquations> {
+        get_equations(&self.bits)
+    }
+
+    /// Dump the Boolean equations for each macrocell in the bitstream to the given `writer` object.
+    pub fn dump_equations<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write!(writer, "// xc2bit equations for {}-{}-{}\n", self.bits.device_type(), self.speed_grade,
+            self.package)?;
+        for equations in self.get_equations() {
+            write!(writer, "\n")?;
+            equations.dump(self.bits.device_type(), self.package, &mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a Verilog module implementing the logic in the bitstream to the given `writer` object.
+    pub fn to_verilog<W: Write>(&self, writer: W) -> Result<(), io::Error> {
+        write_verilog(self, writer)
+    }
+
+    /// Checks the bitstream against the design rules and returns all of the violations that were found
+    pub fn check_drc(&self) -> Vec<XC2DRCViolation> {
+        check_drc(self)
+    }
+
+    /// Performs static timing analysis of the bitstream using the timing parameters of its device and speed grade.
+    /// Returns `None` if there are no timing parameters for this combination.
+    pub fn analyze_timing(&self) -> Option<XC2TimingReport> {
+        let params = get_timing_params(self.bits.device_type(), self.speed_grade)?;
+        Some(analyze_timing(&self.bits, &params))
+    }
+
     /// Write a .jed representation of the bitstream to the given `writer` object.
     pub fn to_jed<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
         write!(writer, ".JED fuse map written by xc2bit\n")?;
         write!(writer, "https://github.com/azonenberg/openfpga\n\n")?;
 
         let mut linebreaks = LinebreakSet::new();
-        let mut jed = JEDECFile::new(total_logical_fuse_count(self.bits.device_type()));
-        jed.dev_name_str = Some(format!("{}-{}-{}", self.bits.device_type(), self.speed_grade, self.package));
-        self.bits.to_jed(&mut jed, &mut linebreaks);
+        let jed = self.to_jed_file(&mut linebreaks);
 
         jed.write_custom_linebreaks(&mut writer, linebreaks.iter())?;
 
         Ok(())
     }
 
+    /// Converts the bitstream into a `JEDECFile` object
+    fn to_jed_file(&self, linebreaks: &mut LinebreakSet) -> JEDECFile {
+        let mut jed = JEDECFile::new(total_logical_fuse_count(self.bits.device_type()));
+        jed.dev_name_str = Some(format!("{}-{}-{}", self.bits.device_type(), self.speed_grade, self.package));
+        jed.secure = self.secure;
+        if let Some(usercode) = self.usercode {
+            jed.usercode = Some((0..32).rev().map(|i| usercode & (1 << i) != 0).collect());
+        }
+        jed.test_vectors = self.test_vectors.clone();
+        self.bits.to_jed(&mut jed, linebreaks);
+
+        jed
+    }
+
+    /// Returns the indices of all .jed fuses that differ between this bitstream and `other`. This does not include
+    /// the security fuse and USERCODE, which are not part of the fuse array in the .jed format.
+    pub fn diff_fuses(&self, other: &XC2Bitstream) -> Vec<usize> {
+        let mut linebreaks = LinebreakSet::new();
+        let jed_self = self.to_jed_file(&mut linebreaks);
+        let jed_other = other.to_jed_file(&mut linebreaks);
+
+        jed_self.diff_fuses(&jed_other)
+    }
+
     /// Converts the bitstream into a FuseArray object so that it can be written to the native "crbit" format
     pub fn to_crbit(&self) -> FuseArray {
         let (w, h) = fuse_array_dims(self.bits.device_type());
@@ -80,9 +260,41 @@ impl XC2Bitstream {
 
         self.bits.to_crbit(&mut fuse_array);
 
+        if self.secure == Some(true) {
+            let ((sec0_x, sec0_y), (sec1_x, sec1_y)) = security_fuse_coords(self.bits.device_type());
+            fuse_array.set(sec0_x, sec0_y, false);
+            fuse_array.set(sec1_x, sec1_y, false);
+        }
+
+        if let Some(usercode) = self.usercode {
+            for i in 0..32 {
+                let (x, y) = usercode_fuse_coord(self.bits.device_type(), i);
+                fuse_array.set(x, y, usercode & (1 << i) != 0);
+            }
+        }
+
         fuse_array
     }
 
+    /// Generates the sequence of JTAG operations that programs this bitstream into a device using the in-system
+    /// configuration instructions
+    pub fn to_isc_sequence(&self) -> Vec<XC2JTAGOp> {
+        let mut ops = vec![XC2JTAGOp::Comment(format!("Programming sequence for {}-{}-{}",
+            self.bits.device_type(), self.speed_grade, self.package))];
+        ops.extend(isc_program_sequence(self.bits.device_type(), self.package, &self.to_crbit()));
+        ops
+    }
+
+    /// Write an SVF file that programs this bitstream into a device to the given `writer` object.
+    pub fn to_svf<W: Write>(&self, writer: W) -> Result<(), io::Error> {
+        write_svf(&self.to_isc_sequence(), writer)
+    }
+
+    /// Write an XSVF file that programs this bitstream into a device to the given `writer` object.
+    pub fn to_xsvf<W: Write>(&self, writer: W) -> Result<(), io::Error> {
+        write_xsvf(&self.to_isc_sequence(), writer)
+    }
+
     /// Processes a fuse array into a bitstream object
     pub fn from_jed(jed: &JEDECFile) -> Result<Self, XC2BitError> {
         if jed.dev_name_str.is_none() {
@@ -101,6 +313,16 @@ impl XC2Bitstream {
         } = device_combination.unwrap();
 
         let fuses = &jed.f;
+        let secure = jed.secure;
+        let usercode = if let Some(ref usercode_bits) = jed.usercode {
+            if usercode_bits.len() != 32 {
+                return Err(XC2BitError::WrongUsercodeLength);
+            }
+            Some(usercode_bits.iter().fold(0, |acc, &x| (acc << 1) | (x as u32)))
+        } else {
+            None
+        };
+        let test_vectors = jed.test_vectors.clone();
 
         if fuses.len() != total_logical_fuse_count(dev) {
             return Err(XC2BitError::WrongFuseCount);
@@ -113,6 +335,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C32A => {
@@ -121,6 +346,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C64 => {
@@ -129,6 +357,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C64A => {
@@ -137,6 +368,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C128 => {
@@ -145,6 +379,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C256 => {
@@ -153,6 +390,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C384 => {
@@ -161,6 +401,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
             XC2Device::XC2C512 => {
@@ -169,6 +412,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors,
                 })
             },
         }
@@ -194,6 +440,19 @@ impl XC2Bitstream {
             return Err(XC2BitError::WrongFuseCount);
         }
 
+        // Either security bit being programmed prevents readback
+        let ((sec0_x, sec0_y), (sec1_x, sec1_y)) = security_fuse_coords(dev);
+        // The crbit format cannot tell an explicitly clear security fuse apart from a missing one
+        let secure = if !fuse_array.get(sec0_x, sec0_y) || !fuse_array.get(sec1_x, sec1_y) {Some(true)} else {None};
+
+        let mut usercode = 0;
+        for i in 0..32 {
+            let (x, y) = usercode_fuse_coord(dev, i);
+            if fuse_array.get(x, y) {
+                usercode |= 1 << i;
+            }
+        }
+        let usercode = if usercode == 0xFFFFFFFF { None } else { Some(usercode) };
 
         match dev {
             XC2Device::XC2C32 => {
@@ -202,6 +461,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C32A => {
@@ -210,6 +472,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C64 => {
@@ -218,6 +483,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C64A => {
@@ -226,6 +494,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C128 => {
@@ -234,6 +505,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C256 => {
@@ -242,6 +516,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C384 => {
@@ -250,6 +527,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
             XC2Device::XC2C512 => {
@@ -258,6 +538,9 @@ impl XC2Bitstream {
                     speed_grade: spd,
                     package: pkg,
                     bits,
+                    secure,
+                    usercode,
+                    test_vectors: None,
                 })
             },
         }
@@ -274,6 +557,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C32 {
                         fb: [XC2BitstreamFB::default(); 2],
                         iobs: [XC2MCSmallIOB::default(); 32],
@@ -288,6 +574,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C32A {
                         fb: [XC2BitstreamFB::default(); 2],
                         iobs: [XC2MCSmallIOB::default(); 32],
@@ -304,6 +593,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C64 {
                         fb: [XC2BitstreamFB::default(); 4],
                         iobs: [[XC2MCSmallIOB::default(); 32]; 2],
@@ -317,6 +609,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C64A {
                         fb: [XC2BitstreamFB::default(); 4],
                         iobs: [[XC2MCSmallIOB::default(); 32]; 2],
@@ -332,6 +627,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C128 {
                         fb: [XC2BitstreamFB::default(); 8],
                         iobs: [[XC2MCLargeIOB::default(); 25]; 4],
@@ -348,6 +646,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C256 {
                         fb: [XC2BitstreamFB::default(); 16],
                         iobs: [[XC2MCLargeIOB::default(); 23]; 8],
@@ -364,6 +665,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C384 {
                         fb: [XC2BitstreamFB::default(); 24],
                         iobs: [[XC2MCLargeIOB::default(); 24]; 10],
@@ -380,6 +684,9 @@ impl XC2Bitstream {
                 XC2Bitstream {
                     speed_grade,
                     package,
+                    secure: None,
+                    usercode: None,
+                    test_vectors: None,
                     bits: XC2BitstreamBits::XC2C512 {
                         fb: [XC2BitstreamFB::default(); 32],
                         iobs: [[XC2MCLargeIOB::default(); 27]; 10],
@@ -671,6 +978,64 @@ impl XC2BitstreamBits {
         }
     }
 
+    /// Helper to extract the input-only pin configuration, which only exists on 32-macrocell parts
+    pub fn get_inpin(&self) -> Option<&XC2ExtraIBuf> {
+        match self {
+            &XC2BitstreamBits::XC2C32{ref inpin, ..} |
+            &XC2BitstreamBits::XC2C32A{ref inpin, ..} => Some(inpin),
+            _ => None,
+        }
+    }
+
+    /// Returns the device-wide settings that are stored directly in this struct rather than in one of the per-block
+    /// structs, as `(name, value)` pairs. Per-bank settings are listed once per bank (e.g. `ivoltage[1]`), in bank
+    /// order.
+    pub fn misc_settings(&self) -> Vec<(&'static str, bool)> {
+        let mut bits = *self;
+        bits.misc_settings_mut().into_iter().map(|(name, val)| (name, *val)).collect()
+    }
+
+    /// Same as `misc_settings`, but the settings can be changed
+    pub fn misc_settings_mut(&mut self) -> Vec<(&'static str, &mut bool)> {
+        static IVOLTAGE_NAMES: [&str; 4] = ["ivoltage[0]", "ivoltage[1]", "ivoltage[2]", "ivoltage[3]"];
+        static OVOLTAGE_NAMES: [&str; 4] = ["ovoltage[0]", "ovoltage[1]", "ovoltage[2]", "ovoltage[3]"];
+
+        let mut ret = Vec::new();
+
+        match *self {
+            XC2BitstreamBits::XC2C32{ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C64{ref mut ivoltage, ref mut ovoltage, ..} => {
+                ret.push(("ivoltage", ivoltage));
+                ret.push(("ovoltage", ovoltage));
+            },
+            XC2BitstreamBits::XC2C32A{ref mut legacy_ivoltage, ref mut legacy_ovoltage,
+                ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C64A{ref mut legacy_ivoltage, ref mut legacy_ovoltage,
+                ref mut ivoltage, ref mut ovoltage, ..} => {
+                ret.push(("legacy_ivoltage", legacy_ivoltage));
+                ret.push(("legacy_ovoltage", legacy_ovoltage));
+                ret.extend(IVOLTAGE_NAMES.iter().cloned().zip(ivoltage.iter_mut()));
+                ret.extend(OVOLTAGE_NAMES.iter().cloned().zip(ovoltage.iter_mut()));
+            },
+            XC2BitstreamBits::XC2C128{ref mut data_gate, ref mut use_vref, ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C256{ref mut data_gate, ref mut use_vref, ref mut ivoltage, ref mut ovoltage, ..} => {
+                ret.push(("data_gate", data_gate));
+                ret.push(("use_vref", use_vref));
+                ret.extend(IVOLTAGE_NAMES.iter().cloned().zip(ivoltage.iter_mut()));
+                ret.extend(OVOLTAGE_NAMES.iter().cloned().zip(ovoltage.iter_mut()));
+            },
+            XC2BitstreamBits::XC2C384{ref mut data_gate, ref mut use_vref, ref mut ivoltage, ref mut ovoltage, ..} |
+            XC2BitstreamBits::XC2C512{ref mut data_gate, ref mut use_vref, ref mut ivoltage, ref mut ovoltage, ..} => {
+                ret.push(("data_gate", data_gate));
+                ret.push(("use_vref", use_vref));
+                ret.extend(IVOLTAGE_NAMES.iter().cloned().zip(ivoltage.iter_mut()));
+                ret.extend(OVOLTAGE_NAMES.iter().cloned().zip(ovoltage.iter_mut()));
+            },
+        }
+
+        ret
+    }
+
     /// Convert the actual bitstream bits to crbit format
     pub fn to_crbit(&self, fuse_array: &mut FuseArray) {
         // FBs
@@ -702,96 +1067,35 @@ impl XC2BitstreamBits {
 
         // Clock divider
         if let Some(clock_div) = self.get_clock_div() {
-            let ((clken_x, clken_y), (clkdiv0_x, clkdiv0_y), (clkdiv1_x, clkdiv1_y), (clkdiv2_x, clkdiv2_y),
-                (clkdelay_x, clkdelay_y)) = clock_div_fuse_coord(self.device_type());
-
-            fuse_array.set(clken_x, clken_y, !clock_div.enabled);
-
-            let divratio = clock_div.div_ratio.encode();
-            fuse_array.set(clkdiv0_x, clkdiv0_y, divratio.0);
-            fuse_array.set(clkdiv1_x, clkdiv1_y, divratio.1);
-            fuse_array.set(clkdiv2_x, clkdiv2_y, divratio.2);
-
-            fuse_array.set(clkdelay_x, clkdelay_y, !clock_div.delay);
+            clock_div.to_crbit(self.device_type(), fuse_array);
         }
 
         // Bank voltages and miscellaneous
         match self {
-            &XC2BitstreamBits::XC2C32 {ref ivoltage, ref ovoltage, ..} |
-            &XC2BitstreamBits::XC2C32A {legacy_ivoltage: ref ivoltage, legacy_ovoltage: ref ovoltage, ..} => {
-                fuse_array.set(130, 24, !*ovoltage);
-                fuse_array.set(130, 25, !*ivoltage);
-            }
-            &XC2BitstreamBits::XC2C64 {ref ivoltage, ref ovoltage, ..} |
-            &XC2BitstreamBits::XC2C64A {legacy_ivoltage: ref ivoltage, legacy_ovoltage: ref ovoltage, ..} => {
-                fuse_array.set(137, 23, !*ovoltage);
-                fuse_array.set(138, 23, !*ivoltage);
-            }
-            &XC2BitstreamBits::XC2C128 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
-                fuse_array.set(371, 67, !*data_gate);
-
-                fuse_array.set(8, 67, !ivoltage[0]);
-                fuse_array.set(9, 67, !ovoltage[0]);
-                fuse_array.set(368, 67, !ivoltage[1]);
-                fuse_array.set(369, 67, !ovoltage[1]);
-
-                fuse_array.set(10, 67, !*use_vref);
-            }
-            &XC2BitstreamBits::XC2C256 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
-                fuse_array.set(518, 23, !*data_gate);
-
-                fuse_array.set(175, 23, !ivoltage[0]);
-                fuse_array.set(176, 23, !ovoltage[0]);
-                fuse_array.set(515, 23, !ivoltage[1]);
-                fuse_array.set(516, 23, !ovoltage[1]);
-                
-                fuse_array.set(177, 23, !*use_vref);
-            }
-            &XC2BitstreamBits::XC2C384 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
-                fuse_array.set(932, 17, !*data_gate);
-
-                fuse_array.set(936, 17, !ivoltage[0]);
-                fuse_array.set(937, 17, !ovoltage[0]);
-                fuse_array.set(1864, 17, !ivoltage[1]);
-                fuse_array.set(1865, 17, !ovoltage[1]);
-                fuse_array.set(1, 17, !ivoltage[2]);
-                fuse_array.set(2, 17, !ovoltage[2]);
-                fuse_array.set(929, 17, !ivoltage[3]);
-                fuse_array.set(930, 17, !ovoltage[3]);
-                
-                fuse_array.set(3, 17, !*use_vref);
-            }
-            &XC2BitstreamBits::XC2C512 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
-                fuse_array.set(982, 147, !*data_gate);
-
-                fuse_array.set(992, 147, ivoltage[0]);
-                fuse_array.set(991, 147, ovoltage[0]);
-                fuse_array.set(1965, 147, ivoltage[1]);
-                fuse_array.set(1964, 147, ovoltage[1]);
-                fuse_array.set(3, 147, ivoltage[2]);
-                fuse_array.set(2, 147, ovoltage[2]);
-                fuse_array.set(985, 147, ivoltage[3]);
-                fuse_array.set(984, 147, ovoltage[3]);
-                
-                fuse_array.set(1, 147, !*use_vref);
-            }
-        }
-
-        // A-variant bank voltages
-        match self {
-            &XC2BitstreamBits::XC2C32A {ref ivoltage, ref ovoltage, ..} => {
-                fuse_array.set(131, 25, !ivoltage[0]);
-                fuse_array.set(132, 25, !ovoltage[0]);
-                fuse_array.set(133, 25, !ivoltage[1]);
-                fuse_array.set(134, 25, !ovoltage[1]);
+            &XC2BitstreamBits::XC2C32 {ivoltage, ovoltage, ..} => {
+                XC2MiscBits32 {ivoltage, ovoltage}.encode_crbit32(fuse_array);
             },
-            &XC2BitstreamBits::XC2C64A {ref ivoltage, ref ovoltage, ..} => {
-                fuse_array.set(139, 23, !ivoltage[0]);
-                fuse_array.set(140, 23, !ovoltage[0]);
-                fuse_array.set(141, 23, !ivoltage[1]);
-                fuse_array.set(142, 23, !ovoltage[1]);
+            &XC2BitstreamBits::XC2C64 {ivoltage, ovoltage, ..} => {
+                XC2MiscBits32 {ivoltage, ovoltage}.encode_crbit64(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C32A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage, ..} => {
+                XC2MiscBits32A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage}.encode_crbit32(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C64A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage, ..} => {
+                XC2MiscBits32A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage}.encode_crbit64(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C128 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
+                XC2MiscBits128 {data_gate, use_vref, ivoltage, ovoltage}.encode_crbit128(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C256 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
+                XC2MiscBits128 {data_gate, use_vref, ivoltage, ovoltage}.encode_crbit256(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C384 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
+                XC2MiscBits384 {data_gate, use_vref, ivoltage, ovoltage}.encode_crbit384(fuse_array);
+            },
+            &XC2BitstreamBits::XC2C512 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
+                XC2MiscBits384 {data_gate, use_vref, ivoltage, ovoltage}.encode_crbit512(fuse_array);
             },
-            _ => {}
         }
 
         // Initialize security/done/usercode rows to all 1s
@@ -801,28 +1105,10 @@ impl XC2BitstreamBits {
         }
 
         // Set done1 to 0
-        match self {
-            &XC2BitstreamBits::XC2C32{..} | &XC2BitstreamBits::XC2C32A{..} => {
-                fuse_array.set(9, 48, false);
-            },
-            &XC2BitstreamBits::XC2C64{..} | &XC2BitstreamBits::XC2C64A{..} => {
-                fuse_array.set(8, 96, false);
-            },
-            &XC2BitstreamBits::XC2C128{..} => {
-                fuse_array.set(9, 80, false);
-            },
-            &XC2BitstreamBits::XC2C256{..} => {
-                fuse_array.set(9, 96, false);
-            },
-            &XC2BitstreamBits::XC2C384{..} => {
-                fuse_array.set(9, 120, false);
-            },
-            &XC2BitstreamBits::XC2C512{..} => {
-                fuse_array.set(9, 160, false);
-            },
-        }
+        let (done1_x, done1_y) = done1_fuse_coord(self.device_type());
+        fuse_array.set(done1_x, done1_y, false);
 
-        // TODO: Security bits and USERCODE bits
+        // Security bits and USERCODE bits are set by XC2Bitstream::to_crbit
     }
 
     /// Dump a human-readable explanation of the bitstream to the given `writer` object.
@@ -948,162 +1234,109 @@ impl XC2BitstreamBits {
         // GCK
         linebreaks.add(gck_fuse_idx(self.device_type()));
         linebreaks.add(gck_fuse_idx(self.device_type()));
-        jed.f[gck_fuse_idx(self.device_type()) + 0] = self.get_global_nets().gck_enable[0];
-        jed.f[gck_fuse_idx(self.device_type()) + 1] = self.get_global_nets().gck_enable[1];
-        jed.f[gck_fuse_idx(self.device_type()) + 2] = self.get_global_nets().gck_enable[2];
 
         // Clock divider
         if let Some(clock_div) = self.get_clock_div() {
             let clock_fuse_block = clock_div_fuse_idx(self.device_type());
 
             linebreaks.add(clock_fuse_block);
-            jed.f[clock_fuse_block] = !clock_div.enabled;
-            let clk_div_bits = clock_div.div_ratio.encode();
-            jed.f[clock_fuse_block+1] = clk_div_bits.0;
-            jed.f[clock_fuse_block+2] = clk_div_bits.1;
-            jed.f[clock_fuse_block+3] = clk_div_bits.2;
             linebreaks.add(clock_fuse_block + 4);
-            jed.f[clock_fuse_block + 4] = !clock_div.delay;
+            clock_div.to_jed(self.device_type(), jed);
         }
 
         // GSR
         linebreaks.add(gsr_fuse_idx(self.device_type()));
-        jed.f[gsr_fuse_idx(self.device_type()) + 0] = self.get_global_nets().gsr_invert;
-        jed.f[gsr_fuse_idx(self.device_type()) + 1] = self.get_global_nets().gsr_enable;
 
         // GTS
         linebreaks.add(gts_fuse_idx(self.device_type()));
-        jed.f[gts_fuse_idx(self.device_type()) + 0] = self.get_global_nets().gts_invert[0];
-        jed.f[gts_fuse_idx(self.device_type()) + 1] = !self.get_global_nets().gts_enable[0];
-        jed.f[gts_fuse_idx(self.device_type()) + 2] = self.get_global_nets().gts_invert[1];
-        jed.f[gts_fuse_idx(self.device_type()) + 3] = !self.get_global_nets().gts_enable[1];
-        jed.f[gts_fuse_idx(self.device_type()) + 4] = self.get_global_nets().gts_invert[2];
-        jed.f[gts_fuse_idx(self.device_type()) + 5] = !self.get_global_nets().gts_enable[2];
-        jed.f[gts_fuse_idx(self.device_type()) + 6] = self.get_global_nets().gts_invert[3];
-        jed.f[gts_fuse_idx(self.device_type()) + 7] = !self.get_global_nets().gts_enable[3];
 
         // Global termination
         linebreaks.add(global_term_fuse_idx(self.device_type()));
-        jed.f[global_term_fuse_idx(self.device_type())] = self.get_global_nets().global_pu;
+
+        self.get_global_nets().to_jed(self.device_type(), jed);
 
         // Bank voltages and miscellaneous
         match self {
-            &XC2BitstreamBits::XC2C32 {ref inpin, ref ivoltage, ref ovoltage, ..} |
-            &XC2BitstreamBits::XC2C32A {ref inpin, legacy_ivoltage: ref ivoltage,
-                legacy_ovoltage: ref ovoltage, ..} => {
+            &XC2BitstreamBits::XC2C32 {ref inpin, ivoltage, ovoltage, ..} => {
+                linebreaks.add(12270);
+                linebreaks.add(12271);
+                XC2MiscBits32 {ivoltage, ovoltage}.encode_jed32(&mut jed.f);
 
+                linebreaks.add(12272);
+                inpin.encode_jed(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C32A {ref inpin, legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage, ..} => {
                 linebreaks.add(12270);
-                jed.f[12270] = !ovoltage;
                 linebreaks.add(12271);
-                jed.f[12271] = !ivoltage;
 
                 linebreaks.add(12272);
                 inpin.encode_jed(&mut jed.f);
-            }
-            &XC2BitstreamBits::XC2C64 {ref ivoltage, ref ovoltage, ..} |
-            &XC2BitstreamBits::XC2C64A {legacy_ivoltage: ref ivoltage, legacy_ovoltage: ref ovoltage, ..} => {
+
+                linebreaks.add(12274);
+                linebreaks.add(12275);
+                linebreaks.add(12276);
+                linebreaks.add(12277);
+                XC2MiscBits32A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage}.encode_jed32(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C64 {ivoltage, ovoltage, ..} => {
                 linebreaks.add(25806);
-                jed.f[25806] = !ovoltage;
                 linebreaks.add(25807);
-                jed.f[25807] = !ivoltage;
-            }
-            &XC2BitstreamBits::XC2C128 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
+                XC2MiscBits32 {ivoltage, ovoltage}.encode_jed64(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C64A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage, ..} => {
+                linebreaks.add(25806);
+                linebreaks.add(25807);
+                linebreaks.add(25808);
+                linebreaks.add(25809);
+                linebreaks.add(25810);
+                linebreaks.add(25811);
+                XC2MiscBits32A {legacy_ivoltage, legacy_ovoltage, ivoltage, ovoltage}.encode_jed64(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C128 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
                 linebreaks.add(55335);
-                jed.f[55335] = !data_gate;
-
                 linebreaks.add(55336);
-                jed.f[55336] = !ivoltage[0];
-                jed.f[55337] = !ivoltage[1];
                 linebreaks.add(55338);
-                jed.f[55338] = !ovoltage[0];
-                jed.f[55339] = !ovoltage[1];
-
                 linebreaks.add(55340);
-                jed.f[55340] = !use_vref;
-            }
-            &XC2BitstreamBits::XC2C256 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
+                XC2MiscBits128 {data_gate, use_vref, ivoltage, ovoltage}.encode_jed128(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C256 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
                 linebreaks.add(123243);
-                jed.f[123243] = !data_gate;
-
                 linebreaks.add(123244);
-                jed.f[123244] = !ivoltage[0];
-                jed.f[123245] = !ivoltage[1];
                 linebreaks.add(123246);
-                jed.f[123246] = !ovoltage[0];
-                jed.f[123247] = !ovoltage[1];
-
                 linebreaks.add(123248);
-                jed.f[123248] = !use_vref;
-            }
-            &XC2BitstreamBits::XC2C384 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
+                XC2MiscBits128 {data_gate, use_vref, ivoltage, ovoltage}.encode_jed256(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C384 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
                 linebreaks.add(209347);
-                jed.f[209347] = !data_gate;
-
                 linebreaks.add(209348);
-                jed.f[209348] = !ivoltage[0];
-                jed.f[209349] = !ivoltage[1];
-                jed.f[209350] = !ivoltage[2];
-                jed.f[209351] = !ivoltage[3];
-
                 linebreaks.add(209352);
-                jed.f[209352] = !ovoltage[0];
-                jed.f[209353] = !ovoltage[1];
-                jed.f[209354] = !ovoltage[2];
-                jed.f[209355] = !ovoltage[3];
-
                 linebreaks.add(209356);
-                jed.f[209356] = !use_vref;
-            }
-            &XC2BitstreamBits::XC2C512 {ref ivoltage, ref ovoltage, ref data_gate, ref use_vref, ..}  => {
+                XC2MiscBits384 {data_gate, use_vref, ivoltage, ovoltage}.encode_jed384(&mut jed.f);
+            },
+            &XC2BitstreamBits::XC2C512 {data_gate, use_vref, ivoltage, ovoltage, ..} => {
                 linebreaks.add(296393);
-                jed.f[296393] = !data_gate;
-
                 linebreaks.add(296394);
-                jed.f[296394] = ivoltage[0];
-                jed.f[296395] = ivoltage[1];
-                jed.f[296396] = ivoltage[2];
-                jed.f[296397] = ivoltage[3];
-
                 linebreaks.add(296398);
-                jed.f[296398] = ovoltage[0];
-                jed.f[296399] = ovoltage[1];
-                jed.f[296400] = ovoltage[2];
-                jed.f[296401] = ovoltage[3];
-
                 linebreaks.add(296402);
-                jed.f[296402] = !use_vref;
-            }
-        }
-
-        // A-variant bank voltages
-        match self {
-            &XC2BitstreamBits::XC2C32A {ref ivoltage, ref ovoltage, ..} => {
-                linebreaks.add(12274);
-                jed.f[12274] = !ivoltage[0];
-                linebreaks.add(12275);
-                jed.f[12275] = !ovoltage[0];
-                linebreaks.add(12276);
-                jed.f[12276] = !ivoltage[1];
-                linebreaks.add(12277);
-                jed.f[12277] = !ovoltage[1];
+                XC2MiscBits384 {data_gate, use_vref, ivoltage, ovoltage}.encode_jed512(&mut jed.f);
             },
-            &XC2BitstreamBits::XC2C64A {ref ivoltage, ref ovoltage, ..} => {
-                linebreaks.add(25808);
-                jed.f[25808] = !ivoltage[0];
-                linebreaks.add(25809);
-                jed.f[25809] = !ovoltage[0];
-                linebreaks.add(25810);
-                jed.f[25810] = !ivoltage[1];
-                linebreaks.add(25811);
-                jed.f[25811] = !ovoltage[1];
-            },
-            _ => {}
         }
     }
 }
 
+/// Internal function to read the per-bank voltage lines of the human-readable dump for the given `direction`
+fn read_bank_voltages(reader: &mut HumanReadableReader, direction: &str, banks: &mut [bool])
+    -> Result<(), XC2BitError> {
+
+    for (i, bank) in banks.iter_mut().enumerate() {
+        *bank = reader.flag(&format!("bank {} {} voltage range: ", i, direction), "high", "low")?;
+    }
+
+    Ok(())
+}
+
 /// Common logic for reading bitstreams on "small" devices
-fn read_bitstream_logical_common_small(fuses: &[bool], device: XC2Device,
+fn read_bitstream_logical_common_small<F: FuseRead<usize> + ?Sized>(fuses: &F, device: XC2Device,
     fb: &mut [XC2BitstreamFB], iobs: &mut [XC2MCSmallIOB]) -> Result<(), XC2BitError> {
 
     for i in 0..fb.len() {
@@ -1128,7 +1361,7 @@ fn read_bitstream_logical_common_small(fuses: &[bool], device: XC2Device,
 }
 
 /// Common logic for reading bitstreams on "large" devices
-fn read_bitstream_logical_common_large(fuses: &[bool], device: XC2Device,
+fn read_bitstream_logical_common_large<F: FuseRead<usize> + ?Sized>(fuses: &F, device: XC2Device,
     fb: &mut [XC2BitstreamFB], iobs: &mut [XC2MCLargeIOB]) -> Result<(), XC2BitError> {
 
     for i in 0..fb.len() {
@@ -1158,7 +1391,7 @@ fn read_bitstream_logical_common_large(fuses: &[bool], device: XC2Device,
     Ok(())
 }
 /// Internal function for parsing an XC2C32 bitstream
-fn read_32_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_32_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 2];
     let mut iobs = [XC2MCSmallIOB::default(); 32];
     
@@ -1168,18 +1401,20 @@ fn read_32_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitE
 
     let global_nets = XC2GlobalNets::from_jed(XC2Device::XC2C32, fuses);
 
+    let misc = XC2MiscBits32::decode_jed32(fuses);
+
     Ok(XC2BitstreamBits::XC2C32 {
         fb,
         iobs,
         inpin,
         global_nets,
-        ovoltage: !fuses[12270],
-        ivoltage: !fuses[12271],
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C32A bitstream
-fn read_32a_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_32a_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 2];
     let mut iobs = [XC2MCSmallIOB::default(); 32];
     
@@ -1189,26 +1424,22 @@ fn read_32a_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
 
     let global_nets = XC2GlobalNets::from_jed(XC2Device::XC2C32A, fuses);
 
+    let misc = XC2MiscBits32A::decode_jed32(fuses);
+
     Ok(XC2BitstreamBits::XC2C32A {
         fb,
         iobs,
         inpin,
         global_nets,
-        legacy_ovoltage: !fuses[12270],
-        legacy_ivoltage: !fuses[12271],
-        ivoltage: [
-            !fuses[12274],
-            !fuses[12276],
-        ],
-        ovoltage: [
-            !fuses[12275],
-            !fuses[12277],
-        ]
+        legacy_ivoltage: misc.legacy_ivoltage,
+        legacy_ovoltage: misc.legacy_ovoltage,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C64 bitstream
-fn read_64_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_64_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 4];
     let mut iobs = [XC2MCSmallIOB::default(); 64];
     
@@ -1221,17 +1452,19 @@ fn read_64_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitE
         iobs2[i / 32][i % 32] = iobs[i];
     }
 
+    let misc = XC2MiscBits32::decode_jed64(fuses);
+
     Ok(XC2BitstreamBits::XC2C64 {
         fb,
         iobs: iobs2,
         global_nets,
-        ovoltage: !fuses[25806],
-        ivoltage: !fuses[25807],
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C64A bitstream
-fn read_64a_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_64a_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 4];
     let mut iobs = [XC2MCSmallIOB::default(); 64];
     
@@ -1244,25 +1477,21 @@ fn read_64a_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
         iobs2[i / 32][i % 32] = iobs[i];
     }
 
+    let misc = XC2MiscBits32A::decode_jed64(fuses);
+
     Ok(XC2BitstreamBits::XC2C64A {
         fb,
         iobs: iobs2,
         global_nets,
-        legacy_ovoltage: !fuses[25806],
-        legacy_ivoltage: !fuses[25807],
-        ivoltage: [
-            !fuses[25808],
-            !fuses[25810],
-        ],
-        ovoltage: [
-            !fuses[25809],
-            !fuses[25811],
-        ]
+        legacy_ivoltage: misc.legacy_ivoltage,
+        legacy_ovoltage: misc.legacy_ovoltage,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C128 bitstream
-fn read_128_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_128_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 8];
     let mut iobs = [XC2MCLargeIOB::default(); 100];
     
@@ -1275,26 +1504,22 @@ fn read_128_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
         iobs2[i / 25][i % 25] = iobs[i];
     }
 
+    let misc = XC2MiscBits128::decode_jed128(fuses);
+
     Ok(XC2BitstreamBits::XC2C128 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_jed(XC2Device::XC2C128, fuses),
-        data_gate: !fuses[55335],
-        use_vref: !fuses[55340],
-        ivoltage: [
-            !fuses[55336],
-            !fuses[55337],
-        ],
-        ovoltage: [
-            !fuses[55338],
-            !fuses[55339],
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C256 bitstream
-fn read_256_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_256_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 16];
     let mut iobs = [XC2MCLargeIOB::default(); 184];
     
@@ -1307,26 +1532,22 @@ fn read_256_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
         iobs2[i / 23][i % 23] = iobs[i];
     }
 
+    let misc = XC2MiscBits128::decode_jed256(fuses);
+
     Ok(XC2BitstreamBits::XC2C256 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_jed(XC2Device::XC2C256, fuses),
-        data_gate: !fuses[123243],
-        use_vref: !fuses[123248],
-        ivoltage: [
-            !fuses[123244],
-            !fuses[123245],
-        ],
-        ovoltage: [
-            !fuses[123246],
-            !fuses[123247],
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C384 bitstream
-fn read_384_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_384_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 24];
     let mut iobs = [XC2MCLargeIOB::default(); 240];
     
@@ -1339,30 +1560,22 @@ fn read_384_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
         iobs2[i / 24][i % 24] = iobs[i];
     }
 
+    let misc = XC2MiscBits384::decode_jed384(fuses);
+
     Ok(XC2BitstreamBits::XC2C384 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_jed(XC2Device::XC2C384, fuses),
-        data_gate: !fuses[209347],
-        use_vref: !fuses[209356],
-        ivoltage: [
-            !fuses[209348],
-            !fuses[209349],
-            !fuses[209350],
-            !fuses[209351],
-        ],
-        ovoltage: [
-            !fuses[209352],
-            !fuses[209353],
-            !fuses[209354],
-            !fuses[209355],
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
 /// Internal function for parsing an XC2C512 bitstream
-fn read_512_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2BitError> {
+fn read_512_bitstream_logical<F: FuseRead<usize> + ?Sized>(fuses: &F) -> Result<XC2BitstreamBits, XC2BitError> {
     let mut fb = [XC2BitstreamFB::default(); 32];
     let mut iobs = [XC2MCLargeIOB::default(); 270];
     
@@ -1375,25 +1588,17 @@ fn read_512_bitstream_logical(fuses: &[bool]) -> Result<XC2BitstreamBits, XC2Bit
         iobs2[i / 27][i % 27] = iobs[i];
     }
 
+    let misc = XC2MiscBits384::decode_jed512(fuses);
+
     Ok(XC2BitstreamBits::XC2C512 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_jed(XC2Device::XC2C512, fuses),
-        data_gate: !fuses[296393],
-        use_vref: !fuses[296402],
-        ivoltage: [
-            fuses[296394],
-            fuses[296395],
-            fuses[296396],
-            fuses[296397],
-        ],
-        ovoltage: [
-            fuses[296398],
-            fuses[296399],
-            fuses[296400],
-            fuses[296401],
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1438,13 +1643,15 @@ fn read_32_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBits
 
     let global_nets = XC2GlobalNets::from_crbit(XC2Device::XC2C32, fuse_array);
 
+    let misc = XC2MiscBits32::decode_crbit32(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C32 {
         fb,
         iobs,
         inpin,
         global_nets,
-        ovoltage: !fuse_array.get(130, 24),
-        ivoltage: !fuse_array.get(130, 25),
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1459,21 +1666,17 @@ fn read_32a_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
 
     let global_nets = XC2GlobalNets::from_crbit(XC2Device::XC2C32A, fuse_array);
 
+    let misc = XC2MiscBits32A::decode_crbit32(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C32A {
         fb,
         iobs,
         inpin,
         global_nets,
-        legacy_ovoltage: !fuse_array.get(130, 24),
-        legacy_ivoltage: !fuse_array.get(130, 25),
-        ivoltage: [
-            !fuse_array.get(131, 25),
-            !fuse_array.get(133, 25),
-        ],
-        ovoltage: [
-            !fuse_array.get(132, 25),
-            !fuse_array.get(134, 25),
-        ]
+        legacy_ivoltage: misc.legacy_ivoltage,
+        legacy_ovoltage: misc.legacy_ovoltage,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1491,12 +1694,14 @@ fn read_64_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBits
         iobs2[i / 32][i % 32] = iobs[i];
     }
 
+    let misc = XC2MiscBits32::decode_crbit64(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C64 {
         fb,
         iobs: iobs2,
         global_nets,
-        ovoltage: !fuse_array.get(137, 23),
-        ivoltage: !fuse_array.get(138, 23),
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1514,20 +1719,16 @@ fn read_64a_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
         iobs2[i / 32][i % 32] = iobs[i];
     }
 
+    let misc = XC2MiscBits32A::decode_crbit64(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C64A {
         fb,
         iobs: iobs2,
         global_nets,
-        legacy_ovoltage: !fuse_array.get(137, 23),
-        legacy_ivoltage: !fuse_array.get(138, 23),
-        ivoltage: [
-            !fuse_array.get(139, 23),
-            !fuse_array.get(141, 23),
-        ],
-        ovoltage: [
-            !fuse_array.get(140, 23),
-            !fuse_array.get(142, 23),
-        ]
+        legacy_ivoltage: misc.legacy_ivoltage,
+        legacy_ovoltage: misc.legacy_ovoltage,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1545,21 +1746,17 @@ fn read_128_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
         iobs2[i / 25][i % 25] = iobs[i];
     }
 
+    let misc = XC2MiscBits128::decode_crbit128(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C128 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_crbit(XC2Device::XC2C128, fuse_array),
-        data_gate: !fuse_array.get(371, 67),
-        use_vref: !fuse_array.get(10, 67),
-        ivoltage: [
-            !fuse_array.get(8, 67),
-            !fuse_array.get(368, 67),
-        ],
-        ovoltage: [
-            !fuse_array.get(9, 67),
-            !fuse_array.get(369, 67),
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1577,21 +1774,17 @@ fn read_256_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
         iobs2[i / 23][i % 23] = iobs[i];
     }
 
+    let misc = XC2MiscBits128::decode_crbit256(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C256 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_crbit(XC2Device::XC2C256, fuse_array),
-        data_gate: !fuse_array.get(518, 23),
-        use_vref: !fuse_array.get(177, 23),
-        ivoltage: [
-            !fuse_array.get(175, 23),
-            !fuse_array.get(515, 23),
-        ],
-        ovoltage: [
-            !fuse_array.get(176, 23),
-            !fuse_array.get(516, 23),
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1609,25 +1802,17 @@ fn read_384_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
         iobs2[i / 24][i % 24] = iobs[i];
     }
 
+    let misc = XC2MiscBits384::decode_crbit384(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C384 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_crbit(XC2Device::XC2C384, fuse_array),
-        data_gate: !fuse_array.get(932, 17),
-        use_vref: !fuse_array.get(3, 17),
-        ivoltage: [
-            !fuse_array.get(936, 17),
-            !fuse_array.get(1864, 17),
-            !fuse_array.get(1, 17),
-            !fuse_array.get(929, 17),
-        ],
-        ovoltage: [
-            !fuse_array.get(937, 17),
-            !fuse_array.get(1865, 17),
-            !fuse_array.get(2, 17),
-            !fuse_array.get(930, 17),
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
 
@@ -1645,24 +1830,16 @@ fn read_512_bitstream_physical(fuse_array: &FuseArray) -> Result<XC2BitstreamBit
         iobs2[i / 27][i % 27] = iobs[i];
     }
 
+    let misc = XC2MiscBits384::decode_crbit512(fuse_array);
+
     Ok(XC2BitstreamBits::XC2C512 {
         fb,
         iobs: iobs2,
         global_nets,
         clock_div: XC2ClockDiv::from_crbit(XC2Device::XC2C512, fuse_array),
-        data_gate: !fuse_array.get(982, 147),
-        use_vref: !fuse_array.get(1, 147),
-        ivoltage: [
-            fuse_array.get(992, 147),
-            fuse_array.get(1965, 147),
-            fuse_array.get(3, 147),
-            fuse_array.get(985, 147),
-        ],
-        ovoltage: [
-            fuse_array.get(991, 147),
-            fuse_array.get(1964, 147),
-            fuse_array.get(2, 147),
-            fuse_array.get(984, 147),
-        ]
+        data_gate: misc.data_gate,
+        use_vref: misc.use_vref,
+        ivoltage: misc.ivoltage,
+        ovoltage: misc.ovoltage,
     })
 }
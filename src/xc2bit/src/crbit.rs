@@ -27,17 +27,22 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use util::{b2s};
 
+use jedec::{BitVec, FuseRead, FuseStore};
+
 use std::io;
 use std::io::Write;
-use std::ops::{Index, IndexMut};
+use std::ops::{Index};
 use std::str;
 
 /// Struct representing a 2-dimensional fuse array and handles converting xy-coordinates into a single linear index.
 /// The x-axis is horizontal and the y-axis is vertical. The origin is at the top-left corner. (This is the standard
 /// "computer graphics" coordinate scheme.)
+///
+/// Since xc2bit 0.0.4 the fuses are stored packed, so `fuse_array[(x, y)]` can be read but fuses must be written with
+/// `set(x, y, val)` instead of `fuse_array[(x, y)] = val`.
 pub struct FuseArray {
     /// Internal 1-dimensional storage
-    v: Vec<bool>,
+    v: BitVec,
     /// Width of the array
     w: usize,
     /// Possibly contains a device name
@@ -47,12 +52,17 @@ pub struct FuseArray {
 impl FuseArray {
     /// Get a fuse value at the particular xy coordinate
     pub fn get(&self, x: usize, y: usize) -> bool {
-        self.v[y * self.w + x]
+        self.v.get(y * self.w + x)
     }
 
     /// Set the fuse value at the particular xy coordinate
     pub fn set(&mut self, x: usize, y: usize, val: bool) {
-        self.v[y * self.w + x] = val;
+        self.v.set(y * self.w + x, val);
+    }
+
+    /// Returns the underlying packed storage. The fuse at (x, y) is at index `y * width + x`.
+    pub fn bits(&self) -> &BitVec {
+        &self.v
     }
 
     /// Returns the dimensions of this array as (width, height)
@@ -62,8 +72,7 @@ impl FuseArray {
 
     /// Processes the given data and converts it into a `FuseArray` struct.
     pub fn from_file_contents(in_bytes: &[u8]) -> Result<Self, &'static str> {
-        // This capacity is approximate but close enough
-        let mut v = Vec::with_capacity(in_bytes.len());
+        let mut v = BitVec::new();
         let mut w = None;
         let mut dev_name_str = None;
 
@@ -112,7 +121,7 @@ impl FuseArray {
     pub fn from_dim(w: usize, h: usize) -> Self {
         FuseArray {
             w,
-            v: vec![false; w*h],
+            v: BitVec::from_elem(w*h, false),
             dev_name_str: None,
         }
     }
@@ -148,9 +157,15 @@ impl Index<(usize, usize)> for FuseArray {
     }
 }
 
-impl IndexMut<(usize, usize)> for FuseArray {
-    fn index_mut(&mut self, coords: (usize, usize)) -> &mut bool {
-        &mut self.v[coords.1 * self.w + coords.0]
+impl FuseRead<(usize, usize)> for FuseArray {
+    fn get_fuse(&self, coords: (usize, usize)) -> bool {
+        self.get(coords.0, coords.1)
+    }
+}
+
+impl FuseStore<(usize, usize)> for FuseArray {
+    fn set_fuse(&mut self, coords: (usize, usize), val: bool) {
+        self.set(coords.0, coords.1, val);
     }
 }
 
@@ -0,0 +1,481 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains functions for comparing bitstreams
+
+use std::fmt;
+use std::fmt::Debug;
+
+use *;
+
+/// Represents one semantic difference between two bitstreams. Values of fields are shown using their `Debug`
+/// representation.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum XC2BitstreamChange {
+    /// The bitstreams are for different devices. Bitstreams for different devices are not compared any further.
+    Device {
+        old: XC2Device,
+        new: XC2Device,
+    },
+    /// The speed grade differs
+    SpeedGrade {
+        old: XC2Speed,
+        new: XC2Speed,
+    },
+    /// The package differs
+    Package {
+        old: XC2Package,
+        new: XC2Package,
+    },
+    /// The security fuse differs
+    SecurityFuse {
+        old: bool,
+        new: bool,
+    },
+    /// The USERCODE differs
+    Usercode {
+        old: Option<u32>,
+        new: Option<u32>,
+    },
+    /// A device-wide setting (global nets, clock divider, voltage levels, etc.) differs
+    Global {
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// A field of a macrocell differs
+    Macrocell {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// A field of an IOB differs. The IOB is identified by the macrocell it is attached to.
+    IOB {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// The input selected by a ZIA row differs
+    ZIARow {
+        fb: u32,
+        row: u32,
+        old: XC2ZIAInput,
+        new: XC2ZIAInput,
+    },
+    /// Whether an input (`complement` = `false`) or its complement (`complement` = `true`) is used in an AND term
+    /// differs. `new` is the state in the new bitstream.
+    AndTermInput {
+        fb: u32,
+        term: u32,
+        input: u32,
+        complement: bool,
+        new: bool,
+    },
+    /// Whether an AND term is used in an OR term differs. `new` is the state in the new bitstream.
+    OrTermInput {
+        fb: u32,
+        mc: u32,
+        term: u32,
+        new: bool,
+    },
+}
+
+impl fmt::Display for XC2BitstreamChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2BitstreamChange::Device{old, new} =>
+                write!(f, "device: {} -> {}", old, new),
+            XC2BitstreamChange::SpeedGrade{old, new} =>
+                write!(f, "speed grade: {} -> {}", old, new),
+            XC2BitstreamChange::Package{old, new} =>
+                write!(f, "package: {} -> {}", old, new),
+            XC2BitstreamChange::SecurityFuse{old, new} =>
+                write!(f, "security fuse: {} -> {}", if old {"set"} else {"not set"}, if new {"set"} else {"not set"}),
+            XC2BitstreamChange::Usercode{old, new} => {
+                write!(f, "usercode: ")?;
+                match old {
+                    Some(x) => write!(f, "{:08X}", x)?,
+                    None => write!(f, "none")?,
+                }
+                write!(f, " -> ")?;
+                match new {
+                    Some(x) => write!(f, "{:08X}", x),
+                    None => write!(f, "none"),
+                }
+            },
+            XC2BitstreamChange::Global{field, ref old, ref new} =>
+                write!(f, "{}: {} -> {}", field, old, new),
+            XC2BitstreamChange::Macrocell{fb, mc, field, ref old, ref new} =>
+                write!(f, "FB{}_{} {}: {} -> {}", fb + 1, mc + 1, field, old, new),
+            XC2BitstreamChange::IOB{fb, mc, field, ref old, ref new} =>
+                write!(f, "FB{}_{} I/O {}: {} -> {}", fb + 1, mc + 1, field, old, new),
+            XC2BitstreamChange::ZIARow{fb, row, old, new} =>
+                write!(f, "FB{} ZIA row {}: {:?} -> {:?}", fb + 1, row, old, new),
+            XC2BitstreamChange::AndTermInput{fb, term, input, complement, new} =>
+                write!(f, "FB{} AND term {} input {}{}: {}", fb + 1, term, if complement {"~"} else {""}, input,
+                    if new {"added"} else {"removed"}),
+            XC2BitstreamChange::OrTermInput{fb, mc, term, new} =>
+                write!(f, "FB{}_{} OR term input {}: {}", fb + 1, mc + 1, term, if new {"added"} else {"removed"}),
+        }
+    }
+}
+
+/// Internal helper that records a field if it differs
+fn diff_field<T: PartialEq + Debug>(out: &mut Vec<(&'static str, String, String)>, field: &'static str,
+    old: &T, new: &T) {
+
+    if old != new {
+        out.push((field, format!("{:?}", old), format!("{:?}", new)));
+    }
+}
+
+impl XC2BitstreamFB {
+    /// Compares this FB against `other` and appends the differences to `changes`. `fb` must be the index of this
+    /// function block.
+    pub fn diff(&self, other: &XC2BitstreamFB, fb: u32, changes: &mut Vec<XC2BitstreamChange>) {
+        for mc in 0..MCS_PER_FB {
+            self.mcs[mc].diff(&other.mcs[mc], fb, mc as u32, changes);
+        }
+
+        for row in 0..INPUTS_PER_ANDTERM {
+            if self.get_zia(row) != other.get_zia(row) {
+                changes.push(XC2BitstreamChange::ZIARow {
+                    fb,
+                    row: row as u32,
+                    old: *self.get_zia(row),
+                    new: *other.get_zia(row),
+                });
+            }
+        }
+
+        for term in 0..ANDTERMS_PER_FB {
+            let old_term = self.get_andterm(term);
+            let new_term = other.get_andterm(term);
+            for input in 0..INPUTS_PER_ANDTERM {
+                if old_term.get(input) != new_term.get(input) {
+                    changes.push(XC2BitstreamChange::AndTermInput {
+                        fb,
+                        term: term as u32,
+                        input: input as u32,
+                        complement: false,
+                        new: new_term.get(input),
+                    });
+                }
+                if old_term.get_b(input) != new_term.get_b(input) {
+                    changes.push(XC2BitstreamChange::AndTermInput {
+                        fb,
+                        term: term as u32,
+                        input: input as u32,
+                        complement: true,
+                        new: new_term.get_b(input),
+                    });
+                }
+            }
+        }
+
+        for mc in 0..MCS_PER_FB {
+            for term in 0..ANDTERMS_PER_FB {
+                if self.or_terms[mc].get(term) != other.or_terms[mc].get(term) {
+                    changes.push(XC2BitstreamChange::OrTermInput {
+                        fb,
+                        mc: mc as u32,
+                        term: term as u32,
+                        new: other.or_terms[mc].get(term),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl XC2Macrocell {
+    /// Compares this macrocell against `other` and appends the differences to `changes`. `fb` and `mc` must be the
+    /// function block and macrocell index of this macrocell.
+    pub fn diff(&self, other: &XC2Macrocell, fb: u32, mc: u32, changes: &mut Vec<XC2BitstreamChange>) {
+        let mut fields = Vec::new();
+        diff_field(&mut fields, "clk_src", &self.clk_src, &other.clk_src);
+        diff_field(&mut fields, "clk_invert_pol", &self.clk_invert_pol, &other.clk_invert_pol);
+        diff_field(&mut fields, "is_ddr", &self.is_ddr, &other.is_ddr);
+        diff_field(&mut fields, "r_src", &self.r_src, &other.r_src);
+        diff_field(&mut fields, "s_src", &self.s_src, &other.s_src);
+        diff_field(&mut fields, "init_state", &self.init_state, &other.init_state);
+        diff_field(&mut fields, "reg_mode", &self.reg_mode, &other.reg_mode);
+        diff_field(&mut fields, "fb_mode", &self.fb_mode, &other.fb_mode);
+        diff_field(&mut fields, "ff_in_ibuf", &self.ff_in_ibuf, &other.ff_in_ibuf);
+        diff_field(&mut fields, "xor_mode", &self.xor_mode, &other.xor_mode);
+
+        for (field, old, new) in fields {
+            changes.push(XC2BitstreamChange::Macrocell{fb, mc, field, old, new});
+        }
+    }
+}
+
+impl XC2MCSmallIOB {
+    /// Compares this IOB against `other` and appends the differences to `changes`. `fb` and `mc` must be the
+    /// function block and macrocell index this IOB is attached to.
+    pub fn diff(&self, other: &XC2MCSmallIOB, fb: u32, mc: u32, changes: &mut Vec<XC2BitstreamChange>) {
+        let mut fields = Vec::new();
+        diff_field(&mut fields, "zia_mode", &self.zia_mode, &other.zia_mode);
+        diff_field(&mut fields, "schmitt_trigger", &self.schmitt_trigger, &other.schmitt_trigger);
+        diff_field(&mut fields, "obuf_uses_ff", &self.obuf_uses_ff, &other.obuf_uses_ff);
+        diff_field(&mut fields, "obuf_mode", &self.obuf_mode, &other.obuf_mode);
+        diff_field(&mut fields, "termination_enabled", &self.termination_enabled, &other.termination_enabled);
+        diff_field(&mut fields, "slew_is_fast", &self.slew_is_fast, &other.slew_is_fast);
+
+        for (field, old, new) in fields {
+            changes.push(XC2BitstreamChange::IOB{fb, mc, field, old, new});
+        }
+    }
+}
+
+impl XC2MCLargeIOB {
+    /// Compares this IOB against `other` and appends the differences to `changes`. `fb` and `mc` must be the
+    /// function block and macrocell index this IOB is attached to.
+    pub fn diff(&self, other: &XC2MCLargeIOB, fb: u32, mc: u32, changes: &mut Vec<XC2BitstreamChange>) {
+        let mut fields = Vec::new();
+        diff_field(&mut fields, "zia_mode", &self.zia_mode, &other.zia_mode);
+        diff_field(&mut fields, "ibuf_mode", &self.ibuf_mode, &other.ibuf_mode);
+        diff_field(&mut fields, "obuf_uses_ff", &self.obuf_uses_ff, &other.obuf_uses_ff);
+        diff_field(&mut fields, "obuf_mode", &self.obuf_mode, &other.obuf_mode);
+        diff_field(&mut fields, "termination_enabled", &self.termination_enabled, &other.termination_enabled);
+        diff_field(&mut fields, "slew_is_fast", &self.slew_is_fast, &other.slew_is_fast);
+        diff_field(&mut fields, "uses_data_gate", &self.uses_data_gate, &other.uses_data_gate);
+
+        for (field, old, new) in fields {
+            changes.push(XC2BitstreamChange::IOB{fb, mc, field, old, new});
+        }
+    }
+}
+
+impl XC2Bitstream {
+    /// Compares this bitstream against `other` and returns a list of the semantic differences between them.
+    /// Differences are reported in the order: part information, security/USERCODE, device-wide settings, IOBs,
+    /// and then function blocks.
+    pub fn diff(&self, other: &XC2Bitstream) -> Vec<XC2BitstreamChange> {
+        let mut changes = Vec::new();
+
+        let device = self.bits.device_type();
+        if device != other.bits.device_type() {
+            changes.push(XC2BitstreamChange::Device{old: device, new: other.bits.device_type()});
+            return changes;
+        }
+        if self.speed_grade != other.speed_grade {
+            changes.push(XC2BitstreamChange::SpeedGrade{old: self.speed_grade, new: other.speed_grade});
+        }
+        if self.package != other.package {
+            changes.push(XC2BitstreamChange::Package{old: self.package, new: other.package});
+        }
+        // A missing G field is the same as a clear security fuse
+        let (old_secure, new_secure) = (self.secure == Some(true), other.secure == Some(true));
+        if old_secure != new_secure {
+            changes.push(XC2BitstreamChange::SecurityFuse{old: old_secure, new: new_secure});
+        }
+        if self.usercode != other.usercode {
+            changes.push(XC2BitstreamChange::Usercode{old: self.usercode, new: other.usercode});
+        }
+
+        // Global nets
+        let mut fields = Vec::new();
+        let old_nets = self.bits.get_global_nets();
+        let new_nets = other.bits.get_global_nets();
+        diff_field(&mut fields, "gck_enable", &old_nets.gck_enable, &new_nets.gck_enable);
+        diff_field(&mut fields, "gsr_enable", &old_nets.gsr_enable, &new_nets.gsr_enable);
+        diff_field(&mut fields, "gsr_invert", &old_nets.gsr_invert, &new_nets.gsr_invert);
+        diff_field(&mut fields, "gts_enable", &old_nets.gts_enable, &new_nets.gts_enable);
+        diff_field(&mut fields, "gts_invert", &old_nets.gts_invert, &new_nets.gts_invert);
+        diff_field(&mut fields, "global_pu", &old_nets.global_pu, &new_nets.global_pu);
+
+        // Clock divider
+        if let (Some(old_div), Some(new_div)) = (self.bits.get_clock_div(), other.bits.get_clock_div()) {
+            diff_field(&mut fields, "clock_div.div_ratio", &old_div.div_ratio, &new_div.div_ratio);
+            diff_field(&mut fields, "clock_div.delay", &old_div.delay, &new_div.delay);
+            diff_field(&mut fields, "clock_div.enabled", &old_div.enabled, &new_div.enabled);
+        }
+
+        // Input-only pin
+        if let (Some(old_inpin), Some(new_inpin)) = (self.bits.get_inpin(), other.bits.get_inpin()) {
+            diff_field(&mut fields, "inpin.schmitt_trigger", &old_inpin.schmitt_trigger, &new_inpin.schmitt_trigger);
+            diff_field(&mut fields, "inpin.termination_enabled",
+                &old_inpin.termination_enabled, &new_inpin.termination_enabled);
+        }
+
+        // Everything else
+        for ((field, old), (_, new)) in self.bits.misc_settings().into_iter().zip(other.bits.misc_settings()) {
+            diff_field(&mut fields, field, &old, &new);
+        }
+
+        for (field, old, new) in fields {
+            changes.push(XC2BitstreamChange::Global{field, old, new});
+        }
+
+        // IOBs
+        for i in 0..device.num_iobs() {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, i as u32).unwrap();
+            if device.is_small_iob() {
+                self.bits.get_small_iob(i).unwrap().diff(other.bits.get_small_iob(i).unwrap(), fb, mc, &mut changes);
+            } else {
+                self.bits.get_large_iob(i).unwrap().diff(other.bits.get_large_iob(i).unwrap(), fb, mc, &mut changes);
+            }
+        }
+
+        // FBs
+        for i in 0..device.num_fbs() {
+            self.bits.get_fb()[i].diff(&other.bits.get_fb()[i], i as u32, &mut changes);
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use jedec::*;
+
+    #[test]
+    fn diff_identical() {
+        let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-6-vq44").unwrap());
+        let b = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-6-vq44").unwrap());
+
+        assert_eq!(a.diff(&b), vec![]);
+        assert_eq!(a.diff_fuses(&b), vec![]);
+    }
+
+    #[test]
+    fn diff_different_device() {
+        let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-6-vq44").unwrap());
+        let b = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+
+        assert_eq!(a.diff(&b), vec![XC2BitstreamChange::Device{old: XC2Device::XC2C32A, new: XC2Device::XC2C64A}]);
+    }
+
+    #[test]
+    fn diff_semantic() {
+        let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+        let mut b = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+
+        b.usercode = Some(0x12345678);
+        b.bits.get_global_nets_mut().gsr_enable = true;
+        b.bits.get_mut_small_iob(0).unwrap().schmitt_trigger = false;
+        {
+            let fb = &mut b.bits.get_fb_mut()[1];
+            fb.mcs[4].init_state = !fb.mcs[4].init_state;
+            *fb.get_mut_zia(3) = XC2ZIAInput::Zero;
+            fb.get_mut_andterm(7).set_b(2, true);
+            fb.or_terms[5].set(7, true);
+        }
+
+        let (iob_fb, iob_mc) = iob_num_to_fb_mc_num(XC2Device::XC2C64A, 0).unwrap();
+        let init_state = a.bits.get_fb()[1].mcs[4].init_state;
+        assert_eq!(a.diff(&b), vec![
+            XC2BitstreamChange::Usercode{old: None, new: Some(0x12345678)},
+            XC2BitstreamChange::Global{field: "gsr_enable", old: "false".to_owned(), new: "true".to_owned()},
+            XC2BitstreamChange::IOB{fb: iob_fb, mc: iob_mc, field: "schmitt_trigger",
+                old: "true".to_owned(), new: "false".to_owned()},
+            XC2BitstreamChange::Macrocell{fb: 1, mc: 4, field: "init_state",
+                old: format!("{:?}", init_state), new: format!("{:?}", !init_state)},
+            XC2BitstreamChange::ZIARow{fb: 1, row: 3, old: XC2ZIAInput::One, new: XC2ZIAInput::Zero},
+            XC2BitstreamChange::AndTermInput{fb: 1, term: 7, input: 2, complement: true, new: true},
+            XC2BitstreamChange::OrTermInput{fb: 1, mc: 5, term: 7, new: true},
+        ]);
+
+        // The USERCODE is not part of the fuse array, but every other change is. Changing the ZIA row from One to
+        // Zero changes two of its fuses.
+        assert_eq!(a.diff_fuses(&b), vec![6032, 6503, 6504, 7653, 11685, 12598, 25796]);
+        assert_eq!(a.diff_fuses(&b).into_iter().map(|x| explain_jed_fuse(XC2Device::XC2C64A, x).unwrap().owner)
+            .collect::<Vec<_>>(), vec![
+            XC2FuseOwner::IOB{fb: iob_fb, mc: iob_mc, field: "schmitt_trigger", bit: None},
+            XC2FuseOwner::ZIARow{fb: 1, row: 3, bit: 7},
+            XC2FuseOwner::ZIARow{fb: 1, row: 3, bit: 8},
+            XC2FuseOwner::AndTermInput{fb: 1, term: 7, input: 2, complement: true},
+            XC2FuseOwner::OrTermInput{fb: 1, mc: 5, term: 7},
+            XC2FuseOwner::Macrocell{fb: 1, mc: 4, field: "init_state", bit: None},
+            XC2FuseOwner::GlobalNets{field: "gsr_enable", bit: None},
+        ]);
+        b.usercode = None;
+        assert_eq!(a.diff(&b).len(), 6);
+    }
+
+    #[test]
+    fn diff_every_field() {
+        // Changing any fuse of a setting must show up as a change of that setting, so that fields added to the
+        // structs cannot be forgotten here
+        for &part in &["XC2C32-4-VQ44", "XC2C32A-4-VQ44", "XC2C64-5-VQ44", "XC2C64A-5-VQ44", "XC2C128-6-VQ100",
+                       "XC2C256-6-VQ100", "XC2C384-7-TQ144", "XC2C512-7-PQ208"] {
+            let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap());
+            let device = a.bits.device_type();
+            let mut jed_data = Vec::new();
+            a.to_jed(&mut jed_data).unwrap();
+            let jed = JEDECFile::from_bytes(&jed_data).unwrap();
+
+            for (fuse, info) in jed_fuse_map(device) {
+                let expected = |change: &XC2BitstreamChange| match (info.owner, change) {
+                    (XC2FuseOwner::Macrocell{fb, mc, field, ..}, &XC2BitstreamChange::Macrocell{
+                        fb: change_fb, mc: change_mc, field: change_field, ..}) |
+                    (XC2FuseOwner::IOB{fb, mc, field, ..}, &XC2BitstreamChange::IOB{
+                        fb: change_fb, mc: change_mc, field: change_field, ..}) =>
+                        fb == change_fb && mc == change_mc && field == change_field,
+                    (XC2FuseOwner::InputOnlyPin{field, ..}, &XC2BitstreamChange::Global{field: change_field, ..}) =>
+                        change_field == format!("inpin.{}", field),
+                    (XC2FuseOwner::ClockDiv{field, ..}, &XC2BitstreamChange::Global{field: change_field, ..}) =>
+                        change_field == format!("clock_div.{}", field),
+                    (XC2FuseOwner::GlobalNets{field, ..}, &XC2BitstreamChange::Global{field: change_field, ..}) =>
+                        change_field == field.split('[').next().unwrap(),
+                    (XC2FuseOwner::Global{field}, &XC2BitstreamChange::Global{field: change_field, ..}) =>
+                        change_field == field,
+                    _ => false,
+                };
+                // The PLA and ZIA are diffed structurally, and only the first two macrocells are checked to keep
+                // this reasonably fast
+                match info.owner {
+                    XC2FuseOwner::ZIARow{..} | XC2FuseOwner::AndTermInput{..} | XC2FuseOwner::OrTermInput{..} =>
+                        continue,
+                    XC2FuseOwner::Macrocell{fb, mc, ..} | XC2FuseOwner::IOB{fb, mc, ..} if fb != 0 || mc >= 2 =>
+                        continue,
+                    _ => {},
+                }
+
+                let mut new_jed = jed.clone();
+                new_jed.f.set(fuse, !new_jed.f.get(fuse));
+                // Some settings cannot be changed on their own
+                let b = match XC2Bitstream::from_jed(&new_jed) {
+                    Ok(x) => x,
+                    Err(_) => continue,
+                };
+                if a.diff_fuses(&b).is_empty() {
+                    continue;
+                }
+
+                let changes = a.diff(&b);
+                assert!(changes.len() == 1 && expected(&changes[0]), "{} {}: {:?}", part, info, changes);
+            }
+        }
+    }
+}
@@ -0,0 +1,491 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains a design rule checker for bitstreams
+//!
+//! `XC2Bitstream` can represent configurations that are electrically wrong (such as driving a pin that is being used
+//! as a global clock input) or meaningless (such as a register clocked by a global clock that is not enabled). The
+//! checker looks for these and reports each one as an error or a warning along with where in the device it is.
+//! Whether a pin is a VREF pin can only be checked for packages that are in the pin database.
+
+use std::fmt;
+
+use *;
+use equations::{iob_modes, reg_used, zia_signal};
+
+/// How serious a design rule violation is
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2DRCSeverity {
+    /// The configuration is legal but probably not what was intended
+    Warning,
+    /// The configuration can damage the device or cannot work
+    Error,
+}
+
+impl fmt::Display for XC2DRCSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            XC2DRCSeverity::Warning => "warning",
+            XC2DRCSeverity::Error => "error",
+        })
+    }
+}
+
+/// The part of the device that a design rule is about
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2DRCCategory {
+    /// Global clock, set/reset and tristate nets
+    GlobalNets,
+    /// Clocks, set/reset and clock enables of registers
+    Registers,
+    /// The GCK2 clock divider
+    ClockDivider,
+    /// The DataGATE feature
+    DataGate,
+    /// Input standards that use VREF
+    Vref,
+    /// I/O bank voltage settings
+    Voltage,
+    /// Connections between the ZIA and the PLA
+    Interconnect,
+}
+
+impl fmt::Display for XC2DRCCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            XC2DRCCategory::GlobalNets => "global nets",
+            XC2DRCCategory::Registers => "registers",
+            XC2DRCCategory::ClockDivider => "clock divider",
+            XC2DRCCategory::DataGate => "DataGATE",
+            XC2DRCCategory::Vref => "VREF",
+            XC2DRCCategory::Voltage => "voltage",
+            XC2DRCCategory::Interconnect => "interconnect",
+        })
+    }
+}
+
+/// Where in the device a design rule violation is
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2DRCLocation {
+    /// The device as a whole
+    Device,
+    /// A function block
+    FB(u32),
+    /// A macrocell
+    MC {
+        fb: u32,
+        mc: u32,
+    },
+    /// An I/O pin in the internal numbering scheme
+    IOB(u32),
+    /// An I/O bank, numbered as in the voltage settings of `XC2BitstreamBits`
+    Bank(u32),
+}
+
+/// One design rule violation
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2DRCViolation {
+    pub severity: XC2DRCSeverity,
+    pub category: XC2DRCCategory,
+    pub location: XC2DRCLocation,
+    pub message: String,
+}
+
+impl XC2DRCViolation {
+    /// Formats this violation. The location of I/O pins is also given as the macrocell they belong to on `device`.
+    pub fn format(&self, device: XC2Device) -> String {
+        let location = match self.location {
+            XC2DRCLocation::Device => "device".to_owned(),
+            XC2DRCLocation::FB(fb) => format!("FB{}", fb + 1),
+            XC2DRCLocation::MC {fb, mc} => format!("FB{}_{}", fb + 1, mc + 1),
+            XC2DRCLocation::IOB(iob) => match iob_num_to_fb_mc_num(device, iob) {
+                Some((fb, mc)) => format!("IOB {} (FB{}_{})", iob, fb + 1, mc + 1),
+                None => format!("IOB {}", iob),
+            },
+            XC2DRCLocation::Bank(bank) => format!("bank {}", bank),
+        };
+
+        format!("{} [{}] {}: {}", self.severity, self.category, location, self.message)
+    }
+}
+
+/// Returns a description of the source of a ZIA input
+fn zia_input_name(device: XC2Device, input: XC2ZIAInput) -> String {
+    match input {
+        XC2ZIAInput::Macrocell {fb, mc} => format!("macrocell FB{}_{}", fb + 1, mc + 1),
+        XC2ZIAInput::IBuf {ibuf} => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+            format!("the pin of FB{}_{}", fb + 1, mc + 1)
+        },
+        XC2ZIAInput::DedicatedInput => "the dedicated input".to_owned(),
+        XC2ZIAInput::Zero => "0".to_owned(),
+        XC2ZIAInput::One => "1".to_owned(),
+    }
+}
+
+/// Returns `true` if the given product term does not have any inputs and is therefore always 1
+fn term_is_empty(bits: &XC2BitstreamBits, fb: u32, term: u32) -> bool {
+    let and_term = bits.get_fb()[fb as usize].get_andterm(term as usize);
+    (0..INPUTS_PER_ANDTERM).all(|i| !and_term.get(i) && !and_term.get_b(i))
+}
+
+/// Returns the DataGATE enable, VREF enable, and clock divider of devices that have them
+fn large_device_settings(bits: &XC2BitstreamBits) -> Option<(bool, bool, XC2ClockDiv)> {
+    match *bits {
+        XC2BitstreamBits::XC2C128 {data_gate, use_vref, clock_div, ..} |
+        XC2BitstreamBits::XC2C256 {data_gate, use_vref, clock_div, ..} |
+        XC2BitstreamBits::XC2C384 {data_gate, use_vref, clock_div, ..} |
+        XC2BitstreamBits::XC2C512 {data_gate, use_vref, clock_div, ..} => Some((data_gate, use_vref, clock_div)),
+        _ => None,
+    }
+}
+
+/// Checks the given bitstream against the design rules and returns all of the violations that were found
+pub fn check_drc(bitstream: &XC2Bitstream) -> Vec<XC2DRCViolation> {
+    let bits = &bitstream.bits;
+    let device = bits.device_type();
+    let global_nets = bits.get_global_nets();
+    let mut ret = Vec::new();
+
+    {
+        let mut violation = |severity, category, location, message: String| {
+            ret.push(XC2DRCViolation {severity, category, location, message});
+        };
+
+        // Returns the IOB of a dual-purpose pin if the pin is being driven as an output
+        let driven_iob = |(fb, mc): (u32, u32)| {
+            fb_mc_num_to_iob_num(device, fb, mc).filter(|&iob| iob_modes(bits, iob).2 != XC2IOBOBufMode::Disabled)
+        };
+
+        // Global nets whose pins are driven
+        for i in 0..NUM_BUFG_CLK {
+            if let (true, Some(iob)) = (global_nets.gck_enable[i], driven_iob(get_gck(device, i).unwrap())) {
+                violation(XC2DRCSeverity::Error, XC2DRCCategory::GlobalNets, XC2DRCLocation::IOB(iob),
+                    format!("GCK{} is enabled but its pin is driven as an output", i));
+            }
+        }
+        for i in 0..NUM_BUFG_GTS {
+            if let (true, Some(iob)) = (global_nets.gts_enable[i], driven_iob(get_gts(device, i).unwrap())) {
+                violation(XC2DRCSeverity::Error, XC2DRCCategory::GlobalNets, XC2DRCLocation::IOB(iob),
+                    format!("GTS{} is enabled but its pin is driven as an output", i));
+            }
+        }
+        if let (true, Some(iob)) = (global_nets.gsr_enable, driven_iob(get_gsr(device))) {
+            violation(XC2DRCSeverity::Error, XC2DRCCategory::GlobalNets, XC2DRCLocation::IOB(iob),
+                "GSR is enabled but its pin is driven as an output".to_owned());
+        }
+
+        // Device-wide settings of the larger devices
+        if let Some((data_gate, use_vref, clock_div)) = large_device_settings(bits) {
+            if clock_div.enabled {
+                if let Some(iob) = driven_iob(get_cdrst(device).unwrap()) {
+                    violation(XC2DRCSeverity::Error, XC2DRCCategory::ClockDivider, XC2DRCLocation::IOB(iob),
+                        "the clock divider is enabled but the pin of CDRST is driven as an output".to_owned());
+                }
+                if !global_nets.gck_enable[2] {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::ClockDivider, XC2DRCLocation::Device,
+                        "the clock divider is enabled but GCK2 is not".to_owned());
+                }
+                let used = (0..device.num_fbs() as u32).any(|fb| (0..MCS_PER_FB as u32).any(|mc| {
+                    reg_used(bits, fb, mc) &&
+                        bits.get_fb()[fb as usize].mcs[mc as usize].clk_src == XC2MCRegClkSrc::GCK2
+                }));
+                if !used {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::ClockDivider, XC2DRCLocation::Device,
+                        "the clock divider is enabled but no register is clocked by it".to_owned());
+                }
+            } else if clock_div.delay {
+                violation(XC2DRCSeverity::Warning, XC2DRCCategory::ClockDivider, XC2DRCLocation::Device,
+                    "the clock divider delay is enabled but the clock divider is not".to_owned());
+            }
+
+            if data_gate {
+                if let Some(iob) = driven_iob(get_dge(device).unwrap()) {
+                    violation(XC2DRCSeverity::Error, XC2DRCCategory::DataGate, XC2DRCLocation::IOB(iob),
+                        "DataGATE is enabled but the pin of DGE is driven as an output".to_owned());
+                }
+                if !(0..device.num_iobs()).any(|iob| bits.get_large_iob(iob).unwrap().uses_data_gate) {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::DataGate, XC2DRCLocation::Device,
+                        "DataGATE is enabled but no pin uses it".to_owned());
+                }
+            }
+
+            let ibuf_mode_used = |mode|
+                (0..device.num_iobs()).any(|iob| bits.get_large_iob(iob).unwrap().ibuf_mode == mode);
+            let has_vref_pin = ibuf_mode_used(XC2IOBIbufMode::IsVref);
+            if use_vref && !has_vref_pin && !ibuf_mode_used(XC2IOBIbufMode::UsesVref) {
+                violation(XC2DRCSeverity::Warning, XC2DRCCategory::Vref, XC2DRCLocation::Device,
+                    "VREF is enabled but no pin uses it".to_owned());
+            }
+
+            for iob in 0..device.num_iobs() as u32 {
+                let iob_bits = bits.get_large_iob(iob as usize).unwrap();
+                let location = XC2DRCLocation::IOB(iob);
+
+                if iob_bits.uses_data_gate && !data_gate {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::DataGate, location,
+                        "the pin uses DataGATE but DataGATE is not enabled".to_owned());
+                }
+
+                match iob_bits.ibuf_mode {
+                    XC2IOBIbufMode::IsVref => {
+                        if !use_vref {
+                            violation(XC2DRCSeverity::Error, XC2DRCCategory::Vref, location,
+                                "the pin is a VREF input but VREF is not enabled".to_owned());
+                        }
+                        if let Some(pin) = get_pin_by_iob(device, bitstream.package, iob) {
+                            if pin.function != XC2PinFunction::VREF {
+                                violation(XC2DRCSeverity::Error, XC2DRCCategory::Vref, location,
+                                    format!("the pin is a VREF input but {} is not a VREF pin", pin.name));
+                            }
+                        }
+                        if iob_bits.obuf_mode != XC2IOBOBufMode::Disabled {
+                            violation(XC2DRCSeverity::Error, XC2DRCCategory::Vref, location,
+                                "the pin is a VREF input but is driven as an output".to_owned());
+                        }
+                        if iob_bits.zia_mode == XC2IOBZIAMode::PAD {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::Vref, location,
+                                "the pin is a VREF input but is also used as a logic input".to_owned());
+                        }
+                    },
+                    XC2IOBIbufMode::UsesVref => {
+                        if !use_vref {
+                            violation(XC2DRCSeverity::Error, XC2DRCCategory::Vref, location,
+                                "the pin uses VREF but VREF is not enabled".to_owned());
+                        }
+                        if !has_vref_pin {
+                            violation(XC2DRCSeverity::Error, XC2DRCCategory::Vref, location,
+                                "the pin uses VREF but no pin is a VREF input".to_owned());
+                        }
+                    },
+                    XC2IOBIbufMode::NoVrefNoSt | XC2IOBIbufMode::NoVrefSt => {},
+                }
+            }
+        }
+
+        // Bank voltages
+        let misc_settings = bits.misc_settings();
+        let bank_setting = |prefix: &str| misc_settings.iter()
+            .filter(|&&(name, _)| name.starts_with(prefix)).map(|&(_, val)| val).collect::<Vec<_>>();
+        let bank_voltages = bank_setting("ivoltage").into_iter().zip(bank_setting("ovoltage"));
+        for (bank, (ivoltage, ovoltage)) in bank_voltages.enumerate() {
+            if ivoltage != ovoltage {
+                violation(XC2DRCSeverity::Warning, XC2DRCCategory::Voltage, XC2DRCLocation::Bank(bank as u32),
+                    format!("the input voltage range is {} but the output voltage range is {}",
+                        if ivoltage {"high"} else {"low"}, if ovoltage {"high"} else {"low"}));
+            }
+        }
+        if bank_setting("legacy_").into_iter().any(|x| x) {
+            violation(XC2DRCSeverity::Warning, XC2DRCCategory::Voltage, XC2DRCLocation::Device,
+                "the legacy voltage range settings are set to high".to_owned());
+        }
+
+        for fb in 0..device.num_fbs() as u32 {
+            let fb_bits = &bits.get_fb()[fb as usize];
+
+            // ZIA inputs that are used by the PLA but whose source is turned off
+            for row in 0..INPUTS_PER_ANDTERM {
+                let used = (0..ANDTERMS_PER_FB).any(|term| {
+                    let and_term = fb_bits.get_andterm(term);
+                    and_term.get(row) || and_term.get_b(row)
+                });
+                let input = *fb_bits.get_zia(row);
+                if used && zia_signal(bits, input) == Ok(XC2EqnSignal::Disconnected) {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::Interconnect, XC2DRCLocation::FB(fb),
+                        format!("ZIA input {} selects {}, which does not drive the ZIA", row,
+                            zia_input_name(device, input)));
+                }
+            }
+
+            for mc in 0..MCS_PER_FB as u32 {
+                let mc_bits = &fb_bits.mcs[mc as usize];
+                let location = XC2DRCLocation::MC {fb, mc};
+                let iob = fb_mc_num_to_iob_num(device, fb, mc);
+
+                if let Some(iob) = iob {
+                    let gts = match iob_modes(bits, iob).2 {
+                        XC2IOBOBufMode::TriStateGTS0 => Some(0),
+                        XC2IOBOBufMode::TriStateGTS1 => Some(1),
+                        XC2IOBOBufMode::TriStateGTS2 => Some(2),
+                        XC2IOBOBufMode::TriStateGTS3 => Some(3),
+                        _ => None,
+                    };
+                    if let Some(gts) = gts {
+                        if !global_nets.gts_enable[gts] {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::GlobalNets, XC2DRCLocation::IOB(iob),
+                                format!("the output is enabled by GTS{}, which is not enabled", gts));
+                        }
+                    }
+                }
+
+                if !reg_used(bits, fb, mc) {
+                    continue;
+                }
+
+                if mc_bits.ff_in_ibuf && iob.is_none() {
+                    violation(XC2DRCSeverity::Error, XC2DRCCategory::Registers, location,
+                        "the register is an input register but the macrocell has no pin".to_owned());
+                }
+
+                match mc_bits.clk_src {
+                    XC2MCRegClkSrc::GCK0 | XC2MCRegClkSrc::GCK1 | XC2MCRegClkSrc::GCK2 => {
+                        let gck = match mc_bits.clk_src {
+                            XC2MCRegClkSrc::GCK0 => 0,
+                            XC2MCRegClkSrc::GCK1 => 1,
+                            _ => 2,
+                        };
+                        if !global_nets.gck_enable[gck] {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::GlobalNets, location,
+                                format!("the register is clocked by GCK{}, which is not enabled", gck));
+                        }
+                    },
+                    XC2MCRegClkSrc::PTC | XC2MCRegClkSrc::CTC => {
+                        let (name, term) = if mc_bits.clk_src == XC2MCRegClkSrc::PTC {
+                            ("PTC", get_ptc(mc))
+                        } else {
+                            ("CTC", CTC)
+                        };
+                        if term_is_empty(bits, fb, term) {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::Registers, location,
+                                format!("the register is clocked by {}, which has no inputs", name));
+                        }
+                    },
+                }
+
+                if mc_bits.reg_mode == XC2MCRegMode::DFFCE && mc_bits.clk_src == XC2MCRegClkSrc::PTC {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::Registers, location,
+                        "PTC is used as both the clock and the clock enable of the register".to_owned());
+                }
+                if mc_bits.s_src == XC2MCRegSetSrc::PTA && mc_bits.r_src == XC2MCRegResetSrc::PTA {
+                    violation(XC2DRCSeverity::Warning, XC2DRCCategory::Registers, location,
+                        "PTA is used as both the set and the reset of the register".to_owned());
+                }
+
+                let set = match mc_bits.s_src {
+                    XC2MCRegSetSrc::Disabled => None,
+                    XC2MCRegSetSrc::PTA => Some(("PTA", Some(get_pta(mc)))),
+                    XC2MCRegSetSrc::CTS => Some(("CTS", Some(CTS))),
+                    XC2MCRegSetSrc::GSR => Some(("GSR", None)),
+                };
+                let reset = match mc_bits.r_src {
+                    XC2MCRegResetSrc::Disabled => None,
+                    XC2MCRegResetSrc::PTA => Some(("PTA", Some(get_pta(mc)))),
+                    XC2MCRegResetSrc::CTR => Some(("CTR", Some(CTR))),
+                    XC2MCRegResetSrc::GSR => Some(("GSR", None)),
+                };
+                for &(kind, src) in &[("set", set), ("reset", reset)] {
+                    match src {
+                        Some((name, Some(term))) if term_is_empty(bits, fb, term) => {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::Registers, location,
+                                format!("the register is {} by {}, which has no inputs, so it is always {}", kind,
+                                    name, kind));
+                        },
+                        Some((name, None)) if !global_nets.gsr_enable => {
+                            violation(XC2DRCSeverity::Warning, XC2DRCCategory::GlobalNets, location,
+                                format!("the register is {} by {}, which is not enabled", kind, name));
+                        },
+                        _ => {},
+                    }
+                }
+            }
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank(part: &str) -> XC2Bitstream {
+        XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap())
+    }
+
+    #[test]
+    fn blank_bitstreams() {
+        for &part in &["XC2C32-4-VQ44", "XC2C32A-4-VQ44", "XC2C64-5-VQ44", "XC2C64A-5-VQ44", "XC2C128-6-VQ100",
+                       "XC2C256-6-VQ100", "XC2C384-7-TQ144", "XC2C512-7-PQ208"] {
+            assert_eq!(blank(part).check_drc(), Vec::new(), "{}", part);
+        }
+    }
+
+    #[test]
+    fn small_device() {
+        let mut bitstream = blank("XC2C32A-4-VQ44");
+        {
+            let bits = &mut bitstream.bits;
+            // GCK0 is the pin of FB2_5
+            bits.get_global_nets_mut().gck_enable[0] = true;
+            bits.get_mut_small_iob(20).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            // FB1_1 is clocked by GCK1, and FB1_2 is reset by an empty PTA
+            bits.get_mut_small_iob(0).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(0).unwrap().obuf_uses_ff = true;
+            bits.get_mut_small_iob(1).unwrap().obuf_mode = XC2IOBOBufMode::TriStateGTS2;
+            bits.get_mut_small_iob(1).unwrap().obuf_uses_ff = true;
+            let fbs = bits.get_fb_mut();
+            fbs[0].mcs[0].clk_src = XC2MCRegClkSrc::GCK1;
+            fbs[0].mcs[1].r_src = XC2MCRegResetSrc::PTA;
+            // ZIA input 0 selects FB1_1, which has its feedback disabled
+            *fbs[0].get_mut_zia(0) = XC2ZIAInput::Macrocell {fb: 0, mc: 0};
+            fbs[0].get_mut_andterm(20).set(0, true);
+        }
+
+        let device = XC2Device::XC2C32A;
+        let violations = bitstream.check_drc().iter().map(|x| x.format(device)).collect::<Vec<_>>();
+        assert_eq!(violations, vec![
+            "error [global nets] IOB 20 (FB2_5): GCK0 is enabled but its pin is driven as an output",
+            "warning [interconnect] FB1: ZIA input 0 selects macrocell FB1_1, which does not drive the ZIA",
+            "warning [global nets] FB1_1: the register is clocked by GCK1, which is not enabled",
+            "warning [global nets] IOB 1 (FB1_2): the output is enabled by GTS2, which is not enabled",
+            "warning [registers] FB1_2: the register is reset by PTA, which has no inputs, so it is always reset",
+        ]);
+    }
+
+    #[test]
+    fn large_device() {
+        let mut bitstream = blank("XC2C128-6-VQ100");
+        if let XC2BitstreamBits::XC2C128 {ref mut clock_div, ref mut iobs, ref mut ivoltage, ..} = bitstream.bits {
+            clock_div.enabled = true;
+            iobs[0][0].ibuf_mode = XC2IOBIbufMode::UsesVref;
+            iobs[0][1].ibuf_mode = XC2IOBIbufMode::IsVref;
+            iobs[0][1].obuf_mode = XC2IOBOBufMode::OpenDrain;
+            iobs[0][2].uses_data_gate = true;
+            ivoltage[1] = true;
+        }
+
+        let violations = bitstream.check_drc();
+        assert_eq!(violations.iter().map(|x| (x.severity, x.category, x.location)).collect::<Vec<_>>(), vec![
+            (XC2DRCSeverity::Warning, XC2DRCCategory::ClockDivider, XC2DRCLocation::Device),
+            (XC2DRCSeverity::Warning, XC2DRCCategory::ClockDivider, XC2DRCLocation::Device),
+            (XC2DRCSeverity::Error, XC2DRCCategory::Vref, XC2DRCLocation::IOB(0)),
+            (XC2DRCSeverity::Error, XC2DRCCategory::Vref, XC2DRCLocation::IOB(1)),
+            (XC2DRCSeverity::Error, XC2DRCCategory::Vref, XC2DRCLocation::IOB(1)),
+            (XC2DRCSeverity::Warning, XC2DRCCategory::DataGate, XC2DRCLocation::IOB(2)),
+            (XC2DRCSeverity::Warning, XC2DRCCategory::Voltage, XC2DRCLocation::Bank(1)),
+        ]);
+        assert_eq!(violations[6].message, "the input voltage range is high but the output voltage range is low");
+    }
+}
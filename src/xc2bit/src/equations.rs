@@ -0,0 +1,676 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains routines for decompiling a bitstream into Boolean equations for each macrocell
+
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use *;
+
+/// A signal that can appear in an equation
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2EqnSignal {
+    /// The combinatorial output (the output of the XOR gate) of a macrocell
+    MCComb {
+        fb: u32,
+        mc: u32,
+    },
+    /// The output of the register in a macrocell
+    MCReg {
+        fb: u32,
+        mc: u32,
+    },
+    /// The input from the pad of the I/O pin corresponding to a macrocell
+    Pad {
+        fb: u32,
+        mc: u32,
+    },
+    /// The input from the dedicated input-only pin
+    DedicatedInput,
+    /// The global clock net GCKn
+    GCK(u32),
+    /// The global set/reset net
+    GSR,
+    /// The global tristate net GTSn
+    GTS(u32),
+    /// A ZIA input whose source has been disabled (e.g. a macrocell with its ZIA feedback turned off)
+    Disconnected,
+}
+
+impl fmt::Display for XC2EqnSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2EqnSignal::MCComb {fb, mc} => write!(f, "FB{}_{}", fb + 1, mc + 1),
+            XC2EqnSignal::MCReg {fb, mc} => write!(f, "FB{}_{}.Q", fb + 1, mc + 1),
+            XC2EqnSignal::Pad {fb, mc} => write!(f, "FB{}_{}.PAD", fb + 1, mc + 1),
+            XC2EqnSignal::DedicatedInput => write!(f, "INPUT_ONLY"),
+            XC2EqnSignal::GCK(i) => write!(f, "GCK{}", i),
+            XC2EqnSignal::GSR => write!(f, "GSR"),
+            XC2EqnSignal::GTS(i) => write!(f, "GTS{}", i),
+            XC2EqnSignal::Disconnected => write!(f, "DISCONNECTED"),
+        }
+    }
+}
+
+/// A signal or its complement
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnLiteral {
+    pub signal: XC2EqnSignal,
+    pub inverted: bool,
+}
+
+impl fmt::Display for XC2EqnLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", if self.inverted {"!"} else {""}, self.signal)
+    }
+}
+
+/// The AND of a number of literals. A product with no literals is a constant 1.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnProduct {
+    pub literals: Vec<XC2EqnLiteral>,
+}
+
+impl XC2EqnProduct {
+    /// Formats this product using `not_op` as the NOT operator and `name` to get the name of each signal
+    pub fn format<F: Fn(XC2EqnSignal) -> String>(&self, not_op: &str, name: &F) -> String {
+        if self.literals.is_empty() {
+            return "1".to_owned();
+        }
+
+        self.literals.iter().map(|literal| {
+            format!("{}{}", if literal.inverted {not_op} else {""}, name(literal.signal))
+        }).collect::<Vec<_>>().join(" & ")
+    }
+}
+
+impl fmt::Display for XC2EqnProduct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format("!", &|x| x.to_string()))
+    }
+}
+
+/// The OR of a number of products. A sum with no products is a constant 0.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnSum {
+    pub products: Vec<XC2EqnProduct>,
+}
+
+impl XC2EqnSum {
+    /// Returns a sum that consists of only the given literal
+    pub fn from_literal(signal: XC2EqnSignal, inverted: bool) -> Self {
+        XC2EqnSum {
+            products: vec![XC2EqnProduct {
+                literals: vec![XC2EqnLiteral {signal, inverted}],
+            }],
+        }
+    }
+
+    /// Returns a sum that is the given constant
+    pub fn from_const(val: bool) -> Self {
+        XC2EqnSum {
+            products: if val {vec![XC2EqnProduct {literals: Vec::new()}]} else {Vec::new()},
+        }
+    }
+
+    /// Returns `true` if this sum is a constant 0
+    pub fn is_zero(&self) -> bool {
+        self.products.is_empty()
+    }
+
+    /// Returns `true` if this sum is a constant 1
+    pub fn is_one(&self) -> bool {
+        self.products.iter().any(|product| product.literals.is_empty())
+    }
+
+    /// Returns `true` if this sum needs parentheses around it when it is inverted
+    pub fn is_compound(&self) -> bool {
+        self.products.len() > 1 || self.products.iter().any(|product| product.literals.len() > 1)
+    }
+
+    /// Formats this sum using `not_op` as the NOT operator and `name` to get the name of each signal
+    pub fn format<F: Fn(XC2EqnSignal) -> String>(&self, not_op: &str, name: &F) -> String {
+        if self.products.is_empty() {
+            return "0".to_owned();
+        }
+
+        self.products.iter().map(|product| product.format(not_op, name)).collect::<Vec<_>>().join(" | ")
+    }
+}
+
+impl fmt::Display for XC2EqnSum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format("!", &|x| x.to_string()))
+    }
+}
+
+/// The output of the XOR gate in a macrocell. This is the sum of products from the OR term, optionally XORed with
+/// the special product term PTC, and then optionally inverted.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnXor {
+    pub sum: XC2EqnSum,
+    pub xor_term: Option<XC2EqnSum>,
+    pub inverted: bool,
+}
+
+impl XC2EqnXor {
+    /// Formats this XOR gate output using `not_op` as the NOT operator and `name` to get the name of each signal
+    pub fn format<F: Fn(XC2EqnSignal) -> String>(&self, not_op: &str, name: &F) -> String {
+        // Inverting a single inverted literal cancels out rather than being printed as a double negation
+        if self.inverted && self.xor_term.is_none() && self.sum.products.len() == 1 &&
+            self.sum.products[0].literals.len() == 1 && self.sum.products[0].literals[0].inverted {
+            return name(self.sum.products[0].literals[0].signal);
+        }
+
+        let sum = self.sum.format(not_op, name);
+        let ret = match self.xor_term {
+            Some(ref xor_term) => {
+                let xor_term_str = xor_term.format(not_op, name);
+                format!("{} ^ {}",
+                    if self.sum.products.len() > 1 {format!("({})", sum)} else {sum},
+                    if xor_term.products.len() > 1 {format!("({})", xor_term_str)} else {xor_term_str})
+            },
+            None => sum,
+        };
+
+        if !self.inverted {
+            ret
+        } else if self.xor_term.is_some() || self.sum.is_compound() {
+            format!("{}({})", not_op, ret)
+        } else {
+            format!("{}{}", not_op, ret)
+        }
+    }
+}
+
+impl fmt::Display for XC2EqnXor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format("!", &|x| x.to_string()))
+    }
+}
+
+/// The register in a macrocell
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnRegister {
+    pub mode: XC2MCRegMode,
+    /// The D or T input of the register
+    pub input: XC2EqnXor,
+    /// The clock (or latch gate) of the register
+    pub clk: XC2EqnSum,
+    /// `true` if the register is triggered on the falling edge of `clk` (or transparent when `clk` is low)
+    pub clk_invert: bool,
+    /// `true` if the register is triggered on both edges of `clk`
+    pub ddr: bool,
+    /// The clock enable of the register, if it has one
+    pub ce: Option<XC2EqnSum>,
+    /// The asynchronous set of the register, if it has one
+    pub set: Option<XC2EqnSum>,
+    /// The asynchronous reset of the register, if it has one
+    pub reset: Option<XC2EqnSum>,
+    pub init_state: bool,
+}
+
+/// The output driver of an I/O pin
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2EqnOutput {
+    pub data: XC2EqnSum,
+    pub oe: XC2EqnSum,
+}
+
+/// The equations describing everything that one macrocell (and its I/O pin, if it has one) computes
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2MCEquations {
+    pub fb: u32,
+    pub mc: u32,
+    /// The combinatorial output of the macrocell. This is only present if it is used by the ZIA or the output pin.
+    pub comb: Option<XC2EqnXor>,
+    /// The register of the macrocell. This is only present if its output is used anywhere.
+    pub reg: Option<XC2EqnRegister>,
+    /// The output driver of the I/O pin of the macrocell. This is only present if the output is enabled.
+    pub output: Option<XC2EqnOutput>,
+}
+
+/// Returns the ZIA input mode, output source, and output mode of the given I/O pin
+pub fn iob_modes(bits: &XC2BitstreamBits, iob: u32) -> (XC2IOBZIAMode, bool, XC2IOBOBufMode) {
+    if let Some(iob) = bits.get_small_iob(iob as usize) {
+        (iob.zia_mode, iob.obuf_uses_ff, iob.obuf_mode)
+    } else {
+        let iob = bits.get_large_iob(iob as usize).unwrap();
+        (iob.zia_mode, iob.obuf_uses_ff, iob.obuf_mode)
+    }
+}
+
+/// Returns `true` if the output of the register in the given macrocell is used by the ZIA or by an output pin
+pub fn reg_used(bits: &XC2BitstreamBits, fb: u32, mc: u32) -> bool {
+    if bits.get_fb()[fb as usize].mcs[mc as usize].fb_mode == XC2MCFeedbackMode::REG {
+        return true;
+    }
+
+    match fb_mc_num_to_iob_num(bits.device_type(), fb, mc) {
+        Some(iob) => {
+            let (zia_mode, obuf_uses_ff, obuf_mode) = iob_modes(bits, iob);
+            zia_mode == XC2IOBZIAMode::REG ||
+                (obuf_uses_ff && obuf_mode != XC2IOBOBufMode::Disabled && obuf_mode != XC2IOBOBufMode::CGND)
+        },
+        None => false,
+    }
+}
+
+/// Returns the signal selected by a ZIA input, or `Err` with the value of a constant input
+pub fn zia_signal(bits: &XC2BitstreamBits, input: XC2ZIAInput) -> Result<XC2EqnSignal, bool> {
+    let device = bits.device_type();
+    match input {
+        XC2ZIAInput::Zero => Err(false),
+        XC2ZIAInput::One => Err(true),
+        XC2ZIAInput::DedicatedInput => Ok(XC2EqnSignal::DedicatedInput),
+        XC2ZIAInput::Macrocell {fb, mc} => {
+            let (fb, mc) = (fb as u32, mc as u32);
+            Ok(match bits.get_fb()[fb as usize].mcs[mc as usize].fb_mode {
+                XC2MCFeedbackMode::Disabled => XC2EqnSignal::Disconnected,
+                XC2MCFeedbackMode::COMB => XC2EqnSignal::MCComb {fb, mc},
+                XC2MCFeedbackMode::REG => XC2EqnSignal::MCReg {fb, mc},
+            })
+        },
+        XC2ZIAInput::IBuf {ibuf} => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+            Ok(match iob_modes(bits, ibuf as u32).0 {
+                XC2IOBZIAMode::Disabled => XC2EqnSignal::Disconnected,
+                XC2IOBZIAMode::PAD => XC2EqnSignal::Pad {fb, mc},
+                XC2IOBZIAMode::REG => XC2EqnSignal::MCReg {fb, mc},
+            })
+        },
+    }
+}
+
+/// Returns the given AND term as a sum, which will either be empty (if the AND term is always 0) or contain exactly
+/// one product. Constant inputs are removed.
+fn and_term_sum(bits: &XC2BitstreamBits, fb: u32, term: u32) -> XC2EqnSum {
+    let fb_bits = &bits.get_fb()[fb as usize];
+    let and_term = fb_bits.get_andterm(term as usize);
+    let mut literals: Vec<XC2EqnLiteral> = Vec::new();
+
+    for i in 0..INPUTS_PER_ANDTERM {
+        for &inverted in &[false, true] {
+            let used = if inverted {and_term.get_b(i)} else {and_term.get(i)};
+            if !used {
+                continue;
+            }
+
+            match zia_signal(bits, *fb_bits.get_zia(i)) {
+                Err(val) => {
+                    if val == inverted {
+                        return XC2EqnSum::from_const(false);
+                    }
+                },
+                Ok(signal) => {
+                    if let Some(other) = literals.iter().find(|x| x.signal == signal) {
+                        if other.inverted != inverted {
+                            return XC2EqnSum::from_const(false);
+                        }
+                        continue;
+                    }
+                    literals.push(XC2EqnLiteral {signal, inverted});
+                },
+            }
+        }
+    }
+
+    XC2EqnSum {
+        products: vec![XC2EqnProduct {literals}],
+    }
+}
+
+/// Returns the output of the XOR gate of the given macrocell
+fn mc_xor(bits: &XC2BitstreamBits, fb: u32, mc: u32) -> XC2EqnXor {
+    let fb_bits = &bits.get_fb()[fb as usize];
+    let mut sum = XC2EqnSum::from_const(false);
+    for term in 0..ANDTERMS_PER_FB {
+        if fb_bits.or_terms[mc as usize].get(term) {
+            sum.products.extend(and_term_sum(bits, fb, term as u32).products);
+        }
+    }
+
+    let (xor_term, inverted) = match fb_bits.mcs[mc as usize].xor_mode {
+        XC2MCXorMode::ZERO => (None, false),
+        XC2MCXorMode::ONE => (None, true),
+        XC2MCXorMode::PTC => (Some(and_term_sum(bits, fb, get_ptc(mc))), false),
+        XC2MCXorMode::PTCB => (Some(and_term_sum(bits, fb, get_ptc(mc))), true),
+    };
+    // XORing with a constant either does nothing or inverts the output
+    let (xor_term, inverted) = match xor_term {
+        Some(ref xor_term) if xor_term.is_zero() => (None, inverted),
+        Some(ref xor_term) if xor_term.is_one() => (None, !inverted),
+        _ => (xor_term, inverted),
+    };
+
+    XC2EqnXor {
+        sum,
+        xor_term,
+        inverted,
+    }
+}
+
+/// Decompiles the given bitstream into equations for each macrocell. Macrocells that do not drive anything are
+/// skipped.
+pub fn get_equations(bits: &XC2BitstreamBits) -> Vec<XC2MCEquations> {
+    let device = bits.device_type();
+    let global_nets = bits.get_global_nets();
+    let mut ret = Vec::new();
+
+    // Disabled global nets are held at 0. GTS acts as a T signal unless it is inverted, and a disabled GTS never
+    // turns off an output.
+    let gck = |i: u32| if global_nets.gck_enable[i as usize] {
+        XC2EqnSum::from_literal(XC2EqnSignal::GCK(i), false)
+    } else {
+        XC2EqnSum::from_const(false)
+    };
+    let gsr = if global_nets.gsr_enable {
+        XC2EqnSum::from_literal(XC2EqnSignal::GSR, !global_nets.gsr_invert)
+    } else {
+        XC2EqnSum::from_const(false)
+    };
+    let gts_oe = |i: u32| if global_nets.gts_enable[i as usize] {
+        XC2EqnSum::from_literal(XC2EqnSignal::GTS(i), !global_nets.gts_invert[i as usize])
+    } else {
+        XC2EqnSum::from_const(true)
+    };
+
+    for fb in 0..device.num_fbs() as u32 {
+        let fb_bits = &bits.get_fb()[fb as usize];
+
+        for mc in 0..MCS_PER_FB as u32 {
+            let mc_bits = &fb_bits.mcs[mc as usize];
+            let iob = fb_mc_num_to_iob_num(device, fb, mc);
+            let (iob_zia_mode, obuf_uses_ff, obuf_mode) = match iob {
+                Some(iob) => iob_modes(bits, iob),
+                None => (XC2IOBZIAMode::Disabled, false, XC2IOBOBufMode::Disabled),
+            };
+
+            let output_data = if obuf_uses_ff {
+                XC2EqnSignal::MCReg {fb, mc}
+            } else {
+                XC2EqnSignal::MCComb {fb, mc}
+            };
+            let output = match obuf_mode {
+                XC2IOBOBufMode::Disabled => None,
+                XC2IOBOBufMode::CGND => Some((XC2EqnSum::from_const(false), XC2EqnSum::from_const(true))),
+                XC2IOBOBufMode::PushPull => Some((XC2EqnSum::from_literal(output_data, false),
+                    XC2EqnSum::from_const(true))),
+                // An open-drain output only drives when the output is low
+                XC2IOBOBufMode::OpenDrain => Some((XC2EqnSum::from_const(false),
+                    XC2EqnSum::from_literal(output_data, true))),
+                XC2IOBOBufMode::TriStateGTS0 | XC2IOBOBufMode::TriStateGTS1 | XC2IOBOBufMode::TriStateGTS2 |
+                XC2IOBOBufMode::TriStateGTS3 => {
+                    let gts = match obuf_mode {
+                        XC2IOBOBufMode::TriStateGTS0 => 0,
+                        XC2IOBOBufMode::TriStateGTS1 => 1,
+                        XC2IOBOBufMode::TriStateGTS2 => 2,
+                        XC2IOBOBufMode::TriStateGTS3 => 3,
+                        _ => unreachable!(),
+                    };
+                    Some((XC2EqnSum::from_literal(output_data, false), gts_oe(gts)))
+                },
+                XC2IOBOBufMode::TriStatePTB => Some((XC2EqnSum::from_literal(output_data, false),
+                    and_term_sum(bits, fb, get_ptb(mc)))),
+                XC2IOBOBufMode::TriStateCTE => Some((XC2EqnSum::from_literal(output_data, false),
+                    and_term_sum(bits, fb, CTE))),
+            };
+            let output = output.map(|(data, oe)| XC2EqnOutput {data, oe});
+
+            let comb_used = mc_bits.fb_mode == XC2MCFeedbackMode::COMB ||
+                (output.is_some() && !obuf_uses_ff && obuf_mode != XC2IOBOBufMode::CGND);
+            let reg_used = mc_bits.fb_mode == XC2MCFeedbackMode::REG || iob_zia_mode == XC2IOBZIAMode::REG ||
+                (output.is_some() && obuf_uses_ff && obuf_mode != XC2IOBOBufMode::CGND);
+
+            let comb = if comb_used {
+                Some(mc_xor(bits, fb, mc))
+            } else {
+                None
+            };
+
+            let reg = if reg_used {
+                let input = if mc_bits.ff_in_ibuf {
+                    XC2EqnXor {
+                        sum: XC2EqnSum::from_literal(XC2EqnSignal::Pad {fb, mc}, false),
+                        xor_term: None,
+                        inverted: false,
+                    }
+                } else {
+                    mc_xor(bits, fb, mc)
+                };
+
+                let clk = match mc_bits.clk_src {
+                    XC2MCRegClkSrc::GCK0 => gck(0),
+                    XC2MCRegClkSrc::GCK1 => gck(1),
+                    XC2MCRegClkSrc::GCK2 => gck(2),
+                    XC2MCRegClkSrc::PTC => and_term_sum(bits, fb, get_ptc(mc)),
+                    XC2MCRegClkSrc::CTC => and_term_sum(bits, fb, CTC),
+                };
+
+                // The clock enable uses PTC
+                let ce = if mc_bits.reg_mode == XC2MCRegMode::DFFCE {
+                    Some(and_term_sum(bits, fb, get_ptc(mc)))
+                } else {
+                    None
+                };
+
+                let set = match mc_bits.s_src {
+                    XC2MCRegSetSrc::Disabled => None,
+                    XC2MCRegSetSrc::PTA => Some(and_term_sum(bits, fb, get_pta(mc))),
+                    XC2MCRegSetSrc::GSR => Some(gsr.clone()),
+                    XC2MCRegSetSrc::CTS => Some(and_term_sum(bits, fb, CTS)),
+                };
+                let reset = match mc_bits.r_src {
+                    XC2MCRegResetSrc::Disabled => None,
+                    XC2MCRegResetSrc::PTA => Some(and_term_sum(bits, fb, get_pta(mc))),
+                    XC2MCRegResetSrc::GSR => Some(gsr.clone()),
+                    XC2MCRegResetSrc::CTR => Some(and_term_sum(bits, fb, CTR)),
+                };
+
+                Some(XC2EqnRegister {
+                    mode: mc_bits.reg_mode,
+                    input,
+                    clk,
+                    clk_invert: mc_bits.clk_invert_pol,
+                    ddr: mc_bits.is_ddr,
+                    ce,
+                    set,
+                    reset,
+                    init_state: mc_bits.init_state,
+                })
+            } else {
+                None
+            };
+
+            if comb.is_some() || reg.is_some() || output.is_some() {
+                ret.push(XC2MCEquations {
+                    fb,
+                    mc,
+                    comb,
+                    reg,
+                    output,
+                });
+            }
+        }
+    }
+
+    ret
+}
+
+impl XC2MCEquations {
+    /// Dump the equations in a format similar to ABEL to the given `writer` object. If the pin name for this
+    /// macrocell is known for the given `device` and `package`, it is written as a comment.
+    pub fn dump<W: Write>(&self, device: XC2Device, package: XC2Package, mut writer: W) -> Result<(), io::Error> {
+        let name = format!("FB{}_{}", self.fb + 1, self.mc + 1);
+
+        if let Some(ref comb) = self.comb {
+            write!(writer, "{} = {};\n", name, comb)?;
+        }
+
+        if let Some(ref reg) = self.reg {
+            let (input_name, clk_name) = match reg.mode {
+                XC2MCRegMode::DFF | XC2MCRegMode::DFFCE => ("D", "CLK"),
+                XC2MCRegMode::LATCH => ("D", "LE"),
+                XC2MCRegMode::TFF => ("T", "CLK"),
+            };
+            write!(writer, "{}.{} = {};\n", name, input_name, reg.input)?;
+            if reg.clk_invert {
+                if reg.clk.is_compound() {
+                    write!(writer, "{}.{} = !({});", name, clk_name, reg.clk)?;
+                } else {
+                    write!(writer, "{}.{} = !{};", name, clk_name, reg.clk)?;
+                }
+            } else {
+                write!(writer, "{}.{} = {};", name, clk_name, reg.clk)?;
+            }
+            if reg.ddr {
+                write!(writer, " // both edges")?;
+            }
+            write!(writer, "\n")?;
+            if let Some(ref ce) = reg.ce {
+                write!(writer, "{}.CE = {};\n", name, ce)?;
+            }
+            if let Some(ref set) = reg.set {
+                write!(writer, "{}.AP = {};\n", name, set)?;
+            }
+            if let Some(ref reset) = reg.reset {
+                write!(writer, "{}.AR = {};\n", name, reset)?;
+            }
+            write!(writer, "{}.INIT = {};\n", name, if reg.init_state {1} else {0})?;
+        }
+
+        if let Some(ref output) = self.output {
+            write!(writer, "{}.PAD = {};", name, output.data)?;
+            let pin_name = fb_mc_num_to_iob_num(device, self.fb, self.mc)
+                .and_then(|iob| iob_num_to_pin_name(device, package, iob));
+            if let Some(pin_name) = pin_name {
+                write!(writer, " // pin {}", pin_name)?;
+            }
+            write!(writer, "\n")?;
+            write!(writer, "{}.PAD.OE = {};\n", name, output.oe)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank() {
+        let bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C64A-5-VQ44").unwrap());
+        assert_eq!(bitstream.get_equations(), Vec::new());
+    }
+
+    #[test]
+    fn decompile() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_mut_small_iob(0).unwrap().zia_mode = XC2IOBZIAMode::PAD;
+            bits.get_mut_small_iob(4).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_global_nets_mut().gck_enable[1] = true;
+            bits.get_global_nets_mut().gsr_enable = true;
+
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 0};
+            *fbs[0].get_mut_zia(1) = XC2ZIAInput::Macrocell{fb: 0, mc: 8};
+            *fbs[0].get_mut_zia(2) = XC2ZIAInput::Macrocell{fb: 1, mc: 4};
+            *fbs[0].get_mut_zia(3) = XC2ZIAInput::One;
+            *fbs[0].get_mut_zia(4) = XC2ZIAInput::Zero;
+            fbs[0].mcs[8].fb_mode = XC2MCFeedbackMode::COMB;
+            fbs[1].mcs[4].fb_mode = XC2MCFeedbackMode::REG;
+
+            // FB1_5 = FB1_1.PAD & !FB1_9 | FB2_5.Q
+            fbs[0].get_mut_andterm(20).set(0, true);
+            fbs[0].get_mut_andterm(20).set_b(1, true);
+            fbs[0].get_mut_andterm(21).set(2, true);
+            fbs[0].get_mut_andterm(21).set(3, true);
+            // These terms are always 0
+            fbs[0].get_mut_andterm(22).set(0, true);
+            fbs[0].get_mut_andterm(22).set_b(0, true);
+            fbs[0].get_mut_andterm(23).set(4, true);
+            for term in 20..24 {
+                fbs[0].or_terms[4].set(term, true);
+            }
+
+            // A T flip-flop whose XOR gate is XNORed with a PTC that is always 1
+            *fbs[1].get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 0};
+            fbs[1].get_mut_andterm(get_pta(4) as usize).set(0, true);
+            fbs[1].mcs[4].reg_mode = XC2MCRegMode::TFF;
+            fbs[1].mcs[4].xor_mode = XC2MCXorMode::PTCB;
+            fbs[1].mcs[4].clk_src = XC2MCRegClkSrc::GCK1;
+            fbs[1].mcs[4].clk_invert_pol = true;
+            fbs[1].mcs[4].s_src = XC2MCRegSetSrc::GSR;
+            fbs[1].mcs[4].r_src = XC2MCRegResetSrc::PTA;
+        }
+
+        let equations = bitstream.get_equations();
+        assert_eq!(equations.len(), 3);
+        assert_eq!((equations[0].fb, equations[0].mc), (0, 4));
+        assert_eq!(equations[0].comb.as_ref().unwrap().sum.products.len(), 2);
+        assert_eq!(equations[0].reg, None);
+        assert_eq!(equations[1].comb.as_ref().unwrap().sum, XC2EqnSum::from_const(false));
+        assert_eq!(equations[2].reg.as_ref().unwrap().reset,
+            Some(XC2EqnSum::from_literal(XC2EqnSignal::Pad{fb: 0, mc: 0}, false)));
+
+        let mut eqn_text = Vec::new();
+        bitstream.dump_equations(&mut eqn_text).unwrap();
+        assert_eq!(str::from_utf8(&eqn_text).unwrap(), "// xc2bit equations for XC2C32A-4-VQ44\n\
+            \n\
+            FB1_5 = FB1_1.PAD & !FB1_9 | FB2_5.Q;\n\
+            FB1_5.PAD = FB1_5; // pin P33\n\
+            FB1_5.PAD.OE = 1;\n\
+            \n\
+            FB1_9 = 0;\n\
+            \n\
+            FB2_5.T = 0;\n\
+            FB2_5.CLK = !GCK1;\n\
+            FB2_5.AP = !GSR;\n\
+            FB2_5.AR = FB1_1.PAD;\n\
+            FB2_5.INIT = 1;\n");
+    }
+
+    #[test]
+    fn xor_display() {
+        let a = XC2EqnSum::from_literal(XC2EqnSignal::GCK(0), false);
+        let b = XC2EqnSum::from_literal(XC2EqnSignal::DedicatedInput, true);
+        let mut ab = a.clone();
+        ab.products.extend(b.products.clone());
+
+        let xor = XC2EqnXor {sum: ab.clone(), xor_term: Some(b.clone()), inverted: true};
+        assert_eq!(xor.to_string(), "!((GCK0 | !INPUT_ONLY) ^ !INPUT_ONLY)");
+        let xor = XC2EqnXor {sum: a.clone(), xor_term: None, inverted: true};
+        assert_eq!(xor.to_string(), "!GCK0");
+        let xor = XC2EqnXor {sum: b.clone(), xor_term: None, inverted: true};
+        assert_eq!(xor.to_string(), "INPUT_ONLY");
+        let xor = XC2EqnXor {sum: ab, xor_term: None, inverted: true};
+        assert_eq!(xor.to_string(), "!(GCK0 | !INPUT_ONLY)");
+    }
+}
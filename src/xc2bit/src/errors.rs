@@ -45,6 +45,10 @@ pub enum XC2BitError {
     UnsupportedOeConfiguration((bool, bool, bool, bool)),
     /// An unknown value was used in the ZIA selection bits
     UnsupportedZIAConfiguration(Vec<bool>),
+    /// The USERCODE was not 32 bits long
+    WrongUsercodeLength,
+    /// The human-readable dump could not be parsed. Contains the line number of the offending line.
+    HumanReadableParseError(usize),
 }
 
 impl From<JedParserError> for XC2BitError {
@@ -61,6 +65,8 @@ impl error::Error for XC2BitError {
             XC2BitError::WrongFuseCount => "wrong number of fuses",
             XC2BitError::UnsupportedOeConfiguration(_) => "unknown Oe field value",
             XC2BitError::UnsupportedZIAConfiguration(_) => "unknown ZIA selection bit pattern",
+            XC2BitError::WrongUsercodeLength => "wrong number of USERCODE bits",
+            XC2BitError::HumanReadableParseError(_) => "human-readable dump parsing failed",
         }
     }
 
@@ -71,6 +77,8 @@ impl error::Error for XC2BitError {
             XC2BitError::WrongFuseCount => None,
             XC2BitError::UnsupportedOeConfiguration(_) => None,
             XC2BitError::UnsupportedZIAConfiguration(_) => None,
+            XC2BitError::WrongUsercodeLength => None,
+            XC2BitError::HumanReadableParseError(_) => None,
         }
     }
 }
@@ -99,6 +107,73 @@ impl fmt::Display for XC2BitError {
                 }
                 Ok(())
             },
+            XC2BitError::WrongUsercodeLength => {
+                write!(f, "{}", self.description())
+            },
+            XC2BitError::HumanReadableParseError(line) => {
+                write!(f, "human-readable dump parsing failed on line {}", line)
+            },
+        }
+    }
+}
+
+/// Errors that can occur when reading JTAG programming files or running them on the JTAG emulator
+#[derive(Debug, PartialEq, Eq)]
+pub enum XC2JTAGError {
+    /// The SVF file could not be parsed. Contains the line number of the offending statement.
+    SVFParseError(usize),
+    /// The SVF file uses a command or option that is not supported. Contains the offending command.
+    UnsupportedSVFCommand(String),
+    /// The XSVF file could not be parsed. Contains the byte offset of the offending command.
+    XSVFParseError(usize),
+    /// The XSVF file uses a command or option that is not supported. Contains the offending opcode.
+    UnsupportedXSVFCommand(u8),
+    /// The bits shifted out of the device did not match the expected bits. Contains the index of the operation and the
+    /// bits that were actually shifted out (in shift order).
+    TDOMismatch(usize, Vec<bool>),
+    /// A JTAG operation is malformed, e.g. its expected TDO or mask is not as long as its TDI. Contains the index of
+    /// the operation.
+    MalformedOp(usize),
+}
+
+impl error::Error for XC2JTAGError {
+    fn description(&self) -> &'static str {
+        match *self {
+            XC2JTAGError::SVFParseError(_) => "SVF parsing failed",
+            XC2JTAGError::UnsupportedSVFCommand(_) => "unsupported SVF command",
+            XC2JTAGError::XSVFParseError(_) => "XSVF parsing failed",
+            XC2JTAGError::UnsupportedXSVFCommand(_) => "unsupported XSVF command",
+            XC2JTAGError::TDOMismatch(_, _) => "TDO did not match the expected value",
+            XC2JTAGError::MalformedOp(_) => "malformed JTAG operation",
+        }
+    }
+}
+
+impl fmt::Display for XC2JTAGError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2JTAGError::SVFParseError(line) => {
+                write!(f, "SVF parsing failed on line {}", line)
+            },
+            XC2JTAGError::UnsupportedSVFCommand(ref cmd) => {
+                write!(f, "unsupported SVF command \"{}\"", cmd)
+            },
+            XC2JTAGError::XSVFParseError(offset) => {
+                write!(f, "XSVF parsing failed at offset {}", offset)
+            },
+            XC2JTAGError::UnsupportedXSVFCommand(opcode) => {
+                write!(f, "unsupported XSVF command 0x{:02x}", opcode)
+            },
+            XC2JTAGError::TDOMismatch(op_i, ref bits) => {
+                write!(f, "TDO did not match the expected value in operation {}, got ", op_i)?;
+                for &bit in bits {
+                    write!(f, "{}", b2s(bit))?;
+                }
+                Ok(())
+            },
+            XC2JTAGError::MalformedOp(op_i) => {
+                write!(f, "JTAG operation {} is malformed", op_i)
+            },
         }
     }
 }
@@ -32,8 +32,8 @@ use jedec::*;
 
 use *;
 use fusemap_physical::{zia_block_loc, and_block_loc, or_block_loc};
-use util::{LinebreakSet};
-use zia::{zia_get_row_width};
+use util::{LinebreakSet, HumanReadableReader, parse_fb_mc_name, without_suffix};
+use zia::{zia_get_row_width, zia_table_get_row};
 
 /// Represents a collection of all the parts that make up one function block
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
@@ -77,45 +77,89 @@ impl Default for XC2BitstreamFB {
     }
 }
 
-/// Internal helper that writes a ZIA row to the fuse array
-fn zia_row_crbit_write_helper(x: usize, y: usize, zia_row: usize, zia_bits: &[bool], has_gap: bool,
-    fuse_array: &mut FuseArray) {
+// Function blocks do not use the BitTwiddler derive because the locations of the PLA and ZIA fuses are looked up in
+// per-device tables, and the ZIA choices that are valid depend on the device and the row. With the "gen" feature,
+// they get a random value generator matching the ones generated for the types they contain instead.
+#[cfg(feature = "gen")]
+impl XC2BitstreamFB {
+    /// Returns a random function block for function block `fb` of the given device, using `rng` as the source of
+    /// random numbers
+    pub fn random_value<R: FnMut() -> u64>(device: XC2Device, fb: u32, rng: &mut R) -> Self {
+        let mut ret = Self::default();
+
+        for term_i in 0..ANDTERMS_PER_FB {
+            let andterm = ret.get_mut_andterm(term_i);
+            for input_i in 0..INPUTS_PER_ANDTERM {
+                andterm.set(input_i, rng() % 2 != 0);
+                andterm.set_b(input_i, rng() % 2 != 0);
+            }
+        }
+
+        for zia_row in 0..INPUTS_PER_ANDTERM {
+            let choices = zia_table_get_row(device, zia_row);
+            // Any input in the row, or a constant zero or one
+            let choice_i = (rng() % (choices.len() as u64 + 2)) as usize;
+            *ret.get_mut_zia(zia_row) = match choice_i.checked_sub(2) {
+                Some(i) => choices[i],
+                None => if choice_i == 0 {XC2ZIAInput::Zero} else {XC2ZIAInput::One},
+            };
+        }
+
+        for mc_i in 0..MCS_PER_FB {
+            for term_i in 0..ANDTERMS_PER_FB {
+                ret.or_terms[mc_i].set(term_i, rng() % 2 != 0);
+            }
+
+            ret.mcs[mc_i] = XC2Macrocell::random_value(rng);
+            // Buried macrocells have no IOB to take the register input from
+            if fb_mc_num_to_iob_num(device, fb, mc_i as u32).is_none() {
+                ret.mcs[mc_i].ff_in_ibuf = false;
+            }
+        }
+
+        ret
+    }
+}
+
+/// Internal helper that writes a ZIA row to a view of the fuse array starting at the ZIA block
+fn zia_row_crbit_write_helper<S>(zia_block: &mut S, zia_row: usize, zia_bits: &[bool], has_gap: bool)
+    where S: FuseStore<(usize, usize)> {
 
     for zia_bit in 0..zia_bits.len() {
-        let mut out_y = y + zia_row;
+        let mut out_y = zia_row;
         if has_gap && zia_row >= 20 {
             // There is an OR array in the middle, 8 rows high
             out_y += 8;
         }
 
-        let out_x = x + zia_bit * 2;
+        let out_x = zia_bit * 2;
 
-        fuse_array.set(out_x, out_y, zia_bits[zia_bits.len() - 1 - zia_bit]);
+        zia_block.set_fuse((out_x, out_y), zia_bits[zia_bits.len() - 1 - zia_bit]);
     }
 }
 
-/// Internal helper that reads a ZIA row from the fuse array
-fn zia_row_crbit_read_helper(x: usize, y: usize, zia_row: usize, zia_bits: &mut [bool], has_gap: bool,
-    fuse_array: &FuseArray) {
+/// Internal helper that reads a ZIA row from a view of the fuse array starting at the ZIA block
+fn zia_row_crbit_read_helper<S>(zia_block: &S, zia_row: usize, zia_bits: &mut [bool], has_gap: bool)
+    where S: FuseRead<(usize, usize)> {
 
     let l = zia_bits.len();
 
     for zia_bit in 0..l {
-        let mut out_y = y + zia_row;
+        let mut out_y = zia_row;
         if has_gap && zia_row >= 20 {
             // There is an OR array in the middle, 8 rows high
             out_y += 8;
         }
 
-        let out_x = x + zia_bit * 2;
+        let out_x = zia_bit * 2;
 
-        zia_bits[l - 1 - zia_bit] = fuse_array.get(out_x, out_y);
+        zia_bits[l - 1 - zia_bit] = zia_block.get_fuse((out_x, out_y));
     }
 }
 
 // Weird mapping here in (mostly) groups of 3
 // TODO: Explain better
-static AND_BLOCK_TYPE2_P2L_MAP: [usize; ANDTERMS_PER_FB] = [
+pub static AND_BLOCK_TYPE2_P2L_MAP: [usize; ANDTERMS_PER_FB] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10,
     55, 54, 53,
     11, 12, 13,
@@ -133,7 +177,7 @@ static AND_BLOCK_TYPE2_P2L_MAP: [usize; ANDTERMS_PER_FB] = [
     29, 30, 31,
     34, 33, 32];
 
-static OR_BLOCK_TYPE2_ROW_MAP: [usize; ANDTERMS_PER_FB / 2] =
+pub static OR_BLOCK_TYPE2_ROW_MAP: [usize; ANDTERMS_PER_FB / 2] =
     [17, 19, 22, 20, 0, 1, 3, 4, 5, 7, 8, 11, 12, 13, 15, 16, 23, 24, 26, 27, 28, 31, 32, 34, 35, 36, 38, 39];
 
 impl XC2BitstreamFB {
@@ -207,6 +251,77 @@ impl XC2BitstreamFB {
         Ok(())
     }
 
+    /// Read back the settings for this FB from the output of `dump_human_readable`.
+    /// `device` must be the device type this FB belongs to and is needed to decode I/O pin numbers.
+    /// `fb` must be the index of this function block.
+    pub fn from_human_readable(device: XC2Device, fb: u32, reader: &mut HumanReadableReader)
+        -> Result<XC2BitstreamFB, XC2BitError> {
+
+        let mut ret = XC2BitstreamFB::default();
+
+        for i in 0..MCS_PER_FB {
+            ret.mcs[i] = XC2Macrocell::from_human_readable(fb, i as u32, reader)?;
+        }
+
+        reader.expect("")?;
+        reader.expect(&format!("ZIA inputs for FB{}", fb + 1))?;
+        for i in 0..INPUTS_PER_ANDTERM {
+            let choice = match reader.value(&format!("{:2}: ", i))? {
+                "0" => XC2ZIAInput::Zero,
+                "1" => XC2ZIAInput::One,
+                "dedicated input" => XC2ZIAInput::DedicatedInput,
+                x => {
+                    if let Some(name) = without_suffix(x, " FF") {
+                        let (fb, mc) = parse_fb_mc_name(name).ok_or_else(|| reader.err())?;
+                        if fb as usize >= device.num_fbs() || mc as usize >= MCS_PER_FB {
+                            return Err(reader.err());
+                        }
+                        XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8}
+                    } else if let Some(name) = without_suffix(x, " pad") {
+                        let (fb, mc) = parse_fb_mc_name(name).ok_or_else(|| reader.err())?;
+                        let ibuf = fb_mc_num_to_iob_num(device, fb, mc).ok_or_else(|| reader.err())?;
+                        XC2ZIAInput::IBuf{ibuf: ibuf as u16}
+                    } else {
+                        return Err(reader.err());
+                    }
+                }
+            };
+            // Only accept choices that this ZIA row can actually encode
+            if choice != XC2ZIAInput::Zero && choice != XC2ZIAInput::One &&
+                !zia_table_get_row(device, i).contains(&choice) {
+
+                return Err(reader.err());
+            }
+            *ret.get_mut_zia(i) = choice;
+        }
+
+        reader.expect("")?;
+        reader.expect(&format!("AND terms for FB{}", fb + 1))?;
+        let and_header = (0..INPUTS_PER_ANDTERM).map(|i| format!("{:3}|{:>3}", i, format!("~{}", i)))
+            .collect::<Vec<_>>().join("|");
+        reader.expect(&format!("   |{}", and_header))?;
+        for i in 0..ANDTERMS_PER_FB {
+            let cells = read_grid_row(reader, i, INPUTS_PER_ANDTERM * 2, "XXX")?;
+            for j in 0..INPUTS_PER_ANDTERM {
+                ret.get_mut_andterm(i).set(j, cells[j * 2]);
+                ret.get_mut_andterm(i).set_b(j, cells[j * 2 + 1]);
+            }
+        }
+
+        reader.expect("")?;
+        reader.expect(&format!("OR terms for FB{}", fb + 1))?;
+        let or_header = (0..ANDTERMS_PER_FB).map(|i| format!("{:2}", i)).collect::<Vec<_>>().join("|");
+        reader.expect(&format!("   |{}", or_header))?;
+        for i in 0..MCS_PER_FB {
+            let cells = read_grid_row(reader, i, ANDTERMS_PER_FB, "XX")?;
+            for (j, &cell) in cells.iter().enumerate() {
+                ret.or_terms[i].set(j, cell);
+            }
+        }
+
+        Ok(ret)
+    }
+
     /// Write the crbit representation of the settings for this FB to the given `fuse_array`.
     /// `device` must be the device type this FB was extracted from.
     /// `fb` must be the index of this function block.
@@ -217,7 +332,7 @@ impl XC2BitstreamFB {
         }
 
         // ZIA
-        let (x, y) = zia_block_loc(device, fb);
+        let mut zia_block = FuseView2D::new(&mut *fuse_array, zia_block_loc(device, fb), (false, false));
         for zia_row in 0..INPUTS_PER_ANDTERM {
             match device {
                 XC2Device::XC2C32 | XC2Device::XC2C32A => {
@@ -225,70 +340,64 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, true, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, true);
                 },
                 XC2Device::XC2C64 | XC2Device::XC2C64A => {
                     let zia_choice_bits = XC2ZIAInput::encode_64_zia_choice(zia_row as u32, *self.get_zia(zia_row))
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, true, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, true);
                 },
                 XC2Device::XC2C128 => {
                     let zia_choice_bits = XC2ZIAInput::encode_128_zia_choice(zia_row as u32, *self.get_zia(zia_row))
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, false, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, false);
                 },
                 XC2Device::XC2C256 => {
                     let zia_choice_bits = XC2ZIAInput::encode_256_zia_choice(zia_row as u32, *self.get_zia(zia_row))
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, true, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, true);
                 },
                 XC2Device::XC2C384 => {
                     let zia_choice_bits = XC2ZIAInput::encode_384_zia_choice(zia_row as u32, *self.get_zia(zia_row))
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, false, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, false);
                 },
                 XC2Device::XC2C512 => {
                     let zia_choice_bits = XC2ZIAInput::encode_512_zia_choice(zia_row as u32, *self.get_zia(zia_row))
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
 
-                    zia_row_crbit_write_helper(x, y, zia_row, &zia_choice_bits, false, fuse_array);
+                    zia_row_crbit_write_helper(&mut zia_block, zia_row, &zia_choice_bits, false);
                 },
             };
         }
 
         // AND block
         let (x, y, mirror) = and_block_loc(device, fb);
+        let mut and_block = FuseView2D::new(&mut *fuse_array, (x, y), (mirror, false));
         match device {
             // "Type 1" blocks (OR array is in the middle)
             XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => {
                 for term_idx in 0..ANDTERMS_PER_FB {
                     for input_idx in 0..INPUTS_PER_ANDTERM {
-                        let mut out_y = y + input_idx;
+                        let mut out_y = input_idx;
                         if input_idx >= 20 {
                             // There is an OR array in the middle, 8 rows high
                             out_y += 8;
                         }
 
-                        if !mirror {
-                            // true input
-                            fuse_array.set(x + term_idx * 2 + 1, out_y, !self.get_andterm(term_idx).get(input_idx));
-                            // complement input
-                            fuse_array.set(x + term_idx * 2 + 0, out_y, !self.get_andterm(term_idx).get_b(input_idx));
-                        } else {
-                            // true input
-                            fuse_array.set(x - term_idx * 2 - 1, out_y, !self.get_andterm(term_idx).get(input_idx));
-                            // complement input
-                            fuse_array.set(x - term_idx * 2 - 0, out_y, !self.get_andterm(term_idx).get_b(input_idx));
-                        }
+                        // true input
+                        and_block.set_fuse((term_idx * 2 + 1, out_y), !self.get_andterm(term_idx).get(input_idx));
+                        // complement input
+                        and_block.set_fuse((term_idx * 2 + 0, out_y), !self.get_andterm(term_idx).get_b(input_idx));
                     }
                 }
             },
@@ -297,21 +406,12 @@ impl XC2BitstreamFB {
                 for term_idx in 0..ANDTERMS_PER_FB {
                     for input_idx in 0..INPUTS_PER_ANDTERM {
                         let phys_term_idx = AND_BLOCK_TYPE2_P2L_MAP[term_idx];
-                        if !mirror {
-                            // true input
-                            fuse_array.set(x + term_idx * 2 + 1, y + input_idx,
-                                !self.get_andterm(phys_term_idx).get(input_idx));
-                            // complement input
-                            fuse_array.set(x + term_idx * 2 + 0, y + input_idx,
-                                !self.get_andterm(phys_term_idx).get_b(input_idx));
-                        } else {
-                            // true input
-                            fuse_array.set(x - term_idx * 2 - 1, y + input_idx,
-                                !self.get_andterm(phys_term_idx).get(input_idx));
-                            // complement input
-                            fuse_array.set(x - term_idx * 2 - 0, y + input_idx,
-                                !self.get_andterm(phys_term_idx).get_b(input_idx));
-                        }
+                        // true input
+                        and_block.set_fuse((term_idx * 2 + 1, input_idx),
+                            !self.get_andterm(phys_term_idx).get(input_idx));
+                        // complement input
+                        and_block.set_fuse((term_idx * 2 + 0, input_idx),
+                            !self.get_andterm(phys_term_idx).get_b(input_idx));
                     }
                 }
             },
@@ -319,20 +419,16 @@ impl XC2BitstreamFB {
 
         // OR block
         let (x, y, mirror) = or_block_loc(device, fb);
+        let mut or_block = FuseView2D::new(&mut *fuse_array, (x, y), (mirror, false));
         match device {
             // "Type 1" blocks (OR array is in the middle)
             XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => {
                 for or_term_idx in 0..MCS_PER_FB {
                     for and_term_idx in 0..ANDTERMS_PER_FB {
-                        let out_y = y + (or_term_idx / 2);
-                        let off_x = and_term_idx * 2 + (or_term_idx % 2);
-                        let out_x = if !mirror {
-                            x + off_x
-                        } else {
-                            x - off_x
-                        };
+                        let out_y = or_term_idx / 2;
+                        let out_x = and_term_idx * 2 + (or_term_idx % 2);
 
-                        fuse_array.set(out_x, out_y, !self.or_terms[or_term_idx].get(and_term_idx));
+                        or_block.set_fuse((out_x, out_y), !self.or_terms[or_term_idx].get(and_term_idx));
                     }
                 }
             },
@@ -340,7 +436,7 @@ impl XC2BitstreamFB {
             XC2Device::XC2C128 | XC2Device::XC2C384 | XC2Device::XC2C512 => {
                 for or_term_idx in 0..MCS_PER_FB {
                     for and_term_idx in 0..ANDTERMS_PER_FB {
-                        let out_y = y + OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2];
+                        let out_y = OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2];
                         let mut out_x = or_term_idx * 2;
                         // TODO: Explain wtf is happening here
                         if OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2] >= 23 {
@@ -354,13 +450,7 @@ impl XC2BitstreamFB {
                             }
                         }
 
-                        let out_x = if !mirror {
-                            x + out_x
-                        } else {
-                            x - out_x
-                        };
-
-                        fuse_array.set(out_x, out_y, !self.or_terms[or_term_idx].get(and_term_idx));
+                        or_block.set_fuse((out_x, out_y), !self.or_terms[or_term_idx].get(and_term_idx));
                     }
                 }
             },
@@ -374,37 +464,37 @@ impl XC2BitstreamFB {
         let mut ret = Self::default();
 
         // ZIA
-        let (x, y) = zia_block_loc(device, fb);
+        let zia_block = FuseView2D::new(fuse_array, zia_block_loc(device, fb), (false, false));
         for zia_row in 0..INPUTS_PER_ANDTERM {
             *ret.get_mut_zia(zia_row) = match device {
                 XC2Device::XC2C32 | XC2Device::XC2C32A => {
                     let mut zia_bits = [false; 8];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, true, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, true);
                     XC2ZIAInput::decode_32_zia_choice(zia_row, &zia_bits)?
                 },
                 XC2Device::XC2C64 | XC2Device::XC2C64A => {
                     let mut zia_bits = [false; 16];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, true, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, true);
                     XC2ZIAInput::decode_64_zia_choice(zia_row, &zia_bits)?
                 },
                 XC2Device::XC2C128 => {
                     let mut zia_bits = [false; 28];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, false, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, false);
                     XC2ZIAInput::decode_128_zia_choice(zia_row, &zia_bits)?
                 },
                 XC2Device::XC2C256 => {
                     let mut zia_bits = [false; 48];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, true, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, true);
                     XC2ZIAInput::decode_256_zia_choice(zia_row, &zia_bits)?
                 },
                 XC2Device::XC2C384 => {
                     let mut zia_bits = [false; 74];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, false, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, false);
                     XC2ZIAInput::decode_384_zia_choice(zia_row, &zia_bits)?
                 },
                 XC2Device::XC2C512 => {
                     let mut zia_bits = [false; 88];
-                    zia_row_crbit_read_helper(x, y, zia_row, &mut zia_bits, false, fuse_array);
+                    zia_row_crbit_read_helper(&zia_block, zia_row, &mut zia_bits, false);
                     XC2ZIAInput::decode_512_zia_choice(zia_row, &zia_bits)?
                 },
             };
@@ -412,32 +502,24 @@ impl XC2BitstreamFB {
 
         // AND block
         let (x, y, mirror) = and_block_loc(device, fb);
+        let and_block = FuseView2D::new(fuse_array, (x, y), (mirror, false));
         match device {
             // "Type 1" blocks (OR array is in the middle)
             XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => {
                 for term_idx in 0..ANDTERMS_PER_FB {
                     for input_idx in 0..INPUTS_PER_ANDTERM {
-                        let mut out_y = y + input_idx;
+                        let mut out_y = input_idx;
                         if input_idx >= 20 {
                             // There is an OR array in the middle, 8 rows high
                             out_y += 8;
                         }
 
-                        if !mirror {
-                            // true input
-                            ret.get_mut_andterm(term_idx)
-                                .set(input_idx, !fuse_array.get(x + term_idx * 2 + 1, out_y));
-                            // complement input
-                            ret.get_mut_andterm(term_idx)
-                                .set_b(input_idx, !fuse_array.get(x + term_idx * 2 + 0, out_y));
-                        } else {
-                            // true input
-                            ret.get_mut_andterm(term_idx)
-                                .set(input_idx, !fuse_array.get(x - term_idx * 2 - 1, out_y));
-                            // complement input
-                            ret.get_mut_andterm(term_idx)
-                                .set_b(input_idx, !fuse_array.get(x - term_idx * 2 - 0, out_y));
-                        }
+                        // true input
+                        ret.get_mut_andterm(term_idx)
+                            .set(input_idx, !and_block.get_fuse((term_idx * 2 + 1, out_y)));
+                        // complement input
+                        ret.get_mut_andterm(term_idx)
+                            .set_b(input_idx, !and_block.get_fuse((term_idx * 2 + 0, out_y)));
                     }
                 }
             },
@@ -446,21 +528,12 @@ impl XC2BitstreamFB {
                 for term_idx in 0..ANDTERMS_PER_FB {
                     for input_idx in 0..INPUTS_PER_ANDTERM {
                         let phys_term_idx = AND_BLOCK_TYPE2_P2L_MAP[term_idx];
-                        if !mirror {
-                            // true input
-                            ret.get_mut_andterm(phys_term_idx).set(input_idx,
-                                !fuse_array.get(x + term_idx * 2 + 1, y + input_idx));
-                            // complement input
-                            ret.get_mut_andterm(phys_term_idx).set_b(input_idx,
-                                !fuse_array.get(x + term_idx * 2 + 0, y + input_idx));
-                        } else {
-                            // true input
-                            ret.get_mut_andterm(phys_term_idx).set(input_idx,
-                                !fuse_array.get(x - term_idx * 2 - 1, y + input_idx));
-                            // complement input
-                            ret.get_mut_andterm(phys_term_idx).set_b(input_idx,
-                                !fuse_array.get(x - term_idx * 2 - 0, y + input_idx));
-                        }
+                        // true input
+                        ret.get_mut_andterm(phys_term_idx).set(input_idx,
+                            !and_block.get_fuse((term_idx * 2 + 1, input_idx)));
+                        // complement input
+                        ret.get_mut_andterm(phys_term_idx).set_b(input_idx,
+                            !and_block.get_fuse((term_idx * 2 + 0, input_idx)));
                     }
                 }
             },
@@ -468,20 +541,16 @@ impl XC2BitstreamFB {
 
         // OR block
         let (x, y, mirror) = or_block_loc(device, fb);
+        let or_block = FuseView2D::new(fuse_array, (x, y), (mirror, false));
         match device {
             // "Type 1" blocks (OR array is in the middle)
             XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => {
                 for or_term_idx in 0..MCS_PER_FB {
                     for and_term_idx in 0..ANDTERMS_PER_FB {
-                        let out_y = y + (or_term_idx / 2);
-                        let off_x = and_term_idx * 2 + (or_term_idx % 2);
-                        let out_x = if !mirror {
-                            x + off_x
-                        } else {
-                            x - off_x
-                        };
+                        let out_y = or_term_idx / 2;
+                        let out_x = and_term_idx * 2 + (or_term_idx % 2);
 
-                        ret.or_terms[or_term_idx].set(and_term_idx, !fuse_array.get(out_x, out_y));
+                        ret.or_terms[or_term_idx].set(and_term_idx, !or_block.get_fuse((out_x, out_y)));
                     }
                 }
             },
@@ -489,7 +558,7 @@ impl XC2BitstreamFB {
             XC2Device::XC2C128 | XC2Device::XC2C384 | XC2Device::XC2C512 => {
                 for or_term_idx in 0..MCS_PER_FB {
                     for and_term_idx in 0..ANDTERMS_PER_FB {
-                        let out_y = y + OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2];
+                        let out_y = OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2];
                         let mut out_x = or_term_idx * 2;
                         // TODO: Explain wtf is happening here
                         if OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2] >= 23 {
@@ -503,13 +572,7 @@ impl XC2BitstreamFB {
                             }
                         }
 
-                        let out_x = if !mirror {
-                            x + out_x
-                        } else {
-                            x - out_x
-                        };
-
-                        ret.or_terms[or_term_idx].set(and_term_idx, !fuse_array.get(out_x, out_y));
+                        ret.or_terms[or_term_idx].set(and_term_idx, !or_block.get_fuse((out_x, out_y)));
                     }
                 }
             },
@@ -544,7 +607,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -553,7 +616,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -562,7 +625,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -571,7 +634,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -580,7 +643,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -589,7 +652,7 @@ impl XC2BitstreamFB {
                         // FIXME: Fold this into the error system??
                         .expect("invalid ZIA input");
                     for j in 0..zia_choice_bits.len() {
-                        jed.f[zia_fuse_base] = zia_choice_bits[j];
+                        jed.f.set(zia_fuse_base, zia_choice_bits[j]);
                         zia_fuse_base += 1;
                     }
                 },
@@ -597,30 +660,34 @@ impl XC2BitstreamFB {
         }
 
         // AND terms
-        linebreaks.add(fuse_base + zia_row_width * INPUTS_PER_ANDTERM);
-        for i in 0..ANDTERMS_PER_FB {
-            let and_fuse_base = fuse_base + zia_row_width * INPUTS_PER_ANDTERM + i * INPUTS_PER_ANDTERM * 2;
-            linebreaks.add(and_fuse_base);
-            for j in 0..INPUTS_PER_ANDTERM {
-                jed.f[and_fuse_base + j * 2 + 0] = !self.get_andterm(i).get(j);
-                jed.f[and_fuse_base + j * 2 + 1] = !self.get_andterm(i).get_b(j);
+        let and_block_idx = fuse_base + zia_row_width * INPUTS_PER_ANDTERM;
+        linebreaks.add(and_block_idx);
+        {
+            let mut and_block = FuseView1D::new(&mut jed.f, and_block_idx, false);
+            for i in 0..ANDTERMS_PER_FB {
+                let and_term_base = i * INPUTS_PER_ANDTERM * 2;
+                linebreaks.add(and_block_idx + and_term_base);
+                for j in 0..INPUTS_PER_ANDTERM {
+                    and_block.set_fuse(and_term_base + j * 2 + 0, !self.get_andterm(i).get(j));
+                    and_block.set_fuse(and_term_base + j * 2 + 1, !self.get_andterm(i).get_b(j));
+                }
             }
         }
 
         // OR terms
-        linebreaks.add(fuse_base + zia_row_width * INPUTS_PER_ANDTERM + ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2);
+        let or_block_idx = and_block_idx + ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2;
+        linebreaks.add(or_block_idx);
+        let mut or_block = FuseView1D::new(&mut jed.f, or_block_idx, false);
         for i in 0..ANDTERMS_PER_FB {
-            let or_fuse_base = fuse_base + zia_row_width * INPUTS_PER_ANDTERM +
-                ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2 + i * MCS_PER_FB;
-            linebreaks.add(or_fuse_base);
+            linebreaks.add(or_block_idx + i * MCS_PER_FB);
             for j in 0..MCS_PER_FB {
-                jed.f[or_fuse_base + j] = !self.or_terms[j].get(i);
+                or_block.set_fuse(i * MCS_PER_FB + j, !self.or_terms[j].get(i));
             }
         }
     }
 
     /// Internal function that reads a function block
-    pub fn from_jed(device: XC2Device, fuses: &[bool], fb: u32, fuse_base: usize)
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(device: XC2Device, fuses: &F, fb: u32, fuse_base: usize)
         -> Result<XC2BitstreamFB, XC2BitError> {
 
         let zia_row_width = zia_get_row_width(device);
@@ -656,8 +723,9 @@ impl XC2BitstreamFB {
 
         let zia_block_idx = fuse_base;
         for i in 0..INPUTS_PER_ANDTERM {
-            let zia_row_fuses = &fuses[zia_block_idx + i * zia_row_width..zia_block_idx + (i + 1) * zia_row_width];
-            let result = zia_row_decode_function(i, zia_row_fuses)?;
+            let zia_row_fuses = (zia_block_idx + i * zia_row_width..zia_block_idx + (i + 1) * zia_row_width)
+                .map(|fuse| fuses.get_fuse(fuse)).collect::<Vec<_>>();
+            let result = zia_row_decode_function(i, &zia_row_fuses)?;
             *ret.get_mut_zia(i) = result;
         }
 
@@ -684,6 +752,34 @@ impl XC2BitstreamFB {
     }
 }
 
+/// Internal function that reads one row of the AND or OR term tables in the human-readable dump. Each of the `width`
+/// cells is either `set_cell` or the same number of spaces.
+fn read_grid_row(reader: &mut HumanReadableReader, row: usize, width: usize, set_cell: &str)
+    -> Result<Vec<bool>, XC2BitError> {
+
+    let line = reader.value(&format!("{:2}:", row))?;
+    let unset_cell = " ".repeat(set_cell.len());
+    let mut cells = line.split('|');
+    // The row starts with a separator, so the first piece is always empty
+    if cells.next() != Some("") {
+        return Err(reader.err());
+    }
+    let cells = cells.map(|cell| {
+        if cell == set_cell {
+            Some(true)
+        } else if cell == unset_cell {
+            Some(false)
+        } else {
+            None
+        }
+    }).collect::<Option<Vec<_>>>().ok_or_else(|| reader.err())?;
+    if cells.len() != width {
+        return Err(reader.err());
+    }
+
+    Ok(cells)
+}
+
 // TODO: This is the same across all sizes, right?
 
 /// The index of the special CTC product term
@@ -712,3 +808,53 @@ pub fn get_ptb(mc: u32) -> u32 {
 pub fn get_ptc(mc: u32) -> u32 {
     3 * mc + 10
 }
+
+#[cfg(all(test, feature = "gen"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_fb_roundtrip() {
+        let mut rng_state = 0x2545F4914F6CDD1Du64;
+        let mut rng = move || {
+            rng_state ^= rng_state << 13;
+            rng_state ^= rng_state >> 7;
+            rng_state ^= rng_state << 17;
+            rng_state
+        };
+
+        for &part in &["xc2c32-6-vq44", "xc2c32a-6-vq44", "xc2c64-7-vq44", "xc2c64a-7-vq44", "xc2c128-7-vq100",
+            "xc2c256-7-vq100", "xc2c384-7-tq144", "xc2c512-7-pq208"] {
+
+            let part = XC2DeviceSpeedPackage::from_str(part).unwrap();
+            let device = part.dev;
+
+            for _ in 0..4 {
+                let mut bitstream = XC2Bitstream::blank_bitstream(part);
+                for fb_i in 0..device.num_fbs() {
+                    bitstream.bits.get_fb_mut()[fb_i] = XC2BitstreamFB::random_value(device, fb_i as u32, &mut rng);
+                }
+
+                assert!(!bitstream.diff(&XC2Bitstream::blank_bitstream(part)).is_empty());
+
+                let mut jed = Vec::new();
+                bitstream.to_jed(&mut jed).unwrap();
+                let from_jed = XC2Bitstream::from_jed(&JEDECFile::from_bytes(&jed).unwrap()).unwrap();
+                assert_eq!(bitstream.diff(&from_jed), vec![]);
+
+                let from_crbit = XC2Bitstream::from_crbit(&bitstream.to_crbit()).unwrap();
+                assert_eq!(bitstream.diff(&from_crbit), vec![]);
+            }
+        }
+    }
+
+    #[test]
+    fn random_value_unbiased() {
+        // 2^64 is not a multiple of 3, so the top number is thrown away rather than folded onto the first variant
+        let mut numbers = vec![u64::MAX, 4].into_iter();
+        let mut rng = || numbers.next().unwrap();
+        assert_eq!(XC2MCFeedbackMode::value_count(), 3);
+        assert_eq!(XC2MCFeedbackMode::random_value(&mut rng), XC2MCFeedbackMode::nth_value(1));
+        assert_eq!(numbers.next(), None);
+    }
+}
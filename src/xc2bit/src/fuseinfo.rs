@@ -0,0 +1,705 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains functions for finding out what each fuse of a device controls
+//!
+//! Fuses can be looked up either by their index in the logical (.jed) fuse array or by their coordinate in the
+//! physical (crbit) fuse array. Everything except the AND/OR arrays, the ZIA, and the security/USERCODE/done fuses is
+//! located using the `FUSE_LAYOUT` tables generated by `BitTwiddler`.
+
+use std::fmt;
+
+use jedec::*;
+
+use *;
+use fb::{AND_BLOCK_TYPE2_P2L_MAP, OR_BLOCK_TYPE2_ROW_MAP};
+use fusemap_logical::{fb_fuse_idx};
+use fusemap_physical::{and_block_loc, or_block_loc, zia_block_loc, mc_block_loc, done1_fuse_coord,
+                       security_fuse_coords, usercode_fuse_coord};
+use globalbits::{XC2MiscBits32, XC2MiscBits32A, XC2MiscBits128, XC2MiscBits384};
+use mc::{MC_TO_ROW_MAP_LARGE};
+use util::{without_prefix};
+use zia::{zia_get_row_width};
+
+/// The setting that a fuse belongs to. Bit numbers of multi-bit fields follow the `FUSE_LAYOUT` tables and are `None`
+/// for fields that are only one bit wide.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum XC2FuseOwner {
+    /// One of the selection bits of a ZIA row. The bits are numbered in the order used by the .jed format.
+    ZIARow {
+        fb: u32,
+        row: u32,
+        bit: u32,
+    },
+    /// Whether an input (`complement` = `false`) or its complement (`complement` = `true`) is used in an AND term
+    AndTermInput {
+        fb: u32,
+        term: u32,
+        input: u32,
+        complement: bool,
+    },
+    /// Whether an AND term is used in the OR term of a macrocell
+    OrTermInput {
+        fb: u32,
+        mc: u32,
+        term: u32,
+    },
+    /// A field of a macrocell
+    Macrocell {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A field of an IOB. The IOB is identified by the macrocell it is attached to.
+    IOB {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A field of the input-only pin on 32-macrocell devices
+    InputOnlyPin {
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A field of the global net configuration
+    GlobalNets {
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A field of the clock divider
+    ClockDiv {
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A device-wide setting stored directly in `XC2BitstreamBits` (voltage levels, etc.)
+    Global {
+        field: &'static str,
+    },
+    /// One of the two security fuses. These only exist in the crbit format.
+    SecurityFuse(u32),
+    /// A bit of the USERCODE (0 is the LSB). These only exist in the crbit format.
+    Usercode(u32),
+    /// The done1 fuse that marks the fuse array as valid. This only exists in the crbit format.
+    Done1,
+}
+
+/// Internal helper that prints the bit number of a multi-bit field
+fn write_bit(f: &mut fmt::Formatter, bit: Option<u32>) -> fmt::Result {
+    match bit {
+        Some(bit) => write!(f, " bit {}", bit),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for XC2FuseOwner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2FuseOwner::ZIARow{fb, row, bit} =>
+                write!(f, "FB{} ZIA row {} bit {}", fb + 1, row, bit),
+            XC2FuseOwner::AndTermInput{fb, term, input, complement} =>
+                write!(f, "FB{} AND term {} input {}{}", fb + 1, term, if complement {"~"} else {""}, input),
+            XC2FuseOwner::OrTermInput{fb, mc, term} =>
+                write!(f, "FB{}_{} OR term input {}", fb + 1, mc + 1, term),
+            XC2FuseOwner::Macrocell{fb, mc, field, bit} => {
+                write!(f, "FB{}_{} {}", fb + 1, mc + 1, field)?;
+                write_bit(f, bit)
+            },
+            XC2FuseOwner::IOB{fb, mc, field, bit} => {
+                write!(f, "FB{}_{} I/O {}", fb + 1, mc + 1, field)?;
+                write_bit(f, bit)
+            },
+            XC2FuseOwner::InputOnlyPin{field, bit} => {
+                write!(f, "inpin.{}", field)?;
+                write_bit(f, bit)
+            },
+            XC2FuseOwner::GlobalNets{field, bit} => {
+                write!(f, "{}", field)?;
+                write_bit(f, bit)
+            },
+            XC2FuseOwner::ClockDiv{field, bit} => {
+                write!(f, "clock_div.{}", field)?;
+                write_bit(f, bit)
+            },
+            XC2FuseOwner::Global{field} =>
+                write!(f, "{}", field),
+            XC2FuseOwner::SecurityFuse(i) =>
+                write!(f, "security fuse {}", i),
+            XC2FuseOwner::Usercode(bit) =>
+                write!(f, "USERCODE bit {}", bit),
+            XC2FuseOwner::Done1 =>
+                write!(f, "done1"),
+        }
+    }
+}
+
+impl XC2FuseOwner {
+    /// Returns whether this is part of the setting called `name`. `name` uses the same format as the `Display`
+    /// output, but can leave off any number of trailing parts, so that e.g. "FB1_3 r_src" matches every bit of that
+    /// field, "gck_enable" matches every element of that array, and "FB2 AND term 5" matches every input of that term.
+    pub fn is_part_of(&self, name: &str) -> bool {
+        let full_name = self.to_string();
+        match without_prefix(&full_name, name) {
+            Some(rest) => rest.is_empty() || rest.starts_with(' ') || rest.starts_with('['),
+            None => false,
+        }
+    }
+}
+
+/// Describes what one fuse controls
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2FuseInfo {
+    /// The setting that the fuse belongs to
+    pub owner: XC2FuseOwner,
+    /// Whether the fuse holds the inverse of the bit, e.g. AND term fuses are 0 when the input is used
+    pub inverted: bool,
+}
+
+impl fmt::Display for XC2FuseInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.owner)?;
+        if self.inverted {
+            write!(f, " (inverted)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Internal helper that returns the suffix of the `BitTwiddler` instance names of the device-wide settings
+fn instance_suffix(device: XC2Device) -> &'static str {
+    match device {
+        XC2Device::XC2C32 | XC2Device::XC2C32A => "32",
+        XC2Device::XC2C64 | XC2Device::XC2C64A => "64",
+        XC2Device::XC2C128 => "128",
+        XC2Device::XC2C256 => "256",
+        XC2Device::XC2C384 => "384",
+        XC2Device::XC2C512 => "512",
+    }
+}
+
+/// Internal helper that calls `f` for every bit of every field of the given `BitTwiddler` instance. Bits that are not
+/// stored in any fuse are skipped.
+fn for_each_layout_bit<F>(layout: &'static [FuseLayoutField], instance: &str, mut f: F)
+    where F: FnMut(&'static FuseLayoutField, Option<u32>, &'static FuseLayoutBit) {
+
+    for field in layout.iter().filter(|x| x.instance == instance) {
+        for (i, bit) in field.bits.iter().enumerate() {
+            if let FuseLayoutLoc::Const(_) = bit.loc {
+                continue;
+            }
+            f(field, if field.bits.len() == 1 {None} else {Some(i as u32)}, bit);
+        }
+    }
+}
+
+/// Internal helper that returns the `FUSE_LAYOUT` table of the device-wide settings that are stored directly in
+/// `XC2BitstreamBits`. Its instances are named in the same way as those of `XC2GlobalNets`.
+fn misc_layout(device: XC2Device) -> &'static [FuseLayoutField] {
+    match device {
+        XC2Device::XC2C32 | XC2Device::XC2C64 => XC2MiscBits32::FUSE_LAYOUT,
+        XC2Device::XC2C32A | XC2Device::XC2C64A => XC2MiscBits32A::FUSE_LAYOUT,
+        XC2Device::XC2C128 | XC2Device::XC2C256 => XC2MiscBits128::FUSE_LAYOUT,
+        XC2Device::XC2C384 | XC2Device::XC2C512 => XC2MiscBits384::FUSE_LAYOUT,
+    }
+}
+
+/// Returns what every fuse in the .jed fuse array of the given device controls, sorted by fuse index
+pub fn jed_fuse_map(device: XC2Device) -> Vec<(usize, XC2FuseInfo)> {
+    let mut ret = Vec::new();
+    let zia_row_width = zia_get_row_width(device);
+
+    for fb in 0..device.num_fbs() as u32 {
+        let fuse_base = fb_fuse_idx(device, fb);
+
+        // ZIA
+        for row in 0..INPUTS_PER_ANDTERM {
+            for bit in 0..zia_row_width {
+                ret.push((fuse_base + row * zia_row_width + bit, XC2FuseInfo {
+                    owner: XC2FuseOwner::ZIARow{fb, row: row as u32, bit: bit as u32},
+                    inverted: false,
+                }));
+            }
+        }
+
+        // AND terms
+        let and_block_idx = fuse_base + zia_row_width * INPUTS_PER_ANDTERM;
+        for term in 0..ANDTERMS_PER_FB {
+            for input in 0..INPUTS_PER_ANDTERM {
+                for &complement in &[false, true] {
+                    ret.push((and_block_idx + term * INPUTS_PER_ANDTERM * 2 + input * 2 + (complement as usize),
+                        XC2FuseInfo {
+                            owner: XC2FuseOwner::AndTermInput{fb, term: term as u32, input: input as u32, complement},
+                            inverted: true,
+                        }));
+                }
+            }
+        }
+
+        // OR terms
+        let or_block_idx = and_block_idx + ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2;
+        for term in 0..ANDTERMS_PER_FB {
+            for mc in 0..MCS_PER_FB {
+                ret.push((or_block_idx + term * MCS_PER_FB + mc, XC2FuseInfo {
+                    owner: XC2FuseOwner::OrTermInput{fb, mc: mc as u32, term: term as u32},
+                    inverted: true,
+                }));
+            }
+        }
+
+        // Macrocells and IOBs
+        let mut mc_fuse_base = or_block_idx + ANDTERMS_PER_FB * MCS_PER_FB;
+        for mc in 0..MCS_PER_FB as u32 {
+            let has_iob = fb_mc_num_to_iob_num(device, fb, mc).is_some();
+            let (mc_instance, iob_layout, size) = if device.is_small_iob() {
+                ("jed_internal_small", Some(XC2MCSmallIOB::FUSE_LAYOUT), 27)
+            } else if has_iob {
+                ("jed_internal_large", Some(XC2MCLargeIOB::FUSE_LAYOUT), 29)
+            } else {
+                ("jed_internal_large_buried", None, 16)
+            };
+
+            for_each_layout_bit(XC2Macrocell::FUSE_LAYOUT, mc_instance, |field, bit, layout_bit| {
+                ret.push((layout_bit.fuse_idx(field, mc_fuse_base, false).unwrap(), XC2FuseInfo {
+                    owner: XC2FuseOwner::Macrocell{fb, mc, field: field.field, bit},
+                    inverted: layout_bit.invert,
+                }));
+            });
+            if let Some(iob_layout) = iob_layout {
+                for_each_layout_bit(iob_layout, "jed_internal", |field, bit, layout_bit| {
+                    ret.push((layout_bit.fuse_idx(field, mc_fuse_base, false).unwrap(), XC2FuseInfo {
+                        owner: XC2FuseOwner::IOB{fb, mc, field: field.field, bit},
+                        inverted: layout_bit.invert,
+                    }));
+                });
+            }
+
+            mc_fuse_base += size;
+        }
+    }
+
+    // Input-only pin
+    if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A {
+        for_each_layout_bit(XC2ExtraIBuf::FUSE_LAYOUT, "jed", |field, bit, layout_bit| {
+            ret.push((layout_bit.fuse_idx(field, 0, false).unwrap(), XC2FuseInfo {
+                owner: XC2FuseOwner::InputOnlyPin{field: field.field, bit},
+                inverted: layout_bit.invert,
+            }));
+        });
+    }
+
+    // Global nets and clock divider
+    let instance = format!("jed{}", instance_suffix(device));
+    for_each_layout_bit(XC2GlobalNets::FUSE_LAYOUT, &instance, |field, bit, layout_bit| {
+        ret.push((layout_bit.fuse_idx(field, 0, false).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::GlobalNets{field: field.field, bit},
+            inverted: layout_bit.invert,
+        }));
+    });
+    for_each_layout_bit(XC2ClockDiv::FUSE_LAYOUT, &instance, |field, bit, layout_bit| {
+        ret.push((layout_bit.fuse_idx(field, 0, false).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::ClockDiv{field: field.field, bit},
+            inverted: layout_bit.invert,
+        }));
+    });
+
+    // Everything else
+    for_each_layout_bit(misc_layout(device), &instance, |field, _, layout_bit| {
+        ret.push((layout_bit.fuse_idx(field, 0, false).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::Global{field: field.field},
+            inverted: layout_bit.invert,
+        }));
+    });
+
+    ret.sort_by_key(|&(fuse, _)| fuse);
+    ret
+}
+
+/// Returns what every fuse in the crbit fuse array of the given device controls, sorted by (x, y) coordinate. Fuses
+/// that do not control anything are not included.
+pub fn crbit_fuse_map(device: XC2Device) -> Vec<((usize, usize), XC2FuseInfo)> {
+    let mut ret = Vec::new();
+    let zia_row_width = zia_get_row_width(device);
+    // "Type 1" blocks have the OR array in the middle, and "type 2" blocks have it on the sides
+    let type1_blocks = match device {
+        XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => true,
+        XC2Device::XC2C128 | XC2Device::XC2C384 | XC2Device::XC2C512 => false,
+    };
+
+    for fb in 0..device.num_fbs() as u32 {
+        // ZIA
+        let (zia_x, zia_y) = zia_block_loc(device, fb);
+        for row in 0..INPUTS_PER_ANDTERM {
+            // The OR array in the middle of "type 1" blocks is 8 rows high
+            let out_y = if type1_blocks && row >= 20 {row + 8} else {row};
+            for bit in 0..zia_row_width {
+                // The bits are stored backwards
+                let out_x = (zia_row_width - 1 - bit) * 2;
+                ret.push(((zia_x + out_x, zia_y + out_y), XC2FuseInfo {
+                    owner: XC2FuseOwner::ZIARow{fb, row: row as u32, bit: bit as u32},
+                    inverted: false,
+                }));
+            }
+        }
+
+        // AND block
+        let (and_x, and_y, and_mirror) = and_block_loc(device, fb);
+        let and_coord = |x: usize, y: usize| (if and_mirror {and_x - x} else {and_x + x}, and_y + y);
+        for (phys_term, &type2_term) in AND_BLOCK_TYPE2_P2L_MAP.iter().enumerate() {
+            let term = if type1_blocks {phys_term} else {type2_term};
+            for input in 0..INPUTS_PER_ANDTERM {
+                let out_y = if type1_blocks && input >= 20 {input + 8} else {input};
+                for &complement in &[false, true] {
+                    let out_x = phys_term * 2 + if complement {0} else {1};
+                    ret.push((and_coord(out_x, out_y), XC2FuseInfo {
+                        owner: XC2FuseOwner::AndTermInput{fb, term: term as u32, input: input as u32, complement},
+                        inverted: true,
+                    }));
+                }
+            }
+        }
+
+        // OR block
+        let (or_x, or_y, or_mirror) = or_block_loc(device, fb);
+        let or_coord = |x: usize, y: usize| (if or_mirror {or_x - x} else {or_x + x}, or_y + y);
+        for mc in 0..MCS_PER_FB {
+            for term in 0..ANDTERMS_PER_FB {
+                let (out_x, out_y) = if type1_blocks {
+                    (term * 2 + (mc % 2), mc / 2)
+                } else {
+                    let out_y = OR_BLOCK_TYPE2_ROW_MAP[term / 2];
+                    // Rows from 23 onwards have the two terms the other way around
+                    let odd = if out_y >= 23 {term % 2 == 0} else {term % 2 == 1};
+                    (mc * 2 + (odd as usize), out_y)
+                };
+                ret.push((or_coord(out_x, out_y), XC2FuseInfo {
+                    owner: XC2FuseOwner::OrTermInput{fb, mc: mc as u32, term: term as u32},
+                    inverted: true,
+                }));
+            }
+        }
+
+        // Macrocells
+        let (mc_x, mc_y, mc_mirror) = mc_block_loc(device, fb);
+        for mc in 0..MCS_PER_FB as u32 {
+            let (instance, y) = match device {
+                XC2Device::XC2C32 | XC2Device::XC2C32A => ("crbit32", mc_y + (mc as usize) * 3),
+                XC2Device::XC2C64 | XC2Device::XC2C64A => ("crbit64", mc_y + (mc as usize) * 3),
+                XC2Device::XC2C256 => ("crbit256", mc_y + (mc as usize) * 3),
+                _ => ("crbit_large", mc_y + MC_TO_ROW_MAP_LARGE[mc as usize]),
+            };
+            for_each_layout_bit(XC2Macrocell::FUSE_LAYOUT, instance, |field, bit, layout_bit| {
+                ret.push((layout_bit.coord(field, (mc_x, y), (mc_mirror, false)).unwrap(), XC2FuseInfo {
+                    owner: XC2FuseOwner::Macrocell{fb, mc, field: field.field, bit},
+                    inverted: layout_bit.invert,
+                }));
+            });
+        }
+    }
+
+    // IOBs
+    for iob in 0..device.num_iobs() as u32 {
+        let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+        let (x, y, mirror) = mc_block_loc(device, fb);
+        let (layout, instance, y) = match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A =>
+                (XC2MCSmallIOB::FUSE_LAYOUT, "crbit32", y + (mc as usize) * 3),
+            XC2Device::XC2C64 | XC2Device::XC2C64A =>
+                (XC2MCSmallIOB::FUSE_LAYOUT, "crbit64", y + (mc as usize) * 3),
+            XC2Device::XC2C256 =>
+                (XC2MCLargeIOB::FUSE_LAYOUT, "crbit256", y + (mc as usize) * 3),
+            _ => (XC2MCLargeIOB::FUSE_LAYOUT, "crbit_not256", y + MC_TO_ROW_MAP_LARGE[mc as usize]),
+        };
+        for_each_layout_bit(layout, instance, |field, bit, layout_bit| {
+            ret.push((layout_bit.coord(field, (x, y), (mirror, false)).unwrap(), XC2FuseInfo {
+                owner: XC2FuseOwner::IOB{fb, mc, field: field.field, bit},
+                inverted: layout_bit.invert,
+            }));
+        });
+    }
+
+    // Input-only pin
+    if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A {
+        for_each_layout_bit(XC2ExtraIBuf::FUSE_LAYOUT, "crbit", |field, bit, layout_bit| {
+            ret.push((layout_bit.coord(field, (0, 0), (false, false)).unwrap(), XC2FuseInfo {
+                owner: XC2FuseOwner::InputOnlyPin{field: field.field, bit},
+                inverted: layout_bit.invert,
+            }));
+        });
+    }
+
+    // Global nets and clock divider
+    let instance = format!("crbit{}", instance_suffix(device));
+    for_each_layout_bit(XC2GlobalNets::FUSE_LAYOUT, &instance, |field, bit, layout_bit| {
+        ret.push((layout_bit.coord(field, (0, 0), (false, false)).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::GlobalNets{field: field.field, bit},
+            inverted: layout_bit.invert,
+        }));
+    });
+    for_each_layout_bit(XC2ClockDiv::FUSE_LAYOUT, &instance, |field, bit, layout_bit| {
+        ret.push((layout_bit.coord(field, (0, 0), (false, false)).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::ClockDiv{field: field.field, bit},
+            inverted: layout_bit.invert,
+        }));
+    });
+
+    // Everything else
+    for_each_layout_bit(misc_layout(device), &instance, |field, _, layout_bit| {
+        ret.push((layout_bit.coord(field, (0, 0), (false, false)).unwrap(), XC2FuseInfo {
+            owner: XC2FuseOwner::Global{field: field.field},
+            inverted: layout_bit.invert,
+        }));
+    });
+
+    // Security, USERCODE, and done
+    let (sec0, sec1) = security_fuse_coords(device);
+    ret.push((sec0, XC2FuseInfo {owner: XC2FuseOwner::SecurityFuse(0), inverted: true}));
+    ret.push((sec1, XC2FuseInfo {owner: XC2FuseOwner::SecurityFuse(1), inverted: true}));
+    for bit in 0..32 {
+        ret.push((usercode_fuse_coord(device, bit), XC2FuseInfo {
+            owner: XC2FuseOwner::Usercode(bit as u32),
+            inverted: false,
+        }));
+    }
+    ret.push((done1_fuse_coord(device), XC2FuseInfo {owner: XC2FuseOwner::Done1, inverted: true}));
+
+    ret.sort_by_key(|&(coord, _)| coord);
+    ret
+}
+
+/// Returns what the given .jed fuse of the given device controls
+pub fn explain_jed_fuse(device: XC2Device, fuse: usize) -> Option<XC2FuseInfo> {
+    let map = jed_fuse_map(device);
+    map.binary_search_by_key(&fuse, |&(x, _)| x).ok().map(|i| map[i].1)
+}
+
+/// Returns what the given crbit fuse of the given device controls, or `None` if it does not control anything
+pub fn explain_crbit_fuse(device: XC2Device, coord: (usize, usize)) -> Option<XC2FuseInfo> {
+    let map = crbit_fuse_map(device);
+    map.binary_search_by_key(&coord, |&(x, _)| x).ok().map(|i| map[i].1)
+}
+
+/// Returns all of the .jed fuses of the setting called `name` (see `XC2FuseOwner::is_part_of`)
+pub fn find_jed_fuses(device: XC2Device, name: &str) -> Vec<(usize, XC2FuseInfo)> {
+    jed_fuse_map(device).into_iter().filter(|x| x.1.owner.is_part_of(name)).collect()
+}
+
+/// Returns all of the crbit fuses of the setting called `name` (see `XC2FuseOwner::is_part_of`)
+pub fn find_crbit_fuses(device: XC2Device, name: &str) -> Vec<((usize, usize), XC2FuseInfo)> {
+    crbit_fuse_map(device).into_iter().filter(|x| x.1.owner.is_part_of(name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusemap_logical::{total_logical_fuse_count};
+    use fusemap_physical::{fuse_array_dims};
+    use util::{LinebreakSet};
+
+    use std::collections::{HashMap, HashSet};
+    use std::fs::File;
+    use std::io::Read;
+
+    static ALL_DEVICES: [XC2Device; 8] = [XC2Device::XC2C32, XC2Device::XC2C32A, XC2Device::XC2C64,
+        XC2Device::XC2C64A, XC2Device::XC2C128, XC2Device::XC2C256, XC2Device::XC2C384, XC2Device::XC2C512];
+
+    fn blank(device: XC2Device) -> XC2Bitstream {
+        let part = match device {
+            XC2Device::XC2C32 => "XC2C32-4-VQ44",
+            XC2Device::XC2C32A => "XC2C32A-4-VQ44",
+            XC2Device::XC2C64 => "XC2C64-5-VQ44",
+            XC2Device::XC2C64A => "XC2C64A-5-VQ44",
+            XC2Device::XC2C128 => "XC2C128-6-VQ100",
+            XC2Device::XC2C256 => "XC2C256-6-VQ100",
+            XC2Device::XC2C384 => "XC2C384-7-TQ144",
+            XC2Device::XC2C512 => "XC2C512-7-PQ208",
+        };
+        XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap())
+    }
+
+    fn encode_jed(bitstream: &XC2Bitstream) -> JEDECFile {
+        let device = bitstream.bits.device_type();
+        let mut jed = JEDECFile::new(total_logical_fuse_count(device));
+        jed.dev_name_str = Some(format!("{}-{}-{}", device, bitstream.speed_grade, bitstream.package));
+        bitstream.bits.to_jed(&mut jed, &mut LinebreakSet::new());
+        jed
+    }
+
+    #[test]
+    fn fuse_maps_complete() {
+        for &device in &ALL_DEVICES {
+            let jed_map = jed_fuse_map(device);
+            let jed_fuses = jed_map.iter().map(|x| x.0).collect::<Vec<_>>();
+            assert_eq!(jed_fuses, (0..total_logical_fuse_count(device)).collect::<Vec<_>>(), "{:?}", device);
+
+            let (w, h) = fuse_array_dims(device);
+            let crbit_map = crbit_fuse_map(device);
+            let mut crbit_fuses = HashSet::new();
+            for &((x, y), _) in &crbit_map {
+                assert!(x < w && y < h, "{:?} ({}, {})", device, x, y);
+                assert!(crbit_fuses.insert((x, y)), "{:?} ({}, {})", device, x, y);
+            }
+
+            // Every setting in the .jed format is also in the crbit format. The crbit format additionally has the
+            // security fuses etc. and an unused direct input path fuse in buried macrocells.
+            let jed_owners = jed_map.iter().map(|x| x.1).collect::<HashSet<_>>();
+            let crbit_owners = crbit_map.iter().map(|x| x.1).collect::<HashSet<_>>();
+            assert!(jed_owners.is_subset(&crbit_owners), "{:?}", device);
+            for info in crbit_owners.difference(&jed_owners) {
+                match info.owner {
+                    XC2FuseOwner::SecurityFuse(_) | XC2FuseOwner::Usercode(_) | XC2FuseOwner::Done1 => {},
+                    XC2FuseOwner::Macrocell{fb, mc, field: "ff_in_ibuf", bit: None}
+                        if fb_mc_num_to_iob_num(device, fb, mc).is_none() => {},
+                    _ => panic!("{:?} {}", device, info),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_fuse_changes() {
+        for &device in &ALL_DEVICES {
+            let bitstream = blank(device);
+            let blank_jed = encode_jed(&bitstream);
+            let blank_crbit = bitstream.to_crbit();
+            let crbit_map = crbit_fuse_map(device).into_iter().map(|(coord, info)| (info, coord))
+                .collect::<HashMap<_, _>>();
+            let (w, _) = fuse_array_dims(device);
+
+            // Changing a .jed fuse changes exactly the crbit fuse of the same setting
+            let num_fuses = total_logical_fuse_count(device);
+            for (fuse, info) in jed_fuse_map(device).into_iter().step_by(num_fuses / 64) {
+                let mut jed = blank_jed.clone();
+                jed.f.set(fuse, !jed.f.get(fuse));
+                // Some settings cannot be changed on their own, and some values have more than one encoding
+                let new_bitstream = match XC2Bitstream::from_jed(&jed) {
+                    Ok(x) => x,
+                    Err(_) => continue,
+                };
+                if encode_jed(&new_bitstream).f != jed.f {
+                    continue;
+                }
+                let crbit = new_bitstream.to_crbit();
+
+                let crbit_fuse = crbit_map[&info];
+                assert_eq!(blank_crbit.bits().diff_indices(crbit.bits()), vec![crbit_fuse.1 * w + crbit_fuse.0],
+                    "{:?} {}", device, info);
+            }
+        }
+    }
+
+    #[test]
+    fn reference_bitstreams() {
+        for &name in &["32-fb", "32-zia", "64-fb", "128-fb", "256-fb", "384-fb", "512-fb"] {
+            let path = format!("{}/../../tests/xc2bit/reftests/{}.jed", env!("CARGO_MANIFEST_DIR"), name);
+            let mut jed_data = Vec::new();
+            File::open(&path).expect("failed to open jed file")
+                .read_to_end(&mut jed_data).expect("failed to read jed file");
+            let jed = JEDECFile::from_bytes(&jed_data).expect("failed to read jed");
+            let bitstream = XC2Bitstream::from_jed(&jed).expect("failed to process jed");
+            let crbit = bitstream.to_crbit();
+
+            // Every .jed fuse holds the same value as the crbit fuse of the same setting
+            let device = bitstream.bits.device_type();
+            let crbit_map = crbit_fuse_map(device).into_iter().map(|(coord, info)| (info.owner, (coord, info.inverted)))
+                .collect::<HashMap<_, _>>();
+            for (fuse, info) in jed_fuse_map(device) {
+                let ((x, y), crbit_inverted) = crbit_map[&info.owner];
+                assert_eq!(jed.f.get(fuse) ^ info.inverted, crbit.get(x, y) ^ crbit_inverted, "{} {}", name, info);
+            }
+        }
+    }
+
+    #[test]
+    fn field_changes() {
+        let mut bitstream = blank(XC2Device::XC2C32A);
+        bitstream.bits.get_mut_small_iob(0).unwrap().slew_is_fast = false;
+        let diff = encode_jed(&blank(XC2Device::XC2C32A)).f.diff_indices(&encode_jed(&bitstream).f);
+        let (iob_fb, iob_mc) = iob_num_to_fb_mc_num(XC2Device::XC2C32A, 0).unwrap();
+        let name = format!("FB{}_{} I/O slew_is_fast", iob_fb + 1, iob_mc + 1);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C32A, &name).iter().map(|x| x.0).collect::<Vec<_>>(), diff);
+
+        let mut bitstream = blank(XC2Device::XC2C128);
+        bitstream.bits.get_global_nets_mut().gck_enable[2] = true;
+        let diff = blank(XC2Device::XC2C128).to_crbit().bits()
+            .diff_indices(bitstream.to_crbit().bits());
+        let (w, _) = fuse_array_dims(XC2Device::XC2C128);
+        assert_eq!(find_crbit_fuses(XC2Device::XC2C128, "gck_enable[2]").iter()
+            .map(|x| (x.0).1 * w + (x.0).0).collect::<Vec<_>>(), diff);
+
+        let mut bitstream = blank(XC2Device::XC2C256);
+        bitstream.bits.get_fb_mut()[3].get_mut_andterm(7).set(5, true);
+        let diff = encode_jed(&blank(XC2Device::XC2C256)).f.diff_indices(&encode_jed(&bitstream).f);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(explain_jed_fuse(XC2Device::XC2C256, diff[0]).unwrap().to_string(),
+            "FB4 AND term 7 input 5 (inverted)");
+    }
+
+    #[test]
+    fn misc_settings_layout() {
+        for &device in &ALL_DEVICES {
+            let blank_jed = encode_jed(&blank(device));
+            let blank_crbit = blank(device).to_crbit();
+            let jed_map = jed_fuse_map(device);
+            let crbit_map = crbit_fuse_map(device);
+            let (w, _) = fuse_array_dims(device);
+            let num_settings = blank(device).bits.misc_settings().len();
+            for i in 0..num_settings {
+                let mut bitstream = blank(device);
+                let field = {
+                    let mut settings = bitstream.bits.misc_settings_mut();
+                    *settings[i].1 = !*settings[i].1;
+                    settings[i].0
+                };
+                let owner = XC2FuseOwner::Global{field};
+
+                let jed_diff = blank_jed.f.diff_indices(&encode_jed(&bitstream).f);
+                assert_eq!(jed_map.iter().filter(|x| x.1.owner == owner).map(|x| x.0).collect::<Vec<_>>(), jed_diff,
+                    "{:?} {}", device, field);
+
+                let crbit_diff = blank_crbit.bits().diff_indices(bitstream.to_crbit().bits());
+                assert_eq!(crbit_map.iter().filter(|x| x.1.owner == owner).map(|x| (x.0).1 * w + (x.0).0)
+                    .collect::<Vec<_>>(), crbit_diff, "{:?} {}", device, field);
+            }
+        }
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "FB1_1 clk_src").len(), 3);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "FB1_1 clk").len(), 0);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "gck_enable").len(), 3);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "FB2 AND term 5").len(), INPUTS_PER_ANDTERM * 2);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "FB2 ZIA row 3").len(), zia_get_row_width(XC2Device::XC2C64A));
+        assert_eq!(find_crbit_fuses(XC2Device::XC2C64A, "USERCODE").len(), 32);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "USERCODE").len(), 0);
+        assert_eq!(find_jed_fuses(XC2Device::XC2C64A, "ivoltage").len(), 2);
+
+        assert_eq!(explain_jed_fuse(XC2Device::XC2C32A, 0).unwrap().to_string(), "FB1 ZIA row 0 bit 0");
+        assert_eq!(explain_jed_fuse(XC2Device::XC2C32A, total_logical_fuse_count(XC2Device::XC2C32A)), None);
+        assert_eq!(explain_crbit_fuse(XC2Device::XC2C32A, done1_fuse_coord(XC2Device::XC2C32A)).unwrap().owner,
+            XC2FuseOwner::Done1);
+    }
+}
@@ -202,70 +202,35 @@ pub fn mc_block_loc(device: XC2Device, fb: u32) -> (usize, usize, bool) {
     }
 }
 
-pub fn gck_fuse_coords(device: XC2Device) -> ((usize, usize), (usize, usize), (usize, usize)) {
+// done1; this is programmed to 0 to mark the array as valid
+pub fn done1_fuse_coord(device: XC2Device) -> (usize, usize) {
     match device {
-        XC2Device::XC2C32 | XC2Device::XC2C32A => ((126, 23), (127, 23), (128, 23)),
-        XC2Device::XC2C64 | XC2Device::XC2C64A => ((133, 23), (134, 23), (135, 23)),
-        XC2Device::XC2C128 => ((365, 67), (366, 67), (367, 67)),
-        XC2Device::XC2C256 => ((519, 23), (520, 23), (521, 23)),
-        XC2Device::XC2C384 => ((467, 102), (468, 102), (469, 102)),
-        XC2Device::XC2C512 => ((979, 147), (980, 147), (981, 147)),
+        XC2Device::XC2C32 | XC2Device::XC2C32A => (9, 48),
+        XC2Device::XC2C64 | XC2Device::XC2C64A => (8, 96),
+        XC2Device::XC2C128 => (9, 80),
+        XC2Device::XC2C256 => (9, 96),
+        XC2Device::XC2C384 => (9, 120),
+        XC2Device::XC2C512 => (9, 160),
     }
 }
 
-// (enable, invert)
-pub fn gsr_fuse_coords(device: XC2Device) -> ((usize, usize), (usize, usize)) {
+// (sec0, sec1); these live in the done row immediately before done1. The row assignment (second-to-last row for
+// SEC/done, last row for USERCODE) follows the comment at the end of hdl/xc2c-model/XC2CBitstream.v. The column
+// positions have not been checked against a vendor .crbit or a readback of a secured part and are our best guess.
+pub fn security_fuse_coords(device: XC2Device) -> ((usize, usize), (usize, usize)) {
     match device {
-        XC2Device::XC2C32 | XC2Device::XC2C32A => ((130, 23), (129, 23)),
-        XC2Device::XC2C64 | XC2Device::XC2C64A => ((136, 73), (135, 73)),
-        XC2Device::XC2C128 => ((2, 67), (1, 67)),
-        XC2Device::XC2C256 => ((179, 23), (178, 23)),
-        XC2Device::XC2C384 => ((2, 97), (1, 97)),
-        XC2Device::XC2C512 => ((2, 27), (1, 27)),
+        XC2Device::XC2C32 | XC2Device::XC2C32A => ((7, 48), (8, 48)),
+        XC2Device::XC2C64 | XC2Device::XC2C64A => ((6, 96), (7, 96)),
+        XC2Device::XC2C128 => ((7, 80), (8, 80)),
+        XC2Device::XC2C256 => ((7, 96), (8, 96)),
+        XC2Device::XC2C384 => ((7, 120), (8, 120)),
+        XC2Device::XC2C512 => ((7, 160), (8, 160)),
     }
 }
 
-// (enable, invert)
-pub fn gts_fuse_coords(device: XC2Device) ->
-    (((usize, usize), (usize, usize)), ((usize, usize), (usize, usize)),
-     ((usize, usize), (usize, usize)), ((usize, usize), (usize, usize))) {
-
-    match device {
-        XC2Device::XC2C32 | XC2Device::XC2C32A =>
-            (((127, 24), (126, 24)), ((129, 24), (128, 24)), ((127, 25), (126, 25)), ((129, 25), (128, 25))),
-        XC2Device::XC2C64 | XC2Device::XC2C64A =>
-            (((134, 24), (133, 24)), ((136, 24), (135, 24)), ((138, 73), (137, 73)), ((138, 24), (137, 24))),
-        XC2Device::XC2C128 =>
-            (((5, 27), (4, 27)), ((7, 27), (6, 27)), ((5, 67), (4, 67)), ((7, 67), (6, 67))),
-        XC2Device::XC2C256 =>
-            (((182, 23), (181, 23)), ((177, 24), (176, 24)), ((179, 24), (178, 24)), ((182, 24), (181, 24))),
-        XC2Device::XC2C384 =>
-            (((463, 107), (463, 102)), ((464, 107), (464, 102)), ((465, 107), (465, 102)), ((466, 107), (466, 102))),
-        XC2Device::XC2C512 =>
-            (((4, 27), (3, 27)), ((481, 27), (480, 27)), ((6, 27), (5, 27)), ((8, 27), (7, 27))),
-    }
-}
-
-pub fn global_term_fuse_coord(device: XC2Device) -> (usize, usize) {
-    match device {
-        XC2Device::XC2C32 | XC2Device::XC2C32A => (131, 23),
-        XC2Device::XC2C64 | XC2Device::XC2C64A => (136, 23),
-        XC2Device::XC2C128 => (370, 67),
-        XC2Device::XC2C256 => (517, 23),
-        XC2Device::XC2C384 => (931, 17),
-        XC2Device::XC2C512 => (983, 147),
-    }
-}
-
-// enable, div, div, div, delay
-pub fn clock_div_fuse_coord(device: XC2Device)
-    -> ((usize, usize), (usize, usize), (usize, usize), (usize, usize), (usize, usize)) {
-
-    match device {
-        XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A => unreachable!(),
-        XC2Device::XC2C128 => ((364, 67), (363, 67), (362, 67), (361, 67), (360, 67)),
-        XC2Device::XC2C256 => ((519, 24), (518, 24), (517, 24), (516, 24), (515, 24)),
-        XC2Device::XC2C384 => ((471, 107), (470, 107), (469, 107), (468, 107), (467, 107)),
-        XC2Device::XC2C512 => ((978, 147), (977, 147), (976, 147), (975, 147), (974, 147)),
-    }
+// USERCODE bit `bit` (0 is the LSB); these live in the last row of the array (see the note on security_fuse_coords
+// above, the same caveat about the column positions applies)
+pub fn usercode_fuse_coord(device: XC2Device, bit: usize) -> (usize, usize) {
+    let (_, h) = fuse_array_dims(device);
+    (10 + bit, h - 1)
 }
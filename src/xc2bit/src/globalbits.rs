@@ -28,32 +28,117 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use std::io;
 use std::io::Write;
 
+use jedec::*;
+
 use *;
-use fusemap_logical::{gck_fuse_idx, gsr_fuse_idx, gts_fuse_idx, global_term_fuse_idx, clock_div_fuse_idx};
-use fusemap_physical::{gck_fuse_coords, gsr_fuse_coords, gts_fuse_coords, global_term_fuse_coord,
-                       clock_div_fuse_coord};
+use util::{HumanReadableReader};
 
 /// Represents the configuration of the global nets. Coolrunner-II parts have various global control signals that have
 /// dedicated low-skew paths.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed32 pub abs"]
+#[bittwiddler = "jed64 pub abs"]
+#[bittwiddler = "jed128 pub abs"]
+#[bittwiddler = "jed256 pub abs"]
+#[bittwiddler = "jed384 pub abs"]
+#[bittwiddler = "jed512 pub abs"]
+#[bittwiddler = "crbit32 pub abs"]
+#[bittwiddler = "crbit64 pub abs"]
+#[bittwiddler = "crbit128 pub abs"]
+#[bittwiddler = "crbit256 pub abs"]
+#[bittwiddler = "crbit384 pub abs"]
+#[bittwiddler = "crbit512 pub abs"]
 pub struct XC2GlobalNets {
     /// Controls whether the three global clock nets are enabled or not
+    #[bittwiddler_field = "jed32 12256 12257 12258"]
+    #[bittwiddler_field = "jed64 25792 25793 25794"]
+    #[bittwiddler_field = "jed128 55316 55317 55318"]
+    #[bittwiddler_field = "jed256 123224 123225 123226"]
+    #[bittwiddler_field = "jed384 209328 209329 209330"]
+    #[bittwiddler_field = "jed512 296374 296375 296376"]
+    #[bittwiddler_field = "crbit32 126|23 127|23 128|23"]
+    #[bittwiddler_field = "crbit64 133|23 134|23 135|23"]
+    #[bittwiddler_field = "crbit128 365|67 366|67 367|67"]
+    #[bittwiddler_field = "crbit256 519|23 520|23 521|23"]
+    #[bittwiddler_field = "crbit384 467|102 468|102 469|102"]
+    #[bittwiddler_field = "crbit512 979|147 980|147 981|147"]
     pub gck_enable: [bool; 3],
     /// Controls whether the global set/reset net is enabled or not
+    #[bittwiddler_field = "jed32 12260"]
+    #[bittwiddler_field = "jed64 25796"]
+    #[bittwiddler_field = "jed128 55325"]
+    #[bittwiddler_field = "jed256 123233"]
+    #[bittwiddler_field = "jed384 209337"]
+    #[bittwiddler_field = "jed512 296383"]
+    #[bittwiddler_field = "crbit32 130|23"]
+    #[bittwiddler_field = "crbit64 136|73"]
+    #[bittwiddler_field = "crbit128 2|67"]
+    #[bittwiddler_field = "crbit256 179|23"]
+    #[bittwiddler_field = "crbit384 2|97"]
+    #[bittwiddler_field = "crbit512 2|27"]
     pub gsr_enable: bool,
     /// Controls the polarity of the global set/reset signal
     ///
     /// `false` = active low, `true` = active high
+    #[bittwiddler_field = "jed32 12259"]
+    #[bittwiddler_field = "jed64 25795"]
+    #[bittwiddler_field = "jed128 55324"]
+    #[bittwiddler_field = "jed256 123232"]
+    #[bittwiddler_field = "jed384 209336"]
+    #[bittwiddler_field = "jed512 296382"]
+    #[bittwiddler_field = "crbit32 129|23"]
+    #[bittwiddler_field = "crbit64 135|73"]
+    #[bittwiddler_field = "crbit128 1|67"]
+    #[bittwiddler_field = "crbit256 178|23"]
+    #[bittwiddler_field = "crbit384 1|97"]
+    #[bittwiddler_field = "crbit512 1|27"]
     pub gsr_invert: bool,
     /// Controls whether the four global tristate nets are enabled or not
+    #[bittwiddler_field = "jed32 !12262 !12264 !12266 !12268"]
+    #[bittwiddler_field = "jed64 !25798 !25800 !25802 !25804"]
+    #[bittwiddler_field = "jed128 !55327 !55329 !55331 !55333"]
+    #[bittwiddler_field = "jed256 !123235 !123237 !123239 !123241"]
+    #[bittwiddler_field = "jed384 !209339 !209341 !209343 !209345"]
+    #[bittwiddler_field = "jed512 !296385 !296387 !296389 !296391"]
+    #[bittwiddler_field = "crbit32 !127|24 !129|24 !127|25 !129|25"]
+    #[bittwiddler_field = "crbit64 !134|24 !136|24 !138|73 !138|24"]
+    #[bittwiddler_field = "crbit128 !5|27 !7|27 !5|67 !7|67"]
+    #[bittwiddler_field = "crbit256 !182|23 !177|24 !179|24 !182|24"]
+    #[bittwiddler_field = "crbit384 !463|107 !464|107 !465|107 !466|107"]
+    #[bittwiddler_field = "crbit512 !4|27 !481|27 !6|27 !8|27"]
     pub gts_enable: [bool; 4],
     /// Controls the polarity of the global tristate signal
     ///
     /// `false` = used as T, `true` = used as !T
+    #[bittwiddler_field = "jed32 12261 12263 12265 12267"]
+    #[bittwiddler_field = "jed64 25797 25799 25801 25803"]
+    #[bittwiddler_field = "jed128 55326 55328 55330 55332"]
+    #[bittwiddler_field = "jed256 123234 123236 123238 123240"]
+    #[bittwiddler_field = "jed384 209338 209340 209342 209344"]
+    #[bittwiddler_field = "jed512 296384 296386 296388 296390"]
+    #[bittwiddler_field = "crbit32 126|24 128|24 126|25 128|25"]
+    #[bittwiddler_field = "crbit64 133|24 135|24 137|73 137|24"]
+    #[bittwiddler_field = "crbit128 4|27 6|27 4|67 6|67"]
+    #[bittwiddler_field = "crbit256 181|23 176|24 178|24 181|24"]
+    #[bittwiddler_field = "crbit384 463|102 464|102 465|102 466|102"]
+    #[bittwiddler_field = "crbit512 3|27 480|27 5|27 7|27"]
     pub gts_invert: [bool; 4],
     /// Controls the mode of the global termination
     ///
     /// `false` = keeper, `true` = pull-up
+    #[bittwiddler_field = "jed32 12269"]
+    #[bittwiddler_field = "jed64 25805"]
+    #[bittwiddler_field = "jed128 55334"]
+    #[bittwiddler_field = "jed256 123242"]
+    #[bittwiddler_field = "jed384 209346"]
+    #[bittwiddler_field = "jed512 296392"]
+    #[bittwiddler_field = "crbit32 131|23"]
+    #[bittwiddler_field = "crbit64 136|23"]
+    #[bittwiddler_field = "crbit128 370|67"]
+    #[bittwiddler_field = "crbit256 517|23"]
+    #[bittwiddler_field = "crbit384 931|17"]
+    #[bittwiddler_field = "crbit512 983|147"]
     pub global_pu: bool,
 }
 
@@ -101,92 +186,95 @@ impl XC2GlobalNets {
         Ok(())
     }
 
+    /// Read back the global net configuration from the output of `dump_human_readable`.
+    pub fn from_human_readable(reader: &mut HumanReadableReader) -> Result<Self, XC2BitError> {
+        reader.expect("")?;
+        let mut gck_enable = [false; 3];
+        for (i, gck) in gck_enable.iter_mut().enumerate() {
+            *gck = reader.flag(&format!("GCK{} ", i), "enabled", "disabled")?;
+        }
+
+        let gsr = reader.value("GSR ")?;
+        let (gsr_enable, gsr_invert) = match gsr {
+            "enabled, active high" => (true, true),
+            "enabled, active low" => (true, false),
+            "disabled, active high" => (false, true),
+            "disabled, active low" => (false, false),
+            _ => return Err(reader.err()),
+        };
+
+        let mut gts_enable = [false; 4];
+        let mut gts_invert = [false; 4];
+        for i in 0..4 {
+            let gts = reader.value(&format!("GTS{} ", i))?;
+            let (enable, invert) = match gts {
+                "enabled, acts as !T" => (true, true),
+                "enabled, acts as T" => (true, false),
+                "disabled, acts as !T" => (false, true),
+                "disabled, acts as T" => (false, false),
+                _ => return Err(reader.err()),
+            };
+            gts_enable[i] = enable;
+            gts_invert[i] = invert;
+        }
+
+        let global_pu = reader.flag("global termination is ", "pull-up", "bus hold")?;
+
+        Ok(XC2GlobalNets {
+            gck_enable,
+            gsr_enable,
+            gsr_invert,
+            gts_enable,
+            gts_invert,
+            global_pu,
+        })
+    }
+
     /// Write the crbit representation of the global net settings to the given `fuse_array`.
     pub fn to_crbit(&self, device: XC2Device, fuse_array: &mut FuseArray) {
-        let ((gck0x, gck0y), (gck1x, gck1y), (gck2x, gck2y)) = gck_fuse_coords(device);
-        fuse_array.set(gck0x, gck0y, self.gck_enable[0]);
-        fuse_array.set(gck1x, gck1y, self.gck_enable[1]);
-        fuse_array.set(gck2x, gck2y, self.gck_enable[2]);
-
-        let ((gsren_x, gsren_y), (gsrinv_x, gsrinv_y)) = gsr_fuse_coords(device);
-        fuse_array.set(gsren_x, gsren_y, self.gsr_enable);
-        fuse_array.set(gsrinv_x, gsrinv_y, self.gsr_invert);
-
-        let (((gts0en_x, gts0en_y), (gts0inv_x, gts0inv_y)), ((gts1en_x, gts1en_y), (gts1inv_x, gts1inv_y)),
-             ((gts2en_x, gts2en_y), (gts2inv_x, gts2inv_y)), ((gts3en_x, gts3en_y), (gts3inv_x, gts3inv_y))) =
-                gts_fuse_coords(device);
-        fuse_array.set(gts0en_x, gts0en_y, !self.gts_enable[0]);
-        fuse_array.set(gts0inv_x, gts0inv_y, self.gts_invert[0]);
-        fuse_array.set(gts1en_x, gts1en_y, !self.gts_enable[1]);
-        fuse_array.set(gts1inv_x, gts1inv_y, self.gts_invert[1]);
-        fuse_array.set(gts2en_x, gts2en_y, !self.gts_enable[2]);
-        fuse_array.set(gts2inv_x, gts2inv_y, self.gts_invert[2]);
-        fuse_array.set(gts3en_x, gts3en_y, !self.gts_enable[3]);
-        fuse_array.set(gts3inv_x, gts3inv_y, self.gts_invert[3]);
-
-        let (term_x, term_y) = global_term_fuse_coord(device);
-        fuse_array.set(term_x, term_y, self.global_pu);
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A => self.encode_crbit32(fuse_array),
+            XC2Device::XC2C64 | XC2Device::XC2C64A => self.encode_crbit64(fuse_array),
+            XC2Device::XC2C128 => self.encode_crbit128(fuse_array),
+            XC2Device::XC2C256 => self.encode_crbit256(fuse_array),
+            XC2Device::XC2C384 => self.encode_crbit384(fuse_array),
+            XC2Device::XC2C512 => self.encode_crbit512(fuse_array),
+        }
+    }
+
+    /// Internal function to write the global nets
+    pub fn to_jed(&self, device: XC2Device, jed: &mut JEDECFile) {
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A => self.encode_jed32(&mut jed.f),
+            XC2Device::XC2C64 | XC2Device::XC2C64A => self.encode_jed64(&mut jed.f),
+            XC2Device::XC2C128 => self.encode_jed128(&mut jed.f),
+            XC2Device::XC2C256 => self.encode_jed256(&mut jed.f),
+            XC2Device::XC2C384 => self.encode_jed384(&mut jed.f),
+            XC2Device::XC2C512 => self.encode_jed512(&mut jed.f),
+        }
     }
 
     /// Internal function to read the global nets
-    pub fn from_jed(device: XC2Device, fuses: &[bool]) -> Self {
-        XC2GlobalNets {
-            gck_enable: [
-                fuses[gck_fuse_idx(device) + 0],
-                fuses[gck_fuse_idx(device) + 1],
-                fuses[gck_fuse_idx(device) + 2],
-            ],
-            gsr_enable: fuses[gsr_fuse_idx(device) + 1],
-            gsr_invert: fuses[gsr_fuse_idx(device) + 0],
-            gts_enable: [
-                !fuses[gts_fuse_idx(device) + 1],
-                !fuses[gts_fuse_idx(device) + 3],
-                !fuses[gts_fuse_idx(device) + 5],
-                !fuses[gts_fuse_idx(device) + 7],
-            ],
-            gts_invert: [
-                fuses[gts_fuse_idx(device) + 0],
-                fuses[gts_fuse_idx(device) + 2],
-                fuses[gts_fuse_idx(device) + 4],
-                fuses[gts_fuse_idx(device) + 6],
-            ],
-            global_pu: fuses[global_term_fuse_idx(device)],
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(device: XC2Device, fuses: &F) -> Self {
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A => Self::decode_jed32(fuses),
+            XC2Device::XC2C64 | XC2Device::XC2C64A => Self::decode_jed64(fuses),
+            XC2Device::XC2C128 => Self::decode_jed128(fuses),
+            XC2Device::XC2C256 => Self::decode_jed256(fuses),
+            XC2Device::XC2C384 => Self::decode_jed384(fuses),
+            XC2Device::XC2C512 => Self::decode_jed512(fuses),
         }
     }
 
     /// Internal function to read the global nets
     pub fn from_crbit(device: XC2Device, fuse_array: &FuseArray) -> Self {
-        let ((gck0x, gck0y), (gck1x, gck1y), (gck2x, gck2y)) = gck_fuse_coords(device);
-
-        let ((gsren_x, gsren_y), (gsrinv_x, gsrinv_y)) = gsr_fuse_coords(device);
-
-        let (((gts0en_x, gts0en_y), (gts0inv_x, gts0inv_y)), ((gts1en_x, gts1en_y), (gts1inv_x, gts1inv_y)),
-             ((gts2en_x, gts2en_y), (gts2inv_x, gts2inv_y)), ((gts3en_x, gts3en_y), (gts3inv_x, gts3inv_y))) =
-                gts_fuse_coords(device);
-
-        let (term_x, term_y) = global_term_fuse_coord(device);
-
-        XC2GlobalNets {
-            gck_enable: [
-                fuse_array.get(gck0x, gck0y),
-                fuse_array.get(gck1x, gck1y),
-                fuse_array.get(gck2x, gck2y),
-            ],
-            gsr_enable: fuse_array.get(gsren_x, gsren_y),
-            gsr_invert: fuse_array.get(gsrinv_x, gsrinv_y),
-            gts_enable: [
-                !fuse_array.get(gts0en_x, gts0en_y),
-                !fuse_array.get(gts1en_x, gts1en_y),
-                !fuse_array.get(gts2en_x, gts2en_y),
-                !fuse_array.get(gts3en_x, gts3en_y),
-            ],
-            gts_invert: [
-                fuse_array.get(gts0inv_x, gts0inv_y),
-                fuse_array.get(gts1inv_x, gts1inv_y),
-                fuse_array.get(gts2inv_x, gts2inv_y),
-                fuse_array.get(gts3inv_x, gts3inv_y),
-            ],
-            global_pu: fuse_array.get(term_x, term_y),
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A => Self::decode_crbit32(fuse_array),
+            XC2Device::XC2C64 | XC2Device::XC2C64A => Self::decode_crbit64(fuse_array),
+            XC2Device::XC2C128 => Self::decode_crbit128(fuse_array),
+            XC2Device::XC2C256 => Self::decode_crbit256(fuse_array),
+            XC2Device::XC2C384 => Self::decode_crbit384(fuse_array),
+            XC2Device::XC2C512 => Self::decode_crbit512(fuse_array),
         }
     }
 }
@@ -213,15 +301,64 @@ pub enum XC2ClockDivRatio {
     Div16,
 }
 
+impl XC2ClockDivRatio {
+    /// Returns the number that the input clock is divided by
+    pub fn ratio(&self) -> u32 {
+        match *self {
+            XC2ClockDivRatio::Div2 => 2,
+            XC2ClockDivRatio::Div4 => 4,
+            XC2ClockDivRatio::Div6 => 6,
+            XC2ClockDivRatio::Div8 => 8,
+            XC2ClockDivRatio::Div10 => 10,
+            XC2ClockDivRatio::Div12 => 12,
+            XC2ClockDivRatio::Div14 => 14,
+            XC2ClockDivRatio::Div16 => 16,
+        }
+    }
+}
+
 /// Represents the configuration of the programmable clock divider in devices with 128 macrocells or more. This is
 /// hard-wired onto the GCK2 clock pin.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed128 pub abs"]
+#[bittwiddler = "jed256 pub abs"]
+#[bittwiddler = "jed384 pub abs"]
+#[bittwiddler = "jed512 pub abs"]
+#[bittwiddler = "crbit128 pub abs"]
+#[bittwiddler = "crbit256 pub abs"]
+#[bittwiddler = "crbit384 pub abs"]
+#[bittwiddler = "crbit512 pub abs"]
 pub struct XC2ClockDiv {
     /// Ratio that input clock is divided by
+    #[bittwiddler_field = "jed128 55320 55321 55322"]
+    #[bittwiddler_field = "jed256 123228 123229 123230"]
+    #[bittwiddler_field = "jed384 209332 209333 209334"]
+    #[bittwiddler_field = "jed512 296378 296379 296380"]
+    #[bittwiddler_field = "crbit128 363|67 362|67 361|67"]
+    #[bittwiddler_field = "crbit256 518|24 517|24 516|24"]
+    #[bittwiddler_field = "crbit384 470|107 469|107 468|107"]
+    #[bittwiddler_field = "crbit512 977|147 976|147 975|147"]
     pub div_ratio: XC2ClockDivRatio,
     /// Whether the "delay" feature is enabled
+    #[bittwiddler_field = "jed128 !55323"]
+    #[bittwiddler_field = "jed256 !123231"]
+    #[bittwiddler_field = "jed384 !209335"]
+    #[bittwiddler_field = "jed512 !296381"]
+    #[bittwiddler_field = "crbit128 !360|67"]
+    #[bittwiddler_field = "crbit256 !515|24"]
+    #[bittwiddler_field = "crbit384 !467|107"]
+    #[bittwiddler_field = "crbit512 !974|147"]
     pub delay: bool,
     /// Whether the clock divider is enabled (other settings are ignored if not)
+    #[bittwiddler_field = "jed128 !55319"]
+    #[bittwiddler_field = "jed256 !123227"]
+    #[bittwiddler_field = "jed384 !209331"]
+    #[bittwiddler_field = "jed512 !296377"]
+    #[bittwiddler_field = "crbit128 !364|67"]
+    #[bittwiddler_field = "crbit256 !519|24"]
+    #[bittwiddler_field = "crbit384 !471|107"]
+    #[bittwiddler_field = "crbit512 !978|147"]
     pub enabled: bool,
 }
 
@@ -232,19 +369,33 @@ impl XC2ClockDiv {
         write!(writer, "GCK2 clock divider {}\n", if self.enabled {"enabled"} else {"disabled"})?;
         write!(writer, "clock divider delay {}\n", if self.delay {"enabled"} else {"disabled"})?;
 
-        write!(writer, "clock division ratio: {}\n", match self.div_ratio {
-            XC2ClockDivRatio::Div2 => "2",
-            XC2ClockDivRatio::Div4 => "4",
-            XC2ClockDivRatio::Div6 => "6",
-            XC2ClockDivRatio::Div8 => "8",
-            XC2ClockDivRatio::Div10 => "10",
-            XC2ClockDivRatio::Div12 => "12",
-            XC2ClockDivRatio::Div14 => "14",
-            XC2ClockDivRatio::Div16 => "16",
-        })?;
+        write!(writer, "clock division ratio: {}\n", self.div_ratio.ratio())?;
 
         Ok(())
     }
+
+    /// Read back the clock divider configuration from the output of `dump_human_readable`.
+    pub fn from_human_readable(reader: &mut HumanReadableReader) -> Result<Self, XC2BitError> {
+        reader.expect("")?;
+        let enabled = reader.flag("GCK2 clock divider ", "enabled", "disabled")?;
+        let delay = reader.flag("clock divider delay ", "enabled", "disabled")?;
+        let div_ratio = reader.choice("clock division ratio: ", &[
+            ("2", XC2ClockDivRatio::Div2),
+            ("4", XC2ClockDivRatio::Div4),
+            ("6", XC2ClockDivRatio::Div6),
+            ("8", XC2ClockDivRatio::Div8),
+            ("10", XC2ClockDivRatio::Div10),
+            ("12", XC2ClockDivRatio::Div12),
+            ("14", XC2ClockDivRatio::Div14),
+            ("16", XC2ClockDivRatio::Div16),
+        ])?;
+
+        Ok(XC2ClockDiv {
+            div_ratio,
+            delay,
+            enabled,
+        })
+    }
 }
 
 impl Default for XC2ClockDiv {
@@ -259,30 +410,299 @@ impl Default for XC2ClockDiv {
 }
 
 impl XC2ClockDiv {
-    /// Internal function to read the clock divider configuration from a 128-macrocell part
-    pub fn from_jed(device: XC2Device, fuses: &[bool]) -> Self {
-        let clock_fuse_block = clock_div_fuse_idx(device);
+    /// Write the crbit representation of the clock divider settings to the given `fuse_array`.
+    pub fn to_crbit(&self, device: XC2Device, fuse_array: &mut FuseArray) {
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A => unreachable!(),
+            XC2Device::XC2C128 => self.encode_crbit128(fuse_array),
+            XC2Device::XC2C256 => self.encode_crbit256(fuse_array),
+            XC2Device::XC2C384 => self.encode_crbit384(fuse_array),
+            XC2Device::XC2C512 => self.encode_crbit512(fuse_array),
+        }
+    }
 
-        XC2ClockDiv {
-            delay: !fuses[clock_fuse_block + 4],
-            enabled: !fuses[clock_fuse_block],
-            div_ratio: XC2ClockDivRatio::decode((fuses[clock_fuse_block + 1], fuses[clock_fuse_block + 2], fuses[clock_fuse_block + 3])),
+    /// Internal function to write the clock divider configuration to a 128-macrocell part
+    pub fn to_jed(&self, device: XC2Device, jed: &mut JEDECFile) {
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A => unreachable!(),
+            XC2Device::XC2C128 => self.encode_jed128(&mut jed.f),
+            XC2Device::XC2C256 => self.encode_jed256(&mut jed.f),
+            XC2Device::XC2C384 => self.encode_jed384(&mut jed.f),
+            XC2Device::XC2C512 => self.encode_jed512(&mut jed.f),
+        }
+    }
+
+    /// Internal function to read the clock divider configuration from a 128-macrocell part
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(device: XC2Device, fuses: &F) -> Self {
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A => unreachable!(),
+            XC2Device::XC2C128 => Self::decode_jed128(fuses),
+            XC2Device::XC2C256 => Self::decode_jed256(fuses),
+            XC2Device::XC2C384 => Self::decode_jed384(fuses),
+            XC2Device::XC2C512 => Self::decode_jed512(fuses),
         }
     }
 
     /// Internal function to read the clock divider configuration from a 128-macrocell part
     pub fn from_crbit(device: XC2Device, fuse_array: &FuseArray) -> Self {
-        let ((clken_x, clken_y), (clkdiv0_x, clkdiv0_y), (clkdiv1_x, clkdiv1_y), (clkdiv2_x, clkdiv2_y),
-            (clkdelay_x, clkdelay_y)) = clock_div_fuse_coord(device);
+        match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A => unreachable!(),
+            XC2Device::XC2C128 => Self::decode_crbit128(fuse_array),
+            XC2Device::XC2C256 => Self::decode_crbit256(fuse_array),
+            XC2Device::XC2C384 => Self::decode_crbit384(fuse_array),
+            XC2Device::XC2C512 => Self::decode_crbit512(fuse_array),
+        }
+    }
+}
 
-        let div_ratio_bits = (fuse_array.get(clkdiv0_x, clkdiv0_y),
-                              fuse_array.get(clkdiv1_x, clkdiv1_y),
-                              fuse_array.get(clkdiv2_x, clkdiv2_y));
+/// Internal struct describing the fuses of the voltage settings of the XC2C32 and XC2C64. These settings are stored
+/// directly in `XC2BitstreamBits`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed32 pub abs"]
+#[bittwiddler = "jed64 pub abs"]
+#[bittwiddler = "crbit32 pub abs"]
+#[bittwiddler = "crbit64 pub abs"]
+pub struct XC2MiscBits32 {
+    #[bittwiddler_field = "jed32 !12271"]
+    #[bittwiddler_field = "jed64 !25807"]
+    #[bittwiddler_field = "crbit32 !130|25"]
+    #[bittwiddler_field = "crbit64 !138|23"]
+    pub ivoltage: bool,
+    #[bittwiddler_field = "jed32 !12270"]
+    #[bittwiddler_field = "jed64 !25806"]
+    #[bittwiddler_field = "crbit32 !130|24"]
+    #[bittwiddler_field = "crbit64 !137|23"]
+    pub ovoltage: bool,
+}
 
-        XC2ClockDiv {
-            delay: !fuse_array.get(clkdelay_x, clkdelay_y),
-            enabled: !fuse_array.get(clken_x, clken_y),
-            div_ratio: XC2ClockDivRatio::decode(div_ratio_bits),
+/// Internal struct describing the fuses of the voltage settings of the XC2C32A and XC2C64A. The legacy settings use
+/// the same fuses as the settings of the non-A parts.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed32 pub abs"]
+#[bittwiddler = "jed64 pub abs"]
+#[bittwiddler = "crbit32 pub abs"]
+#[bittwiddler = "crbit64 pub abs"]
+pub struct XC2MiscBits32A {
+    #[bittwiddler_field = "jed32 !12271"]
+    #[bittwiddler_field = "jed64 !25807"]
+    #[bittwiddler_field = "crbit32 !130|25"]
+    #[bittwiddler_field = "crbit64 !138|23"]
+    pub legacy_ivoltage: bool,
+    #[bittwiddler_field = "jed32 !12270"]
+    #[bittwiddler_field = "jed64 !25806"]
+    #[bittwiddler_field = "crbit32 !130|24"]
+    #[bittwiddler_field = "crbit64 !137|23"]
+    pub legacy_ovoltage: bool,
+    #[bittwiddler_field = "jed32 !12274 !12276"]
+    #[bittwiddler_field = "jed64 !25808 !25810"]
+    #[bittwiddler_field = "crbit32 !131|25 !133|25"]
+    #[bittwiddler_field = "crbit64 !139|23 !141|23"]
+    pub ivoltage: [bool; 2],
+    #[bittwiddler_field = "jed32 !12275 !12277"]
+    #[bittwiddler_field = "jed64 !25809 !25811"]
+    #[bittwiddler_field = "crbit32 !132|25 !134|25"]
+    #[bittwiddler_field = "crbit64 !140|23 !142|23"]
+    pub ovoltage: [bool; 2],
+}
+
+/// Internal struct describing the fuses of the voltage and miscellaneous settings of the XC2C128 and XC2C256
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed128 pub abs"]
+#[bittwiddler = "jed256 pub abs"]
+#[bittwiddler = "crbit128 pub abs"]
+#[bittwiddler = "crbit256 pub abs"]
+pub struct XC2MiscBits128 {
+    #[bittwiddler_field = "jed128 !55335"]
+    #[bittwiddler_field = "jed256 !123243"]
+    #[bittwiddler_field = "crbit128 !371|67"]
+    #[bittwiddler_field = "crbit256 !518|23"]
+    pub data_gate: bool,
+    #[bittwiddler_field = "jed128 !55340"]
+    #[bittwiddler_field = "jed256 !123248"]
+    #[bittwiddler_field = "crbit128 !10|67"]
+    #[bittwiddler_field = "crbit256 !177|23"]
+    pub use_vref: bool,
+    #[bittwiddler_field = "jed128 !55336 !55337"]
+    #[bittwiddler_field = "jed256 !123244 !123245"]
+    #[bittwiddler_field = "crbit128 !8|67 !368|67"]
+    #[bittwiddler_field = "crbit256 !175|23 !515|23"]
+    pub ivoltage: [bool; 2],
+    #[bittwiddler_field = "jed128 !55338 !55339"]
+    #[bittwiddler_field = "jed256 !123246 !123247"]
+    #[bittwiddler_field = "crbit128 !9|67 !369|67"]
+    #[bittwiddler_field = "crbit256 !176|23 !516|23"]
+    pub ovoltage: [bool; 2],
+}
+
+/// Internal struct describing the fuses of the voltage and miscellaneous settings of the XC2C384 and XC2C512. Unlike
+/// every other part, the bank voltage fuses of the XC2C512 are not inverted.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(BitTwiddler)]
+#[bittwiddler = "jed384 pub abs"]
+#[bittwiddler = "jed512 pub abs"]
+#[bittwiddler = "crbit384 pub abs"]
+#[bittwiddler = "crbit512 pub abs"]
+pub struct XC2MiscBits384 {
+    #[bittwiddler_field = "jed384 !209347"]
+    #[bittwiddler_field = "jed512 !296393"]
+    #[bittwiddler_field = "crbit384 !932|17"]
+    #[bittwiddler_field = "crbit512 !982|147"]
+    pub data_gate: bool,
+    #[bittwiddler_field = "jed384 !209356"]
+    #[bittwiddler_field = "jed512 !296402"]
+    #[bittwiddler_field = "crbit384 !3|17"]
+    #[bittwiddler_field = "crbit512 !1|147"]
+    pub use_vref: bool,
+    #[bittwiddler_field = "jed384 !209348 !209349 !209350 !209351"]
+    #[bittwiddler_field = "jed512 296394 296395 296396 296397"]
+    #[bittwiddler_field = "crbit384 !936|17 !1864|17 !1|17 !929|17"]
+    #[bittwiddler_field = "crbit512 992|147 1965|147 3|147 985|147"]
+    pub ivoltage: [bool; 4],
+    #[bittwiddler_field = "jed384 !209352 !209353 !209354 !209355"]
+    #[bittwiddler_field = "jed512 296398 296399 296400 296401"]
+    #[bittwiddler_field = "crbit384 !937|17 !1865|17 !2|17 !930|17"]
+    #[bittwiddler_field = "crbit512 991|147 1964|147 2|147 984|147"]
+    pub ovoltage: [bool; 4],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fusemap_logical::{gck_fuse_idx, gsr_fuse_idx, gts_fuse_idx, global_term_fuse_idx, clock_div_fuse_idx,
+                          total_logical_fuse_count};
+    use fusemap_physical::fuse_array_dims;
+
+    #[derive(PartialEq, Debug)]
+    #[derive(BitTwiddler)]
+    #[bittwiddler = "jed abs"]
+    struct IntFields {
+        #[bittwiddler_field = "jed 0 1 2"]
+        lsb: u8,
+        #[bittwiddler_field = "jed msb 3 4 !5"]
+        msb: u8,
+        #[bittwiddler_field = "jed msb 6 7 8 9"]
+        arr: [u8; 2],
+    }
+
+    #[test]
+    fn int_fields() {
+        let x = IntFields {
+            lsb: 0b110,
+            msb: 0b101,
+            arr: [0b10, 0b01],
+        };
+        let mut fuses = [false; 10];
+        x.encode_jed(&mut fuses[..]);
+        assert_eq!(fuses, [false, true, true, true, false, false, true, false, false, true]);
+
+        assert_eq!(IntFields::decode_jed(&fuses[..]), x);
+
+        let arr1 = IntFields::FUSE_LAYOUT.iter().find(|x| x.field == "arr[1]").unwrap();
+        assert_eq!(arr1.bits.iter().map(|x| x.fuse_idx(arr1, 0, false).unwrap()).collect::<Vec<_>>(), vec![8, 9]);
+    }
+
+    #[test]
+    fn global_nets_roundtrip() {
+        let nets = XC2GlobalNets {
+            gck_enable: [true, false, true],
+            gsr_enable: true,
+            gsr_invert: false,
+            gts_enable: [true, false, false, true],
+            gts_invert: [false, true, true, false],
+            global_pu: false,
+        };
+        let clock_div = XC2ClockDiv {
+            div_ratio: XC2ClockDivRatio::Div6,
+            delay: true,
+            enabled: true,
+        };
+
+        let mut text = Vec::new();
+        nets.dump_human_readable(&mut text).unwrap();
+        clock_div.dump_human_readable(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        let mut reader = HumanReadableReader::new(&text);
+        assert_eq!(XC2GlobalNets::from_human_readable(&mut reader), Ok(nets));
+        assert_eq!(XC2ClockDiv::from_human_readable(&mut reader), Ok(clock_div));
+        assert_eq!(reader.finish(), Ok(()));
+
+        for &device in &[XC2Device::XC2C32, XC2Device::XC2C32A, XC2Device::XC2C64, XC2Device::XC2C64A,
+            XC2Device::XC2C128, XC2Device::XC2C256, XC2Device::XC2C384, XC2Device::XC2C512] {
+
+            let mut jed = JEDECFile::new(total_logical_fuse_count(device));
+            nets.to_jed(device, &mut jed);
+            // GTS enable is active low
+            assert!(!jed.f.get(gts_fuse_idx(device) + 1));
+            assert!(jed.f.get(gts_fuse_idx(device) + 3));
+            assert_eq!(XC2GlobalNets::from_jed(device, &jed.f), nets);
+
+            let (w, h) = fuse_array_dims(device);
+            let mut fuse_array = FuseArray::from_dim(w, h);
+            nets.to_crbit(device, &mut fuse_array);
+            assert_eq!(XC2GlobalNets::from_crbit(device, &fuse_array), nets);
+
+            // Only the larger parts have a clock divider
+            if device.is_large_iob() {
+                clock_div.to_jed(device, &mut jed);
+                assert_eq!(XC2ClockDiv::from_jed(device, &jed.f), clock_div);
+                assert_eq!(XC2GlobalNets::from_jed(device, &jed.f), nets);
+
+                clock_div.to_crbit(device, &mut fuse_array);
+                assert_eq!(XC2ClockDiv::from_crbit(device, &fuse_array), clock_div);
+                assert_eq!(XC2GlobalNets::from_crbit(device, &fuse_array), nets);
+            }
+        }
+    }
+
+    // The fuse locations in the `BitTwiddler` attributes have to be literals, so check that they agree with the ones
+    // used elsewhere (e.g. for line breaks in .jed files)
+    #[test]
+    fn global_layout_matches_fusemap() {
+        for &(device, instance) in &[(XC2Device::XC2C32, "jed32"), (XC2Device::XC2C32A, "jed32"),
+            (XC2Device::XC2C64, "jed64"), (XC2Device::XC2C64A, "jed64"), (XC2Device::XC2C128, "jed128"),
+            (XC2Device::XC2C256, "jed256"), (XC2Device::XC2C384, "jed384"), (XC2Device::XC2C512, "jed512")] {
+
+            let fuses_of = |layout: &[FuseLayoutField], field_name: &str| {
+                let field = layout.iter().find(|x| x.instance == instance && x.field == field_name).unwrap();
+                field.bits.iter().map(|x| x.fuse_idx(field, 0, false).unwrap()).collect::<Vec<_>>()
+            };
+
+            let gck = gck_fuse_idx(device);
+            for i in 0..3 {
+                assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, &format!("gck_enable[{}]", i)), vec![gck + i]);
+            }
+            let gsr = gsr_fuse_idx(device);
+            assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, "gsr_invert"), vec![gsr]);
+            assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, "gsr_enable"), vec![gsr + 1]);
+            let gts = gts_fuse_idx(device);
+            for i in 0..4 {
+                assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, &format!("gts_invert[{}]", i)), vec![gts + 2 * i]);
+                assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, &format!("gts_enable[{}]", i)),
+                    vec![gts + 2 * i + 1]);
+            }
+            assert_eq!(fuses_of(XC2GlobalNets::FUSE_LAYOUT, "global_pu"), vec![global_term_fuse_idx(device)]);
+
+            // Every fuse from the first global clock fuse up to the termination fuse must belong to exactly one field
+            let expected_fuses = (gck..global_term_fuse_idx(device) + 1).collect::<Vec<_>>();
+            let mut layout_fuses = XC2GlobalNets::FUSE_LAYOUT.iter().filter(|x| x.instance == instance)
+                .flat_map(|x| x.bits.iter().map(move |y| y.fuse_idx(x, 0, false).unwrap())).collect::<Vec<_>>();
+
+            if device.is_large_iob() {
+                let clock_div = clock_div_fuse_idx(device);
+                assert_eq!(fuses_of(XC2ClockDiv::FUSE_LAYOUT, "enabled"), vec![clock_div]);
+                assert_eq!(fuses_of(XC2ClockDiv::FUSE_LAYOUT, "div_ratio"),
+                    vec![clock_div + 1, clock_div + 2, clock_div + 3]);
+                assert_eq!(fuses_of(XC2ClockDiv::FUSE_LAYOUT, "delay"), vec![clock_div + 4]);
+
+                layout_fuses.extend(XC2ClockDiv::FUSE_LAYOUT.iter().filter(|x| x.instance == instance)
+                    .flat_map(|x| x.bits.iter().map(move |y| y.fuse_idx(x, 0, false).unwrap())));
+            }
+
+            layout_fuses.sort();
+            assert_eq!(layout_fuses, expected_fuses);
         }
     }
 }
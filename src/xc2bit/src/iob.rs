@@ -33,6 +33,7 @@ use jedec::*;
 use *;
 use fusemap_physical::{mc_block_loc};
 use mc::{MC_TO_ROW_MAP_LARGE};
+use util::{HumanReadableReader};
 use zia::{zia_get_row_width};
 
 /// Mux selection for the ZIA input from this I/O pin's input. The ZIA input can be chosen to come from either the
@@ -81,9 +82,9 @@ pub enum XC2IOBOBufMode {
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 #[derive(BitTwiddler)]
 // FIXME: Probably should not be pub
-#[bittwiddler = "jed_internal pub err=XC2BitError"]
-#[bittwiddler = "crbit32 mirror0 err=XC2BitError"]
-#[bittwiddler = "crbit64 mirror0 err=XC2BitError"]
+#[bittwiddler = "jed_internal pub err=XC2BitError width=27"]
+#[bittwiddler = "crbit32 mirror0 err=XC2BitError width=9x3"]
+#[bittwiddler = "crbit64 mirror0 err=XC2BitError width=9x3"]
 // #[bittwiddler = "crbit_internal"]
 pub struct XC2MCSmallIOB {
     /// Mux selection for the ZIA input for this pin
@@ -169,6 +170,31 @@ impl XC2MCSmallIOB {
         Ok(())
     }
 
+    /// Read back the settings for this pin from the output of `dump_human_readable`.
+    /// `my_idx` must be the index of this I/O pin in the internal numbering scheme.
+    pub fn from_human_readable(device: XC2Device, my_idx: u32, reader: &mut HumanReadableReader)
+        -> Result<Self, XC2BitError> {
+
+        reader.expect("")?;
+        let (fb, mc) = iob_num_to_fb_mc_num(device, my_idx).unwrap();
+        reader.expect(&format!("I/O configuration for FB{}_{}", fb + 1, mc + 1))?;
+        let obuf_mode = read_obuf_mode(reader)?;
+        let obuf_uses_ff = reader.flag("output comes from ", "FF", "XOR gate")?;
+        let slew_is_fast = reader.flag("slew rate: ", "fast", "slow")?;
+        let zia_mode = read_zia_mode(reader)?;
+        let schmitt_trigger = reader.flag("Schmitt trigger input: ", "yes", "no")?;
+        let termination_enabled = reader.flag("termination: ", "yes", "no")?;
+
+        Ok(XC2MCSmallIOB {
+            zia_mode,
+            schmitt_trigger,
+            obuf_uses_ff,
+            obuf_mode,
+            termination_enabled,
+            slew_is_fast,
+        })
+    }
+
     /// Write the crbit representation of the settings for this IO pin to the given `fuse_array`.
     /// `device` must be the device type this FB was extracted from.
     /// `iob` must be the index of this IO pin.
@@ -216,7 +242,7 @@ impl XC2MCSmallIOB {
     }
 
     /// Internal function that reads only the IO-related bits from the macrocell configuration
-    pub fn from_jed(fuses: &[bool], fuse_idx: usize) -> Result<Self, XC2BitError> {
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(fuses: &F, fuse_idx: usize) -> Result<Self, XC2BitError> {
         Self::decode_jed_internal(fuses, fuse_idx)
     }
 
@@ -351,6 +377,38 @@ impl XC2MCLargeIOB {
         Ok(())
     }
 
+    /// Read back the settings for this pin from the output of `dump_human_readable`.
+    /// `my_idx` must be the index of this I/O pin in the internal numbering scheme.
+    pub fn from_human_readable(device: XC2Device, my_idx: u32, reader: &mut HumanReadableReader)
+        -> Result<Self, XC2BitError> {
+
+        reader.expect("")?;
+        let (fb, mc) = iob_num_to_fb_mc_num(device, my_idx).unwrap();
+        reader.expect(&format!("I/O configuration for FB{}_{}", fb + 1, mc + 1))?;
+        let obuf_mode = read_obuf_mode(reader)?;
+        let ibuf_mode = reader.choice("input mode: ", &[
+            ("no VREF, no Schmitt trigger", XC2IOBIbufMode::NoVrefNoSt),
+            ("no VREF, Schmitt trigger", XC2IOBIbufMode::NoVrefSt),
+            ("uses VREF (HSTL/SSTL)", XC2IOBIbufMode::UsesVref),
+            ("is a VREF pin", XC2IOBIbufMode::IsVref),
+        ])?;
+        let obuf_uses_ff = reader.flag("output comes from ", "FF", "XOR gate")?;
+        let slew_is_fast = reader.flag("slew rate: ", "fast", "slow")?;
+        let zia_mode = read_zia_mode(reader)?;
+        let termination_enabled = reader.flag("termination: ", "yes", "no")?;
+        let uses_data_gate = reader.flag("DataGate used: ", "yes", "no")?;
+
+        Ok(XC2MCLargeIOB {
+            zia_mode,
+            ibuf_mode,
+            obuf_uses_ff,
+            obuf_mode,
+            termination_enabled,
+            slew_is_fast,
+            uses_data_gate,
+        })
+    }
+
     /// Write the crbit representation of the settings for this IO pin to the given `fuse_array`.
     /// `device` must be the device type this FB was extracted from.
     /// `iob` must be the index of this IO pin.
@@ -398,7 +456,7 @@ impl XC2MCLargeIOB {
     }
 
     /// Internal function that reads only the IO-related bits from the macrocell configuration
-    pub fn from_jed(fuses: &[bool], fuse_idx: usize) -> Result<Self, XC2BitError> {
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(fuses: &F, fuse_idx: usize) -> Result<Self, XC2BitError> {
         Self::decode_jed_internal(fuses, fuse_idx)
     }
 
@@ -444,6 +502,44 @@ impl XC2ExtraIBuf {
 
         Ok(())
     }
+
+    /// Read back the settings for this pin from the output of `dump_human_readable`.
+    pub fn from_human_readable(reader: &mut HumanReadableReader) -> Result<Self, XC2BitError> {
+        reader.expect("")?;
+        reader.expect("I/O configuration for input-only pin")?;
+        let schmitt_trigger = reader.flag("Schmitt trigger input: ", "yes", "no")?;
+        let termination_enabled = reader.flag("termination: ", "yes", "no")?;
+
+        Ok(XC2ExtraIBuf {
+            schmitt_trigger,
+            termination_enabled,
+        })
+    }
+}
+
+/// Internal function to read the output mode line shared by both I/O pin types
+fn read_obuf_mode(reader: &mut HumanReadableReader) -> Result<XC2IOBOBufMode, XC2BitError> {
+    reader.choice("output mode: ", &[
+        ("disabled", XC2IOBOBufMode::Disabled),
+        ("push-pull", XC2IOBOBufMode::PushPull),
+        ("open-drain", XC2IOBOBufMode::OpenDrain),
+        ("GTS0-controlled tri-state", XC2IOBOBufMode::TriStateGTS0),
+        ("GTS1-controlled tri-state", XC2IOBOBufMode::TriStateGTS1),
+        ("GTS2-controlled tri-state", XC2IOBOBufMode::TriStateGTS2),
+        ("GTS3-controlled tri-state", XC2IOBOBufMode::TriStateGTS3),
+        ("PTB-controlled tri-state", XC2IOBOBufMode::TriStatePTB),
+        ("CTE-controlled tri-state", XC2IOBOBufMode::TriStateCTE),
+        ("CGND", XC2IOBOBufMode::CGND),
+    ])
+}
+
+/// Internal function to read the ZIA mode line shared by both I/O pin types
+fn read_zia_mode(reader: &mut HumanReadableReader) -> Result<XC2IOBZIAMode, XC2BitError> {
+    reader.choice("ZIA driven from: ", &[
+        ("disabled", XC2IOBZIAMode::Disabled),
+        ("input pad", XC2IOBZIAMode::PAD),
+        ("register", XC2IOBZIAMode::REG),
+    ])
 }
 
 /// Function to map from the internal numbering scheme for I/O pins to a function block and macrocell number.
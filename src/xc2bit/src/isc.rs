@@ -0,0 +1,979 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains functions for generating JTAG in-system configuration (ISC) programming sequences and reading and writing
+//! them as SVF or XSVF files
+
+use std::cmp;
+use std::io;
+use std::io::Write;
+
+use *;
+use fusemap_physical::{fuse_array_dims, security_fuse_coords};
+
+// JTAG instructions, see the XC2C programmer qualification specification. The instruction register is 8 bits long.
+/// JTAG instruction that selects the IDCODE register
+pub const INST_IDCODE: u8           = 0x01;
+/// JTAG instruction that leaves in-system configuration mode
+pub const INST_ISC_DISABLE: u8      = 0xc0;
+/// JTAG instruction that enters in-system configuration mode
+pub const INST_ISC_ENABLE: u8       = 0xe8;
+/// JTAG instruction that writes one row of the configuration memory
+pub const INST_ISC_PROGRAM: u8      = 0xea;
+/// JTAG instruction that erases the entire configuration memory
+pub const INST_ISC_ERASE: u8        = 0xed;
+/// JTAG instruction that reads back one row of the configuration memory
+pub const INST_ISC_READ: u8         = 0xee;
+/// JTAG instruction that loads the configuration memory into the device
+pub const INST_ISC_INIT: u8         = 0xf0;
+/// JTAG instruction that selects the USERCODE register
+pub const INST_USERCODE: u8         = 0xfd;
+/// JTAG instruction that selects the BYPASS register
+pub const INST_BYPASS: u8           = 0xff;
+
+/// The length of the JTAG instruction register
+pub const IR_LENGTH: usize = 8;
+
+// Minimum times to wait in Run-Test/Idle after each ISC instruction, in microseconds
+const ISC_ENABLE_WAIT_US: u32       = 800;
+const ISC_ERASE_WAIT_US: u32        = 100000;
+const ISC_PROGRAM_WAIT_US: u32      = 10000;
+const ISC_INIT_WAIT_US: u32         = 20;
+const ISC_DISABLE_WAIT_US: u32      = 100;
+
+/// Returns the expected IDCODE of the given device and package, along with a mask of the bits that are fixed. The
+/// stepping bits can vary and are never checked. If the package code is not known, the package bits are not checked
+/// either.
+pub fn isc_idcode(device: XC2Device, package: XC2Package) -> (u32, u32) {
+    let macrocells = match device {
+        XC2Device::XC2C32 => 0x01,
+        XC2Device::XC2C32A => 0x21,
+        XC2Device::XC2C64 => 0x05,
+        XC2Device::XC2C64A => 0x25,
+        XC2Device::XC2C128 => 0x18,
+        XC2Device::XC2C256 => 0x14,
+        XC2Device::XC2C384 => 0x15,
+        XC2Device::XC2C512 => 0x17,
+    };
+
+    let package_code = match (device, package) {
+        (XC2Device::XC2C32, XC2Package::CP56) | (XC2Device::XC2C32A, XC2Package::CP56) => Some(3),
+        (XC2Device::XC2C32, XC2Package::VQ44) | (XC2Device::XC2C32A, XC2Package::VQ44) => Some(4),
+        (XC2Device::XC2C32A, XC2Package::QFG32) => Some(1),
+        (XC2Device::XC2C64, XC2Package::VQ44) | (XC2Device::XC2C64A, XC2Package::VQ44) => Some(6),
+        (XC2Device::XC2C64, XC2Package::CP56) | (XC2Device::XC2C64A, XC2Package::CP56) => Some(5),
+        (XC2Device::XC2C64, XC2Package::VQ100) | (XC2Device::XC2C64A, XC2Package::VQ100) => Some(4),
+        (XC2Device::XC2C64, XC2Package::CP132) | (XC2Device::XC2C64A, XC2Package::CP132) => Some(3),
+        (XC2Device::XC2C64A, XC2Package::QFG48) => Some(1),
+        (XC2Device::XC2C128, XC2Package::VQ100) | (XC2Device::XC2C256, XC2Package::VQ100) => Some(2),
+        (XC2Device::XC2C128, XC2Package::CP132) | (XC2Device::XC2C256, XC2Package::CP132) => Some(3),
+        (XC2Device::XC2C128, XC2Package::TQ144) | (XC2Device::XC2C256, XC2Package::TQ144) => Some(4),
+        (XC2Device::XC2C128, XC2Package::FT256) | (XC2Device::XC2C256, XC2Package::FT256) => Some(6),
+        (XC2Device::XC2C256, XC2Package::PQ208) => Some(5),
+        (XC2Device::XC2C384, XC2Package::TQ144) => Some(4),
+        (XC2Device::XC2C384, XC2Package::PQ208) => Some(5),
+        (XC2Device::XC2C384, XC2Package::FT256) => Some(7),
+        (XC2Device::XC2C384, XC2Package::FG324) => Some(2),
+        (XC2Device::XC2C512, XC2Package::PQ208) => Some(4),
+        (XC2Device::XC2C512, XC2Package::FT256) => Some(6),
+        (XC2Device::XC2C512, XC2Package::FG324) => Some(2),
+        _ => None,
+    };
+
+    // Family code in bits 27:22, always 1 in bit 15, and the Xilinx manufacturer code in bits 11:0
+    let idcode = (0b011011 << 22) | (macrocells << 16) | (1 << 15) | (package_code.unwrap_or(0) << 12) | 0x093;
+    let mask = if package_code.is_some() {
+        0x0fffffff
+    } else {
+        0x0fff8fff
+    };
+
+    (idcode, mask)
+}
+
+/// Returns the number of bits in a configuration memory row address for the given device
+pub fn isc_addr_bits(device: XC2Device) -> usize {
+    match device {
+        XC2Device::XC2C32 | XC2Device::XC2C32A => 6,
+        XC2Device::XC2C64 | XC2Device::XC2C64A => 7,
+        XC2Device::XC2C128 => 7,
+        XC2Device::XC2C256 => 7,
+        XC2Device::XC2C384 => 7,
+        XC2Device::XC2C512 => 8,
+    }
+}
+
+/// Returns the address that is shifted in to access the given row of the configuration memory. Rows are addressed
+/// using a Gray code.
+pub fn isc_row_to_addr(row: usize) -> u32 {
+    (row ^ (row >> 1)) as u32
+}
+
+/// Returns the configuration memory row that is accessed by the given address
+pub fn isc_addr_to_row(addr: u32) -> usize {
+    let mut row = addr;
+    let mut shift = addr >> 1;
+    while shift != 0 {
+        row ^= shift;
+        shift >>= 1;
+    }
+    row as usize
+}
+
+/// Returns the bits of `val` in the order they are shifted, with the MSB of the `len`-bit value shifted first. This
+/// is how row addresses are shifted in.
+fn bits_msb_first(val: u32, len: usize) -> Vec<bool> {
+    (0..len).rev().map(|i| val & (1 << i) != 0).collect()
+}
+
+/// Returns the bits of `val` in the order they are shifted, with the LSB of the `len`-bit value shifted first. This
+/// is how IDCODE and USERCODE are shifted out.
+fn bits_lsb_first(val: u32, len: usize) -> Vec<bool> {
+    (0..len).map(|i| val & (1 << i) != 0).collect()
+}
+
+/// One operation in a JTAG programming sequence. Bit vectors are stored in the order they are shifted, so element 0
+/// is the first bit shifted in or out. All operations end in the Run-Test/Idle state.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum XC2JTAGOp {
+    /// A comment explaining the following operations
+    Comment(String),
+    /// Reset the TAP by holding TMS high and then go to Run-Test/Idle
+    Reset,
+    /// Shift the given instruction into the instruction register
+    ShiftIR(u8),
+    /// Shift `tdi` into the selected data register. If `tdo` is given, the bits shifted out must match it in all the
+    /// positions where `mask` is true (or in all positions if there is no mask).
+    ShiftDR {
+        tdi: Vec<bool>,
+        tdo: Option<Vec<bool>>,
+        mask: Option<Vec<bool>>,
+    },
+    /// Stay in Run-Test/Idle for at least the given number of microseconds
+    Idle(u32),
+}
+
+/// Generates the sequence of JTAG operations that programs `fuse_array` into a device of the given type using the
+/// in-system configuration instructions. The device IDCODE is checked first, then the device is erased, every row
+/// other than the one containing the done and security bits is programmed and verified, and finally the done and
+/// security bits are programmed (and verified, if the security bits are not set).
+pub fn isc_program_sequence(device: XC2Device, package: XC2Package, fuse_array: &FuseArray) -> Vec<XC2JTAGOp> {
+    let (w, h) = fuse_array_dims(device);
+    let addr_bits = isc_addr_bits(device);
+    // The done and security bits are in the row before the last (USERCODE) row
+    let done_row = h - 2;
+    let ((sec0_x, sec0_y), (sec1_x, sec1_y)) = security_fuse_coords(device);
+    let secure = !fuse_array.get(sec0_x, sec0_y) || !fuse_array.get(sec1_x, sec1_y);
+
+    let row_bits = |row: usize| (0..w).map(|x| fuse_array.get(x, row)).collect::<Vec<_>>();
+
+    let program_row = |ops: &mut Vec<XC2JTAGOp>, row: usize| {
+        let mut tdi = row_bits(row);
+        tdi.extend(bits_msb_first(isc_row_to_addr(row), addr_bits));
+        ops.push(XC2JTAGOp::ShiftDR {tdi, tdo: None, mask: None});
+        ops.push(XC2JTAGOp::Idle(ISC_PROGRAM_WAIT_US));
+    };
+
+    // Reads are pipelined. The address of the next row to read is shifted in at the end of the register while the
+    // contents of the previous row are shifted out.
+    let verify_rows = |ops: &mut Vec<XC2JTAGOp>, rows: &[usize]| {
+        for i in 0..rows.len() + 1 {
+            let mut tdi = vec![false; w - addr_bits];
+            if i < rows.len() {
+                tdi.extend(bits_msb_first(isc_row_to_addr(rows[i]), addr_bits));
+            } else {
+                tdi.extend(vec![false; addr_bits]);
+            }
+            let tdo = if i > 0 {
+                Some(row_bits(rows[i - 1]))
+            } else {
+                None
+            };
+            ops.push(XC2JTAGOp::ShiftDR {tdi, tdo, mask: None});
+        }
+    };
+
+    let mut ops = Vec::new();
+
+    ops.push(XC2JTAGOp::Comment("Check IDCODE".to_owned()));
+    ops.push(XC2JTAGOp::Reset);
+    ops.push(XC2JTAGOp::ShiftIR(INST_IDCODE));
+    let (idcode, idcode_mask) = isc_idcode(device, package);
+    ops.push(XC2JTAGOp::ShiftDR {
+        tdi: vec![false; 32],
+        tdo: Some(bits_lsb_first(idcode, 32)),
+        mask: Some(bits_lsb_first(idcode_mask, 32)),
+    });
+
+    ops.push(XC2JTAGOp::Comment("Enter ISC mode".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_ENABLE));
+    ops.push(XC2JTAGOp::Idle(ISC_ENABLE_WAIT_US));
+
+    ops.push(XC2JTAGOp::Comment("Erase".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_ERASE));
+    ops.push(XC2JTAGOp::Idle(ISC_ERASE_WAIT_US));
+
+    let rows = (0..h).filter(|&row| row != done_row).collect::<Vec<_>>();
+
+    ops.push(XC2JTAGOp::Comment("Program".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_PROGRAM));
+    for &row in &rows {
+        program_row(&mut ops, row);
+    }
+
+    ops.push(XC2JTAGOp::Comment("Verify".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_READ));
+    verify_rows(&mut ops, &rows);
+
+    ops.push(XC2JTAGOp::Comment("Program done and security bits".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_PROGRAM));
+    program_row(&mut ops, done_row);
+
+    // Once the security bits are set, the configuration can no longer be read back
+    if !secure {
+        ops.push(XC2JTAGOp::Comment("Verify done bits".to_owned()));
+        ops.push(XC2JTAGOp::ShiftIR(INST_ISC_READ));
+        verify_rows(&mut ops, &[done_row]);
+    }
+
+    ops.push(XC2JTAGOp::Comment("Load configuration and leave ISC mode".to_owned()));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_INIT));
+    ops.push(XC2JTAGOp::Idle(ISC_INIT_WAIT_US));
+    ops.push(XC2JTAGOp::ShiftIR(INST_ISC_DISABLE));
+    ops.push(XC2JTAGOp::Idle(ISC_DISABLE_WAIT_US));
+    ops.push(XC2JTAGOp::ShiftIR(INST_BYPASS));
+    ops.push(XC2JTAGOp::Reset);
+
+    ops
+}
+
+/// Formats bits (in shift order) as a hex string the way SVF expects, with the last bit shifted as the MSB
+fn svf_hex(bits: &[bool]) -> String {
+    let mut ret = String::new();
+    for digit_i in (0..(bits.len() + 3) / 4).rev() {
+        let mut digit = 0;
+        for bit_i in 0..4 {
+            if digit_i * 4 + bit_i < bits.len() && bits[digit_i * 4 + bit_i] {
+                digit |= 1 << bit_i;
+            }
+        }
+        ret.push_str(&format!("{:x}", digit));
+    }
+    ret
+}
+
+/// Writes the given JTAG operations to `writer` as an SVF file
+pub fn write_svf<W: Write>(ops: &[XC2JTAGOp], mut writer: W) -> Result<(), io::Error> {
+    write!(writer, "TRST ABSENT;\n")?;
+    write!(writer, "ENDIR IDLE;\n")?;
+    write!(writer, "ENDDR IDLE;\n")?;
+    write!(writer, "HDR 0;\n")?;
+    write!(writer, "HIR 0;\n")?;
+    write!(writer, "TDR 0;\n")?;
+    write!(writer, "TIR 0;\n")?;
+
+    for op in ops {
+        match *op {
+            XC2JTAGOp::Comment(ref s) => {
+                write!(writer, "! {}\n", s)?;
+            },
+            XC2JTAGOp::Reset => {
+                write!(writer, "STATE RESET;\n")?;
+                write!(writer, "STATE IDLE;\n")?;
+            },
+            XC2JTAGOp::ShiftIR(inst) => {
+                write!(writer, "SIR {} TDI ({:02x});\n", IR_LENGTH, inst)?;
+            },
+            XC2JTAGOp::ShiftDR {ref tdi, ref tdo, ref mask} => {
+                write!(writer, "SDR {} TDI ({})", tdi.len(), svf_hex(tdi))?;
+                if let Some(ref tdo) = *tdo {
+                    write!(writer, " TDO ({})", svf_hex(tdo))?;
+                    // SVF compares all bits by default, so the mask has to be written whether there is one or not.
+                    // Otherwise, the mask from the previous SDR would be used.
+                    match *mask {
+                        Some(ref mask) => write!(writer, " MASK ({})", svf_hex(mask))?,
+                        None => write!(writer, " MASK ({})", svf_hex(&vec![true; tdo.len()]))?,
+                    }
+                }
+                write!(writer, ";\n")?;
+            },
+            XC2JTAGOp::Idle(us) => {
+                write!(writer, "RUNTEST {:.2E} SEC;\n", us as f64 / 1000000.0)?;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses an SVF hex string (without the parentheses) into `len` bits in shift order. Returns `None` if the string is
+/// not valid hex or has bits set past the end of the register.
+fn svf_parse_hex(s: &str, len: usize) -> Option<Vec<bool>> {
+    let mut ret = vec![false; len];
+    for (digit_i, c) in s.chars().rev().enumerate() {
+        let digit = c.to_digit(16)?;
+        for bit_i in 0..4 {
+            if digit & (1 << bit_i) != 0 {
+                if digit_i * 4 + bit_i >= len {
+                    return None;
+                }
+                ret[digit_i * 4 + bit_i] = true;
+            }
+        }
+    }
+    Some(ret)
+}
+
+/// Splits an SVF statement into tokens. Parenthesized hex strings (which can contain whitespace) become a single
+/// token starting with '('. Returns `None` if the parentheses are unbalanced.
+fn svf_tokenize(stmt: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut cur = String::new();
+    let mut in_parens = false;
+
+    for c in stmt.chars() {
+        if in_parens {
+            if c == ')' {
+                tokens.push(cur);
+                cur = String::new();
+                in_parens = false;
+            } else if c == '(' {
+                return None;
+            } else if !c.is_whitespace() {
+                cur.push(c);
+            }
+        } else if c == '(' {
+            if !cur.is_empty() {
+                tokens.push(cur);
+            }
+            cur = "(".to_owned();
+            in_parens = true;
+        } else if c == ')' {
+            return None;
+        } else if c.is_whitespace() {
+            if !cur.is_empty() {
+                tokens.push(cur);
+                cur = String::new();
+            }
+        } else {
+            cur.push(c.to_ascii_uppercase());
+        }
+    }
+
+    if in_parens {
+        return None;
+    }
+    if !cur.is_empty() {
+        tokens.push(cur);
+    }
+    Some(tokens)
+}
+
+/// State that SVF carries over from one shift to the next
+struct SVFParserState {
+    sdr_tdi: Option<Vec<bool>>,
+    sdr_mask: Option<Vec<bool>>,
+}
+
+fn parse_svf_statement(tokens: &[String], line: usize, state: &mut SVFParserState, ops: &mut Vec<XC2JTAGOp>)
+    -> Result<(), XC2JTAGError> {
+
+    let unsupported = || XC2JTAGError::UnsupportedSVFCommand(tokens.join(" "));
+    let args = &tokens[1..];
+
+    match tokens[0].as_ref() {
+        "TRST" => {
+            if args != ["ABSENT"] && args != ["OFF"] {
+                return Err(unsupported());
+            }
+        },
+        "ENDIR" | "ENDDR" => {
+            if args != ["IDLE"] {
+                return Err(unsupported());
+            }
+        },
+        "HDR" | "HIR" | "TDR" | "TIR" => {
+            if args != ["0"] {
+                return Err(unsupported());
+            }
+        },
+        "FREQUENCY" => {},
+        "STATE" => {
+            if args.is_empty() {
+                return Err(XC2JTAGError::SVFParseError(line));
+            }
+            // Every operation already ends in Run-Test/Idle, so only a trip through Test-Logic-Reset matters
+            for arg in args {
+                if arg != "RESET" && arg != "IDLE" {
+                    return Err(unsupported());
+                }
+            }
+            if args.iter().any(|arg| arg == "RESET") {
+                ops.push(XC2JTAGOp::Reset);
+            }
+        },
+        "SIR" | "SDR" => {
+            let len = args.first().and_then(|x| x.parse::<usize>().ok()).ok_or(XC2JTAGError::SVFParseError(line))?;
+            let mut tdi = None;
+            let mut tdo = None;
+            let mut mask = None;
+            for pair in args[1..].chunks(2) {
+                if pair.len() != 2 || !pair[1].starts_with('(') {
+                    return Err(XC2JTAGError::SVFParseError(line));
+                }
+                let bits = svf_parse_hex(&pair[1][1..], len).ok_or(XC2JTAGError::SVFParseError(line))?;
+                match pair[0].as_ref() {
+                    "TDI" => tdi = Some(bits),
+                    "TDO" => tdo = Some(bits),
+                    "MASK" => mask = Some(bits),
+                    // Only marks which TDI bits are don't-care
+                    "SMASK" => {},
+                    _ => return Err(XC2JTAGError::SVFParseError(line)),
+                }
+            }
+
+            if tokens[0] == "SIR" {
+                if len != IR_LENGTH || tdo.is_some() {
+                    return Err(unsupported());
+                }
+                let tdi = tdi.ok_or(XC2JTAGError::SVFParseError(line))?;
+                let inst = tdi.iter().enumerate().fold(0, |acc, (i, &x)| acc | ((x as u8) << i));
+                ops.push(XC2JTAGOp::ShiftIR(inst));
+            } else {
+                // TDI and MASK are remembered from the previous shift if it had the same length
+                let tdi = match tdi {
+                    Some(tdi) => tdi,
+                    None => match state.sdr_tdi {
+                        Some(ref old_tdi) if old_tdi.len() == len => old_tdi.clone(),
+                        _ => return Err(XC2JTAGError::SVFParseError(line)),
+                    },
+                };
+                let mask = match mask {
+                    Some(mask) => Some(mask),
+                    None => match state.sdr_mask {
+                        Some(ref old_mask) if old_mask.len() == len => Some(old_mask.clone()),
+                        _ => None,
+                    },
+                };
+                state.sdr_tdi = Some(tdi.clone());
+                state.sdr_mask = mask.clone();
+
+                let mask = if tdo.is_none() || mask.as_ref().map_or(false, |mask| mask.iter().all(|&x| x)) {
+                    None
+                } else {
+                    mask
+                };
+                ops.push(XC2JTAGOp::ShiftDR {tdi, tdo, mask});
+            }
+        },
+        "RUNTEST" => {
+            let mut args = args;
+            if args.first().map(|x| x.as_ref()) == Some("IDLE") {
+                args = &args[1..];
+            }
+
+            let mut us = 0;
+            let mut i = 0;
+            while i < args.len() {
+                match args.get(i + 1).map(|x| x.as_ref()) {
+                    Some("TCK") => {
+                        // Time is not modeled, so clock cycles are converted assuming a 1 MHz TCK
+                        let count = args[i].parse::<u32>().map_err(|_| XC2JTAGError::SVFParseError(line))?;
+                        us = cmp::max(us, count);
+                    },
+                    Some("SEC") => {
+                        let secs = args[i].parse::<f64>().map_err(|_| XC2JTAGError::SVFParseError(line))?;
+                        us = cmp::max(us, (secs * 1000000.0).round() as u32);
+                    },
+                    Some(_) if args[i] == "MAXIMUM" => {
+                        // The maximum time is always satisfied, so just skip over it
+                        i += 1;
+                    },
+                    Some("IDLE") if args[i] == "ENDSTATE" => {},
+                    _ => return Err(unsupported()),
+                }
+                i += 2;
+            }
+
+            ops.push(XC2JTAGOp::Idle(us));
+        },
+        _ => return Err(unsupported()),
+    }
+
+    Ok(())
+}
+
+/// Parses an SVF file into a sequence of JTAG operations. Only the subset of SVF needed for programming a single
+/// device that stays in Run-Test/Idle between shifts is supported. Comments on lines of their own are kept as
+/// `XC2JTAGOp::Comment`.
+pub fn parse_svf(svf: &str) -> Result<Vec<XC2JTAGOp>, XC2JTAGError> {
+    let mut ops = Vec::new();
+    let mut state = SVFParserState {
+        sdr_tdi: None,
+        sdr_mask: None,
+    };
+    let mut stmt = String::new();
+    let mut stmt_line = 0;
+
+    for (line_i, line) in svf.lines().enumerate() {
+        let (code, comment) = match (line.find('!'), line.find("//")) {
+            (Some(a), Some(b)) if b < a => (&line[..b], Some(&line[b + 2..])),
+            (Some(a), _) => (&line[..a], Some(&line[a + 1..])),
+            (None, Some(b)) => (&line[..b], Some(&line[b + 2..])),
+            (None, None) => (line, None),
+        };
+
+        for c in code.chars() {
+            if stmt.trim().is_empty() {
+                stmt_line = line_i + 1;
+            }
+            if c == ';' {
+                let tokens = svf_tokenize(&stmt).ok_or(XC2JTAGError::SVFParseError(stmt_line))?;
+                if tokens.is_empty() || tokens[0].starts_with('(') {
+                    return Err(XC2JTAGError::SVFParseError(stmt_line));
+                }
+                parse_svf_statement(&tokens, stmt_line, &mut state, &mut ops)?;
+                stmt.clear();
+            } else {
+                stmt.push(c);
+            }
+        }
+        stmt.push(' ');
+
+        if let Some(comment) = comment {
+            if stmt.trim().is_empty() {
+                ops.push(XC2JTAGOp::Comment(comment.trim().to_owned()));
+            }
+        }
+    }
+
+    if !stmt.trim().is_empty() {
+        return Err(XC2JTAGError::SVFParseError(stmt_line));
+    }
+
+    Ok(ops)
+}
+
+// XSVF commands, see Xilinx XAPP503
+const XCOMPLETE: u8      = 0x00;
+const XTDOMASK: u8       = 0x01;
+const XSIR: u8           = 0x02;
+const XRUNTEST: u8       = 0x04;
+const XREPEAT: u8        = 0x07;
+const XSDRSIZE: u8       = 0x08;
+const XSDRTDO: u8        = 0x09;
+const XSTATE: u8         = 0x12;
+const XENDIR: u8         = 0x13;
+const XENDDR: u8         = 0x14;
+const XCOMMENT: u8       = 0x16;
+const XWAIT: u8          = 0x17;
+
+// XSVF TAP state codes
+const XSTATE_RESET: u8   = 0x00;
+const XSTATE_IDLE: u8    = 0x01;
+
+/// Packs bits (in shift order) into bytes the way XSVF expects, with the last bit shifted as the MSB of the first byte
+fn xsvf_bytes(bits: &[bool]) -> Vec<u8> {
+    let nbytes = (bits.len() + 7) / 8;
+    let mut ret = vec![0u8; nbytes];
+    for (i, &bit) in bits.iter().enumerate() {
+        if bit {
+            ret[nbytes - 1 - i / 8] |= 1 << (i % 8);
+        }
+    }
+    ret
+}
+
+fn write_u32_be<W: Write>(writer: &mut W, val: u32) -> Result<(), io::Error> {
+    writer.write_all(&[(val >> 24) as u8, (val >> 16) as u8, (val >> 8) as u8, val as u8])
+}
+
+/// Writes the given JTAG operations to `writer` as an XSVF file
+pub fn write_xsvf<W: Write>(ops: &[XC2JTAGOp], mut writer: W) -> Result<(), io::Error> {
+    writer.write_all(&[XREPEAT, 0])?;
+    writer.write_all(&[XENDIR, 0])?;
+    writer.write_all(&[XENDDR, 0])?;
+
+    let mut cur_runtest = 0;
+    let mut cur_sdrsize = None;
+    let mut cur_tdomask = None;
+
+    writer.write_all(&[XRUNTEST])?;
+    write_u32_be(&mut writer, cur_runtest)?;
+
+    for (op_i, op) in ops.iter().enumerate() {
+        // In XSVF, the time to wait after a shift has to be set up before the shift
+        let wait_after = match ops.get(op_i + 1) {
+            Some(&XC2JTAGOp::Idle(us)) => us,
+            _ => 0,
+        };
+
+        match *op {
+            XC2JTAGOp::Comment(ref s) => {
+                writer.write_all(&[XCOMMENT])?;
+                writer.write_all(s.as_bytes())?;
+                writer.write_all(&[0])?;
+            },
+            XC2JTAGOp::Reset => {
+                writer.write_all(&[XSTATE, XSTATE_RESET])?;
+                writer.write_all(&[XSTATE, XSTATE_IDLE])?;
+            },
+            XC2JTAGOp::ShiftIR(inst) => {
+                if wait_after != cur_runtest {
+                    cur_runtest = wait_after;
+                    writer.write_all(&[XRUNTEST])?;
+                    write_u32_be(&mut writer, cur_runtest)?;
+                }
+
+                writer.write_all(&[XSIR, IR_LENGTH as u8, inst])?;
+            },
+            XC2JTAGOp::ShiftDR {ref tdi, ref tdo, ref mask} => {
+                if wait_after != cur_runtest {
+                    cur_runtest = wait_after;
+                    writer.write_all(&[XRUNTEST])?;
+                    write_u32_be(&mut writer, cur_runtest)?;
+                }
+
+                if cur_sdrsize != Some(tdi.len()) {
+                    cur_sdrsize = Some(tdi.len());
+                    writer.write_all(&[XSDRSIZE])?;
+                    write_u32_be(&mut writer, tdi.len() as u32)?;
+                }
+
+                // Every shift is done with XSDRTDO, and shifts that do not check anything use an all-zero mask
+                let this_mask = match (tdo, mask) {
+                    (&None, _) => vec![false; tdi.len()],
+                    (&Some(_), &None) => vec![true; tdi.len()],
+                    (&Some(_), &Some(ref mask)) => mask.clone(),
+                };
+                if cur_tdomask.as_ref() != Some(&this_mask) {
+                    writer.write_all(&[XTDOMASK])?;
+                    writer.write_all(&xsvf_bytes(&this_mask))?;
+                    cur_tdomask = Some(this_mask);
+                }
+
+                writer.write_all(&[XSDRTDO])?;
+                writer.write_all(&xsvf_bytes(tdi))?;
+                match *tdo {
+                    Some(ref tdo) => writer.write_all(&xsvf_bytes(tdo))?,
+                    None => writer.write_all(&xsvf_bytes(&vec![false; tdi.len()]))?,
+                }
+            },
+            XC2JTAGOp::Idle(us) => {
+                // Waits after a shift were already taken care of by XRUNTEST
+                let after_shift = match ops[..op_i].last() {
+                    Some(&XC2JTAGOp::ShiftIR(_)) | Some(&XC2JTAGOp::ShiftDR{..}) => true,
+                    _ => false,
+                };
+                if !after_shift {
+                    writer.write_all(&[XWAIT, XSTATE_IDLE, XSTATE_IDLE])?;
+                    write_u32_be(&mut writer, us)?;
+                }
+            },
+        }
+    }
+
+    writer.write_all(&[XCOMPLETE])?;
+
+    Ok(())
+}
+
+/// Unpacks bytes from an XSVF file into `len` bits in shift order
+fn xsvf_bits(bytes: &[u8], len: usize) -> Vec<bool> {
+    let nbytes = bytes.len();
+    (0..len).map(|i| bytes[nbytes - 1 - i / 8] & (1 << (i % 8)) != 0).collect()
+}
+
+/// Parses an XSVF file into a sequence of JTAG operations. Only the subset of XSVF needed for programming a device that
+/// stays in Run-Test/Idle between shifts is supported.
+pub fn parse_xsvf(xsvf: &[u8]) -> Result<Vec<XC2JTAGOp>, XC2JTAGError> {
+    let mut ops = Vec::new();
+    let mut pos = 0;
+    let mut runtest = 0;
+    let mut sdrsize = 0;
+    let mut tdomask = Vec::new();
+
+    loop {
+        let cmd_pos = pos;
+        let take = |pos: &mut usize, n: usize| {
+            if *pos + n > xsvf.len() {
+                return Err(XC2JTAGError::XSVFParseError(cmd_pos));
+            }
+            *pos += n;
+            Ok(&xsvf[*pos - n..*pos])
+        };
+        let read_u32 = |bytes: &[u8]| bytes.iter().fold(0, |acc, &x| (acc << 8) | (x as u32));
+
+        let opcode = take(&mut pos, 1)?[0];
+        match opcode {
+            XCOMPLETE => break,
+            XTDOMASK => {
+                tdomask = xsvf_bits(take(&mut pos, (sdrsize + 7) / 8)?, sdrsize);
+            },
+            XSIR => {
+                let len = take(&mut pos, 1)?[0] as usize;
+                let bits = xsvf_bits(take(&mut pos, (len + 7) / 8)?, len);
+                if len != IR_LENGTH {
+                    return Err(XC2JTAGError::UnsupportedXSVFCommand(opcode));
+                }
+                let inst = bits.iter().enumerate().fold(0, |acc, (i, &x)| acc | ((x as u8) << i));
+                ops.push(XC2JTAGOp::ShiftIR(inst));
+                if runtest > 0 {
+                    ops.push(XC2JTAGOp::Idle(runtest));
+                }
+            },
+            XRUNTEST => {
+                runtest = read_u32(take(&mut pos, 4)?);
+            },
+            XREPEAT => {
+                // Shifts are never retried
+                take(&mut pos, 1)?;
+            },
+            XSDRSIZE => {
+                sdrsize = read_u32(take(&mut pos, 4)?) as usize;
+            },
+            XSDRTDO => {
+                let tdi = xsvf_bits(take(&mut pos, (sdrsize + 7) / 8)?, sdrsize);
+                let tdo = xsvf_bits(take(&mut pos, (sdrsize + 7) / 8)?, sdrsize);
+                if tdomask.len() != sdrsize {
+                    return Err(XC2JTAGError::XSVFParseError(cmd_pos));
+                }
+
+                let (tdo, mask) = if tdomask.iter().all(|&x| !x) {
+                    (None, None)
+                } else if tdomask.iter().all(|&x| x) {
+                    (Some(tdo), None)
+                } else {
+                    (Some(tdo), Some(tdomask.clone()))
+                };
+                ops.push(XC2JTAGOp::ShiftDR {tdi, tdo, mask});
+                if runtest > 0 {
+                    ops.push(XC2JTAGOp::Idle(runtest));
+                }
+            },
+            XSTATE => {
+                // Every operation already ends in Run-Test/Idle, so only a trip through Test-Logic-Reset matters
+                match take(&mut pos, 1)?[0] {
+                    XSTATE_RESET => ops.push(XC2JTAGOp::Reset),
+                    XSTATE_IDLE => {},
+                    _ => return Err(XC2JTAGError::UnsupportedXSVFCommand(opcode)),
+                }
+            },
+            XENDIR | XENDDR => {
+                if take(&mut pos, 1)?[0] != 0 {
+                    return Err(XC2JTAGError::UnsupportedXSVFCommand(opcode));
+                }
+            },
+            XCOMMENT => {
+                let len = xsvf[pos..].iter().position(|&x| x == 0).ok_or(XC2JTAGError::XSVFParseError(cmd_pos))?;
+                let comment = String::from_utf8_lossy(take(&mut pos, len + 1)?[..len].as_ref()).into_owned();
+                ops.push(XC2JTAGOp::Comment(comment));
+            },
+            XWAIT => {
+                let states = take(&mut pos, 2)?;
+                if states[0] != XSTATE_IDLE || states[1] != XSTATE_IDLE {
+                    return Err(XC2JTAGError::UnsupportedXSVFCommand(opcode));
+                }
+                let us = read_u32(take(&mut pos, 4)?);
+                ops.push(XC2JTAGOp::Idle(us));
+            },
+            _ => return Err(XC2JTAGError::UnsupportedXSVFCommand(opcode)),
+        }
+    }
+
+    Ok(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idcode() {
+        assert_eq!(isc_idcode(XC2Device::XC2C32A, XC2Package::VQ44), (0x06e1c093, 0x0fffffff));
+        // Unknown package code
+        assert_eq!(isc_idcode(XC2Device::XC2C64A, XC2Package::PC44).1, 0x0fff8fff);
+    }
+
+    #[test]
+    fn row_addr() {
+        for row in 0..256 {
+            assert_eq!(isc_addr_to_row(isc_row_to_addr(row)), row);
+        }
+        assert_eq!(isc_row_to_addr(2), 3);
+        assert_eq!(bits_msb_first(isc_row_to_addr(2), 6), vec![false, false, false, false, true, true]);
+    }
+
+    // Minimal model of the ISC instructions that works on whole shifts, used to check the generated sequences. Returns
+    // the configuration memory contents at the end.
+    fn replay(device: XC2Device, package: XC2Package, ops: &[XC2JTAGOp]) -> FuseArray {
+        let (w, h) = fuse_array_dims(device);
+        let addr_bits = isc_addr_bits(device);
+        let mut mem = FuseArray::from_dim(w, h);
+        let mut ir = INST_IDCODE;
+        let mut read_row = None;
+        let mut erased = false;
+
+        let bits_to_u32 = |bits: &[bool]| bits.iter().rev().fold(0, |acc, &x| (acc << 1) | (x as u32));
+
+        for op in ops {
+            match *op {
+                XC2JTAGOp::Reset => ir = INST_IDCODE,
+                XC2JTAGOp::ShiftIR(inst) => {
+                    ir = inst;
+                    if ir == INST_ISC_ERASE {
+                        for y in 0..h {
+                            for x in 0..w {
+                                mem.set(x, y, true);
+                            }
+                        }
+                        erased = true;
+                    }
+                },
+                XC2JTAGOp::ShiftDR {ref tdi, ref tdo, ref mask} => {
+                    match ir {
+                        INST_IDCODE => {
+                            let (idcode, idcode_mask) = isc_idcode(device, package);
+                            assert_eq!(bits_to_u32(tdo.as_ref().unwrap()), idcode);
+                            assert_eq!(bits_to_u32(mask.as_ref().unwrap()), idcode_mask);
+                        },
+                        INST_ISC_PROGRAM => {
+                            assert!(erased);
+                            assert_eq!(tdi.len(), w + addr_bits);
+                            let addr = tdi[w..].iter().fold(0, |acc, &x| (acc << 1) | (x as u32));
+                            let row = isc_addr_to_row(addr);
+                            for (x, &bit) in tdi[..w].iter().enumerate() {
+                                mem.set(x, row, bit);
+                            }
+                        },
+                        INST_ISC_READ => {
+                            assert_eq!(tdi.len(), w);
+                            if let Some(ref tdo) = *tdo {
+                                let row = read_row.unwrap();
+                                for (x, &bit) in tdo.iter().enumerate() {
+                                    assert_eq!(bit, mem.get(x, row));
+                                }
+                            }
+                            let addr = tdi[w - addr_bits..].iter().fold(0, |acc, &x| (acc << 1) | (x as u32));
+                            read_row = Some(isc_addr_to_row(addr));
+                        },
+                        _ => panic!("unexpected DR shift with instruction {:02x}", ir),
+                    }
+                },
+                XC2JTAGOp::Comment(_) | XC2JTAGOp::Idle(_) => {},
+            }
+        }
+
+        assert_eq!(ir, INST_IDCODE);
+        mem
+    }
+
+    #[test]
+    fn program_sequence() {
+        for &(part, secure) in [("XC2C32A-4-VQ44", false), ("XC2C64A-5-VQ44", true), ("XC2C128-6-TQ144", false),
+            ("XC2C256-6-TQ144", false), ("XC2C384-7-TQ144", true), ("XC2C512-7-PQ208", false)].iter() {
+
+            let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap());
+            bitstream.secure = Some(secure);
+            bitstream.usercode = Some(0x12345678);
+            let device = bitstream.bits.device_type();
+
+            let ops = bitstream.to_isc_sequence();
+            let mem = replay(device, bitstream.package, &ops);
+            assert_eq!(mem.bits(), bitstream.to_crbit().bits());
+
+            // The done row must be programmed last
+            let (w, h) = fuse_array_dims(device);
+            let last_program = ops.iter().filter_map(|op| match *op {
+                XC2JTAGOp::ShiftDR {ref tdi, ..} if tdi.len() > w => Some(tdi),
+                _ => None,
+            }).next_back().unwrap();
+            let addr = last_program[w..].iter().fold(0, |acc, &x| (acc << 1) | (x as u32));
+            assert_eq!(isc_addr_to_row(addr), h - 2);
+
+            let mut svf = Vec::new();
+            bitstream.to_svf(&mut svf).unwrap();
+            let svf = String::from_utf8(svf).unwrap();
+            assert!(svf.contains("SDR 32 TDI (00000000) TDO ("));
+            assert_eq!(svf.matches("SIR 8 TDI (ee);").count(), if secure {1} else {2});
+
+            assert_eq!(parse_svf(&svf).unwrap(), ops);
+
+            let mut xsvf = Vec::new();
+            bitstream.to_xsvf(&mut xsvf).unwrap();
+            assert_eq!(xsvf.last(), Some(&XCOMPLETE));
+            assert_eq!(parse_xsvf(&xsvf).unwrap(), ops);
+        }
+    }
+
+    #[test]
+    fn svf_parsing() {
+        let svf = "// header\nTRST OFF;\nstate reset idle;\nSIR 8 TDI (e8) SMASK (ff);\nRUNTEST 100 TCK;\n\
+            SDR 10 TDI (2\n01) TDO (3ff) ! trailing comment\nMASK (0ff);\nSDR 10 TDO (000);\n\
+            RUNTEST IDLE 1E-3 SEC MAXIMUM 1 SEC ENDSTATE IDLE;\n";
+        let bits = vec![true, false, false, false, false, false, false, false, false, true];
+        assert_eq!(parse_svf(svf).unwrap(), vec![
+            XC2JTAGOp::Comment("header".to_owned()),
+            XC2JTAGOp::Reset,
+            XC2JTAGOp::ShiftIR(INST_ISC_ENABLE),
+            XC2JTAGOp::Idle(100),
+            XC2JTAGOp::ShiftDR {
+                tdi: bits.clone(),
+                tdo: Some(vec![true; 10]),
+                mask: Some(vec![true, true, true, true, true, true, true, true, false, false]),
+            },
+            // TDI and MASK are carried over from the previous shift
+            XC2JTAGOp::ShiftDR {
+                tdi: bits.clone(),
+                tdo: Some(vec![false; 10]),
+                mask: Some(vec![true, true, true, true, true, true, true, true, false, false]),
+            },
+            XC2JTAGOp::Idle(1000),
+        ]);
+
+        assert_eq!(parse_svf("SIR 8 TDI (e8);\nSDR 8 TDI (100);\n"), Err(XC2JTAGError::SVFParseError(2)));
+        assert_eq!(parse_svf("SIR 8 TDI (e8"), Err(XC2JTAGError::SVFParseError(1)));
+        assert_eq!(parse_svf("ENDDR DRPAUSE;"), Err(XC2JTAGError::UnsupportedSVFCommand("ENDDR DRPAUSE".to_owned())));
+    }
+
+    #[test]
+    fn xsvf_parsing() {
+        assert_eq!(parse_xsvf(&[XSIR, 8, 0xee, XCOMPLETE]).unwrap(), vec![XC2JTAGOp::ShiftIR(INST_ISC_READ)]);
+        assert_eq!(parse_xsvf(&[XSIR, 8, 0xee]), Err(XC2JTAGError::XSVFParseError(3)));
+        assert_eq!(parse_xsvf(&[XSDRSIZE, 0, 0, 0]), Err(XC2JTAGError::XSVFParseError(0)));
+        assert_eq!(parse_xsvf(&[XENDDR, 1, XCOMPLETE]), Err(XC2JTAGError::UnsupportedXSVFCommand(XENDDR)));
+        assert_eq!(parse_xsvf(&[0x03, XCOMPLETE]), Err(XC2JTAGError::UnsupportedXSVFCommand(0x03)));
+    }
+
+    #[test]
+    fn bit_packing() {
+        let bits = [true, false, false, false, true, true];
+        assert_eq!(svf_hex(&bits), "31");
+        assert_eq!(xsvf_bytes(&bits), vec![0x31]);
+        let bits = [true, false, false, false, false, false, false, false, false, true];
+        assert_eq!(svf_hex(&bits), "201");
+        assert_eq!(xsvf_bytes(&bits), vec![0x02, 0x01]);
+    }
+}
@@ -0,0 +1,605 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains a software model of the JTAG TAP controller and in-system configuration (ISC) logic of a Coolrunner-II.
+//! This allows programming files to be checked without any hardware.
+
+use std::collections::VecDeque;
+
+use *;
+use fusemap_physical::{fuse_array_dims, security_fuse_coords, usercode_fuse_coord};
+
+/// States of the JTAG TAP controller
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum XC2TAPState {
+    TestLogicReset,
+    RunTestIdle,
+    SelectDRScan,
+    CaptureDR,
+    ShiftDR,
+    Exit1DR,
+    PauseDR,
+    Exit2DR,
+    UpdateDR,
+    SelectIRScan,
+    CaptureIR,
+    ShiftIR,
+    Exit1IR,
+    PauseIR,
+    Exit2IR,
+    UpdateIR,
+}
+
+impl XC2TAPState {
+    /// Returns the state that the TAP controller moves to on a rising edge of TCK with the given value of TMS
+    pub fn next(self, tms: bool) -> Self {
+        match (self, tms) {
+            (XC2TAPState::TestLogicReset, false) => XC2TAPState::RunTestIdle,
+            (XC2TAPState::TestLogicReset, true) => XC2TAPState::TestLogicReset,
+            (XC2TAPState::RunTestIdle, false) => XC2TAPState::RunTestIdle,
+            (XC2TAPState::RunTestIdle, true) => XC2TAPState::SelectDRScan,
+            (XC2TAPState::SelectDRScan, false) => XC2TAPState::CaptureDR,
+            (XC2TAPState::SelectDRScan, true) => XC2TAPState::SelectIRScan,
+            (XC2TAPState::CaptureDR, false) => XC2TAPState::ShiftDR,
+            (XC2TAPState::CaptureDR, true) => XC2TAPState::Exit1DR,
+            (XC2TAPState::ShiftDR, false) => XC2TAPState::ShiftDR,
+            (XC2TAPState::ShiftDR, true) => XC2TAPState::Exit1DR,
+            (XC2TAPState::Exit1DR, false) => XC2TAPState::PauseDR,
+            (XC2TAPState::Exit1DR, true) => XC2TAPState::UpdateDR,
+            (XC2TAPState::PauseDR, false) => XC2TAPState::PauseDR,
+            (XC2TAPState::PauseDR, true) => XC2TAPState::Exit2DR,
+            (XC2TAPState::Exit2DR, false) => XC2TAPState::ShiftDR,
+            (XC2TAPState::Exit2DR, true) => XC2TAPState::UpdateDR,
+            (XC2TAPState::UpdateDR, false) => XC2TAPState::RunTestIdle,
+            (XC2TAPState::UpdateDR, true) => XC2TAPState::SelectDRScan,
+            (XC2TAPState::SelectIRScan, false) => XC2TAPState::CaptureIR,
+            (XC2TAPState::SelectIRScan, true) => XC2TAPState::TestLogicReset,
+            (XC2TAPState::CaptureIR, false) => XC2TAPState::ShiftIR,
+            (XC2TAPState::CaptureIR, true) => XC2TAPState::Exit1IR,
+            (XC2TAPState::ShiftIR, false) => XC2TAPState::ShiftIR,
+            (XC2TAPState::ShiftIR, true) => XC2TAPState::Exit1IR,
+            (XC2TAPState::Exit1IR, false) => XC2TAPState::PauseIR,
+            (XC2TAPState::Exit1IR, true) => XC2TAPState::UpdateIR,
+            (XC2TAPState::PauseIR, false) => XC2TAPState::PauseIR,
+            (XC2TAPState::PauseIR, true) => XC2TAPState::Exit2IR,
+            (XC2TAPState::Exit2IR, false) => XC2TAPState::ShiftIR,
+            (XC2TAPState::Exit2IR, true) => XC2TAPState::UpdateIR,
+            (XC2TAPState::UpdateIR, false) => XC2TAPState::RunTestIdle,
+            (XC2TAPState::UpdateIR, true) => XC2TAPState::SelectDRScan,
+        }
+    }
+}
+
+/// Software model of the JTAG and ISC logic of a Coolrunner-II, modeled after the `XC2CJTAG` HDL model. The device
+/// starts out blank (with the configuration memory erased to all 1s) and in the Test-Logic-Reset state.
+///
+/// The configuration memory behaves like EEPROM: programming a row can only change bits from 1 to 0, so rows have to
+/// be erased before they can be reprogrammed. The ISC_ERASE, ISC_PROGRAM, and ISC_READ instructions only have an
+/// effect after ISC_ENABLE. Once either security bit is programmed, ISC_READ returns all 0s. Time is not modeled, so
+/// waits of any length always succeed.
+pub struct XC2JTAGEmulator {
+    device: XC2DeviceSpeedPackage,
+    state: XC2TAPState,
+    ir: u8,
+    ir_shreg: u8,
+    dr_shreg: VecDeque<bool>,
+    mem: FuseArray,
+    read_row: usize,
+    isc_enabled: bool,
+    programmed: bool,
+    configured: bool,
+}
+
+impl XC2JTAGEmulator {
+    /// Constructs a new emulated device of the given type
+    pub fn new(device: XC2DeviceSpeedPackage) -> Self {
+        let (w, h) = fuse_array_dims(device.dev);
+        let mut mem = FuseArray::from_dim(w, h);
+        mem.dev_name_str = Some(format!("{}-{}-{}", device.dev, device.spd, device.pkg));
+
+        let mut ret = Self {
+            device,
+            state: XC2TAPState::TestLogicReset,
+            ir: INST_IDCODE,
+            ir_shreg: 0,
+            dr_shreg: VecDeque::new(),
+            mem,
+            read_row: 0,
+            isc_enabled: false,
+            programmed: false,
+            configured: false,
+        };
+        ret.erase();
+        ret
+    }
+
+    /// Returns the current state of the TAP controller
+    pub fn state(&self) -> XC2TAPState {
+        self.state
+    }
+
+    /// Returns the current contents of the configuration memory. The device name is filled in, so this can be passed
+    /// directly to `XC2Bitstream::from_crbit`.
+    pub fn fuse_array(&self) -> &FuseArray {
+        &self.mem
+    }
+
+    /// Returns whether the device is in ISC mode
+    pub fn isc_enabled(&self) -> bool {
+        self.isc_enabled
+    }
+
+    /// Returns whether the device has been programmed and has left ISC mode
+    pub fn configured(&self) -> bool {
+        self.configured
+    }
+
+    fn erase(&mut self) {
+        let (w, h) = self.mem.dim();
+        for y in 0..h {
+            for x in 0..w {
+                self.mem.set(x, y, true);
+            }
+        }
+    }
+
+    fn read_locked(&self) -> bool {
+        let ((sec0_x, sec0_y), (sec1_x, sec1_y)) = security_fuse_coords(self.device.dev);
+        !self.mem.get(sec0_x, sec0_y) || !self.mem.get(sec1_x, sec1_y)
+    }
+
+    /// Returns the row addressed by the last `isc_addr_bits` bits of the DR, which are shifted in MSB first
+    fn dr_addr_row(&self) -> usize {
+        let addr_bits = isc_addr_bits(self.device.dev);
+        let addr = self.dr_shreg.iter().skip(self.dr_shreg.len() - addr_bits)
+            .fold(0, |acc, &x| (acc << 1) | (x as u32));
+        isc_addr_to_row(addr)
+    }
+
+    fn capture_dr(&mut self) {
+        let (w, h) = fuse_array_dims(self.device.dev);
+        self.dr_shreg.clear();
+        match self.ir {
+            INST_IDCODE => {
+                let (idcode, _) = isc_idcode(self.device.dev, self.device.pkg);
+                self.dr_shreg.extend((0..32).map(|i| idcode & (1 << i) != 0));
+            },
+            INST_USERCODE => {
+                for i in 0..32 {
+                    let (x, y) = usercode_fuse_coord(self.device.dev, i);
+                    self.dr_shreg.push_back(self.mem.get(x, y));
+                }
+            },
+            INST_ISC_PROGRAM => {
+                self.dr_shreg.extend(vec![false; w + isc_addr_bits(self.device.dev)]);
+            },
+            INST_ISC_READ => {
+                if self.isc_enabled && self.read_row < h && !self.read_locked() {
+                    for x in 0..w {
+                        self.dr_shreg.push_back(self.mem.get(x, self.read_row));
+                    }
+                } else {
+                    self.dr_shreg.extend(vec![false; w]);
+                }
+            },
+            // BYPASS, as well as every instruction that is not modeled
+            _ => {
+                self.dr_shreg.push_back(false);
+            },
+        }
+    }
+
+    fn update_dr(&mut self) {
+        if !self.isc_enabled {
+            return;
+        }
+
+        let (w, h) = fuse_array_dims(self.device.dev);
+        match self.ir {
+            INST_ISC_PROGRAM => {
+                let row = self.dr_addr_row();
+                if row < h {
+                    for x in 0..w {
+                        if !self.dr_shreg[x] {
+                            self.mem.set(x, row, false);
+                        }
+                    }
+                }
+                self.programmed = true;
+            },
+            INST_ISC_READ => {
+                // Reads are pipelined, and the data for this address is loaded at the next Capture-DR
+                self.read_row = self.dr_addr_row();
+            },
+            _ => {},
+        }
+    }
+
+    fn update_ir(&mut self) {
+        self.ir = self.ir_shreg;
+        match self.ir {
+            INST_ISC_ENABLE => {
+                self.isc_enabled = true;
+            },
+            INST_ISC_DISABLE => {
+                if self.isc_enabled && self.programmed {
+                    self.configured = true;
+                }
+                self.isc_enabled = false;
+                self.programmed = false;
+            },
+            INST_ISC_ERASE if self.isc_enabled => {
+                self.erase();
+                self.configured = false;
+            },
+            _ => {},
+        }
+    }
+
+    /// Applies one rising edge of TCK with the given values of TMS and TDI. Returns the value on TDO before the edge.
+    pub fn clock(&mut self, tms: bool, tdi: bool) -> bool {
+        let tdo = match self.state {
+            XC2TAPState::ShiftIR => self.ir_shreg & 1 != 0,
+            XC2TAPState::ShiftDR => self.dr_shreg.front().cloned().unwrap_or(false),
+            _ => false,
+        };
+
+        match self.state {
+            XC2TAPState::TestLogicReset => {
+                self.ir = INST_IDCODE;
+            },
+            XC2TAPState::CaptureIR => {
+                // The top bits would report ISC_DISABLE being in progress, which is instantaneous here
+                self.ir_shreg = ((self.isc_enabled as u8) << 4) | ((self.read_locked() as u8) << 3) |
+                    ((self.configured as u8) << 2) | 0b01;
+            },
+            XC2TAPState::ShiftIR => {
+                self.ir_shreg = (self.ir_shreg >> 1) | ((tdi as u8) << 7);
+            },
+            XC2TAPState::UpdateIR => {
+                self.update_ir();
+            },
+            XC2TAPState::CaptureDR => {
+                self.capture_dr();
+            },
+            XC2TAPState::ShiftDR => {
+                self.dr_shreg.pop_front();
+                self.dr_shreg.push_back(tdi);
+            },
+            XC2TAPState::UpdateDR => {
+                self.update_dr();
+            },
+            _ => {},
+        }
+
+        self.state = self.state.next(tms);
+        tdo
+    }
+
+    /// Moves to Run-Test/Idle, which is where every operation starts. This is needed if the TAP controller was moved
+    /// somewhere else using `clock`. A scan that was left in progress is finished by going through its Update state,
+    /// and Select-DR-Scan/Select-IR-Scan go through Test-Logic-Reset.
+    fn start_op(&mut self) {
+        while self.state != XC2TAPState::RunTestIdle {
+            let state = self.state;
+            let tms = state != XC2TAPState::TestLogicReset && state != XC2TAPState::UpdateDR &&
+                state != XC2TAPState::UpdateIR;
+            self.clock(tms, false);
+        }
+    }
+
+    /// Shifts `tdi` through the IR or DR starting from Run-Test/Idle and returns the bits shifted out. `to_shift` is
+    /// the sequence of TMS values that goes from Run-Test/Idle to the shift state. Shifting zero bits does nothing.
+    fn shift(&mut self, to_shift: &[bool], tdi: &[bool]) -> Vec<bool> {
+        if tdi.is_empty() {
+            return Vec::new();
+        }
+
+        self.start_op();
+        for &tms in to_shift {
+            self.clock(tms, false);
+        }
+        let mut tdo = Vec::with_capacity(tdi.len());
+        for (i, &bit) in tdi.iter().enumerate() {
+            tdo.push(self.clock(i == tdi.len() - 1, bit));
+        }
+        // Exit1 -> Update -> Run-Test/Idle
+        self.clock(true, false);
+        self.clock(false, false);
+        tdo
+    }
+
+    /// Resets the TAP controller by holding TMS high and then goes to Run-Test/Idle
+    pub fn reset(&mut self) {
+        for _ in 0..5 {
+            self.clock(true, false);
+        }
+        self.clock(false, false);
+    }
+
+    /// Shifts the given instruction into the IR and returns the status bits that were shifted out
+    pub fn shift_ir(&mut self, inst: u8) -> u8 {
+        let tdi = (0..IR_LENGTH).map(|i| inst & (1 << i) != 0).collect::<Vec<_>>();
+        let tdo = self.shift(&[true, true, false, false], &tdi);
+        tdo.iter().enumerate().fold(0, |acc, (i, &x)| acc | ((x as u8) << i))
+    }
+
+    /// Shifts the given bits into the currently selected DR and returns the bits that were shifted out. Bits are in
+    /// shift order.
+    pub fn shift_dr(&mut self, tdi: &[bool]) -> Vec<bool> {
+        self.shift(&[true, false, false], tdi)
+    }
+
+    /// Stays in Run-Test/Idle for the given number of TCK cycles
+    pub fn idle(&mut self, cycles: u32) {
+        self.start_op();
+        for _ in 0..cycles {
+            self.clock(false, false);
+        }
+    }
+
+    /// Runs the given sequence of JTAG operations, checking the bits shifted out wherever the operations say to
+    pub fn run_ops(&mut self, ops: &[XC2JTAGOp]) -> Result<(), XC2JTAGError> {
+        for (op_i, op) in ops.iter().enumerate() {
+            match *op {
+                XC2JTAGOp::Comment(_) => {},
+                XC2JTAGOp::Reset => self.reset(),
+                XC2JTAGOp::ShiftIR(inst) => {
+                    self.shift_ir(inst);
+                },
+                XC2JTAGOp::ShiftDR {ref tdi, ref tdo, ref mask} => {
+                    let wrong_len = |x: &Option<Vec<bool>>| match *x {
+                        Some(ref x) => x.len() != tdi.len(),
+                        None => false,
+                    };
+                    if wrong_len(tdo) || wrong_len(mask) {
+                        return Err(XC2JTAGError::MalformedOp(op_i));
+                    }
+
+                    let actual_tdo = self.shift_dr(tdi);
+                    if let Some(ref tdo) = *tdo {
+                        let matches = actual_tdo.iter().zip(tdo.iter()).enumerate().all(|(i, (&actual, &expected))| {
+                            actual == expected || mask.as_ref().map_or(false, |mask| !mask[i])
+                        });
+                        if !matches {
+                            return Err(XC2JTAGError::TDOMismatch(op_i, actual_tdo));
+                        }
+                    }
+                },
+                // Time is not modeled, so every wait is a single cycle
+                XC2JTAGOp::Idle(_) => self.idle(1),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the given SVF file
+    pub fn run_svf(&mut self, svf: &str) -> Result<(), XC2JTAGError> {
+        self.run_ops(&parse_svf(svf)?)
+    }
+
+    /// Runs the given XSVF file
+    pub fn run_xsvf(&mut self, xsvf: &[u8]) -> Result<(), XC2JTAGError> {
+        self.run_ops(&parse_xsvf(xsvf)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_bitstream(part: &str, secure: bool) -> XC2Bitstream {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap());
+        bitstream.secure = Some(secure);
+        bitstream.usercode = Some(0xdeadbeef);
+        bitstream
+    }
+
+    #[test]
+    fn tap_reset() {
+        let states = [
+            XC2TAPState::TestLogicReset, XC2TAPState::RunTestIdle, XC2TAPState::SelectDRScan, XC2TAPState::CaptureDR,
+            XC2TAPState::ShiftDR, XC2TAPState::Exit1DR, XC2TAPState::PauseDR, XC2TAPState::Exit2DR,
+            XC2TAPState::UpdateDR, XC2TAPState::SelectIRScan, XC2TAPState::CaptureIR, XC2TAPState::ShiftIR,
+            XC2TAPState::Exit1IR, XC2TAPState::PauseIR, XC2TAPState::Exit2IR, XC2TAPState::UpdateIR];
+
+        for &state in states.iter() {
+            let mut x = state;
+            for _ in 0..5 {
+                x = x.next(true);
+            }
+            assert_eq!(x, XC2TAPState::TestLogicReset);
+        }
+    }
+
+    #[test]
+    fn idcode_and_status() {
+        let part = XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap();
+        let mut jtag = XC2JTAGEmulator::new(part);
+
+        // The IDCODE register is selected after reset
+        let tdo = jtag.shift_dr(&[false; 32]);
+        let idcode = tdo.iter().enumerate().fold(0, |acc, (i, &x)| acc | ((x as u32) << i));
+        assert_eq!(idcode, isc_idcode(part.dev, part.pkg).0);
+
+        assert_eq!(jtag.shift_ir(INST_ISC_ENABLE), 0b00000001);
+        assert_eq!(jtag.shift_ir(INST_BYPASS), 0b00010001);
+        assert!(jtag.isc_enabled());
+        assert_eq!(jtag.shift_dr(&[true, false, false]), vec![false, true, false]);
+    }
+
+    #[test]
+    fn values_from_hdl_model() {
+        // These values are copied from hdl/xc2c-model/XC2CJTAG.v rather than computed with the helpers in isc.rs
+        let part = XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap();
+        let mut jtag = XC2JTAGEmulator::new(part);
+
+        // Leave the TAP controller in the middle of a DR scan; the next operation has to get back to Run-Test/Idle
+        for &tms in &[false, true, false, false, false] {
+            jtag.clock(tms, false);
+        }
+        assert_eq!(jtag.state(), XC2TAPState::ShiftDR);
+
+        // IDCODE (8'h01) is selected after reset. The model uses stepping 4'hf, which is not checked here.
+        let tdo = jtag.shift_dr(&[false; 32]);
+        let idcode = tdo.iter().enumerate().fold(0u32, |acc, (i, &x)| acc | ((x as u32) << i));
+        assert_eq!(idcode & 0x0fffffff, 0x06e1c093);
+
+        // USERCODE (8'hfd) of an erased part. The captured IR is {2'b00, isc_disabled, isc_enabled, read_locked,
+        // configured, 2'b01}.
+        assert_eq!(jtag.shift_ir(0xfd), 0b00000001);
+        assert_eq!(jtag.shift_dr(&[false; 32]), vec![true; 32]);
+
+        // ISC_ENABLE (8'he8), then BYPASS (8'hff) with a 1-bit DR, entered from the middle of an IR scan
+        jtag.shift_ir(0xe8);
+        for &tms in &[true, true, false, false] {
+            jtag.clock(tms, false);
+        }
+        assert_eq!(jtag.state(), XC2TAPState::ShiftIR);
+        assert!(jtag.isc_enabled());
+        assert_eq!(jtag.shift_ir(0xff), 0b00010001);
+        assert_eq!(jtag.shift_dr(&[true, false, false]), vec![false, true, false]);
+
+        // Select-DR-Scan goes through Test-Logic-Reset, which selects IDCODE again
+        jtag.clock(true, false);
+        assert_eq!(jtag.state(), XC2TAPState::SelectDRScan);
+        jtag.idle(1);
+        assert_eq!(jtag.state(), XC2TAPState::RunTestIdle);
+        assert_eq!(jtag.shift_dr(&[false; 32]), tdo);
+    }
+
+    #[test]
+    fn program_svf_xsvf() {
+        for &part in ["XC2C32A-4-VQ44", "XC2C64A-5-VQ44", "XC2C256-6-TQ144", "XC2C512-7-PQ208"].iter() {
+            let bitstream = test_bitstream(part, false);
+
+            let mut svf = Vec::new();
+            bitstream.to_svf(&mut svf).unwrap();
+            let mut jtag = XC2JTAGEmulator::new(XC2DeviceSpeedPackage::from_str(part).unwrap());
+            jtag.run_svf(str::from_utf8(&svf).unwrap()).unwrap();
+            assert_eq!(jtag.fuse_array().bits(), bitstream.to_crbit().bits());
+            assert!(jtag.configured());
+            assert!(!jtag.isc_enabled());
+            assert_eq!(jtag.state(), XC2TAPState::RunTestIdle);
+
+            let readback = XC2Bitstream::from_crbit(jtag.fuse_array()).unwrap();
+            assert_eq!(readback.usercode, Some(0xdeadbeef));
+
+            jtag.shift_ir(INST_USERCODE);
+            let tdo = jtag.shift_dr(&[false; 32]);
+            let usercode = tdo.iter().enumerate().fold(0, |acc, (i, &x)| acc | ((x as u32) << i));
+            assert_eq!(usercode, 0xdeadbeef);
+
+            let mut xsvf = Vec::new();
+            bitstream.to_xsvf(&mut xsvf).unwrap();
+            let mut jtag = XC2JTAGEmulator::new(XC2DeviceSpeedPackage::from_str(part).unwrap());
+            jtag.run_xsvf(&xsvf).unwrap();
+            assert_eq!(jtag.fuse_array().bits(), bitstream.to_crbit().bits());
+            assert!(jtag.configured());
+        }
+    }
+
+    #[test]
+    fn secure() {
+        let bitstream = test_bitstream("XC2C64A-5-VQ44", true);
+        let mut jtag = XC2JTAGEmulator::new(XC2DeviceSpeedPackage::from_str("XC2C64A-5-VQ44").unwrap());
+        jtag.run_ops(&bitstream.to_isc_sequence()).unwrap();
+        assert_eq!(jtag.fuse_array().bits(), bitstream.to_crbit().bits());
+
+        // The device reports that it is locked, and reading back only returns 0s
+        assert_eq!(jtag.shift_ir(INST_ISC_ENABLE), 0b00001101);
+        jtag.shift_ir(INST_ISC_READ);
+        let (w, _) = fuse_array_dims(XC2Device::XC2C64A);
+        jtag.shift_dr(&vec![false; w]);
+        assert!(jtag.shift_dr(&vec![false; w]).iter().all(|&x| !x));
+    }
+
+    #[test]
+    fn tdo_mismatch() {
+        // Programming a sequence for the wrong device fails the IDCODE check
+        let bitstream = test_bitstream("XC2C64A-5-VQ44", false);
+        let mut jtag = XC2JTAGEmulator::new(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        match jtag.run_ops(&bitstream.to_isc_sequence()) {
+            Err(XC2JTAGError::TDOMismatch(4, _)) => {},
+            x => panic!("unexpected result {:?}", x),
+        }
+    }
+
+    #[test]
+    fn malformed_shifts() {
+        let mut jtag = XC2JTAGEmulator::new(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        // Zero-length shifts are allowed and do not leave the TAP in the wrong state
+        jtag.run_svf("SDR 0 TDI ();\nSIR 8 TDI (ff);\n").unwrap();
+        jtag.run_ops(&[XC2JTAGOp::ShiftDR {tdi: vec![], tdo: Some(vec![]), mask: None}, XC2JTAGOp::ShiftIR(0xff)])
+            .unwrap();
+
+        // The expected TDO and the mask must be as long as the shift
+        match jtag.run_ops(&[XC2JTAGOp::Reset,
+            XC2JTAGOp::ShiftDR {tdi: vec![false; 4], tdo: Some(vec![false; 4]), mask: Some(vec![true; 2])}]) {
+            Err(XC2JTAGError::MalformedOp(1)) => {},
+            x => panic!("unexpected result {:?}", x),
+        }
+        match jtag.run_ops(&[XC2JTAGOp::ShiftDR {tdi: vec![false; 4], tdo: Some(vec![false; 5]), mask: None}]) {
+            Err(XC2JTAGError::MalformedOp(0)) => {},
+            x => panic!("unexpected result {:?}", x),
+        }
+    }
+
+    #[test]
+    fn eeprom() {
+        let part = XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap();
+        let (w, h) = fuse_array_dims(part.dev);
+        let addr_bits = isc_addr_bits(part.dev);
+        let row_tdi = |val: bool, row: usize| {
+            let mut tdi = vec![val; w];
+            let addr = isc_row_to_addr(row);
+            tdi.extend((0..addr_bits).rev().map(|i| addr & (1 << i) != 0));
+            tdi
+        };
+        let mut jtag = XC2JTAGEmulator::new(part);
+
+        // Nothing happens outside of ISC mode
+        jtag.shift_ir(INST_ISC_PROGRAM);
+        jtag.shift_dr(&row_tdi(false, 5));
+        assert!(jtag.fuse_array().bits().iter().all(|x| x));
+
+        jtag.shift_ir(INST_ISC_ENABLE);
+        jtag.shift_ir(INST_ISC_PROGRAM);
+        jtag.shift_dr(&row_tdi(false, 5));
+        for y in 0..h {
+            for x in 0..w {
+                assert_eq!(jtag.fuse_array().get(x, y), y != 5);
+            }
+        }
+
+        // Programming without erasing first cannot set any bits back to 1
+        jtag.shift_dr(&row_tdi(true, 5));
+        assert!(!jtag.fuse_array().get(0, 5));
+
+        jtag.shift_ir(INST_ISC_ERASE);
+        assert!(jtag.fuse_array().bits().iter().all(|x| x));
+    }
+}
@@ -32,7 +32,7 @@ use jedec::*;
 
 use *;
 use fusemap_physical::{mc_block_loc};
-use util::{LinebreakSet};
+use util::{LinebreakSet, HumanReadableReader};
 use zia::{zia_get_row_width};
 
 /// Clock source for the register in a macrocell
@@ -132,13 +132,13 @@ pub enum XC2MCXorMode {
 /// Represents a macrocell.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 #[derive(BitTwiddler)]
-#[bittwiddler = "jed_internal_small"]
+#[bittwiddler = "jed_internal_small width=27"]
 #[bittwiddler = "jed_internal_large"]
 #[bittwiddler = "jed_internal_large_buried"]
-#[bittwiddler = "crbit32 mirror0"]
-#[bittwiddler = "crbit64 mirror0"]
+#[bittwiddler = "crbit32 mirror0 width=9x3"]
+#[bittwiddler = "crbit64 mirror0 width=9x3"]
 #[bittwiddler = "crbit256 mirror0"]
-#[bittwiddler = "crbit_large mirror0"]
+#[bittwiddler = "crbit_large mirror0 width=15x2"]
 pub struct XC2Macrocell {
     /// Clock source for the register
     #[bittwiddler_field = "jed_internal_small 0 2 3"]
@@ -315,6 +315,66 @@ impl XC2Macrocell {
         Ok(())
     }
 
+    /// Read back the settings for this macrocell from the output of `dump_human_readable`.
+    /// `fb` and `mc` must be the function block number and macrocell number of this macrocell.
+    pub fn from_human_readable(fb: u32, mc: u32, reader: &mut HumanReadableReader) -> Result<Self, XC2BitError> {
+        reader.expect("")?;
+        reader.expect(&format!("FF configuration for FB{}_{}", fb + 1, mc + 1))?;
+        let reg_mode = reader.choice("FF mode: ", &[
+            ("D flip-flop", XC2MCRegMode::DFF),
+            ("transparent latch", XC2MCRegMode::LATCH),
+            ("T flip-flop", XC2MCRegMode::TFF),
+            ("D flip-flop with clock-enable", XC2MCRegMode::DFFCE),
+        ])?;
+        let init_state = reader.flag("initial state: ", "1", "0")?;
+        let clk_invert_pol = reader.flag("", "falling-edge triggered", "rising-edge triggered")?;
+        let is_ddr = reader.flag("DDR: ", "yes", "no")?;
+        let clk_src = reader.choice("clock source: ", &[
+            ("GCK0", XC2MCRegClkSrc::GCK0),
+            ("GCK1", XC2MCRegClkSrc::GCK1),
+            ("GCK2", XC2MCRegClkSrc::GCK2),
+            ("PTC", XC2MCRegClkSrc::PTC),
+            ("CTC", XC2MCRegClkSrc::CTC),
+        ])?;
+        let s_src = reader.choice("set source: ", &[
+            ("disabled", XC2MCRegSetSrc::Disabled),
+            ("PTA", XC2MCRegSetSrc::PTA),
+            ("GSR", XC2MCRegSetSrc::GSR),
+            ("CTS", XC2MCRegSetSrc::CTS),
+        ])?;
+        let r_src = reader.choice("reset source: ", &[
+            ("disabled", XC2MCRegResetSrc::Disabled),
+            ("PTA", XC2MCRegResetSrc::PTA),
+            ("GSR", XC2MCRegResetSrc::GSR),
+            ("CTR", XC2MCRegResetSrc::CTR),
+        ])?;
+        let ff_in_ibuf = reader.flag("using ibuf direct path: ", "yes", "no")?;
+        let xor_mode = reader.choice("XOR gate input: ", &[
+            ("0", XC2MCXorMode::ZERO),
+            ("1", XC2MCXorMode::ONE),
+            ("PTC", XC2MCXorMode::PTC),
+            ("~PTC", XC2MCXorMode::PTCB),
+        ])?;
+        let fb_mode = reader.choice("ZIA feedback: ", &[
+            ("disabled", XC2MCFeedbackMode::Disabled),
+            ("combinatorial", XC2MCFeedbackMode::COMB),
+            ("registered", XC2MCFeedbackMode::REG),
+        ])?;
+
+        Ok(XC2Macrocell {
+            clk_src,
+            clk_invert_pol,
+            is_ddr,
+            r_src,
+            s_src,
+            init_state,
+            reg_mode,
+            fb_mode,
+            ff_in_ibuf,
+            xor_mode,
+        })
+    }
+
     /// Write the crbit representation of this macrocell to the given `fuse_array`.
     pub fn to_crbit(&self, device: XC2Device, fb: u32, mc: u32, fuse_array: &mut FuseArray) {
         let (x, y, mirror) = mc_block_loc(device, fb);
@@ -378,17 +438,17 @@ impl XC2Macrocell {
     }
 
     ///  Internal function that reads only the macrocell-related bits from the macrcocell configuration
-    pub fn from_jed_small(fuses: &[bool], block_idx: usize, mc_idx: usize) -> Self {
+    pub fn from_jed_small<F: FuseRead<usize> + ?Sized>(fuses: &F, block_idx: usize, mc_idx: usize) -> Self {
         Self::decode_jed_internal_small(fuses, block_idx + mc_idx * 27)
     }
 
     ///  Internal function that reads only the macrocell-related bits from the macrcocell configuration
-    pub fn from_jed_large(fuses: &[bool], fuse_idx: usize) -> Self {
+    pub fn from_jed_large<F: FuseRead<usize> + ?Sized>(fuses: &F, fuse_idx: usize) -> Self {
         Self::decode_jed_internal_large(fuses, fuse_idx)
     }
 
     ///  Internal function that reads only the macrocell-related bits from the macrcocell configuration
-    pub fn from_jed_large_buried(fuses: &[bool], fuse_idx: usize) -> Self {
+    pub fn from_jed_large_buried<F: FuseRead<usize> + ?Sized>(fuses: &F, fuse_idx: usize) -> Self {
         Self::decode_jed_internal_large_buried(fuses, fuse_idx)
     }
 
@@ -437,3 +497,47 @@ impl XC2Macrocell {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuse_layout() {
+        let clk_src = XC2Macrocell::FUSE_LAYOUT.iter()
+            .find(|x| x.instance == "crbit32" && x.field == "clk_src").unwrap();
+        assert_eq!(clk_src.bits.iter().map(|x| x.coord(clk_src, (10, 10), (false, false)).unwrap())
+            .collect::<Vec<_>>(), vec![(10, 10), (12, 10), (13, 10)]);
+        assert_eq!(clk_src.bits.iter().map(|x| x.coord(clk_src, (10, 10), (true, false)).unwrap())
+            .collect::<Vec<_>>(), vec![(10, 10), (8, 10), (7, 10)]);
+
+        // Check that the coordinates agree with what is actually encoded, both unmirrored and mirrored
+        for &mirror in &[false, true] {
+            let mut mc = XC2Macrocell::default();
+            let mut fuse_array = FuseArray::from_dim(20, 20);
+            mc.encode_crbit32(&mut fuse_array, (10, 10), mirror);
+            let orig_bits = fuse_array.bits().clone();
+            mc.clk_src = XC2MCRegClkSrc::CTC;
+            mc.encode_crbit32(&mut fuse_array, (10, 10), mirror);
+            let changed = orig_bits.diff_indices(fuse_array.bits()).into_iter().map(|i| (i % 20, i / 20))
+                .collect::<Vec<_>>();
+            for coord in changed {
+                assert!(clk_src.bits.iter().any(|x| x.coord(clk_src, (10, 10), (mirror, false)) == Some(coord)));
+            }
+        }
+
+        // Check that the table agrees with what is actually encoded
+        let mut mc = XC2Macrocell::default();
+        for &init_state in &[false, true] {
+            mc.init_state = init_state;
+            let mut fuses = [false; 27];
+            mc.encode_jed_internal_small(&mut fuses[..], 0);
+
+            let layout = XC2Macrocell::FUSE_LAYOUT.iter()
+                .find(|x| x.instance == "jed_internal_small" && x.field == "init_state").unwrap();
+            assert_eq!(layout.bits.len(), 1);
+            let fuse_idx = layout.bits[0].fuse_idx(layout, 0, false).unwrap();
+            assert_eq!(fuses[fuse_idx] ^ layout.bits[0].invert, init_state);
+        }
+    }
+}
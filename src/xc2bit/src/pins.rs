@@ -0,0 +1,322 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains the package pin database, which maps between package pins and the internal I/O block numbers
+
+use std::fmt;
+
+use *;
+
+/// Special functions that a package pin can have. Pins connected to an IOB can always also be used as ordinary user
+/// I/O, even if they have a special function.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2PinFunction {
+    /// Ordinary user I/O with no special function
+    IO,
+    /// Global clock input GCKn
+    GCK(u32),
+    /// Global tristate input GTSn
+    GTS(u32),
+    /// Global set/reset input GSR
+    GSR,
+    /// Clock divider reset input CDRST
+    CDRST,
+    /// DataGATE enable input DGE
+    DGE,
+    /// Reference voltage input for the I/O bank
+    VREF,
+    /// Dedicated input-only pin
+    InputOnly,
+    /// JTAG test data input
+    TDI,
+    /// JTAG test data output
+    TDO,
+    /// JTAG test clock
+    TCK,
+    /// JTAG test mode select
+    TMS,
+    /// Core power supply
+    VCC,
+    /// Auxiliary (JTAG) power supply
+    VCCAUX,
+    /// I/O power supply for the given bank
+    VCCIO(u32),
+    /// Ground
+    GND,
+    /// Not connected
+    NC,
+}
+
+impl fmt::Display for XC2PinFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2PinFunction::GCK(i) => write!(f, "GCK{}", i),
+            XC2PinFunction::GTS(i) => write!(f, "GTS{}", i),
+            XC2PinFunction::VCCIO(i) => write!(f, "VCCIO{}", i),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Information about one pin of a package
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2PinInfo {
+    /// Name of the pin as used in constraints files, e.g. "P38" for a leaded package or "B14" for a BGA ball
+    pub name: &'static str,
+    /// The internal IOB number the pin is connected to, if any
+    pub iob: Option<u32>,
+    /// The I/O bank the pin belongs to, if known
+    pub bank: Option<u32>,
+    /// The special function of the pin, or `XC2PinFunction::IO` for ordinary user I/O
+    pub function: XC2PinFunction,
+}
+
+// Tables mapping IOB numbers to pin names (see DS310 page 8). IOBs that are not bonded out in the package are "".
+// The XC2C32 and XC2C32A are pin-compatible in all the packages they share.
+static XC2C32_QFG32_PINS: [&str; 32] = [
+    // FB1
+    "",    "",    "",    "P3",  "P2",  "P1",  "P32", "P31", "P30", "P29", "P28", "P24", "",    "P23", "",    "",
+    // FB2
+    "P5",  "",    "",    "",    "P6",  "P7",  "P8",  "P9",  "P10", "",    "",    "P13", "P17", "P18", "P19", "",
+];
+static XC2C32_VQ44_PINS: [&str; 32] = [
+    // FB1
+    "P38", "P37", "P36", "P34", "P33", "P32", "P31", "P30", "P29", "P28", "P27", "P23", "P22", "P21", "P20", "P19",
+    // FB2
+    "P39", "P40", "P41", "P42", "P43", "P44", "P1",  "P2",  "P3",  "P5",  "P6",  "P8",  "P12", "P13", "P14", "P16",
+];
+static XC2C32_CP56_PINS: [&str; 32] = [
+    // FB1
+    "F1",  "E3",  "E1",  "D1",  "C1",  "A3",  "A2",  "B1",  "A1",  "C4",  "C5",  "C8",  "A10", "B10", "C10", "E8",
+    // FB2
+    "G1",  "F3",  "H1",  "G3",  "J1",  "K1",  "K2",  "K3",  "H3",  "K5",  "H5",  "H8",  "K8",  "H10", "G10", "F10",
+];
+
+// Dual-purpose pins of the 32-macrocell parts, by IOB number
+static XC2C32_SPECIAL_IOBS: [(u32, XC2PinFunction); 8] = [
+    (20, XC2PinFunction::GCK(0)),
+    (21, XC2PinFunction::GCK(1)),
+    (22, XC2PinFunction::GCK(2)),
+    (4, XC2PinFunction::GTS(0)),
+    (3, XC2PinFunction::GTS(1)),
+    (6, XC2PinFunction::GTS(2)),
+    (5, XC2PinFunction::GTS(3)),
+    (7, XC2PinFunction::GSR),
+];
+
+/// Returns the I/O bank that the given IOB belongs to, if it is known. Banks are numbered starting from 1 as in the
+/// datasheets. Currently only the banks of the 32-macrocell parts are known.
+pub fn get_iob_bank(device: XC2Device, iob: u32) -> Option<u32> {
+    if iob as usize >= device.num_iobs() {
+        return None;
+    }
+
+    match device {
+        // Only one bank
+        XC2Device::XC2C32 => Some(1),
+        // FB1 is in bank 2 and FB2 is in bank 1
+        XC2Device::XC2C32A => Some(2 - iob / MCS_PER_FB as u32),
+        _ => None,
+    }
+}
+
+/// Returns all of the pins of the given device and package that are in the pin database, or `None` if there is no
+/// data for the combination. Currently only the user I/O pins of the 32-macrocell parts are in the database; pins
+/// that are not connected to an IOB (JTAG, power, the input-only pin, etc.) are not listed yet.
+pub fn get_package_pins(device: XC2Device, package: XC2Package) -> Option<Vec<XC2PinInfo>> {
+    let (iob_pins, special_iobs) = match (device, package) {
+        (XC2Device::XC2C32A, XC2Package::QFG32) => (&XC2C32_QFG32_PINS, &XC2C32_SPECIAL_IOBS),
+        (XC2Device::XC2C32, XC2Package::VQ44) | (XC2Device::XC2C32A, XC2Package::VQ44) =>
+            (&XC2C32_VQ44_PINS, &XC2C32_SPECIAL_IOBS),
+        (XC2Device::XC2C32, XC2Package::CP56) | (XC2Device::XC2C32A, XC2Package::CP56) =>
+            (&XC2C32_CP56_PINS, &XC2C32_SPECIAL_IOBS),
+        _ => return None,
+    };
+
+    let mut ret = Vec::new();
+    for (iob, &name) in iob_pins.iter().enumerate() {
+        if name.is_empty() {
+            continue;
+        }
+
+        let iob = iob as u32;
+        let function = match special_iobs.iter().find(|x| x.0 == iob) {
+            Some(&(_, function)) => function,
+            None => XC2PinFunction::IO,
+        };
+
+        ret.push(XC2PinInfo {
+            name,
+            iob: Some(iob),
+            bank: get_iob_bank(device, iob),
+            function,
+        });
+    }
+
+    Some(ret)
+}
+
+/// Looks up a pin of the given device and package by name. Names are not case-sensitive.
+pub fn get_pin_by_name(device: XC2Device, package: XC2Package, name: &str) -> Option<XC2PinInfo> {
+    get_package_pins(device, package)?.into_iter().find(|x| x.name.eq_ignore_ascii_case(name))
+}
+
+/// Looks up the pin of the given device and package that is connected to the given IOB
+pub fn get_pin_by_iob(device: XC2Device, package: XC2Package, iob: u32) -> Option<XC2PinInfo> {
+    get_package_pins(device, package)?.into_iter().find(|x| x.iob == Some(iob))
+}
+
+/// Function to map from a package pin name to the internal numbering scheme for I/O pins
+pub fn pin_name_to_iob_num(device: XC2Device, package: XC2Package, name: &str) -> Option<u32> {
+    get_pin_by_name(device, package, name)?.iob
+}
+
+/// Function to map from the internal numbering scheme for I/O pins to a package pin name
+pub fn iob_num_to_pin_name(device: XC2Device, package: XC2Package, iob: u32) -> Option<&'static str> {
+    get_pin_by_iob(device, package, iob).map(|x| x.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static ALL_DEVICES: [XC2Device; 8] = [
+        XC2Device::XC2C32, XC2Device::XC2C32A, XC2Device::XC2C64, XC2Device::XC2C64A,
+        XC2Device::XC2C128, XC2Device::XC2C256, XC2Device::XC2C384, XC2Device::XC2C512];
+
+    static ALL_PACKAGES: [XC2Package; 11] = [
+        XC2Package::PC44, XC2Package::QFG32, XC2Package::VQ44, XC2Package::QFG48, XC2Package::CP56,
+        XC2Package::VQ100, XC2Package::CP132, XC2Package::TQ144, XC2Package::PQ208, XC2Package::FT256,
+        XC2Package::FG324];
+
+    #[test]
+    fn pin_lookup() {
+        let pin = get_pin_by_name(XC2Device::XC2C32A, XC2Package::VQ44, "P43").unwrap();
+        assert_eq!(pin.iob, Some(20));
+        assert_eq!(pin.bank, Some(1));
+        assert_eq!(pin.function, XC2PinFunction::GCK(0));
+        assert_eq!(iob_num_to_fb_mc_num(XC2Device::XC2C32A, 20), Some((1, 4)));
+
+        assert_eq!(pin_name_to_iob_num(XC2Device::XC2C32A, XC2Package::CP56, "a10"), Some(12));
+        assert_eq!(iob_num_to_pin_name(XC2Device::XC2C32, XC2Package::VQ44, 0), Some("P38"));
+        assert_eq!(get_pin_by_iob(XC2Device::XC2C32, XC2Package::VQ44, 0).unwrap().bank, Some(1));
+        assert_eq!(get_pin_by_iob(XC2Device::XC2C32A, XC2Package::VQ44, 0).unwrap().bank, Some(2));
+
+        // Not bonded out
+        assert_eq!(iob_num_to_pin_name(XC2Device::XC2C32A, XC2Package::QFG32, 0), None);
+        // No QFG32 package for the non-A part
+        assert!(get_package_pins(XC2Device::XC2C32, XC2Package::QFG32).is_none());
+    }
+
+    #[test]
+    fn pin_tables_consistent() {
+        for &device in ALL_DEVICES.iter() {
+            for &package in ALL_PACKAGES.iter() {
+                let pins = match get_package_pins(device, package) {
+                    Some(pins) => pins,
+                    None => continue,
+                };
+
+                for (i, pin) in pins.iter().enumerate() {
+                    assert!(pins[i + 1..].iter().all(|x| x.name != pin.name && (x.iob.is_none() || x.iob != pin.iob)),
+                        "duplicate pin {} on {} {}", pin.name, device, package);
+
+                    if let Some(iob) = pin.iob {
+                        assert!((iob as usize) < device.num_iobs());
+                        assert_eq!(pin_name_to_iob_num(device, package, pin.name), Some(iob));
+                        assert_eq!(iob_num_to_pin_name(device, package, iob), Some(pin.name));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn iobs_bonded_once() {
+        for &device in ALL_DEVICES.iter() {
+            for &package in ALL_PACKAGES.iter() {
+                let pins = match get_package_pins(device, package) {
+                    Some(pins) => pins,
+                    None => continue,
+                };
+
+                for iob in 0..device.num_iobs() as u32 {
+                    let count = pins.iter().filter(|x| x.iob == Some(iob)).count();
+                    // The VQ44 and CP56 packages bond out every IOB (33 user I/O including the input-only pin)
+                    if package == XC2Package::VQ44 || package == XC2Package::CP56 {
+                        assert_eq!(count, 1, "IOB {} on {} {}", iob, device, package);
+                    } else {
+                        assert!(count <= 1, "IOB {} on {} {}", iob, device, package);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn special_pins_match_structure() {
+        for &device in ALL_DEVICES.iter() {
+            let mut specials = Vec::new();
+            for i in 0..NUM_BUFG_CLK {
+                specials.push((get_gck(device, i).unwrap(), XC2PinFunction::GCK(i as u32)));
+            }
+            for i in 0..NUM_BUFG_GTS {
+                specials.push((get_gts(device, i).unwrap(), XC2PinFunction::GTS(i as u32)));
+            }
+            specials.push((get_gsr(device), XC2PinFunction::GSR));
+            if let Some(cdrst) = get_cdrst(device) {
+                specials.push((cdrst, XC2PinFunction::CDRST));
+            }
+            if let Some(dge) = get_dge(device) {
+                specials.push((dge, XC2PinFunction::DGE));
+            }
+
+            for &package in ALL_PACKAGES.iter() {
+                let pins = match get_package_pins(device, package) {
+                    Some(pins) => pins,
+                    None => continue,
+                };
+
+                // Every global signal must be bonded out at the IOB that the structure says it is at
+                for &((fb, mc), function) in specials.iter() {
+                    let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+                    let pin = get_pin_by_iob(device, package, iob)
+                        .unwrap_or_else(|| panic!("{} not bonded out on {} {}", function, device, package));
+                    assert_eq!(pin.function, function);
+                }
+
+                // and no other pin may claim to be a global signal
+                for pin in pins.iter() {
+                    if let Some(iob) = pin.iob {
+                        let fb_mc = iob_num_to_fb_mc_num(device, iob).unwrap();
+                        let expected = specials.iter().find(|x| x.0 == fb_mc).map(|x| x.1)
+                            .unwrap_or(XC2PinFunction::IO);
+                        assert_eq!(pin.function, expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -25,6 +25,8 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Contains functions pertaining to the PLA
 
+use jedec::FuseRead;
+
 use *;
 
 /// Represents one single AND term in the PLA. Each AND term can perform an AND function on any subset of its inputs
@@ -53,15 +55,15 @@ impl Default for XC2PLAAndTerm {
 
 impl XC2PLAAndTerm {
     /// Internal function that reads one single AND term from a block of fuses using logical fuse indexing
-    pub fn from_jed(fuses: &[bool], block_idx: usize, term_idx: usize) -> XC2PLAAndTerm {
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(fuses: &F, block_idx: usize, term_idx: usize) -> XC2PLAAndTerm {
         let mut input = [0u8; INPUTS_PER_ANDTERM / 8];
         let mut input_b = [0u8; INPUTS_PER_ANDTERM / 8];
 
         for i in 0..INPUTS_PER_ANDTERM {
-            if !fuses[block_idx + term_idx * INPUTS_PER_ANDTERM * 2 + i * 2 + 0] {
+            if !fuses.get_fuse(block_idx + term_idx * INPUTS_PER_ANDTERM * 2 + i * 2 + 0) {
                 input[i / 8] |= 1 << (i % 8);
             }
-            if !fuses[block_idx + term_idx * INPUTS_PER_ANDTERM * 2 + i * 2 + 1] {
+            if !fuses.get_fuse(block_idx + term_idx * INPUTS_PER_ANDTERM * 2 + i * 2 + 1) {
                 input_b[i / 8] |= 1 << (i % 8);
             }
         }
@@ -122,11 +124,11 @@ impl Default for XC2PLAOrTerm {
 
 impl XC2PLAOrTerm {
     /// Internal function that reads one single OR term from a block of fuses using logical fuse indexing
-    pub fn from_jed(fuses: &[bool], block_idx: usize, term_idx: usize) -> XC2PLAOrTerm {
+    pub fn from_jed<F: FuseRead<usize> + ?Sized>(fuses: &F, block_idx: usize, term_idx: usize) -> XC2PLAOrTerm {
         let mut input = [0u8; ANDTERMS_PER_FB / 8];
 
         for i in 0..ANDTERMS_PER_FB {
-            if !fuses[block_idx + term_idx +i * MCS_PER_FB] {
+            if !fuses.get_fuse(block_idx + term_idx +i * MCS_PER_FB) {
                 input[i / 8] |= 1 << (i % 8);
             }
         }
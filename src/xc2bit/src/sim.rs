@@ -0,0 +1,593 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains a cycle-based functional simulator that runs directly from a bitstream
+//!
+//! The simulator evaluates the device at the level of its pins. Each call to `XC2Simulator::step` applies the inputs
+//! that have been set since the previous step and then evaluates the device until it settles, so inputs that are
+//! changed in the same step are seen by the device at the same time. Clocks are ordinary pin inputs, so toggling a
+//! clock pin takes two steps. Registers sample their inputs as they were before the clock edge, so changing the
+//! data and the clock of a register in the same step behaves as if the data changed just after the edge.
+//!
+//! As in the `hdl/xc2c-model` HDL model, macrocells and input buffers whose ZIA drivers are turned off feed a constant
+//! 0 into the ZIA, and outputs using PTB or CTE are tristated while that product term is high. The DataGATE feature is
+//! not modelled.
+
+use std::io;
+use std::io::Write;
+
+use *;
+use equations::iob_modes;
+
+/// Number of evaluation passes after which a step gives up on the device settling
+const MAX_SETTLE_PASSES: usize = 1000;
+
+/// State of the programmable clock divider
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct XC2SimClockDiv {
+    /// Value of the GCK2 pin the last time the divider was evaluated
+    last_clk: bool,
+    /// Number of input rising edges since the output last changed
+    count: u32,
+    /// Divided clock output
+    out: bool,
+    /// Whether the next toggle of the output is being swallowed by the "delay" feature
+    delay_pending: bool,
+}
+
+/// Values of all of the signals in the device that change over time
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct XC2SimState {
+    /// Output of the XOR gate of each macrocell
+    comb: Vec<bool>,
+    /// Output of the register of each macrocell
+    regs: Vec<bool>,
+    /// Value of the clock (or latch gate) of each register the last time it was evaluated
+    reg_clks: Vec<bool>,
+    /// Value of the clock enable of each register the last time it was evaluated
+    reg_ces: Vec<bool>,
+    /// Value driven onto each I/O pin by the device, or `None` if the output is not being driven
+    outputs: Vec<Option<bool>>,
+    /// Value seen by the device on each I/O pin
+    pads: Vec<bool>,
+    clock_div: XC2SimClockDiv,
+}
+
+/// A cycle-based functional simulator for a bitstream
+pub struct XC2Simulator {
+    bits: XC2BitstreamBits,
+    package: XC2Package,
+    /// Values applied to each I/O pin from outside of the device
+    inputs: Vec<bool>,
+    dedicated_input: bool,
+    state: XC2SimState,
+    time: u64,
+}
+
+impl XC2Simulator {
+    /// Creates a new simulator for the given bitstream. All inputs start out low and the device starts out in the
+    /// state that it is in right after it is configured.
+    pub fn new(bitstream: &XC2Bitstream) -> Self {
+        let device = bitstream.bits.device_type();
+        let num_mcs = device.num_fbs() * MCS_PER_FB;
+
+        let mut sim = XC2Simulator {
+            bits: bitstream.bits,
+            package: bitstream.package,
+            inputs: vec![false; device.num_iobs()],
+            dedicated_input: false,
+            state: XC2SimState {
+                comb: vec![false; num_mcs],
+                regs: vec![false; num_mcs],
+                reg_clks: vec![false; num_mcs],
+                reg_ces: vec![false; num_mcs],
+                outputs: vec![None; device.num_iobs()],
+                pads: vec![false; device.num_iobs()],
+                clock_div: XC2SimClockDiv {
+                    last_clk: false,
+                    count: 0,
+                    out: false,
+                    delay_pending: false,
+                },
+            },
+            time: 0,
+        };
+        sim.reset();
+        sim
+    }
+
+    /// Puts the device back into the state that it is in right after it is configured. Every register is set to its
+    /// power-up state and the clock divider is reset. The current inputs and the time are not changed.
+    pub fn reset(&mut self) {
+        for _ in 0..MAX_SETTLE_PASSES {
+            if !self.eval(true) {
+                break;
+            }
+        }
+    }
+
+    /// Returns the device that is being simulated
+    pub fn device_type(&self) -> XC2Device {
+        self.bits.device_type()
+    }
+
+    /// Returns the number of steps that have been simulated
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Sets the value applied to the given I/O pin from outside of the device. This takes effect on the next step.
+    /// The value is ignored while the device is driving the pin.
+    pub fn set_input(&mut self, iob: u32, val: bool) {
+        self.inputs[iob as usize] = val;
+    }
+
+    /// Sets the value applied to the given package pin from outside of the device. Returns `false` if the pin is not
+    /// an I/O pin or is not in the pin database.
+    pub fn set_pin(&mut self, name: &str, val: bool) -> bool {
+        match pin_name_to_iob_num(self.device_type(), self.package, name) {
+            Some(iob) => {
+                self.set_input(iob, val);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Sets the value applied to the dedicated input-only pin. This takes effect on the next step.
+    pub fn set_dedicated_input(&mut self, val: bool) {
+        self.dedicated_input = val;
+    }
+
+    /// Returns the value that the device is driving onto the given I/O pin, or `None` if it is not driving the pin
+    pub fn output(&self, iob: u32) -> Option<bool> {
+        self.state.outputs[iob as usize]
+    }
+
+    /// Returns the value that the device sees on the given I/O pin. This is the value the device is driving if it is
+    /// driving the pin, and otherwise the value applied from outside.
+    pub fn pad(&self, iob: u32) -> bool {
+        self.state.outputs[iob as usize].unwrap_or(self.inputs[iob as usize])
+    }
+
+    /// Returns the value of the dedicated input-only pin
+    pub fn dedicated_input(&self) -> bool {
+        self.dedicated_input
+    }
+
+    /// Returns the output of the XOR gate of the given macrocell
+    pub fn mc_comb(&self, fb: u32, mc: u32) -> bool {
+        self.state.comb[fb as usize * MCS_PER_FB + mc as usize]
+    }
+
+    /// Returns the output of the register of the given macrocell
+    pub fn mc_reg(&self, fb: u32, mc: u32) -> bool {
+        self.state.regs[fb as usize * MCS_PER_FB + mc as usize]
+    }
+
+    /// Applies the current inputs and evaluates the device until it settles. Returns `false` if the device did not
+    /// settle (for example because it contains a combinatorial loop that oscillates).
+    pub fn step(&mut self) -> bool {
+        self.time += 1;
+        for _ in 0..MAX_SETTLE_PASSES {
+            if !self.eval(false) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Evaluates every part of the device once using the signal values from the previous evaluation. If `power_on`
+    /// is set, registers are held in their power-up state and no clock edges are detected. Returns `true` if any
+    /// signal changed.
+    fn eval(&mut self, power_on: bool) -> bool {
+        let device = self.device_type();
+        let global_nets = *self.bits.get_global_nets();
+        let old = &self.state;
+        let pad = |iob: u32| old.outputs[iob as usize].unwrap_or(self.inputs[iob as usize]);
+        let pin_pad = |(fb, mc): (u32, u32)| pad(fb_mc_num_to_iob_num(device, fb, mc).unwrap());
+
+        // Clock divider
+        let mut clock_div = old.clock_div;
+        if let Some(clock_div_bits) = self.bits.get_clock_div() {
+            let clk = pin_pad(get_gck(device, 2).unwrap());
+            if power_on || pin_pad(get_cdrst(device).unwrap()) {
+                clock_div.count = 0;
+                clock_div.out = false;
+                clock_div.delay_pending = clock_div_bits.delay;
+            } else if clk && !clock_div.last_clk {
+                if clock_div.count == clock_div_bits.div_ratio.ratio() / 2 - 1 {
+                    clock_div.count = 0;
+                    if clock_div.delay_pending {
+                        clock_div.delay_pending = false;
+                    } else {
+                        clock_div.out = !clock_div.out;
+                    }
+                } else {
+                    clock_div.count += 1;
+                }
+            }
+            clock_div.last_clk = clk;
+        }
+
+        // Global nets. Disabled nets are held at 0.
+        let mut gck = [false; 3];
+        for (i, gck) in gck.iter_mut().enumerate() {
+            let clk = match self.bits.get_clock_div() {
+                Some(clock_div_bits) if i == 2 && clock_div_bits.enabled => old.clock_div.out,
+                _ => pin_pad(get_gck(device, i).unwrap()),
+            };
+            *gck = global_nets.gck_enable[i] && clk;
+        }
+        let gsr = global_nets.gsr_enable && pin_pad(get_gsr(device)) == global_nets.gsr_invert;
+        let mut gts = [false; 4];
+        for (i, gts) in gts.iter_mut().enumerate() {
+            *gts = global_nets.gts_enable[i] && pin_pad(get_gts(device, i).unwrap()) != global_nets.gts_invert[i];
+        }
+
+        let mut new = XC2SimState {
+            comb: old.comb.clone(),
+            regs: old.regs.clone(),
+            reg_clks: old.reg_clks.clone(),
+            reg_ces: old.reg_ces.clone(),
+            outputs: old.outputs.clone(),
+            pads: (0..device.num_iobs() as u32).map(&pad).collect(),
+            clock_div,
+        };
+
+        for fb in 0..device.num_fbs() {
+            let fb_bits = &self.bits.get_fb()[fb];
+
+            // ZIA
+            let mut zia = [false; INPUTS_PER_ANDTERM];
+            for (i, zia) in zia.iter_mut().enumerate() {
+                *zia = match *fb_bits.get_zia(i) {
+                    XC2ZIAInput::Zero => false,
+                    XC2ZIAInput::One => true,
+                    XC2ZIAInput::DedicatedInput => self.dedicated_input,
+                    XC2ZIAInput::Macrocell {fb, mc} => {
+                        let mc_idx = fb as usize * MCS_PER_FB + mc as usize;
+                        match self.bits.get_fb()[fb as usize].mcs[mc as usize].fb_mode {
+                            XC2MCFeedbackMode::Disabled => false,
+                            XC2MCFeedbackMode::COMB => old.comb[mc_idx],
+                            XC2MCFeedbackMode::REG => old.regs[mc_idx],
+                        }
+                    },
+                    XC2ZIAInput::IBuf {ibuf} => {
+                        let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+                        match iob_modes(&self.bits, ibuf as u32).0 {
+                            XC2IOBZIAMode::Disabled => false,
+                            XC2IOBZIAMode::PAD => pad(ibuf as u32),
+                            XC2IOBZIAMode::REG => old.regs[fb as usize * MCS_PER_FB + mc as usize],
+                        }
+                    },
+                };
+            }
+
+            // PLA AND array. An AND term with no inputs is a constant 1.
+            let mut pterms = [true; ANDTERMS_PER_FB];
+            for (term, pterm) in pterms.iter_mut().enumerate() {
+                let and_term = fb_bits.get_andterm(term);
+                for (i, &zia) in zia.iter().enumerate() {
+                    if (and_term.get(i) && !zia) || (and_term.get_b(i) && zia) {
+                        *pterm = false;
+                    }
+                }
+            }
+
+            for mc in 0..MCS_PER_FB {
+                let mc_bits = &fb_bits.mcs[mc];
+                let mc_idx = fb * MCS_PER_FB + mc;
+                let ptc = pterms[get_ptc(mc as u32) as usize];
+                let iob = fb_mc_num_to_iob_num(device, fb as u32, mc as u32);
+
+                // PLA OR array and XOR gate
+                let or_term = (0..ANDTERMS_PER_FB).any(|term| fb_bits.or_terms[mc].get(term) && pterms[term]);
+                new.comb[mc_idx] = or_term ^ match mc_bits.xor_mode {
+                    XC2MCXorMode::ZERO => false,
+                    XC2MCXorMode::ONE => true,
+                    XC2MCXorMode::PTC => ptc,
+                    XC2MCXorMode::PTCB => !ptc,
+                };
+
+                // Register
+                let clk = match mc_bits.clk_src {
+                    XC2MCRegClkSrc::GCK0 => gck[0],
+                    XC2MCRegClkSrc::GCK1 => gck[1],
+                    XC2MCRegClkSrc::GCK2 => gck[2],
+                    XC2MCRegClkSrc::PTC => ptc,
+                    XC2MCRegClkSrc::CTC => pterms[CTC as usize],
+                };
+                let set = match mc_bits.s_src {
+                    XC2MCRegSetSrc::Disabled => false,
+                    XC2MCRegSetSrc::PTA => pterms[get_pta(mc as u32) as usize],
+                    XC2MCRegSetSrc::GSR => gsr,
+                    XC2MCRegSetSrc::CTS => pterms[CTS as usize],
+                };
+                let reset = match mc_bits.r_src {
+                    XC2MCRegResetSrc::Disabled => false,
+                    XC2MCRegResetSrc::PTA => pterms[get_pta(mc as u32) as usize],
+                    XC2MCRegResetSrc::GSR => gsr,
+                    XC2MCRegResetSrc::CTR => pterms[CTR as usize],
+                };
+                let ce = mc_bits.reg_mode != XC2MCRegMode::DFFCE || ptc;
+                let imux = if mc_bits.ff_in_ibuf {
+                    iob.map_or(false, |iob| old.pads[iob as usize])
+                } else {
+                    old.comb[mc_idx]
+                };
+                let q = old.regs[mc_idx];
+                let d = if mc_bits.reg_mode == XC2MCRegMode::TFF {imux ^ q} else {imux};
+
+                let old_clk = old.reg_clks[mc_idx];
+                let triggered = if mc_bits.reg_mode == XC2MCRegMode::LATCH {
+                    clk != mc_bits.clk_invert_pol
+                } else {
+                    let rising = clk && !old_clk;
+                    let falling = !clk && old_clk;
+                    (rising && (mc_bits.is_ddr || !mc_bits.clk_invert_pol)) ||
+                        (falling && (mc_bits.is_ddr || mc_bits.clk_invert_pol))
+                };
+
+                // Reset has priority over set
+                new.regs[mc_idx] = if power_on {
+                    mc_bits.init_state
+                } else if reset {
+                    false
+                } else if set {
+                    true
+                } else if triggered && old.reg_ces[mc_idx] {
+                    d
+                } else {
+                    q
+                };
+                new.reg_clks[mc_idx] = clk;
+                new.reg_ces[mc_idx] = ce;
+
+                // Output buffer
+                if let Some(iob) = iob {
+                    let (_, obuf_uses_ff, obuf_mode) = iob_modes(&self.bits, iob);
+                    let data = if obuf_uses_ff {new.regs[mc_idx]} else {new.comb[mc_idx]};
+                    new.outputs[iob as usize] = match obuf_mode {
+                        XC2IOBOBufMode::Disabled => None,
+                        XC2IOBOBufMode::PushPull => Some(data),
+                        XC2IOBOBufMode::OpenDrain => if data {None} else {Some(false)},
+                        XC2IOBOBufMode::TriStateGTS0 => if gts[0] {None} else {Some(data)},
+                        XC2IOBOBufMode::TriStateGTS1 => if gts[1] {None} else {Some(data)},
+                        XC2IOBOBufMode::TriStateGTS2 => if gts[2] {None} else {Some(data)},
+                        XC2IOBOBufMode::TriStateGTS3 => if gts[3] {None} else {Some(data)},
+                        XC2IOBOBufMode::TriStatePTB => if pterms[get_ptb(mc as u32) as usize] {None} else {Some(data)},
+                        XC2IOBOBufMode::TriStateCTE => if pterms[CTE as usize] {None} else {Some(data)},
+                        XC2IOBOBufMode::CGND => Some(false),
+                    };
+                }
+            }
+        }
+
+        let changed = new != self.state;
+        self.state = new;
+        changed
+    }
+}
+
+/// Returns the name used for the given I/O pin in VCD files. This is the package pin name if it is known.
+fn vcd_pin_name(device: XC2Device, package: XC2Package, iob: u32) -> String {
+    match iob_num_to_pin_name(device, package, iob) {
+        Some(pin_name) => pin_name.to_owned(),
+        None => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+            format!("FB{}_{}_PIN", fb + 1, mc + 1)
+        }
+    }
+}
+
+/// Returns the VCD identifier code for the `n`th variable
+fn vcd_id(mut n: usize) -> String {
+    // Identifier codes can use any printable ASCII character
+    let mut ret = String::new();
+    loop {
+        ret.push((b'!' + (n % 94) as u8) as char);
+        n /= 94;
+        if n == 0 {
+            return ret;
+        }
+        n -= 1;
+    }
+}
+
+/// Writes the signals of a simulation to a Value Change Dump (VCD) file. Each step of the simulator is one time unit.
+/// The file contains the value on every I/O pin as seen by the device, the dedicated input, and the XOR gate and
+/// register output of every macrocell.
+pub struct XC2VCDWriter<W: Write> {
+    writer: W,
+    /// Last values that were written for each variable
+    values: Vec<char>,
+}
+
+impl<W: Write> XC2VCDWriter<W> {
+    /// Writes the VCD header and the initial values of all of the signals in `sim` to the given `writer` object
+    pub fn new(sim: &XC2Simulator, mut writer: W) -> Result<Self, io::Error> {
+        let device = sim.device_type();
+
+        write!(writer, "$version xc2bit simulator $end\n")?;
+        write!(writer, "$timescale 1ns $end\n")?;
+        write!(writer, "$scope module {} $end\n", device)?;
+        let mut var_i = 0;
+        for iob in 0..device.num_iobs() as u32 {
+            write!(writer, "$var wire 1 {} {} $end\n", vcd_id(var_i), vcd_pin_name(device, sim.package, iob))?;
+            var_i += 1;
+        }
+        write!(writer, "$var wire 1 {} INPUT_ONLY $end\n", vcd_id(var_i))?;
+        var_i += 1;
+        for fb in 0..device.num_fbs() {
+            for mc in 0..MCS_PER_FB {
+                write!(writer, "$var wire 1 {} FB{}_{} $end\n", vcd_id(var_i), fb + 1, mc + 1)?;
+                write!(writer, "$var reg 1 {} FB{}_{}_Q $end\n", vcd_id(var_i + 1), fb + 1, mc + 1)?;
+                var_i += 2;
+            }
+        }
+        write!(writer, "$upscope $end\n")?;
+        write!(writer, "$enddefinitions $end\n")?;
+
+        let mut vcd = XC2VCDWriter {
+            writer,
+            values: Vec::new(),
+        };
+        let values = vcd.sample(sim);
+        write!(vcd.writer, "#{}\n", sim.time())?;
+        write!(vcd.writer, "$dumpvars\n")?;
+        for (i, &val) in values.iter().enumerate() {
+            write!(vcd.writer, "{}{}\n", val, vcd_id(i))?;
+        }
+        write!(vcd.writer, "$end\n")?;
+        vcd.values = values;
+
+        Ok(vcd)
+    }
+
+    /// Returns the current values of all of the variables
+    fn sample(&self, sim: &XC2Simulator) -> Vec<char> {
+        let device = sim.device_type();
+        let bit = |x: bool| if x {'1'} else {'0'};
+
+        let mut ret = Vec::new();
+        for iob in 0..device.num_iobs() as u32 {
+            ret.push(bit(sim.pad(iob)));
+        }
+        ret.push(bit(sim.dedicated_input()));
+        for fb in 0..device.num_fbs() as u32 {
+            for mc in 0..MCS_PER_FB as u32 {
+                ret.push(bit(sim.mc_comb(fb, mc)));
+                ret.push(bit(sim.mc_reg(fb, mc)));
+            }
+        }
+        ret
+    }
+
+    /// Writes the values of the signals in `sim` that have changed since they were last written
+    pub fn dump(&mut self, sim: &XC2Simulator) -> Result<(), io::Error> {
+        let values = self.sample(sim);
+        let changes = values.iter().zip(self.values.iter()).enumerate()
+            .filter(|&(_, (new, old))| new != old).collect::<Vec<_>>();
+        if !changes.is_empty() {
+            write!(self.writer, "#{}\n", sim.time())?;
+            for (i, (val, _)) in changes {
+                write!(self.writer, "{}{}\n", val, vcd_id(i))?;
+            }
+        }
+        self.values = values;
+
+        Ok(())
+    }
+
+    /// Returns the underlying writer object
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a ZIA row of the given device that can select the given input
+    fn zia_row(device: XC2Device, input: XC2ZIAInput) -> usize {
+        (0..INPUTS_PER_ANDTERM).find(|&row| zia_table_get_row(device, row).contains(&input)).unwrap()
+    }
+
+    /// Returns a bitstream in which FB1_9 drives the complement of pin P38 (the pin of FB1_1) onto pin P29
+    fn inverter() -> XC2Bitstream {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_mut_small_iob(0).unwrap().zia_mode = XC2IOBZIAMode::PAD;
+            bits.get_mut_small_iob(8).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+
+            let row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::IBuf{ibuf: 0});
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(row) = XC2ZIAInput::IBuf{ibuf: 0};
+            fbs[0].get_mut_andterm(20).set(row, true);
+            fbs[0].or_terms[8].set(20, true);
+            fbs[0].mcs[8].xor_mode = XC2MCXorMode::ONE;
+        }
+        bitstream
+    }
+
+    #[test]
+    fn pins() {
+        let mut sim = XC2Simulator::new(&inverter());
+        assert_eq!(sim.output(8), Some(true));
+        assert_eq!(sim.output(0), None);
+
+        assert!(sim.set_pin("P38", true));
+        assert!(!sim.set_pin("P4", true));
+        assert!(sim.step());
+        assert_eq!(sim.time(), 1);
+        assert!(sim.pad(0));
+        assert!(!sim.mc_comb(0, 8));
+        assert_eq!(sim.output(8), Some(false));
+    }
+
+    #[test]
+    fn oscillation() {
+        // FB1_1 = !FB1_1
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::Macrocell{fb: 0, mc: 0});
+            let fbs = bitstream.bits.get_fb_mut();
+            *fbs[0].get_mut_zia(row) = XC2ZIAInput::Macrocell{fb: 0, mc: 0};
+            fbs[0].get_mut_andterm(20).set_b(row, true);
+            fbs[0].or_terms[0].set(20, true);
+            fbs[0].mcs[0].fb_mode = XC2MCFeedbackMode::COMB;
+        }
+
+        let mut sim = XC2Simulator::new(&bitstream);
+        assert!(!sim.step());
+    }
+
+    #[test]
+    fn vcd() {
+        let mut sim = XC2Simulator::new(&inverter());
+        let mut vcd = XC2VCDWriter::new(&sim, Vec::new()).unwrap();
+        let header_len = vcd.writer.len();
+        vcd.dump(&sim).unwrap();
+        assert_eq!(vcd.writer.len(), header_len);
+
+        sim.set_input(0, true);
+        sim.step();
+        vcd.dump(&sim).unwrap();
+
+        let vcd = String::from_utf8(vcd.into_inner()).unwrap();
+        assert!(vcd.starts_with("$version xc2bit simulator $end\n$timescale 1ns $end\n$scope module XC2C32A $end\n\
+            $var wire 1 ! P38 $end\n"));
+        assert!(vcd.contains("$var wire 1 R FB1_9 $end\n$var reg 1 S FB1_9_Q $end\n"));
+        assert!(vcd.contains("$enddefinitions $end\n#0\n$dumpvars\n0!\n"));
+        assert!(vcd.ends_with("$end\n#1\n1!\n0)\n0R\n"));
+
+        assert_eq!(vcd_id(93), "~");
+        assert_eq!(vcd_id(94), "!!");
+    }
+}
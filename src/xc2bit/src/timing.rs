@@ -0,0 +1,972 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains a timing model of the device and a static timing analyser that works directly on a bitstream
+//!
+//! Timing is described using the internal timing parameters from the timing model in the Coolrunner-II data sheets.
+//! The parameter values in this module are approximate and should not be used for sign-off. The XC2C32A -4 values are
+//! chosen so that tpd1, tsu, tco and fsystem come out as the 3.8 ns, 1.7 ns, 3.7 ns and 323 MHz given in its data
+//! sheet. The values for the other parts have not been checked against their data sheets, and the XC2C256 and
+//! XC2C512 do not have tables of their own yet (they use the XC2C128 and XC2C384 values respectively). All times are
+//! in picoseconds.
+//!
+//! The analyser finds the longest path from every start point (an input pin or the clock of a register) to every end
+//! point (an output pin or the inputs of a register). Product terms pay a single product term delay when they drive
+//! the XOR gate on their own and the PLA delay otherwise. Control terms, product term clocks, asynchronous set/reset
+//! and output enables each have their own delays. Latches are analysed as if they were flip-flops clocked by their
+//! gate. Combinatorial loops are broken at the point where they are first found, and paths between registers in
+//! different clock domains are not analysed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use *;
+use equations::{iob_modes, reg_used, zia_signal};
+
+/// Internal timing parameters of one device and speed grade. The names follow the data sheets, and all times are in
+/// picoseconds.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2TimingParams {
+    /// Input buffer delay
+    pub t_in: u32,
+    /// Delay from a pin to the register of its macrocell when the register is used as an input register
+    pub t_din: u32,
+    /// Global clock buffer delay
+    pub t_gck: u32,
+    /// Global set/reset buffer delay
+    pub t_gsr: u32,
+    /// Global tristate buffer delay
+    pub t_gts: u32,
+    /// Output buffer delay with fast slew rate
+    pub t_out: u32,
+    /// Output buffer enable/disable delay
+    pub t_en: u32,
+    /// Additional output buffer delay with slow slew rate
+    pub t_slew: u32,
+    /// Additional input buffer delay with the Schmitt trigger enabled
+    pub t_hys: u32,
+    /// ZIA delay (called the feedback delay in the data sheets)
+    pub t_zia: u32,
+    /// Control term delay
+    pub t_ct: u32,
+    /// Logic delay through a single product term
+    pub t_logi1: u32,
+    /// Logic delay through the PLA
+    pub t_logi2: u32,
+    /// Macrocell combinatorial delay
+    pub t_pdi: u32,
+    /// Register setup time
+    pub t_sui: u32,
+    /// Register hold time
+    pub t_hi: u32,
+    /// Register clock-to-output delay
+    pub t_coi: u32,
+    /// Register clock enable setup time
+    pub t_ecsu: u32,
+    /// Delay from asynchronous set/reset to register output
+    pub t_aoi: u32,
+    /// Product term clock delay
+    pub t_ptck: u32,
+    /// Product term set/reset delay
+    pub t_ptsr: u32,
+    /// Product term output enable delay
+    pub t_ptts: u32,
+}
+
+impl XC2TimingParams {
+    /// Pin-to-pin delay through a single product term
+    pub fn tpd1(&self) -> u32 {
+        self.t_in + self.t_zia + self.t_logi1 + self.t_pdi + self.t_out
+    }
+
+    /// Pin-to-pin delay through the PLA
+    pub fn tpd2(&self) -> u32 {
+        self.t_in + self.t_zia + self.t_logi2 + self.t_pdi + self.t_out
+    }
+
+    /// Setup time of a single product term input relative to a global clock pin
+    pub fn tsu(&self) -> u32 {
+        (self.t_in + self.t_zia + self.t_logi1 + self.t_pdi + self.t_sui).saturating_sub(self.t_gck)
+    }
+
+    /// Global clock pin to output delay
+    pub fn tco(&self) -> u32 {
+        self.t_gck + self.t_coi + self.t_out
+    }
+
+    /// Setup time of a single product term input relative to a product term clock driven from a pin
+    pub fn tsu_ptc(&self) -> u32 {
+        (self.t_logi1 + self.t_pdi + self.t_sui).saturating_sub(self.t_ptck)
+    }
+
+    /// Product term clock pin to output delay
+    pub fn tco_ptc(&self) -> u32 {
+        self.t_in + self.t_zia + self.t_ptck + self.t_coi + self.t_out
+    }
+
+    /// Maximum frequency in MHz of a register that feeds back into itself through a single product term
+    pub fn fsystem(&self) -> f64 {
+        1_000_000.0 / (self.t_coi + self.t_zia + self.t_logi1 + self.t_pdi + self.t_sui) as f64
+    }
+}
+
+static TIMING_XC2C32_4: XC2TimingParams = XC2TimingParams {
+    t_in: 1000, t_din: 1300, t_gck: 1300, t_gsr: 1800, t_gts: 2000, t_out: 1300,
+    t_en: 2000, t_slew: 3500, t_hys: 1500, t_zia: 800, t_ct: 1000, t_logi1: 400,
+    t_logi2: 600, t_pdi: 300, t_sui: 500, t_hi: 0, t_coi: 1100, t_ecsu: 1000,
+    t_aoi: 1400, t_ptck: 1000, t_ptsr: 1000, t_ptts: 1600,
+};
+
+static TIMING_XC2C32_6: XC2TimingParams = XC2TimingParams {
+    t_in: 1400, t_din: 1900, t_gck: 1900, t_gsr: 2600, t_gts: 2900, t_out: 2000,
+    t_en: 2900, t_slew: 5100, t_hys: 2200, t_zia: 1000, t_ct: 1400, t_logi1: 600,
+    t_logi2: 900, t_pdi: 400, t_sui: 1400, t_hi: 0, t_coi: 1400, t_ecsu: 1400,
+    t_aoi: 2000, t_ptck: 1400, t_ptsr: 1400, t_ptts: 2300,
+};
+
+static TIMING_XC2C64_5: XC2TimingParams = XC2TimingParams {
+    t_in: 1200, t_din: 1600, t_gck: 1600, t_gsr: 2200, t_gts: 2400, t_out: 1700,
+    t_en: 2400, t_slew: 4200, t_hys: 1800, t_zia: 800, t_ct: 1200, t_logi1: 500,
+    t_logi2: 700, t_pdi: 400, t_sui: 1200, t_hi: 0, t_coi: 1200, t_ecsu: 1200,
+    t_aoi: 1700, t_ptck: 1200, t_ptsr: 1200, t_ptts: 1900,
+};
+
+static TIMING_XC2C64_7: XC2TimingParams = XC2TimingParams {
+    t_in: 1800, t_din: 2300, t_gck: 2300, t_gsr: 3200, t_gts: 3500, t_out: 2500,
+    t_en: 3500, t_slew: 6200, t_hys: 2600, t_zia: 1200, t_ct: 1800, t_logi1: 700,
+    t_logi2: 1100, t_pdi: 500, t_sui: 1800, t_hi: 0, t_coi: 1800, t_ecsu: 1800,
+    t_aoi: 2500, t_ptck: 1800, t_ptsr: 1800, t_ptts: 2800,
+};
+
+static TIMING_XC2C128_6: XC2TimingParams = XC2TimingParams {
+    t_in: 1500, t_din: 2000, t_gck: 2000, t_gsr: 2700, t_gts: 3000, t_out: 2100,
+    t_en: 3000, t_slew: 5300, t_hys: 2300, t_zia: 1100, t_ct: 1500, t_logi1: 600,
+    t_logi2: 900, t_pdi: 500, t_sui: 1500, t_hi: 0, t_coi: 1500, t_ecsu: 1500,
+    t_aoi: 2100, t_ptck: 1500, t_ptsr: 1500, t_ptts: 2400,
+};
+
+static TIMING_XC2C128_7: XC2TimingParams = XC2TimingParams {
+    t_in: 1800, t_din: 2400, t_gck: 2400, t_gsr: 3300, t_gts: 3700, t_out: 2600,
+    t_en: 3700, t_slew: 6400, t_hys: 2800, t_zia: 1300, t_ct: 1800, t_logi1: 700,
+    t_logi2: 1100, t_pdi: 600, t_sui: 1800, t_hi: 0, t_coi: 1800, t_ecsu: 1800,
+    t_aoi: 2600, t_ptck: 1800, t_ptsr: 1800, t_ptts: 2900,
+};
+
+static TIMING_XC2C384_7: XC2TimingParams = XC2TimingParams {
+    t_in: 1900, t_din: 2400, t_gck: 2400, t_gsr: 3400, t_gts: 3700, t_out: 2600,
+    t_en: 3700, t_slew: 6500, t_hys: 2800, t_zia: 1300, t_ct: 1900, t_logi1: 700,
+    t_logi2: 1100, t_pdi: 600, t_sui: 1900, t_hi: 0, t_coi: 1900, t_ecsu: 1900,
+    t_aoi: 2600, t_ptck: 1900, t_ptsr: 1900, t_ptts: 3000,
+};
+
+static TIMING_XC2C384_10: XC2TimingParams = XC2TimingParams {
+    t_in: 2400, t_din: 3100, t_gck: 3100, t_gsr: 4400, t_gts: 4800, t_out: 3400,
+    t_en: 4800, t_slew: 8500, t_hys: 3600, t_zia: 1700, t_ct: 2400, t_logi1: 1000,
+    t_logi2: 1500, t_pdi: 700, t_sui: 2400, t_hi: 0, t_coi: 2400, t_ecsu: 2400,
+    t_aoi: 3400, t_ptck: 2400, t_ptsr: 2400, t_ptts: 3900,
+};
+
+/// Returns the timing parameters of the given device and speed grade, or `None` if the device is not available in
+/// that speed grade
+pub fn get_timing_params(device: XC2Device, speed: XC2Speed) -> Option<XC2TimingParams> {
+    match (device, speed) {
+        (XC2Device::XC2C32, XC2Speed::Speed4) | (XC2Device::XC2C32A, XC2Speed::Speed4) => Some(TIMING_XC2C32_4),
+        (XC2Device::XC2C32, XC2Speed::Speed6) | (XC2Device::XC2C32A, XC2Speed::Speed6) => Some(TIMING_XC2C32_6),
+        (XC2Device::XC2C64, XC2Speed::Speed5) | (XC2Device::XC2C64A, XC2Speed::Speed5) => Some(TIMING_XC2C64_5),
+        (XC2Device::XC2C64, XC2Speed::Speed7) | (XC2Device::XC2C64A, XC2Speed::Speed7) => Some(TIMING_XC2C64_7),
+        // FIXME: The XC2C256 and XC2C512 borrow the values of the next smaller part
+        (XC2Device::XC2C128, XC2Speed::Speed6) | (XC2Device::XC2C256, XC2Speed::Speed6) => Some(TIMING_XC2C128_6),
+        (XC2Device::XC2C128, XC2Speed::Speed7) | (XC2Device::XC2C256, XC2Speed::Speed7) => Some(TIMING_XC2C128_7),
+        (XC2Device::XC2C384, XC2Speed::Speed7) | (XC2Device::XC2C512, XC2Speed::Speed7) => Some(TIMING_XC2C384_7),
+        (XC2Device::XC2C384, XC2Speed::Speed10) | (XC2Device::XC2C512, XC2Speed::Speed10) => Some(TIMING_XC2C384_10),
+        _ => None,
+    }
+}
+
+/// Formats a time in picoseconds as nanoseconds
+fn format_ns(time: u32) -> String {
+    format!("{:.1} ns", time as f64 / 1000.0)
+}
+
+/// A point in the device where a timing path can start or end
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2TimingPoint {
+    /// The I/O pin corresponding to a macrocell
+    Pin {
+        fb: u32,
+        mc: u32,
+    },
+    /// The dedicated input-only pin
+    DedicatedInput,
+    /// The register in a macrocell. Paths that start here are launched by its clock, and paths that end here are
+    /// captured by its clock.
+    Reg {
+        fb: u32,
+        mc: u32,
+    },
+}
+
+impl fmt::Display for XC2TimingPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2TimingPoint::Pin {fb, mc} => write!(f, "FB{}_{}_PIN", fb + 1, mc + 1),
+            XC2TimingPoint::DedicatedInput => write!(f, "INPUT_ONLY"),
+            XC2TimingPoint::Reg {fb, mc} => write!(f, "FB{}_{}.Q", fb + 1, mc + 1),
+        }
+    }
+}
+
+/// The longest path between two points in the device
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2TimingPath {
+    pub start: XC2TimingPoint,
+    pub end: XC2TimingPoint,
+    /// The macrocell outputs that the path goes through on its way from `start` to `end`
+    pub through: Vec<XC2EqnSignal>,
+    /// The delay of the path. What this includes depends on the kind of path (see `XC2TimingReport`).
+    pub delay: u32,
+}
+
+impl XC2TimingPath {
+    /// Formats the points along this path using `name` to get the name of the start and end points
+    pub fn format<F: Fn(XC2TimingPoint) -> String>(&self, name: &F) -> String {
+        let mut points = vec![name(self.start)];
+        points.extend(self.through.iter().map(|x| x.to_string()));
+        points.push(name(self.end));
+        points.join(" -> ")
+    }
+}
+
+impl fmt::Display for XC2TimingPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.format(&|x| x.to_string()), format_ns(self.delay))
+    }
+}
+
+/// A clock that registers can be clocked by
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2TimingClock {
+    /// The global clock net GCKn
+    GCK(u32),
+    /// The global clock net GCK2 after the clock divider
+    GCK2Div,
+    /// The special product term C of a macrocell
+    PTC {
+        fb: u32,
+        mc: u32,
+    },
+    /// The control term clock of a function block
+    CTC {
+        fb: u32,
+    },
+}
+
+impl fmt::Display for XC2TimingClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XC2TimingClock::GCK(i) => write!(f, "GCK{}", i),
+            XC2TimingClock::GCK2Div => write!(f, "GCK2 (divided)"),
+            XC2TimingClock::PTC {fb, mc} => write!(f, "FB{}_{} PTC", fb + 1, mc + 1),
+            XC2TimingClock::CTC {fb} => write!(f, "FB{} CTC", fb + 1),
+        }
+    }
+}
+
+/// The registers clocked by one clock and the longest path between them
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2ClockDomain {
+    pub clock: XC2TimingClock,
+    /// The registers in this clock domain as (function block, macrocell) pairs
+    pub registers: Vec<(u32, u32)>,
+    /// The path that limits the clock period, or `None` if there are no paths between registers in this domain. The
+    /// delay of the path includes the clock-to-output delay of the launching register and the setup time of the
+    /// capturing register.
+    pub critical_path: Option<XC2TimingPath>,
+    /// The minimum clock period. This is twice the delay of the critical path if the path is launched and captured
+    /// on opposite clock edges.
+    pub min_period: Option<u32>,
+}
+
+impl XC2ClockDomain {
+    /// Returns the maximum clock frequency of this domain in MHz, or `None` if it is not limited by any path
+    pub fn fmax(&self) -> Option<f64> {
+        self.min_period.map(|period| 1_000_000.0 / period as f64)
+    }
+}
+
+/// The result of static timing analysis. Paths are sorted from longest to shortest.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct XC2TimingReport {
+    pub device: XC2Device,
+    pub params: XC2TimingParams,
+    /// Combinatorial delays from input pins to output pins. This includes paths through output enables and through
+    /// asynchronous set/reset of registers.
+    pub pin_to_pin: Vec<XC2TimingPath>,
+    /// Setup times of input pins relative to the clocks of the registers that they reach. This is the data delay
+    /// plus the setup time of the register, minus the delay of the clock (but no less than zero).
+    pub setup: Vec<XC2TimingPath>,
+    /// Delays from the clock pins of registers to output pins, including the delay of the clock
+    pub clock_to_out: Vec<XC2TimingPath>,
+    pub clock_domains: Vec<XC2ClockDomain>,
+}
+
+impl XC2TimingReport {
+    /// Dump a human-readable version of the timing report to the given `writer` object. Pins are named after the
+    /// pins of the given package where they are known.
+    pub fn dump_human_readable<W: Write>(&self, package: XC2Package, mut writer: W) -> Result<(), io::Error> {
+        let device = self.device;
+        let name = |point: XC2TimingPoint| match point {
+            XC2TimingPoint::Pin {fb, mc} => {
+                let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+                match iob_num_to_pin_name(device, package, iob) {
+                    Some(pin_name) => pin_name.to_owned(),
+                    None => point.to_string(),
+                }
+            },
+            _ => point.to_string(),
+        };
+
+        write!(writer, "timing report for {}\n", device)?;
+        write!(writer, "tpd1 {}, tpd2 {}, tsu {}, tco {}, fsystem {:.1} MHz\n", format_ns(self.params.tpd1()),
+            format_ns(self.params.tpd2()), format_ns(self.params.tsu()), format_ns(self.params.tco()),
+            self.params.fsystem())?;
+
+        write!(writer, "\npin-to-pin delays:\n")?;
+        for path in &self.pin_to_pin {
+            write!(writer, "{}: {}\n", path.format(&name), format_ns(path.delay))?;
+        }
+
+        write!(writer, "\nsetup times:\n")?;
+        for path in &self.setup {
+            write!(writer, "{}: {}\n", path.format(&name), format_ns(path.delay))?;
+        }
+
+        write!(writer, "\nclock-to-output delays:\n")?;
+        for path in &self.clock_to_out {
+            write!(writer, "{}: {}\n", path.format(&name), format_ns(path.delay))?;
+        }
+
+        write!(writer, "\nclock domains:\n")?;
+        for domain in &self.clock_domains {
+            write!(writer, "{}: {} register{}", domain.clock, domain.registers.len(),
+                if domain.registers.len() == 1 {""} else {"s"})?;
+            match (domain.fmax(), &domain.critical_path) {
+                (Some(fmax), &Some(ref path)) => {
+                    write!(writer, ", fmax {:.1} MHz\n", fmax)?;
+                    write!(writer, "critical path {}: {}\n", path.format(&name), format_ns(path.delay))?;
+                },
+                _ => write!(writer, ", no register-to-register paths\n")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A node in the device whose arrival times are remembered during analysis
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum XC2TimingNode {
+    /// The output of the XOR gate of a macrocell
+    Comb(usize),
+    /// The output of the register of a macrocell
+    Reg(usize),
+}
+
+/// The latest arrival time at some point from each start point, along with the nodes that the path went through
+type XC2Arrivals = HashMap<XC2TimingPoint, (u32, Vec<XC2TimingNode>)>;
+
+/// Adds the arrival times in `from` delayed by `delay` into `into`, keeping the latest arrival from each start point
+fn merge_arrivals(into: &mut XC2Arrivals, from: &XC2Arrivals, delay: u32) {
+    for (&start, &(time, ref nodes)) in from {
+        let time = time + delay;
+        let later = match into.get(&start) {
+            Some(&(into_time, _)) => time > into_time,
+            None => true,
+        };
+        if later {
+            into.insert(start, (time, nodes.clone()));
+        }
+    }
+}
+
+/// Returns the clock of the register in the given macrocell
+fn reg_clock(bits: &XC2BitstreamBits, fb: u32, mc: u32) -> XC2TimingClock {
+    match bits.get_fb()[fb as usize].mcs[mc as usize].clk_src {
+        XC2MCRegClkSrc::GCK0 => XC2TimingClock::GCK(0),
+        XC2MCRegClkSrc::GCK1 => XC2TimingClock::GCK(1),
+        XC2MCRegClkSrc::GCK2 => match bits.get_clock_div() {
+            Some(clock_div) if clock_div.enabled => XC2TimingClock::GCK2Div,
+            _ => XC2TimingClock::GCK(2),
+        },
+        XC2MCRegClkSrc::PTC => XC2TimingClock::PTC {fb, mc},
+        XC2MCRegClkSrc::CTC => XC2TimingClock::CTC {fb},
+    }
+}
+
+/// Keeps track of the arrival times at every node while the analysis is running
+struct XC2TimingAnalyser<'a> {
+    bits: &'a XC2BitstreamBits,
+    params: &'a XC2TimingParams,
+    /// Arrival times of nodes that are not affected by a combinatorial loop that was broken somewhere else
+    arrivals: HashMap<XC2TimingNode, XC2Arrivals>,
+    /// Nodes whose arrival times are currently being computed, used to break combinatorial loops
+    visiting: Vec<XC2TimingNode>,
+    /// The lowest index into `visiting` of a node where a combinatorial loop was broken while computing the arrival
+    /// times of the current node. Arrival times that are missing the paths through such a node are not remembered.
+    loop_depth: usize,
+}
+
+impl<'a> XC2TimingAnalyser<'a> {
+    /// Returns the input buffer delay of the given pin, including the Schmitt trigger if it is enabled
+    fn ibuf_delay(&self, iob: Option<u32>) -> u32 {
+        let schmitt_trigger = match iob {
+            Some(iob) => match self.bits.get_small_iob(iob as usize) {
+                Some(iob) => iob.schmitt_trigger,
+                None => match self.bits.get_large_iob(iob as usize).unwrap().ibuf_mode {
+                    XC2IOBIbufMode::NoVrefSt | XC2IOBIbufMode::UsesVref => true,
+                    XC2IOBIbufMode::NoVrefNoSt | XC2IOBIbufMode::IsVref => false,
+                },
+            },
+            None => match *self.bits {
+                XC2BitstreamBits::XC2C32 {ref inpin, ..} | XC2BitstreamBits::XC2C32A {ref inpin, ..} =>
+                    inpin.schmitt_trigger,
+                _ => false,
+            },
+        };
+
+        self.params.t_in + if schmitt_trigger {self.params.t_hys} else {0}
+    }
+
+    /// Returns the arrival times at the given macrocell output
+    fn node(&mut self, node: XC2TimingNode) -> XC2Arrivals {
+        if let Some(arrivals) = self.arrivals.get(&node) {
+            return arrivals.clone();
+        }
+        if let Some(depth) = self.visiting.iter().position(|&x| x == node) {
+            self.loop_depth = self.loop_depth.min(depth);
+            return XC2Arrivals::new();
+        }
+
+        let outer_loop_depth = self.loop_depth;
+        self.loop_depth = usize::MAX;
+        self.visiting.push(node);
+        let params = self.params;
+        let mut arrivals = XC2Arrivals::new();
+        match node {
+            XC2TimingNode::Comb(mc_idx) => {
+                let fb = (mc_idx / MCS_PER_FB) as u32;
+                let mc = (mc_idx % MCS_PER_FB) as u32;
+                let fb_bits = &self.bits.get_fb()[fb as usize];
+
+                let terms = (0..ANDTERMS_PER_FB).filter(|&term| fb_bits.or_terms[mc as usize].get(term))
+                    .collect::<Vec<_>>();
+                let logi = if terms.len() > 1 {params.t_logi2} else {params.t_logi1};
+                for term in terms {
+                    let term_arrivals = self.term(fb, term as u32);
+                    merge_arrivals(&mut arrivals, &term_arrivals, logi + params.t_pdi);
+                }
+                match fb_bits.mcs[mc as usize].xor_mode {
+                    XC2MCXorMode::PTC | XC2MCXorMode::PTCB => {
+                        let term_arrivals = self.term(fb, get_ptc(mc));
+                        merge_arrivals(&mut arrivals, &term_arrivals, params.t_logi1 + params.t_pdi);
+                    },
+                    XC2MCXorMode::ZERO | XC2MCXorMode::ONE => {},
+                }
+            },
+            XC2TimingNode::Reg(mc_idx) => {
+                let fb = (mc_idx / MCS_PER_FB) as u32;
+                let mc = (mc_idx % MCS_PER_FB) as u32;
+                let mc_bits = &self.bits.get_fb()[fb as usize].mcs[mc as usize];
+
+                arrivals.insert(XC2TimingPoint::Reg {fb, mc}, (params.t_coi, Vec::new()));
+
+                // Asynchronous set and reset
+                let pta = || (Some(get_pta(mc)), params.t_ptsr);
+                let sources = [
+                    match mc_bits.s_src {
+                        XC2MCRegSetSrc::Disabled => (None, 0),
+                        XC2MCRegSetSrc::PTA => pta(),
+                        XC2MCRegSetSrc::CTS => (Some(CTS), params.t_ct),
+                        XC2MCRegSetSrc::GSR => (None, 0),
+                    },
+                    match mc_bits.r_src {
+                        XC2MCRegResetSrc::Disabled => (None, 0),
+                        XC2MCRegResetSrc::PTA => pta(),
+                        XC2MCRegResetSrc::CTR => (Some(CTR), params.t_ct),
+                        XC2MCRegResetSrc::GSR => (None, 0),
+                    },
+                ];
+                let uses_gsr = mc_bits.s_src == XC2MCRegSetSrc::GSR || mc_bits.r_src == XC2MCRegResetSrc::GSR;
+                for &(term, delay) in &sources {
+                    if let Some(term) = term {
+                        let term_arrivals = self.term(fb, term);
+                        merge_arrivals(&mut arrivals, &term_arrivals, delay + params.t_aoi);
+                    }
+                }
+                if uses_gsr && self.bits.get_global_nets().gsr_enable {
+                    let (fb, mc) = get_gsr(self.bits.device_type());
+                    let mut gsr = XC2Arrivals::new();
+                    gsr.insert(XC2TimingPoint::Pin {fb, mc}, (params.t_gsr + params.t_aoi, Vec::new()));
+                    merge_arrivals(&mut arrivals, &gsr, 0);
+                }
+            },
+        }
+        self.visiting.pop();
+
+        // A loop that was broken at this node or at one of its inputs does not leave anything out of its arrival
+        // times, but a loop that was broken further up leaves out the paths through that node
+        if self.loop_depth >= self.visiting.len() {
+            self.arrivals.insert(node, arrivals.clone());
+        }
+        self.loop_depth = self.loop_depth.min(outer_loop_depth);
+        arrivals
+    }
+
+    /// Returns the arrival times at the given ZIA signal
+    fn signal(&mut self, signal: XC2EqnSignal) -> XC2Arrivals {
+        let device = self.bits.device_type();
+        let mut arrivals = XC2Arrivals::new();
+        match signal {
+            XC2EqnSignal::Pad {fb, mc} => {
+                let delay = self.ibuf_delay(fb_mc_num_to_iob_num(device, fb, mc));
+                arrivals.insert(XC2TimingPoint::Pin {fb, mc}, (delay, Vec::new()));
+            },
+            XC2EqnSignal::DedicatedInput => {
+                let delay = self.ibuf_delay(None);
+                arrivals.insert(XC2TimingPoint::DedicatedInput, (delay, Vec::new()));
+            },
+            XC2EqnSignal::MCComb {fb, mc} | XC2EqnSignal::MCReg {fb, mc} => {
+                let mc_idx = fb as usize * MCS_PER_FB + mc as usize;
+                let node = match signal {
+                    XC2EqnSignal::MCComb {..} => XC2TimingNode::Comb(mc_idx),
+                    _ => XC2TimingNode::Reg(mc_idx),
+                };
+                for (start, (time, mut nodes)) in self.node(node) {
+                    // A path starting at a register does not go through the same register
+                    if start != (XC2TimingPoint::Reg {fb, mc}) {
+                        nodes.push(node);
+                    }
+                    arrivals.insert(start, (time, nodes));
+                }
+            },
+            _ => {},
+        }
+        arrivals
+    }
+
+    /// Returns the arrival times at the output of the given product term
+    fn term(&mut self, fb: u32, term: u32) -> XC2Arrivals {
+        let fb_bits = &self.bits.get_fb()[fb as usize];
+        let and_term = fb_bits.get_andterm(term as usize);
+        let mut arrivals = XC2Arrivals::new();
+        for i in 0..INPUTS_PER_ANDTERM {
+            if and_term.get(i) || and_term.get_b(i) {
+                if let Ok(signal) = zia_signal(self.bits, *fb_bits.get_zia(i)) {
+                    let signal_arrivals = self.signal(signal);
+                    merge_arrivals(&mut arrivals, &signal_arrivals, self.params.t_zia);
+                }
+            }
+        }
+        arrivals
+    }
+
+    /// Returns the delay from the clock pin to the clock input of the register in the given macrocell. Product term
+    /// clocks are assumed to be driven by the latest of their inputs.
+    fn clock_delay(&mut self, fb: u32, mc: u32) -> u32 {
+        let (term, delay) = match self.bits.get_fb()[fb as usize].mcs[mc as usize].clk_src {
+            XC2MCRegClkSrc::GCK0 | XC2MCRegClkSrc::GCK1 | XC2MCRegClkSrc::GCK2 => return self.params.t_gck,
+            XC2MCRegClkSrc::PTC => (get_ptc(mc), self.params.t_ptck),
+            XC2MCRegClkSrc::CTC => (CTC, self.params.t_ct),
+        };
+        self.term(fb, term).values().map(|&(time, _)| time).max().unwrap_or(0) + delay
+    }
+
+}
+
+/// Returns the path that arrives at `end` from `start` with the given arrival
+fn path(start: XC2TimingPoint, end: XC2TimingPoint, &(delay, ref nodes): &(u32, Vec<XC2TimingNode>))
+    -> XC2TimingPath {
+
+    let through = nodes.iter().map(|&node| match node {
+        XC2TimingNode::Comb(mc_idx) => XC2EqnSignal::MCComb {
+            fb: (mc_idx / MCS_PER_FB) as u32,
+            mc: (mc_idx % MCS_PER_FB) as u32,
+        },
+        XC2TimingNode::Reg(mc_idx) => XC2EqnSignal::MCReg {
+            fb: (mc_idx / MCS_PER_FB) as u32,
+            mc: (mc_idx % MCS_PER_FB) as u32,
+        },
+    }).collect();
+
+    XC2TimingPath {start, end, through, delay}
+}
+
+/// Sorts paths from longest to shortest
+fn sort_paths(paths: &mut [XC2TimingPath]) {
+    paths.sort_by(|a, b| b.delay.cmp(&a.delay).then(a.start.cmp(&b.start)).then(a.end.cmp(&b.end)));
+}
+
+/// Performs static timing analysis of the given bitstream using the given timing parameters
+pub fn analyze_timing(bits: &XC2BitstreamBits, params: &XC2TimingParams) -> XC2TimingReport {
+    let device = bits.device_type();
+    let global_nets = *bits.get_global_nets();
+    let mut analyser = XC2TimingAnalyser {
+        bits,
+        params,
+        arrivals: HashMap::new(),
+        visiting: Vec::new(),
+        loop_depth: usize::MAX,
+    };
+
+    let mut pin_to_pin = Vec::new();
+    let mut setup = Vec::new();
+    let mut clock_to_out = Vec::new();
+    let mut clock_domains = BTreeMap::new();
+
+    // Returns whether a register is DDR and whether it is clocked on the falling edge
+    let clock_edges = |fb: u32, mc: u32| {
+        let mc_bits = &bits.get_fb()[fb as usize].mcs[mc as usize];
+        (mc_bits.is_ddr, mc_bits.clk_invert_pol)
+    };
+
+    for fb in 0..device.num_fbs() as u32 {
+        for mc in 0..MCS_PER_FB as u32 {
+            let mc_idx = fb as usize * MCS_PER_FB + mc as usize;
+            let mc_bits = &bits.get_fb()[fb as usize].mcs[mc as usize];
+            let iob = fb_mc_num_to_iob_num(device, fb, mc);
+            let (obuf_uses_ff, obuf_mode) = match iob {
+                Some(iob) => {
+                    let (_, obuf_uses_ff, obuf_mode) = iob_modes(bits, iob);
+                    (obuf_uses_ff, obuf_mode)
+                },
+                None => (false, XC2IOBOBufMode::Disabled),
+            };
+            let output_used = obuf_mode != XC2IOBOBufMode::Disabled && obuf_mode != XC2IOBOBufMode::CGND;
+
+            // Output pin
+            if output_used {
+                let slew_is_fast = match bits.get_small_iob(iob.unwrap() as usize) {
+                    Some(iob) => iob.slew_is_fast,
+                    None => bits.get_large_iob(iob.unwrap() as usize).unwrap().slew_is_fast,
+                };
+                let slew = if slew_is_fast {0} else {params.t_slew};
+
+                let mut arrivals = XC2Arrivals::new();
+                let data_node = if obuf_uses_ff {XC2TimingNode::Reg(mc_idx)} else {XC2TimingNode::Comb(mc_idx)};
+                let data = analyser.node(data_node);
+                merge_arrivals(&mut arrivals, &data, params.t_out + slew);
+
+                let oe = match obuf_mode {
+                    XC2IOBOBufMode::TriStateGTS0 | XC2IOBOBufMode::TriStateGTS1 |
+                    XC2IOBOBufMode::TriStateGTS2 | XC2IOBOBufMode::TriStateGTS3 => {
+                        let gts = match obuf_mode {
+                            XC2IOBOBufMode::TriStateGTS0 => 0,
+                            XC2IOBOBufMode::TriStateGTS1 => 1,
+                            XC2IOBOBufMode::TriStateGTS2 => 2,
+                            _ => 3,
+                        };
+                        let mut oe = XC2Arrivals::new();
+                        if global_nets.gts_enable[gts] {
+                            let (fb, mc) = get_gts(device, gts).unwrap();
+                            oe.insert(XC2TimingPoint::Pin {fb, mc}, (params.t_gts, Vec::new()));
+                        }
+                        oe
+                    },
+                    XC2IOBOBufMode::TriStatePTB => {
+                        let mut oe = XC2Arrivals::new();
+                        merge_arrivals(&mut oe, &analyser.term(fb, get_ptb(mc)), params.t_ptts);
+                        oe
+                    },
+                    XC2IOBOBufMode::TriStateCTE => {
+                        let mut oe = XC2Arrivals::new();
+                        merge_arrivals(&mut oe, &analyser.term(fb, CTE), params.t_ct);
+                        oe
+                    },
+                    _ => XC2Arrivals::new(),
+                };
+                merge_arrivals(&mut arrivals, &oe, params.t_en + slew);
+
+                let end = XC2TimingPoint::Pin {fb, mc};
+                for (&start, arrival) in &arrivals {
+                    match start {
+                        XC2TimingPoint::Reg {fb: start_fb, mc: start_mc} => {
+                            let mut path = path(start, end, arrival);
+                            path.delay += analyser.clock_delay(start_fb, start_mc);
+                            clock_to_out.push(path);
+                        },
+                        _ => pin_to_pin.push(path(start, end, arrival)),
+                    }
+                }
+            }
+
+            // Register
+            if reg_used(bits, fb, mc) {
+                let clock = reg_clock(bits, fb, mc);
+                let domain = clock_domains.entry(clock).or_insert_with(|| XC2ClockDomain {
+                    clock,
+                    registers: Vec::new(),
+                    critical_path: None,
+                    min_period: None,
+                });
+                domain.registers.push((fb, mc));
+
+                let mut arrivals = XC2Arrivals::new();
+                if mc_bits.ff_in_ibuf {
+                    if iob.is_some() {
+                        arrivals.insert(XC2TimingPoint::Pin {fb, mc}, (params.t_din + params.t_sui, Vec::new()));
+                    }
+                } else {
+                    merge_arrivals(&mut arrivals, &analyser.node(XC2TimingNode::Comb(mc_idx)), params.t_sui);
+                }
+                if mc_bits.reg_mode == XC2MCRegMode::DFFCE {
+                    merge_arrivals(&mut arrivals, &analyser.term(fb, get_ptc(mc)), params.t_logi1 + params.t_ecsu);
+                }
+
+                let end = XC2TimingPoint::Reg {fb, mc};
+                let clock_delay = analyser.clock_delay(fb, mc);
+                for (&start, arrival) in &arrivals {
+                    match start {
+                        XC2TimingPoint::Reg {fb: start_fb, mc: start_mc} => {
+                            // The clock delay is the same at both registers
+                            if reg_clock(bits, start_fb, start_mc) != clock {
+                                continue;
+                            }
+                            let path = path(start, end, arrival);
+                            let (start_ddr, start_invert) = clock_edges(start_fb, start_mc);
+                            let (end_ddr, end_invert) = clock_edges(fb, mc);
+                            // Paths between opposite clock edges only have half of a clock period
+                            let period = if start_ddr || end_ddr || start_invert != end_invert {
+                                path.delay * 2
+                            } else {
+                                path.delay
+                            };
+                            let slowest = match domain.min_period {
+                                Some(min_period) => period > min_period,
+                                None => true,
+                            };
+                            if slowest {
+                                domain.min_period = Some(period);
+                                domain.critical_path = Some(path);
+                            }
+                        },
+                        _ => {
+                            let mut path = path(start, end, arrival);
+                            path.delay = path.delay.saturating_sub(clock_delay);
+                            setup.push(path);
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    sort_paths(&mut pin_to_pin);
+    sort_paths(&mut setup);
+    sort_paths(&mut clock_to_out);
+
+    XC2TimingReport {
+        device,
+        params: *params,
+        pin_to_pin,
+        setup,
+        clock_to_out,
+        clock_domains: clock_domains.into_iter().map(|x| x.1).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a ZIA row of the given device that can select the given input
+    fn zia_row(device: XC2Device, input: XC2ZIAInput) -> usize {
+        (0..INPUTS_PER_ANDTERM).find(|&row| zia_table_get_row(device, row).contains(&input)).unwrap()
+    }
+
+    fn params() -> XC2TimingParams {
+        get_timing_params(XC2Device::XC2C32A, XC2Speed::Speed4).unwrap()
+    }
+
+    #[test]
+    fn speed_grades() {
+        // XC2C32A -4 figures from its data sheet
+        assert_eq!(params().tpd1(), 3800);
+        assert_eq!(params().tsu(), 1700);
+        assert_eq!(params().tco(), 3700);
+        assert_eq!(params().fsystem().round(), 323.0);
+        assert!(get_timing_params(XC2Device::XC2C32A, XC2Speed::Speed10).is_none());
+        assert!(get_timing_params(XC2Device::XC2C512, XC2Speed::Speed10).is_some());
+
+        let fast = get_timing_params(XC2Device::XC2C256, XC2Speed::Speed6).unwrap();
+        let slow = get_timing_params(XC2Device::XC2C256, XC2Speed::Speed7).unwrap();
+        assert!(fast.tpd1() < slow.tpd1());
+        assert!(fast.fsystem() > slow.fsystem());
+    }
+
+    #[test]
+    fn comb() {
+        // FB1_9 = !P38 onto P29, and FB1_10 = FB1_9 onto P28 through a PLA with two product terms
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_mut_small_iob(0).unwrap().zia_mode = XC2IOBZIAMode::PAD;
+            bits.get_mut_small_iob(0).unwrap().schmitt_trigger = false;
+            bits.get_mut_small_iob(8).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(9).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(9).unwrap().slew_is_fast = false;
+
+            let pin_row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::IBuf{ibuf: 0});
+            let mc_row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::Macrocell{fb: 0, mc: 8});
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(pin_row) = XC2ZIAInput::IBuf{ibuf: 0};
+            *fbs[0].get_mut_zia(mc_row) = XC2ZIAInput::Macrocell{fb: 0, mc: 8};
+            fbs[0].get_mut_andterm(20).set(pin_row, true);
+            fbs[0].or_terms[8].set(20, true);
+            fbs[0].mcs[8].xor_mode = XC2MCXorMode::ONE;
+            fbs[0].mcs[8].fb_mode = XC2MCFeedbackMode::COMB;
+            fbs[0].get_mut_andterm(21).set(mc_row, true);
+            fbs[0].or_terms[9].set(21, true);
+            fbs[0].or_terms[9].set(22, true);
+        }
+
+        let params = params();
+        let report = bitstream.analyze_timing().unwrap();
+        assert_eq!(report.pin_to_pin, vec![
+            XC2TimingPath {
+                start: XC2TimingPoint::Pin {fb: 0, mc: 0},
+                end: XC2TimingPoint::Pin {fb: 0, mc: 9},
+                through: vec![XC2EqnSignal::MCComb {fb: 0, mc: 8}],
+                delay: params.tpd1() + params.t_zia + params.t_logi2 + params.t_pdi + params.t_slew,
+            },
+            XC2TimingPath {
+                start: XC2TimingPoint::Pin {fb: 0, mc: 0},
+                end: XC2TimingPoint::Pin {fb: 0, mc: 8},
+                through: Vec::new(),
+                delay: params.tpd1(),
+            },
+        ]);
+        assert!(report.setup.is_empty());
+        assert!(report.clock_to_out.is_empty());
+        assert!(report.clock_domains.is_empty());
+        assert_eq!(report.pin_to_pin[0].to_string(), "FB1_1_PIN -> FB1_9 -> FB1_10_PIN: 9.0 ns");
+
+        let mut text = Vec::new();
+        report.dump_human_readable(XC2Package::VQ44, &mut text).unwrap();
+        assert!(String::from_utf8(text).unwrap().contains("\npin-to-pin delays:\nP38 -> FB1_9 -> P28: 9.0 ns\n"));
+    }
+
+    #[test]
+    fn comb_loop() {
+        // FB1_1 = P36 | FB1_2 onto P38, and FB1_2 = FB1_1 onto P37. The loop is first found while analysing FB1_1,
+        // but the path from P36 through FB1_1 to P37 must still be found.
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_mut_small_iob(2).unwrap().zia_mode = XC2IOBZIAMode::PAD;
+            bits.get_mut_small_iob(2).unwrap().schmitt_trigger = false;
+            bits.get_mut_small_iob(0).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(1).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+
+            let pin_row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::IBuf{ibuf: 2});
+            let mc0_row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::Macrocell{fb: 0, mc: 0});
+            let mc1_row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::Macrocell{fb: 0, mc: 1});
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(pin_row) = XC2ZIAInput::IBuf{ibuf: 2};
+            *fbs[0].get_mut_zia(mc0_row) = XC2ZIAInput::Macrocell{fb: 0, mc: 0};
+            *fbs[0].get_mut_zia(mc1_row) = XC2ZIAInput::Macrocell{fb: 0, mc: 1};
+            fbs[0].get_mut_andterm(20).set(pin_row, true);
+            fbs[0].get_mut_andterm(21).set(mc1_row, true);
+            fbs[0].or_terms[0].set(20, true);
+            fbs[0].or_terms[0].set(21, true);
+            fbs[0].mcs[0].fb_mode = XC2MCFeedbackMode::COMB;
+            fbs[0].get_mut_andterm(22).set(mc0_row, true);
+            fbs[0].or_terms[1].set(22, true);
+            fbs[0].mcs[1].fb_mode = XC2MCFeedbackMode::COMB;
+        }
+
+        let params = params();
+        let report = bitstream.analyze_timing().unwrap();
+        let pla = params.t_zia + params.t_logi2 + params.t_pdi;
+        assert_eq!(report.pin_to_pin, vec![
+            XC2TimingPath {
+                start: XC2TimingPoint::Pin {fb: 0, mc: 2},
+                end: XC2TimingPoint::Pin {fb: 0, mc: 1},
+                through: vec![XC2EqnSignal::MCComb {fb: 0, mc: 0}],
+                delay: params.t_in + pla + params.t_zia + params.t_logi1 + params.t_pdi + params.t_out,
+            },
+            XC2TimingPath {
+                start: XC2TimingPoint::Pin {fb: 0, mc: 2},
+                end: XC2TimingPoint::Pin {fb: 0, mc: 0},
+                through: Vec::new(),
+                delay: params.t_in + pla + params.t_out,
+            },
+        ]);
+    }
+
+    #[test]
+    fn registers() {
+        // FB1_1 toggles on every rising edge of GCK0 and drives P38. FB1_2 captures FB1_1 on both edges.
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_global_nets_mut().gck_enable[0] = true;
+            bits.get_mut_small_iob(0).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(0).unwrap().obuf_uses_ff = true;
+
+            let row = zia_row(XC2Device::XC2C32A, XC2ZIAInput::Macrocell{fb: 0, mc: 0});
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(row) = XC2ZIAInput::Macrocell{fb: 0, mc: 0};
+            fbs[0].get_mut_andterm(20).set_b(row, true);
+            fbs[0].or_terms[0].set(20, true);
+            fbs[0].mcs[0].fb_mode = XC2MCFeedbackMode::REG;
+            fbs[0].get_mut_andterm(21).set(row, true);
+            fbs[0].or_terms[1].set(21, true);
+            fbs[0].mcs[1].fb_mode = XC2MCFeedbackMode::REG;
+            fbs[0].mcs[1].is_ddr = true;
+        }
+
+        let params = params();
+        let report = bitstream.analyze_timing().unwrap();
+        assert_eq!(report.clock_to_out, vec![
+            XC2TimingPath {
+                start: XC2TimingPoint::Reg {fb: 0, mc: 0},
+                end: XC2TimingPoint::Pin {fb: 0, mc: 0},
+                through: Vec::new(),
+                delay: params.tco(),
+            },
+        ]);
+
+        let period = params.t_coi + params.t_zia + params.t_logi1 + params.t_pdi + params.t_sui;
+        assert_eq!(report.clock_domains.len(), 1);
+        let domain = &report.clock_domains[0];
+        assert_eq!(domain.clock, XC2TimingClock::GCK(0));
+        assert_eq!(domain.registers, vec![(0, 0), (0, 1)]);
+        assert_eq!(domain.min_period, Some(period * 2));
+        assert_eq!(domain.critical_path.as_ref().unwrap().end, XC2TimingPoint::Reg {fb: 0, mc: 1});
+        assert_eq!(domain.fmax().unwrap(), params.fsystem() / 2.0);
+    }
+}
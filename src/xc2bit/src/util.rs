@@ -24,6 +24,9 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 use std::collections::BTreeMap;
+use std::str::Lines;
+
+use errors::XC2BitError;
 
 pub fn b2s(b: bool) -> &'static str {
     if b {"1"} else {"0"}
@@ -81,3 +84,102 @@ impl<'a> Iterator for LinebreakSetIter<'a> {
         }
     }
 }
+
+/// Reads back the output of the `dump_human_readable` functions one line at a time
+pub struct HumanReadableReader<'a> {
+    lines: Lines<'a>,
+    line_num: usize,
+}
+
+impl<'a> HumanReadableReader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            lines: text.lines(),
+            line_num: 0,
+        }
+    }
+
+    /// Returns an error pointing at the most recently read line
+    pub fn err(&self) -> XC2BitError {
+        XC2BitError::HumanReadableParseError(self.line_num)
+    }
+
+    pub fn next_line(&mut self) -> Result<&'a str, XC2BitError> {
+        self.line_num += 1;
+        self.lines.next().ok_or_else(|| self.err())
+    }
+
+    /// Reads a line that must match `expected` exactly
+    pub fn expect(&mut self, expected: &str) -> Result<(), XC2BitError> {
+        if self.next_line()? == expected {
+            Ok(())
+        } else {
+            Err(self.err())
+        }
+    }
+
+    /// Reads a line that must start with `prefix` and returns the rest of it
+    pub fn value(&mut self, prefix: &str) -> Result<&'a str, XC2BitError> {
+        let line = self.next_line()?;
+        without_prefix(line, prefix).ok_or_else(|| self.err())
+    }
+
+    /// Like `value`, but if the line does not start with `prefix` it is left to be read again
+    pub fn optional_value(&mut self, prefix: &str) -> Option<&'a str> {
+        let value = without_prefix(self.lines.clone().next()?, prefix)?;
+        self.lines.next();
+        self.line_num += 1;
+        Some(value)
+    }
+
+    /// Reads a line that must be `prefix` followed by one of the names in `choices`
+    pub fn choice<T: Copy>(&mut self, prefix: &str, choices: &[(&str, T)]) -> Result<T, XC2BitError> {
+        let value = self.value(prefix)?;
+        choices.iter().find(|&&(name, _)| name == value).map(|&(_, x)| x).ok_or_else(|| self.err())
+    }
+
+    /// Reads a line that must be `prefix` followed by either `if_true` or `if_false`
+    pub fn flag(&mut self, prefix: &str, if_true: &str, if_false: &str) -> Result<bool, XC2BitError> {
+        self.choice(prefix, &[(if_true, true), (if_false, false)])
+    }
+
+    /// Checks that there is nothing left to read
+    pub fn finish(&mut self) -> Result<(), XC2BitError> {
+        if self.lines.next().is_some() {
+            self.line_num += 1;
+            Err(self.err())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns `s` without `prefix`, or `None` if `s` does not start with `prefix`
+pub fn without_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.get(..prefix.len()) == Some(prefix) {
+        s.get(prefix.len()..)
+    } else {
+        None
+    }
+}
+
+/// Returns `s` without `suffix`, or `None` if `s` does not end with `suffix`
+pub fn without_suffix<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = s.len().checked_sub(suffix.len())?;
+    if s.get(split..) == Some(suffix) {
+        s.get(..split)
+    } else {
+        None
+    }
+}
+
+/// Parses a macrocell name in the FBx_y format used by the human-readable dumps into 0-based numbers
+pub fn parse_fb_mc_name(name: &str) -> Option<(u32, u32)> {
+    let mut parts = without_prefix(name, "FB")?.splitn(2, '_');
+    let fb = parts.next()?.parse::<u32>().ok()?;
+    let mc = parts.next()?.parse::<u32>().ok()?;
+    if fb == 0 || mc == 0 {
+        return None;
+    }
+    Some((fb - 1, mc - 1))
+}
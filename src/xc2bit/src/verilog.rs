@@ -0,0 +1,546 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Contains routines for exporting a decompiled bitstream as a Verilog module
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+
+use *;
+
+/// Name of the port that is used for the dedicated input pin if its pin name is not known
+const DEDICATED_INPUT_PORT: &str = "INPUT_ONLY";
+/// Name of the wire that carries the output of the GCK2 clock divider
+const CLOCK_DIV_WIRE: &str = "GCK2_DIV";
+
+/// Returns the name of the port for the given IOB. This is the package pin name if it is known.
+fn port_name(device: XC2Device, package: XC2Package, iob: u32) -> String {
+    match iob_num_to_pin_name(device, package, iob) {
+        Some(pin_name) => pin_name.to_owned(),
+        None => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+            format!("FB{}_{}_PIN", fb + 1, mc + 1)
+        }
+    }
+}
+
+/// Returns the IOB that the given signal comes from, if it comes from an I/O pin
+fn signal_iob(device: XC2Device, signal: XC2EqnSignal) -> Option<u32> {
+    let (fb, mc) = match signal {
+        XC2EqnSignal::Pad {fb, mc} => (fb, mc),
+        XC2EqnSignal::GCK(i) => get_gck(device, i as usize)?,
+        XC2EqnSignal::GSR => get_gsr(device),
+        XC2EqnSignal::GTS(i) => get_gts(device, i as usize)?,
+        _ => return None,
+    };
+
+    fb_mc_num_to_iob_num(device, fb, mc)
+}
+
+/// Returns all of the sums that make up the given equations
+fn equation_sums(equations: &XC2MCEquations) -> Vec<&XC2EqnSum> {
+    let mut xors = Vec::new();
+    let mut ret = Vec::new();
+
+    if let Some(ref comb) = equations.comb {
+        xors.push(comb);
+    }
+    if let Some(ref reg) = equations.reg {
+        xors.push(&reg.input);
+        ret.push(&reg.clk);
+        if let Some(ref ce) = reg.ce {
+            ret.push(ce);
+        }
+        if let Some(ref set) = reg.set {
+            ret.push(set);
+        }
+        if let Some(ref reset) = reg.reset {
+            ret.push(reset);
+        }
+    }
+    if let Some(ref output) = equations.output {
+        ret.push(&output.data);
+        ret.push(&output.oe);
+    }
+
+    for xor in xors {
+        ret.push(&xor.sum);
+        if let Some(ref xor_term) = xor.xor_term {
+            ret.push(xor_term);
+        }
+    }
+
+    ret
+}
+
+/// Formats a sum as a Verilog expression
+fn sum_expr<F: Fn(XC2EqnSignal) -> String>(sum: &XC2EqnSum, name: &F) -> String {
+    if sum.is_zero() {
+        "1'b0".to_owned()
+    } else if sum.is_one() {
+        "1'b1".to_owned()
+    } else {
+        sum.format("~", name)
+    }
+}
+
+/// Formats the output of an XOR gate as a Verilog expression
+fn xor_expr<F: Fn(XC2EqnSignal) -> String>(xor: &XC2EqnXor, name: &F) -> String {
+    // XORing a constant with PTC either passes PTC through or inverts it
+    let xor = match xor.xor_term {
+        Some(ref xor_term) if xor.sum.is_zero() || xor.sum.is_one() => XC2EqnXor {
+            sum: xor_term.clone(),
+            xor_term: None,
+            inverted: xor.inverted ^ xor.sum.is_one(),
+        },
+        _ => xor.clone(),
+    };
+
+    if xor.xor_term.is_none() && (xor.sum.is_zero() || xor.sum.is_one()) {
+        if xor.sum.is_one() ^ xor.inverted {"1'b1".to_owned()} else {"1'b0".to_owned()}
+    } else {
+        xor.format("~", name)
+    }
+}
+
+/// Write a Verilog `if`/`else if` chain that updates a register. Each case is an optional condition and the statement
+/// to run if it is true.
+fn write_reg_update<W: Write>(cases: &[(Option<String>, String)], mut writer: W) -> Result<(), io::Error> {
+    for (i, &(ref cond, ref stmt)) in cases.iter().enumerate() {
+        match *cond {
+            Some(ref cond) => {
+                write!(writer, "        {}if ({})\n", if i == 0 {""} else {"else "}, cond)?;
+                write!(writer, "            {}\n", stmt)?;
+            },
+            None if i == 0 => write!(writer, "        {}\n", stmt)?,
+            None => {
+                write!(writer, "        else\n")?;
+                write!(writer, "            {}\n", stmt)?;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a Verilog module named `top` implementing the logic in the given bitstream to the given `writer` object.
+/// The ports of the module are named after the package pins, and each macrocell becomes a set of `assign` statements
+/// and (if its register is used) an `always` block. Product terms whose inputs are disabled in the ZIA read as `1'bx`.
+pub fn write_verilog<W: Write>(bitstream: &XC2Bitstream, mut writer: W) -> Result<(), io::Error> {
+    let device = bitstream.bits.device_type();
+    let package = bitstream.package;
+    let all_equations = bitstream.get_equations();
+    let clock_div = match bitstream.bits.get_clock_div() {
+        Some(clock_div) if clock_div.enabled => Some(*clock_div),
+        _ => None,
+    };
+
+    // Work out which pins are used as inputs and outputs
+    let mut ports: BTreeMap<u32, (bool, bool)> = BTreeMap::new();
+    let mut dedicated_input_used = false;
+    let mut clock_div_used = false;
+    for equations in &all_equations {
+        for sum in equation_sums(equations) {
+            for literal in sum.products.iter().flat_map(|product| product.literals.iter()) {
+                if literal.signal == XC2EqnSignal::DedicatedInput {
+                    dedicated_input_used = true;
+                }
+                if literal.signal == XC2EqnSignal::GCK(2) && clock_div.is_some() {
+                    clock_div_used = true;
+                }
+                if let Some(iob) = signal_iob(device, literal.signal) {
+                    ports.entry(iob).or_insert((false, false)).0 = true;
+                }
+            }
+        }
+
+        if equations.output.is_some() {
+            let iob = fb_mc_num_to_iob_num(device, equations.fb, equations.mc).unwrap();
+            ports.entry(iob).or_insert((false, false)).1 = true;
+        }
+    }
+    let cdrst_iob = if clock_div_used {
+        let (fb, mc) = get_cdrst(device).unwrap();
+        let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+        ports.entry(iob).or_insert((false, false)).0 = true;
+        Some(iob)
+    } else {
+        None
+    };
+
+    let dedicated_input_name = get_package_pins(device, package)
+        .and_then(|pins| pins.into_iter().find(|pin| pin.function == XC2PinFunction::InputOnly))
+        .map_or(DEDICATED_INPUT_PORT, |pin| pin.name);
+
+    let name = |signal: XC2EqnSignal| -> String {
+        match signal {
+            XC2EqnSignal::MCComb {fb, mc} => format!("FB{}_{}", fb + 1, mc + 1),
+            XC2EqnSignal::MCReg {fb, mc} => format!("FB{}_{}_Q", fb + 1, mc + 1),
+            XC2EqnSignal::DedicatedInput => dedicated_input_name.to_owned(),
+            XC2EqnSignal::GCK(2) if clock_div.is_some() => CLOCK_DIV_WIRE.to_owned(),
+            XC2EqnSignal::Disconnected => "1'bx".to_owned(),
+            _ => port_name(device, package, signal_iob(device, signal).unwrap()),
+        }
+    };
+
+    // Module header
+    write!(writer, "// xc2bit Verilog export for {}-{}-{}\n", device, bitstream.speed_grade, package)?;
+    let mut port_decls = ports.iter().map(|(&iob, &(input, output))| {
+        format!("{} {}", match (input, output) {
+            (true, false) => "input",
+            (false, true) => "output",
+            _ => "inout",
+        }, port_name(device, package, iob))
+    }).collect::<Vec<_>>();
+    if dedicated_input_used {
+        port_decls.push(format!("input {}", dedicated_input_name));
+    }
+    if port_decls.is_empty() {
+        write!(writer, "module top();\n")?;
+    } else {
+        write!(writer, "module top(\n")?;
+        write!(writer, "    {}\n", port_decls.join(",\n    "))?;
+        write!(writer, ");\n")?;
+    }
+
+    // Declarations
+    let mut decls = Vec::new();
+    if clock_div_used {
+        decls.push(format!("reg {} = 1'b0;", CLOCK_DIV_WIRE));
+        decls.push(format!("reg [2:0] {}_CNT = 3'd0;", CLOCK_DIV_WIRE));
+        if clock_div.unwrap().delay {
+            decls.push(format!("reg {}_DLY = 1'b1;", CLOCK_DIV_WIRE));
+        }
+    }
+    for equations in &all_equations {
+        let mc_name = format!("FB{}_{}", equations.fb + 1, equations.mc + 1);
+        if equations.comb.is_some() {
+            decls.push(format!("wire {};", mc_name));
+        }
+        if let Some(ref reg) = equations.reg {
+            decls.push(format!("reg {}_Q = 1'b{};", mc_name, if reg.init_state {1} else {0}));
+            decls.push(format!("wire {}_{};", mc_name, if reg.mode == XC2MCRegMode::TFF {"T"} else {"D"}));
+            decls.push(format!("wire {}_CLK;", mc_name));
+            if reg.ce.is_some() {
+                decls.push(format!("wire {}_CE;", mc_name));
+            }
+            if reg.set.is_some() {
+                decls.push(format!("wire {}_S;", mc_name));
+            }
+            if reg.reset.is_some() {
+                decls.push(format!("wire {}_R;", mc_name));
+            }
+        }
+    }
+    if !decls.is_empty() {
+        write!(writer, "\n")?;
+        for decl in decls {
+            write!(writer, "    {}\n", decl)?;
+        }
+    }
+
+    // Clock divider
+    if clock_div_used {
+        let clock_div = clock_div.unwrap();
+        let gck2_name = port_name(device, package, signal_iob(device, XC2EqnSignal::GCK(2)).unwrap());
+        let cdrst_name = port_name(device, package, cdrst_iob.unwrap());
+
+        write!(writer, "\n")?;
+        write!(writer, "    // GCK2 clock divider (divide by {}{})\n", clock_div.div_ratio.ratio(),
+            if clock_div.delay {", delayed"} else {""})?;
+        write!(writer, "    always @(posedge {} or posedge {}) begin\n", gck2_name, cdrst_name)?;
+        write!(writer, "        if ({}) begin\n", cdrst_name)?;
+        write!(writer, "            {} <= 1'b0;\n", CLOCK_DIV_WIRE)?;
+        write!(writer, "            {}_CNT <= 3'd0;\n", CLOCK_DIV_WIRE)?;
+        if clock_div.delay {
+            write!(writer, "            {}_DLY <= 1'b1;\n", CLOCK_DIV_WIRE)?;
+        }
+        write!(writer, "        end else if ({}_CNT == 3'd{}) begin\n", CLOCK_DIV_WIRE,
+            clock_div.div_ratio.ratio() / 2 - 1)?;
+        write!(writer, "            {}_CNT <= 3'd0;\n", CLOCK_DIV_WIRE)?;
+        if clock_div.delay {
+            // The first rising edge of the divided clock is skipped
+            write!(writer, "            if ({}_DLY)\n", CLOCK_DIV_WIRE)?;
+            write!(writer, "                {}_DLY <= 1'b0;\n", CLOCK_DIV_WIRE)?;
+            write!(writer, "            else\n")?;
+            write!(writer, "                {} <= ~{};\n", CLOCK_DIV_WIRE, CLOCK_DIV_WIRE)?;
+        } else {
+            write!(writer, "            {} <= ~{};\n", CLOCK_DIV_WIRE, CLOCK_DIV_WIRE)?;
+        }
+        write!(writer, "        end else\n")?;
+        write!(writer, "            {}_CNT <= {}_CNT + 3'd1;\n", CLOCK_DIV_WIRE, CLOCK_DIV_WIRE)?;
+        write!(writer, "    end\n")?;
+    }
+
+    // Macrocells
+    for equations in &all_equations {
+        let mc_name = format!("FB{}_{}", equations.fb + 1, equations.mc + 1);
+        write!(writer, "\n")?;
+        write!(writer, "    // {}\n", mc_name)?;
+
+        if let Some(ref comb) = equations.comb {
+            write!(writer, "    assign {} = {};\n", mc_name, xor_expr(comb, &name))?;
+        }
+
+        if let Some(ref reg) = equations.reg {
+            let input_name = format!("{}_{}", mc_name, if reg.mode == XC2MCRegMode::TFF {"T"} else {"D"});
+            let q_name = format!("{}_Q", mc_name);
+            write!(writer, "    assign {} = {};\n", input_name, xor_expr(&reg.input, &name))?;
+            write!(writer, "    assign {}_CLK = {};\n", mc_name, sum_expr(&reg.clk, &name))?;
+            if let Some(ref ce) = reg.ce {
+                write!(writer, "    assign {}_CE = {};\n", mc_name, sum_expr(ce, &name))?;
+            }
+            if let Some(ref set) = reg.set {
+                write!(writer, "    assign {}_S = {};\n", mc_name, sum_expr(set, &name))?;
+            }
+            if let Some(ref reset) = reg.reset {
+                write!(writer, "    assign {}_R = {};\n", mc_name, sum_expr(reset, &name))?;
+            }
+
+            // Reset has priority over set
+            let mut cases = Vec::new();
+            if reg.reset.is_some() {
+                cases.push((Some(format!("{}_R", mc_name)), format!("{} <= 1'b0;", q_name)));
+            }
+            if reg.set.is_some() {
+                cases.push((Some(format!("{}_S", mc_name)), format!("{} <= 1'b1;", q_name)));
+            }
+
+            if reg.mode == XC2MCRegMode::LATCH {
+                let gate = format!("{}{}_CLK", if reg.clk_invert {"~"} else {""}, mc_name);
+                cases.push((Some(gate), format!("{} <= {};", q_name, input_name)));
+
+                write!(writer, "    always @(*) begin\n")?;
+            } else {
+                let update = if reg.mode == XC2MCRegMode::TFF {
+                    format!("{} <= ~{};", q_name, q_name)
+                } else {
+                    format!("{} <= {};", q_name, input_name)
+                };
+                let cond = if reg.mode == XC2MCRegMode::TFF {
+                    Some(input_name.clone())
+                } else if reg.ce.is_some() {
+                    Some(format!("{}_CE", mc_name))
+                } else {
+                    None
+                };
+                cases.push((cond, update));
+
+                let mut events = if reg.ddr {
+                    vec![format!("posedge {}_CLK", mc_name), format!("negedge {}_CLK", mc_name)]
+                } else {
+                    vec![format!("{} {}_CLK", if reg.clk_invert {"negedge"} else {"posedge"}, mc_name)]
+                };
+                if reg.reset.is_some() {
+                    events.push(format!("posedge {}_R", mc_name));
+                }
+                if reg.set.is_some() {
+                    events.push(format!("posedge {}_S", mc_name));
+                }
+
+                write!(writer, "    always @({}) begin\n", events.join(" or "))?;
+            }
+            write_reg_update(&cases, &mut writer)?;
+            write!(writer, "    end\n")?;
+        }
+
+        if let Some(ref output) = equations.output {
+            let iob = fb_mc_num_to_iob_num(device, equations.fb, equations.mc).unwrap();
+            let data = sum_expr(&output.data, &name);
+            if output.oe.is_one() {
+                write!(writer, "    assign {} = {};\n", port_name(device, package, iob), data)?;
+            } else {
+                write!(writer, "    assign {} = ({}) ? {} : 1'bz;\n", port_name(device, package, iob),
+                    sum_expr(&output.oe, &name), data)?;
+            }
+        }
+    }
+
+    write!(writer, "endmodule\n")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank() {
+        let bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C64A-5-VQ44").unwrap());
+        let mut verilog = Vec::new();
+        bitstream.to_verilog(&mut verilog).unwrap();
+        assert_eq!(str::from_utf8(&verilog).unwrap(), "// xc2bit Verilog export for XC2C64A-5-VQ44\n\
+            module top();\n\
+            endmodule\n");
+    }
+
+    #[test]
+    fn export() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C32A-4-VQ44").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            bits.get_global_nets_mut().gck_enable = [true, true, false];
+            bits.get_global_nets_mut().gsr_enable = true;
+            bits.get_global_nets_mut().gsr_invert = true;
+            bits.get_mut_small_iob(0).unwrap().zia_mode = XC2IOBZIAMode::PAD;
+            bits.get_mut_small_iob(1).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_small_iob(1).unwrap().obuf_uses_ff = true;
+            bits.get_mut_small_iob(4).unwrap().obuf_mode = XC2IOBOBufMode::TriStatePTB;
+            bits.get_mut_small_iob(16).unwrap().obuf_mode = XC2IOBOBufMode::OpenDrain;
+            bits.get_mut_small_iob(16).unwrap().obuf_uses_ff = true;
+
+            let fbs = bits.get_fb_mut();
+            *fbs[0].get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 0};
+            *fbs[0].get_mut_zia(1) = XC2ZIAInput::Macrocell{fb: 0, mc: 8};
+
+            // FB1_2 is a flip-flop with a clock enable
+            fbs[0].get_mut_andterm(10).set(0, true);
+            fbs[0].or_terms[1].set(10, true);
+            fbs[0].get_mut_andterm(get_ptc(1) as usize).set_b(0, true);
+            fbs[0].mcs[1].reg_mode = XC2MCRegMode::DFFCE;
+            fbs[0].mcs[1].clk_src = XC2MCRegClkSrc::GCK0;
+            fbs[0].mcs[1].r_src = XC2MCRegResetSrc::GSR;
+
+            // FB1_9 is a latch that is transparent when GCK1 is low and is set when the input is low
+            fbs[0].get_mut_andterm(get_pta(8) as usize).set_b(0, true);
+            fbs[0].mcs[8].s_src = XC2MCRegSetSrc::PTA;
+            fbs[0].get_mut_andterm(11).set(0, true);
+            fbs[0].or_terms[8].set(11, true);
+            fbs[0].mcs[8].reg_mode = XC2MCRegMode::LATCH;
+            fbs[0].mcs[8].clk_src = XC2MCRegClkSrc::GCK1;
+            fbs[0].mcs[8].clk_invert_pol = true;
+            fbs[0].mcs[8].fb_mode = XC2MCFeedbackMode::REG;
+
+            // FB1_5 outputs the latch when the input is high
+            fbs[0].get_mut_andterm(12).set(1, true);
+            fbs[0].or_terms[4].set(12, true);
+            fbs[0].get_mut_andterm(get_ptb(4) as usize).set(0, true);
+
+            // FB2_1 is a T flip-flop that toggles on both edges of GCK0 and drives an open-drain output
+            fbs[1].mcs[0].reg_mode = XC2MCRegMode::TFF;
+            fbs[1].mcs[0].xor_mode = XC2MCXorMode::ONE;
+            fbs[1].mcs[0].clk_src = XC2MCRegClkSrc::GCK0;
+            fbs[1].mcs[0].is_ddr = true;
+            fbs[1].mcs[0].s_src = XC2MCRegSetSrc::Disabled;
+            fbs[1].mcs[0].r_src = XC2MCRegResetSrc::Disabled;
+        }
+
+        let mut verilog = Vec::new();
+        bitstream.to_verilog(&mut verilog).unwrap();
+        assert_eq!(str::from_utf8(&verilog).unwrap(), concat!(
+            "// xc2bit Verilog export for XC2C32A-4-VQ44\n",
+            "module top(\n",
+            "    input P38,\n",
+            "    output P37,\n",
+            "    output P33,\n",
+            "    input P30,\n",
+            "    output P39,\n",
+            "    input P43,\n",
+            "    input P44\n",
+            ");\n",
+            "\n",
+            "    reg FB1_2_Q = 1'b1;\n",
+            "    wire FB1_2_D;\n",
+            "    wire FB1_2_CLK;\n",
+            "    wire FB1_2_CE;\n",
+            "    wire FB1_2_R;\n",
+            "    wire FB1_5;\n",
+            "    reg FB1_9_Q = 1'b1;\n",
+            "    wire FB1_9_D;\n",
+            "    wire FB1_9_CLK;\n",
+            "    wire FB1_9_S;\n",
+            "    reg FB2_1_Q = 1'b1;\n",
+            "    wire FB2_1_T;\n",
+            "    wire FB2_1_CLK;\n",
+            "\n",
+            "    // FB1_2\n",
+            "    assign FB1_2_D = P38;\n",
+            "    assign FB1_2_CLK = P43;\n",
+            "    assign FB1_2_CE = ~P38;\n",
+            "    assign FB1_2_R = P30;\n",
+            "    always @(posedge FB1_2_CLK or posedge FB1_2_R) begin\n",
+            "        if (FB1_2_R)\n",
+            "            FB1_2_Q <= 1'b0;\n",
+            "        else if (FB1_2_CE)\n",
+            "            FB1_2_Q <= FB1_2_D;\n",
+            "    end\n",
+            "    assign P37 = FB1_2_Q;\n",
+            "\n",
+            "    // FB1_5\n",
+            "    assign FB1_5 = FB1_9_Q;\n",
+            "    assign P33 = (P38) ? FB1_5 : 1'bz;\n",
+            "\n",
+            "    // FB1_9\n",
+            "    assign FB1_9_D = P38;\n",
+            "    assign FB1_9_CLK = P44;\n",
+            "    assign FB1_9_S = ~P38;\n",
+            "    always @(*) begin\n",
+            "        if (FB1_9_S)\n",
+            "            FB1_9_Q <= 1'b1;\n",
+            "        else if (~FB1_9_CLK)\n",
+            "            FB1_9_Q <= FB1_9_D;\n",
+            "    end\n",
+            "\n",
+            "    // FB2_1\n",
+            "    assign FB2_1_T = 1'b1;\n",
+            "    assign FB2_1_CLK = P43;\n",
+            "    always @(posedge FB2_1_CLK or negedge FB2_1_CLK) begin\n",
+            "        if (FB2_1_T)\n",
+            "            FB2_1_Q <= ~FB2_1_Q;\n",
+            "    end\n",
+            "    assign P39 = (~FB2_1_Q) ? 1'b0 : 1'bz;\n",
+            "endmodule\n",
+        ));
+    }
+    #[test]
+    fn clock_div() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("XC2C128-6-VQ100").unwrap());
+        {
+            let bits = &mut bitstream.bits;
+            match *bits {
+                XC2BitstreamBits::XC2C128 {ref mut clock_div, ..} => {
+                    clock_div.enabled = true;
+                    clock_div.delay = true;
+                    clock_div.div_ratio = XC2ClockDivRatio::Div6;
+                },
+                _ => unreachable!(),
+            }
+            bits.get_mut_large_iob(0).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+            bits.get_mut_large_iob(0).unwrap().obuf_uses_ff = true;
+            bits.get_global_nets_mut().gck_enable[2] = true;
+            bits.get_fb_mut()[0].mcs[0].clk_src = XC2MCRegClkSrc::GCK2;
+        }
+
+        let mut verilog = Vec::new();
+        bitstream.to_verilog(&mut verilog).unwrap();
+        let verilog = str::from_utf8(&verilog).unwrap();
+        assert!(verilog.contains("    // GCK2 clock divider (divide by 6, delayed)\n"));
+        assert!(verilog.contains("    always @(posedge FB2_16_PIN or posedge FB2_15_PIN) begin\n"));
+        assert!(verilog.contains("        end else if (GCK2_DIV_CNT == 3'd2) begin\n"));
+        assert!(verilog.contains("    assign FB1_1_CLK = GCK2_DIV;\n"));
+    }
+}
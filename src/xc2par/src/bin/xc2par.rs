@@ -24,6 +24,7 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 use std::fs::File;
+use std::io::Read;
 use std::path::{Path};
 
 extern crate clap;
@@ -34,6 +35,9 @@ extern crate slog_term;
 extern crate slog;
 use slog::Drain;
 
+extern crate jedec;
+use jedec::JEDECFile;
+
 extern crate xc2bit;
 use xc2bit::*;
 
@@ -62,6 +66,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .help("Seed for internal random number generator (128-bit hex)")
             .long("rng-seed")
             .takes_value(true))
+        .arg(Arg::with_name("usercode")
+            .help("USERCODE to program into the device (32-bit hex)")
+            .long("usercode")
+            .takes_value(true))
+        .arg(Arg::with_name("test-vectors")
+            .help("Copy the test vectors (P, X, and V fields) from this .jed file into the output")
+            .long("test-vectors")
+            .takes_value(true))
 
         .arg(Arg::with_name("part-name")
             .help("Part name (<device>-<speed>-<package>)")
@@ -173,6 +185,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if let Some(usercode_str) = matches.value_of_os("usercode") {
+        if let Some(usercode_str) = usercode_str.to_str() {
+            if let Ok(usercode) = u32::from_str_radix(usercode_str, 16) {
+                options.usercode(Some(usercode));
+            } else {
+                warn!(log, "Illegal value for usercode"; "value" => usercode_str);
+            }
+        } else {
+            warn!(log, "Illegal value for usercode"; "value" => usercode_str.to_string_lossy().into_owned());
+        }
+    }
+
+    if let Some(test_vectors_fn) = matches.value_of_os("test-vectors") {
+        let mut test_vectors_data = Vec::new();
+        File::open(test_vectors_fn)?.read_to_end(&mut test_vectors_data)?;
+        match JEDECFile::from_bytes(&test_vectors_data) {
+            Ok(jed) => {
+                options.test_vectors(jed.test_vectors);
+            },
+            Err(err) => {
+                warn!(log, "Could not read test vectors"; "error" => format!("{}", err));
+            }
+        }
+    }
+
     // Filenames
     let in_fn = Path::new(matches.value_of_os("INPUT").unwrap());
     let out_fn = if let Some(out_fn_str) = matches.value_of_os("OUTPUT") {
@@ -29,6 +29,7 @@ use std::error;
 use std::error::{Error};
 use std::fmt;
 use slog::Drain;
+use jedec::TestVectors;
 use xc2bit::*;
 
 use *;
@@ -44,6 +45,8 @@ pub struct XC2ParOptions {
     pub(crate) max_iter: u32,
     pub(crate) rng_seed: [u32; 4],
     output_fmt: ParOutputFormat,
+    usercode: Option<u32>,
+    test_vectors: Option<TestVectors>,
 }
 
 impl XC2ParOptions {
@@ -52,6 +55,8 @@ impl XC2ParOptions {
             max_iter: 1000,
             rng_seed: [0, 0, 0, 1],
             output_fmt: ParOutputFormat::Jed,
+            usercode: None,
+            test_vectors: None,
         }
     }
 
@@ -72,6 +77,18 @@ impl XC2ParOptions {
 
         self
     }
+
+    pub fn usercode(&mut self, usercode: Option<u32>) -> &mut Self {
+        self.usercode = usercode;
+
+        self
+    }
+
+    pub fn test_vectors(&mut self, test_vectors: Option<TestVectors>) -> &mut Self {
+        self.test_vectors = test_vectors;
+
+        self
+    }
 }
 
 impl Default for XC2ParOptions {
@@ -174,7 +191,9 @@ pub fn xc2par_complete_flow<R, W, L>(options: &XC2ParOptions, device_type: XC2De
 
     match par_result {
         PARResult::Success(x) => {
-            let bitstream = produce_bitstream(device_type, &input_graph, &x);
+            let mut bitstream = produce_bitstream(device_type, &input_graph, &x);
+            bitstream.usercode = options.usercode;
+            bitstream.test_vectors = options.test_vectors.clone();
 
             if options.output_fmt == ParOutputFormat::Jed {
                 bitstream.to_jed(output)?;
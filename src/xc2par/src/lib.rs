@@ -34,6 +34,7 @@ extern crate slog_stdlog;
 
 extern crate yosys_netlist_json;
 
+extern crate jedec;
 extern crate xc2bit;
 
 mod compiler_driver;